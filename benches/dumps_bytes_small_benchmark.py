@@ -0,0 +1,35 @@
+#!/usr/bin/env python3
+"""
+Benchmark high-frequency dumps_bytes calls on small objects.
+
+dumps_bytes serializes into a thread-local buffer that keeps its capacity
+between calls, like dumps, so small messages cost no buffer allocation.
+"""
+
+import json
+import resource
+import timeit
+
+import rjson
+
+CALLS = 1_000_000
+
+message = {"id": 12345, "type": "tick", "price": 101.25, "ok": True, "tags": ["a", "b"]}
+
+bytes_time = timeit.timeit(lambda: rjson.dumps_bytes(message), number=CALLS)
+dumps_time = timeit.timeit(lambda: rjson.dumps(message), number=CALLS)
+json_time = timeit.timeit(lambda: json.dumps(message, separators=(",", ":")).encode(), number=CALLS)
+assert rjson.dumps_bytes(message) == rjson.dumps(message).encode()
+
+print(f"\n--- {CALLS:,} calls on a {len(rjson.dumps_bytes(message))}-byte message ---")
+print(f"rjson.dumps_bytes: {bytes_time:.6f} seconds ({CALLS / bytes_time / 1e6:.2f}M/s)")
+print(f"rjson.dumps:       {dumps_time:.6f} seconds ({CALLS / dumps_time / 1e6:.2f}M/s)")
+print(f"json.dumps+encode: {json_time:.6f} seconds ({CALLS / json_time / 1e6:.2f}M/s)")
+
+# Peak RSS stays flat when the output buffer is reused rather than leaked
+big = [message] * 20_000
+before = resource.getrusage(resource.RUSAGE_SELF).ru_maxrss
+for _ in range(50):
+    rjson.dumps_bytes(big)
+after = resource.getrusage(resource.RUSAGE_SELF).ru_maxrss
+print(f"\npeak RSS growth over 50 x {len(rjson.dumps_bytes(big)) / 1e6:.1f} MB outputs: {(after - before) / 1024:.1f} MB")
@@ -0,0 +1,36 @@
+#!/usr/bin/env python3
+"""
+Benchmark loads of a 100k-element integer array.
+
+Flat integer arrays are parsed without serde: digit runs are found with
+SSE2 and converted 8 digits at a time.
+"""
+
+import json
+import random
+import timeit
+
+import rjson
+
+REPETITIONS = 50
+
+random.seed(0)
+cases = {
+    "small ints (0-999)": [random.randrange(1000) for _ in range(100_000)],
+    "ids (10-13 digits)": [random.randrange(10**9, 10**13) for _ in range(100_000)],
+    "signed 64-bit": [random.randrange(-2**63, 2**63) for _ in range(100_000)],
+}
+
+for name, values in cases.items():
+    text = json.dumps(values)
+    assert rjson.loads(text) == values
+
+    rjson_time = timeit.timeit(lambda: rjson.loads(text), number=REPETITIONS)
+    simd_time = timeit.timeit(lambda: rjson.loads_simd(text), number=REPETITIONS)
+    json_time = timeit.timeit(lambda: json.loads(text), number=REPETITIONS)
+
+    print(f"\n--- 100k-element array, {name} ---")
+    print(f"rjson.loads:      {rjson_time:.6f} seconds")
+    print(f"rjson.loads_simd: {simd_time:.6f} seconds")
+    print(f"json.loads:       {json_time:.6f} seconds")
+    print(f"\nrjson is {json_time / rjson_time:.2f}x faster than json")
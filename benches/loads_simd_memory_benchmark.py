@@ -0,0 +1,47 @@
+#!/usr/bin/env python3
+"""
+Benchmark loads_simd allocation churn on ~0.9MB and ~5MB documents.
+
+loads_simd keeps its input copy, simd-json stage buffers and tape in a
+per-thread scratch area of up to 64 MiB (enough for documents of about
+8 MiB), so repeated parses stop allocating (and faulting in) fresh buffers.
+Rust allocations are invisible to tracemalloc, so this counts minor page
+faults per call as a proxy for freshly touched heap memory.
+"""
+
+import json
+import resource
+import time
+
+import rjson
+
+REPETITIONS = 50
+
+
+def document(count):
+    data = [
+        {"id": i, "name": f"user_{i}", "tags": ["a", "b", "c"], "score": i * 0.5}
+        for i in range(count)
+    ]
+    doc = json.dumps(data)
+    assert rjson.loads_simd(doc) == data
+    return doc
+
+
+def measure(arg):
+    rjson.loads_simd(arg)  # warm up the scratch buffers
+    faults = resource.getrusage(resource.RUSAGE_SELF).ru_minflt
+    start = time.perf_counter()
+    for _ in range(REPETITIONS):
+        rjson.loads_simd(arg)
+    elapsed = time.perf_counter() - start
+    faults = resource.getrusage(resource.RUSAGE_SELF).ru_minflt - faults
+    return elapsed / REPETITIONS, faults / REPETITIONS
+
+
+for count in (12000, 70000):
+    doc = document(count)
+    print(f"\n--- loads_simd on a {len(doc) / 1e6:.1f}MB document ---")
+    for name, arg in [("str", doc), ("bytes", doc.encode()), ("bytearray", bytearray(doc.encode()))]:
+        seconds, faults = measure(arg)
+        print(f"{name:10} {seconds * 1000:8.2f} ms/call  {faults:8.0f} page faults/call")
@@ -0,0 +1,25 @@
+#!/usr/bin/env python3
+"""
+Benchmark uuid.UUID serialization.
+
+rjson formats UUIDs natively; stdlib json needs `default=str`.
+"""
+
+import json
+import timeit
+import uuid
+
+import rjson
+
+REPETITIONS = 100
+
+data = [{"id": uuid.uuid4(), "parent": uuid.uuid4(), "n": i} for i in range(10000)]
+
+rjson_time = timeit.timeit(lambda: rjson.dumps(data), number=REPETITIONS)
+json_time = timeit.timeit(lambda: json.dumps(data, default=str), number=REPETITIONS)
+assert rjson.loads(rjson.dumps(data)) == json.loads(json.dumps(data, default=str))
+
+print("\n--- 10k objects with 2 UUIDs each ---")
+print(f"rjson.dumps:             {rjson_time:.6f} seconds")
+print(f"json.dumps(default=str): {json_time:.6f} seconds")
+print(f"\nrjson is {json_time / rjson_time:.2f}x faster than json")
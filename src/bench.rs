@@ -0,0 +1,136 @@
+//! Benchmark helpers exposed to Python as `rjson.bench`
+//!
+//! Wraps the public `loads`/`dumps` variants (and stdlib `json`) in a timing
+//! harness so users can check the performance claims on their own data and
+//! hardware, and pick the engine that suits their workload.
+//!
+//! Every engine is called through its Python-level callable, so the numbers
+//! include the same call overhead a user would see.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::PyDict;
+use std::time::Instant;
+
+/// Serializers benchmarked against `json.dumps`
+const SERIALIZERS: &[&str] = &["dumps", "dumps_bytes"];
+
+/// Parsers benchmarked against `json.loads`
+const PARSERS: &[&str] = &["loads", "loads_simd"];
+
+/// Per-call timing statistics for one engine
+struct Timing {
+    total: f64,
+    min: f64,
+    max: f64,
+    iterations: usize,
+}
+
+impl Timing {
+    /// Time `iterations` calls of `func(arg)`, stopping at the first error
+    fn measure(func: &Bound<'_, PyAny>, arg: &Bound<'_, PyAny>, iterations: usize) -> PyResult<Self> {
+        let mut timing = Timing { total: 0.0, min: f64::INFINITY, max: 0.0, iterations };
+
+        for _ in 0..iterations {
+            let start = Instant::now();
+            func.call1((arg,))?;
+            let elapsed = start.elapsed().as_secs_f64();
+
+            timing.total += elapsed;
+            timing.min = timing.min.min(elapsed);
+            timing.max = timing.max.max(elapsed);
+        }
+
+        Ok(timing)
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>, baseline: Option<f64>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = PyDict::new(py);
+        stats.set_item("iterations", self.iterations)?;
+        stats.set_item("total", self.total)?;
+        stats.set_item("mean", self.total / self.iterations as f64)?;
+        stats.set_item("min", self.min)?;
+        stats.set_item("max", self.max)?;
+        if let Some(baseline) = baseline {
+            stats.set_item("speedup_vs_json", baseline / self.total)?;
+        }
+        Ok(stats)
+    }
+}
+
+/// Time one engine, recording its error message instead of failing the run
+///
+/// Not every engine supports every input (e.g. rjson rejects the NaN that
+/// `json.dumps` writes), and one unsupported engine shouldn't hide the
+/// other results.
+fn bench_engine<'py>(
+    py: Python<'py>,
+    func: &Bound<'py, PyAny>,
+    arg: &Bound<'py, PyAny>,
+    iterations: usize,
+    baseline: Option<f64>,
+) -> PyResult<Bound<'py, PyDict>> {
+    match Timing::measure(func, arg, iterations) {
+        Ok(timing) => timing.to_dict(py, baseline),
+        Err(err) => {
+            let stats = PyDict::new(py);
+            stats.set_item("error", err.to_string())?;
+            Ok(stats)
+        }
+    }
+}
+
+/// Benchmark every rjson engine and stdlib `json` on the given data.
+///
+/// Serializers are timed on `data` itself; parsers are timed on the
+/// `json.dumps(data)` document, so all engines see identical input.
+///
+/// # Arguments
+/// * `data` - The Python object to benchmark with.
+/// * `iterations` - Number of calls per engine (default 100).
+///
+/// # Returns
+/// A dict mapping engine name (e.g. `"dumps"`, `"json.loads"`) to a dict of
+/// `iterations`, `total`, `mean`, `min`, `max` (seconds) and, for rjson
+/// engines, `speedup_vs_json`. Engines that reject the input report `error`.
+#[pyfunction]
+#[pyo3(signature = (data, iterations = 100))]
+pub fn benchmark<'py>(
+    py: Python<'py>,
+    data: &Bound<'py, PyAny>,
+    iterations: usize,
+) -> PyResult<Bound<'py, PyDict>> {
+    if iterations == 0 {
+        return Err(PyValueError::new_err("iterations must be at least 1"));
+    }
+
+    let rjson = py.import("rjson")?;
+    let json = py.import("json")?;
+    let results = PyDict::new(py);
+
+    let json_dumps = json.getattr("dumps")?;
+    let json_loads = json.getattr("loads")?;
+    let document = json_dumps.call1((data,))?;
+
+    let dumps_baseline = Timing::measure(&json_dumps, data, iterations)?;
+    results.set_item("json.dumps", dumps_baseline.to_dict(py, None)?)?;
+    for &name in SERIALIZERS {
+        let stats = bench_engine(py, &rjson.getattr(name)?, data, iterations, Some(dumps_baseline.total))?;
+        results.set_item(name, stats)?;
+    }
+
+    let loads_baseline = Timing::measure(&json_loads, &document, iterations)?;
+    results.set_item("json.loads", loads_baseline.to_dict(py, None)?)?;
+    for &name in PARSERS {
+        let stats = bench_engine(py, &rjson.getattr(name)?, &document, iterations, Some(loads_baseline.total))?;
+        results.set_item(name, stats)?;
+    }
+
+    Ok(results)
+}
+
+/// Populate the `rjson.bench` submodule
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(benchmark, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! Global tunables exposed to Python as `rjson.configure` / `rjson.get_config`
+//!
+//! One validated entry point for the process-wide knobs scattered across the
+//! optimization modules (object cache, intern cache, SIMD dispatch, parse
+//! thread, default parse depth). `configure` validates every argument before applying any, so a
+//! bad value never leaves the settings half-updated.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::PyDict;
+use std::sync::atomic::Ordering;
+
+use crate::optimizations::{object_cache, simd_parser};
+#[cfg(target_arch = "x86_64")]
+use crate::optimizations::simd_escape;
+
+/// Largest accepted intern cache size
+const MAX_INTERN_CACHE_SIZE: usize = 1 << 20;
+
+/// Largest accepted intern length threshold (bytes)
+const MAX_INTERN_MAX_LEN: usize = 4096;
+
+/// SIMD level requested through `configure(simd_level=...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SimdLevel {
+    /// Detect from the CPU (default)
+    Auto,
+    /// Force AVX2 (32 bytes per step)
+    Avx2,
+    /// Force SSE2 (16 bytes per step, baseline x86_64)
+    Sse2,
+}
+
+impl SimdLevel {
+    fn from_name(name: &str) -> PyResult<Self> {
+        let level = match name {
+            "auto" => SimdLevel::Auto,
+            "avx2" => SimdLevel::Avx2,
+            "sse2" => SimdLevel::Sse2,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid simd_level: {name:?} (expected 'auto', 'avx2' or 'sse2')"
+                )))
+            }
+        };
+
+        #[cfg(target_arch = "x86_64")]
+        if level == SimdLevel::Avx2 && !is_x86_feature_detected!("avx2") {
+            return Err(PyValueError::new_err("simd_level 'avx2' is not supported by this CPU"));
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        if level != SimdLevel::Auto {
+            return Err(PyValueError::new_err(format!(
+                "simd_level {name:?} is only available on x86_64"
+            )));
+        }
+
+        Ok(level)
+    }
+
+    fn apply(self) {
+        #[cfg(target_arch = "x86_64")]
+        simd_escape::set_cpu_feature_level(match self {
+            SimdLevel::Auto => 0,
+            SimdLevel::Sse2 => 1,
+            SimdLevel::Avx2 => 2,
+        });
+    }
+
+    /// Name of the level currently in effect
+    fn current_name() -> &'static str {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if simd_escape::get_cpu_feature_level() == 2 { "avx2" } else { "sse2" }
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            "scalar"
+        }
+    }
+}
+
+/// Configure rjson's global tunables.
+///
+/// Every argument is optional; omitted settings are left unchanged. All
+/// arguments are validated before any is applied. Also frees the buffers
+/// `loads_simd` keeps between calls, as `clear_intern_cache` does.
+///
+/// # Arguments
+/// * `int_cache_max` - Cache int objects in `[-n, n]` for `loads` (default
+///   256, max 65536). Rebuilds the cache.
+/// * `intern_cache_size` - Maximum number of interned dict keys (and string
+///   values, under `loads(intern_values=True)`; default 1024). Shrinking
+///   below the current size drops learned strings.
+/// * `intern_max_len` - Longest string, in UTF-8 bytes, the intern cache
+///   keeps (default 32, max 4096). Lowering it drops longer cached strings.
+/// * `simd_level` - String escaping level: `"auto"` (detect), `"avx2"` or
+///   `"sse2"`.
+/// * `parse_stack_size` - Same as `set_parse_stack_size` (0 disables).
+/// * `default_max_depth` - Nesting limit `loads` and `loads_simd` apply when
+///   called without `max_depth` (default 1024, at least 1). Each level costs
+///   native stack, so raise it only as far as the documents you accept need;
+///   `loads` under `set_parse_stack_size` stays unlimited.
+#[pyfunction]
+#[pyo3(signature = (
+    *, int_cache_max = None, intern_cache_size = None, intern_max_len = None, simd_level = None,
+    parse_stack_size = None, default_max_depth = None,
+))]
+pub fn configure(
+    py: Python,
+    int_cache_max: Option<i64>,
+    intern_cache_size: Option<usize>,
+    intern_max_len: Option<usize>,
+    simd_level: Option<&str>,
+    parse_stack_size: Option<usize>,
+    default_max_depth: Option<usize>,
+) -> PyResult<()> {
+    if let Some(max) = int_cache_max {
+        if !(0..=object_cache::MAX_INT_CACHE_MAX).contains(&max) {
+            return Err(PyValueError::new_err(format!(
+                "int_cache_max must be between 0 and {}, got {max}",
+                object_cache::MAX_INT_CACHE_MAX
+            )));
+        }
+    }
+    if let Some(size) = intern_cache_size {
+        check_intern_cache_size(size)?;
+    }
+    if let Some(len) = intern_max_len {
+        check_intern_max_len(len)?;
+    }
+    let simd_level = simd_level.map(SimdLevel::from_name).transpose()?;
+    if let Some(bytes) = parse_stack_size {
+        crate::check_parse_stack_size(bytes)?;
+    }
+    if default_max_depth == Some(0) {
+        return Err(PyValueError::new_err("default_max_depth must be at least 1, got 0"));
+    }
+
+    if let Some(max) = int_cache_max {
+        if max != object_cache::int_cache_max(py) {
+            object_cache::set_int_cache_max(py, max);
+        }
+    }
+    if let Some(size) = intern_cache_size {
+        simd_parser::set_intern_cache_size(size);
+    }
+    if let Some(len) = intern_max_len {
+        simd_parser::set_intern_max_len(len);
+    }
+    if let Some(level) = simd_level {
+        level.apply();
+    }
+    if let Some(bytes) = parse_stack_size {
+        crate::PARSE_STACK_SIZE.store(bytes, Ordering::Relaxed);
+    }
+    if let Some(depth) = default_max_depth {
+        simd_parser::set_default_max_depth(depth);
+    }
+    simd_parser::release_scratch();
+    Ok(())
+}
+
+/// Return the current global tunables.
+///
+/// # Returns
+/// A dict with the same keys `configure` accepts. `simd_level` reports the
+/// level in effect (`"avx2"`, `"sse2"`, or `"scalar"` off x86_64).
+#[pyfunction]
+pub fn get_config(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let config = PyDict::new(py);
+    config.set_item("int_cache_max", object_cache::int_cache_max(py))?;
+    config.set_item("intern_cache_size", simd_parser::intern_cache_size())?;
+    config.set_item("intern_max_len", simd_parser::intern_max_len())?;
+    config.set_item("simd_level", SimdLevel::current_name())?;
+    config.set_item("parse_stack_size", crate::PARSE_STACK_SIZE.load(Ordering::Relaxed))?;
+    config.set_item("default_max_depth", simd_parser::default_max_depth())?;
+    Ok(config)
+}
+
+/// Set how many strings the intern cache may hold.
+///
+/// Same as `configure(intern_cache_size=size)`.
+#[pyfunction]
+pub fn set_intern_cache_size(size: usize) -> PyResult<()> {
+    check_intern_cache_size(size)?;
+    simd_parser::set_intern_cache_size(size);
+    Ok(())
+}
+
+/// Set the longest string, in UTF-8 bytes, the intern cache keeps.
+///
+/// Same as `configure(intern_max_len=length)`. Applies to dict keys and,
+/// under `loads(intern_values=True)`, string values; 0 stops caching new
+/// strings.
+#[pyfunction]
+pub fn set_intern_max_len(length: usize) -> PyResult<()> {
+    check_intern_max_len(length)?;
+    simd_parser::set_intern_max_len(length);
+    Ok(())
+}
+
+/// Drop every string the intern cache has learned, releasing its memory.
+///
+/// The pre-interned common keys (`"id"`, `"name"`, ...) are kept; strings
+/// already returned by `loads` are unaffected. Also frees the buffers
+/// `loads_simd` keeps between calls: the calling thread's now, other
+/// threads' on their next `loads_simd` call.
+#[pyfunction]
+pub fn clear_intern_cache() {
+    simd_parser::clear_intern_cache();
+    simd_parser::release_scratch();
+}
+
+fn check_intern_cache_size(size: usize) -> PyResult<()> {
+    if size > MAX_INTERN_CACHE_SIZE {
+        return Err(PyValueError::new_err(format!(
+            "intern_cache_size must be at most {MAX_INTERN_CACHE_SIZE}, got {size}"
+        )));
+    }
+    Ok(())
+}
+
+fn check_intern_max_len(len: usize) -> PyResult<()> {
+    if len > MAX_INTERN_MAX_LEN {
+        return Err(PyValueError::new_err(format!(
+            "intern_max_len must be at most {MAX_INTERN_MAX_LEN}, got {len}"
+        )));
+    }
+    Ok(())
+}
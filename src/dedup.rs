@@ -0,0 +1,126 @@
+//! Structural deduplication for `loads(dedup=True)`
+//!
+//! Documents that repeat the same values many times (a status string in
+//! every record, the same coordinates per row) parse into as many identical
+//! objects. This pass hash-conses the immutable parts of the freshly parsed
+//! tree bottom-up: strings, numbers, and tuples (`use_tuples`) whose items
+//! are all immutable. Once a tuple's items are canonical, two tuples are
+//! equal exactly when their item identities match, so each is keyed on its
+//! items' pointers and looked up in one table. Repeats are replaced by the
+//! first occurrence and freed.
+//!
+//! It runs after the whole tree has been built, so it shrinks what the
+//! caller keeps but not the peak: every duplicate exists until the pass
+//! reaches it.
+//!
+//! Dicts and lists are never shared, since mutating one occurrence would
+//! change every other; their contents are deduplicated in place.
+
+use ahash::AHashMap;
+use pyo3::prelude::*;
+use pyo3::ffi;
+use pyo3::types::{PyDict, PyFloat, PyList, PyString, PyTuple};
+
+use crate::optimizations::type_cache::{self, FastType};
+
+/// Structural identity of an immutable node whose items are already canonical
+#[derive(PartialEq, Eq, Hash)]
+enum NodeKey {
+    Str(String),
+    Int(i64),
+    /// Ints outside i64, keyed on their decimal digits
+    BigInt(String),
+    Float(u64),
+    Tuple(Vec<usize>),
+}
+
+/// Canonical object for each immutable structure seen so far
+///
+/// Holding the canonical objects keeps their pointers (used in tuple keys)
+/// valid for the whole pass.
+struct Interner {
+    table: AHashMap<NodeKey, PyObject>,
+}
+
+impl Interner {
+    /// The canonical object for `obj`, and whether it's immutable (and so
+    /// may be shared by a containing tuple's key)
+    fn canonical(&mut self, py: Python, obj: PyObject) -> PyResult<(PyObject, bool)> {
+        let key = {
+            let bound = obj.bind(py);
+            match type_cache::get_fast_type(bound) {
+                FastType::String => NodeKey::Str(bound.downcast::<PyString>()?.to_str()?.to_owned()),
+                FastType::Int => match bound.extract::<i64>() {
+                    Ok(v) => NodeKey::Int(v),
+                    Err(_) => NodeKey::BigInt(bound.str()?.to_str()?.to_owned()),
+                },
+                FastType::Float => NodeKey::Float(bound.downcast::<PyFloat>()?.value().to_bits()),
+                FastType::Tuple => {
+                    let (ids, tuple) = self.canonical_tuple(py, bound.downcast::<PyTuple>()?)?;
+                    return Ok(match ids {
+                        Some(ids) => (self.table.entry(NodeKey::Tuple(ids)).or_insert(tuple).clone_ref(py), true),
+                        // Holds a dict or list, so it must stay distinct
+                        None => (tuple, false),
+                    });
+                }
+                FastType::List => {
+                    self.canonical_list(py, bound.downcast::<PyList>()?)?;
+                    return Ok((obj, false));
+                }
+                FastType::Dict => {
+                    self.canonical_dict(py, bound.downcast::<PyDict>()?)?;
+                    return Ok((obj, false));
+                }
+                // None and bools are singletons already
+                _ => return Ok((obj, true)),
+            }
+        };
+
+        Ok((self.table.entry(key).or_insert(obj).clone_ref(py), true))
+    }
+
+    /// Canonicalize a list's items in place
+    fn canonical_list(&mut self, py: Python, list: &Bound<'_, PyList>) -> PyResult<()> {
+        for (i, item) in list.iter().enumerate() {
+            let (item, _) = self.canonical(py, item.unbind())?;
+            // SAFETY: `i` is in bounds; PyList_SetItem steals the new
+            // reference and releases the replaced item
+            unsafe { ffi::PyList_SetItem(list.as_ptr(), i as ffi::Py_ssize_t, item.into_ptr()) };
+        }
+        Ok(())
+    }
+
+    /// Canonicalize a tuple's items, returning a tuple of the canonical items
+    /// (tuples can't be updated in place) and, if every item is immutable,
+    /// their pointers
+    fn canonical_tuple(&mut self, py: Python, tuple: &Bound<'_, PyTuple>) -> PyResult<(Option<Vec<usize>>, PyObject)> {
+        let mut immutable = true;
+        let mut items = Vec::with_capacity(tuple.len());
+        for item in tuple.iter() {
+            let (item, item_immutable) = self.canonical(py, item.unbind())?;
+            immutable &= item_immutable;
+            items.push(item);
+        }
+        let ids = immutable.then(|| items.iter().map(|item| item.as_ptr() as usize).collect());
+        Ok((ids, PyTuple::new(py, items)?.into_any().unbind()))
+    }
+
+    /// Canonicalize a dict's values in place
+    ///
+    /// Keys stay as they are (replacing them would mean rebuilding the dict);
+    /// `intern_keys`, the default, already shares repeated keys.
+    fn canonical_dict(&mut self, py: Python, dict: &Bound<'_, PyDict>) -> PyResult<()> {
+        let entries: Vec<_> = dict.iter().collect();
+        for (key, value) in entries {
+            let (value, _) = self.canonical(py, value.unbind())?;
+            dict.set_item(key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Replace repeated immutable values in `root` with shared objects
+pub(crate) fn dedup_tree(py: Python, root: PyObject) -> PyResult<PyObject> {
+    let mut interner = Interner { table: AHashMap::new() };
+    Ok(interner.canonical(py, root)?.0)
+}
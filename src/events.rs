@@ -0,0 +1,193 @@
+//! Event-driven (SAX-style) parsing exposed to Python as `rjson.parse_events`
+//!
+//! The serde_json parser drives a visitor that calls methods on a
+//! user-supplied handler instead of building dicts and lists, so a document
+//! can be filtered or aggregated while only the current scalar is ever
+//! materialized. Scalars and keys are created exactly as `loads` creates
+//! them.
+
+use pyo3::prelude::*;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+
+use crate::{KeySeed, LoadsOptions, PyObjectVisitor};
+
+/// The handler's bound event methods; `None` for the ones it doesn't define
+struct Handler<'py> {
+    object_start: Option<Bound<'py, PyAny>>,
+    object_end: Option<Bound<'py, PyAny>>,
+    key: Option<Bound<'py, PyAny>>,
+    array_start: Option<Bound<'py, PyAny>>,
+    array_end: Option<Bound<'py, PyAny>>,
+    value: Option<Bound<'py, PyAny>>,
+}
+
+impl<'py> Handler<'py> {
+    fn new(handler: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let method = |name: &str| -> PyResult<Option<Bound<'py, PyAny>>> {
+            if handler.hasattr(name)? {
+                Ok(Some(handler.getattr(name)?))
+            } else {
+                Ok(None)
+            }
+        };
+        Ok(Handler {
+            object_start: method("on_object_start")?,
+            object_end: method("on_object_end")?,
+            key: method("on_key")?,
+            array_start: method("on_array_start")?,
+            array_end: method("on_array_end")?,
+            value: method("on_value")?,
+        })
+    }
+}
+
+/// Call an event method, if the handler defines it
+///
+/// serde errors only carry a message, so the handler's exception is parked
+/// in the interpreter's error indicator for `parse_events` to re-raise.
+fn emit<E: de::Error>(method: &Option<Bound<'_, PyAny>>, arg: Option<PyObject>) -> Result<(), E> {
+    let Some(method) = method else {
+        return Ok(());
+    };
+    let py = method.py();
+    let result = match arg {
+        Some(arg) => method.call1((arg,)),
+        None => method.call0(),
+    };
+    result.map(drop).map_err(|e| {
+        e.restore(py);
+        E::custom("parse_events handler raised")
+    })
+}
+
+/// Seed for one value: emits its events instead of returning it
+struct EventSeed<'a, 'py> {
+    handler: &'a Handler<'py>,
+    opts: &'a LoadsOptions,
+}
+
+impl<'de> DeserializeSeed<'de> for EventSeed<'_, '_> {
+    type Value = ();
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl EventSeed<'_, '_> {
+    /// Emit `on_value` with the scalar `loads` would have produced
+    fn scalar<E: de::Error>(
+        &self,
+        build: impl FnOnce(PyObjectVisitor<'_, '_>) -> Result<PyObject, E>,
+    ) -> Result<(), E> {
+        let Some(method) = &self.handler.value else {
+            return Ok(());
+        };
+        let value = build(PyObjectVisitor { py: method.py(), opts: self.opts, depth: 0 })?;
+        emit(&self.handler.value, Some(value))
+    }
+}
+
+impl<'de> Visitor<'de> for EventSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<(), E> {
+        self.scalar(|visitor| visitor.visit_bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<(), E> {
+        self.scalar(|visitor| visitor.visit_i64(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<(), E> {
+        self.scalar(|visitor| visitor.visit_u64(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<(), E> {
+        self.scalar(|visitor| visitor.visit_f64(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<(), E> {
+        self.scalar(|visitor| visitor.visit_str(v))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        self.scalar(|visitor| visitor.visit_unit())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        emit(&self.handler.array_start, None)?;
+        while seq.next_element_seed(EventSeed { handler: self.handler, opts: self.opts })?.is_some() {}
+        emit(&self.handler.array_end, None)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        emit(&self.handler.object_start, None)?;
+        loop {
+            match &self.handler.key {
+                Some(method) => match map.next_key_seed(KeySeed { py: method.py(), opts: self.opts })? {
+                    Some(key) => emit(&self.handler.key, Some(key))?,
+                    None => break,
+                },
+                // Keys nobody asked for are never turned into objects
+                None => {
+                    if map.next_key::<de::IgnoredAny>()?.is_none() {
+                        break;
+                    }
+                }
+            }
+            map.next_value_seed(EventSeed { handler: self.handler, opts: self.opts })?;
+        }
+        emit(&self.handler.object_end, None)
+    }
+}
+
+/// Parse a JSON document, reporting its structure to `handler` instead of
+/// building Python objects.
+///
+/// Each event calls the matching method of `handler`, if it defines one:
+/// `on_object_start()`, `on_key(key)`, `on_object_end()`,
+/// `on_array_start()`, `on_array_end()` and `on_value(value)` (for every
+/// string, number, bool and null, created as `loads` would). Only the
+/// current key or scalar is alive at any time, so memory stays constant
+/// however large the document, and scalars aren't even created if there's
+/// no `on_value`.
+///
+/// Events are delivered while parsing, so a malformed document raises
+/// `JSONDecodeError` after the events for the part before the error have
+/// been handled. Exceptions raised by the handler stop the parse and
+/// propagate unchanged. Nesting is limited to 127 levels (serde_json's
+/// recursion limit), well short of the 1024 `loads` accepts, and
+/// `configure(default_max_depth=...)` doesn't change it.
+///
+/// # Arguments
+/// * `data` - The document (`str`, `bytes` or `bytearray`), as for `loads`.
+/// * `handler` - Any object with some of the methods above.
+/// * `intern_keys` - As for `loads`.
+#[pyfunction]
+#[pyo3(signature = (data, handler, *, intern_keys = true))]
+pub fn parse_events(data: &Bound<'_, PyAny>, handler: &Bound<'_, PyAny>, intern_keys: bool) -> PyResult<()> {
+    let py = data.py();
+    let doc = &*crate::json_text(data)?;
+    let handler = Handler::new(handler)?;
+    let opts = LoadsOptions { intern_keys, ..LoadsOptions::default() };
+
+    let mut de = serde_json::Deserializer::from_str(doc);
+    EventSeed { handler: &handler, opts: &opts }
+        .deserialize(&mut de)
+        .and_then(|()| de.end())
+        .map_err(|e| PyErr::take(py).unwrap_or_else(|| crate::decode_error(doc, e)))
+}
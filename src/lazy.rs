@@ -0,0 +1,272 @@
+//! Lazy parse mode exposed to Python as `loads(..., lazy=True)`
+//!
+//! Programs that parse a large document only to read a few fields pay for
+//! building every dict, list and string in it. In lazy mode the document is
+//! validated once (so errors read exactly like `loads`), then each container
+//! is only *indexed*: the scanner records where every child starts and ends
+//! and skips over it. A child is decoded the first time it is accessed, and
+//! nested containers become `LazyValue`s themselves, so untouched subtrees
+//! are never built. Decoded children are memoized.
+//!
+//! Every proxy shares one copy of the source text, which stays alive as long
+//! as any proxy into it does.
+
+use std::sync::{Arc, OnceLock};
+
+use ahash::AHashMap;
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyIndexError, PyKeyError, PyTypeError};
+use pyo3::types::{PyList, PyString};
+use serde::de::{IgnoredAny, Visitor};
+
+use crate::scan::{decode, truncated_error, Cursor, Range};
+use crate::{KeySeed, LoadsOptions, PyObjectSeed};
+
+/// Child layout of a container
+enum Index {
+    Array(Vec<Range>),
+    Object {
+        /// Keys in first-occurrence order, as `loads` would insert them
+        keys: Vec<PyObject>,
+        /// Value range per key (a repeated key keeps its last value)
+        values: Vec<Range>,
+        lookup: AHashMap<String, usize>,
+    },
+}
+
+/// A not-yet-decoded JSON object or array
+///
+/// Supports the read-only mapping/sequence protocol: `len()`, indexing,
+/// iteration (keys for objects, items for arrays), `in`, and for objects
+/// `keys()`, `values()`, `items()` and `get()`. `materialize()` decodes the
+/// whole subtree into plain dicts and lists; `==` compares that decoded form,
+/// so a proxy equals the `loads` result for the same text, and `dumps`
+/// writes it.
+#[pyclass(module = "rjson", frozen)]
+pub struct LazyValue {
+    src: Arc<str>,
+    range: Range,
+    opts: LoadsOptions,
+    index: Index,
+    /// Decoded children, filled on first access
+    children: Vec<OnceLock<PyObject>>,
+}
+
+impl LazyValue {
+    /// Index the container at `range` of `src`
+    fn new(py: Python, src: Arc<str>, range: Range, opts: LoadsOptions) -> PyResult<Self> {
+        let mut cursor = Cursor { bytes: src.as_bytes(), pos: range.0 };
+        let is_object = cursor.next_byte() == Some(b'{');
+        cursor.skip_whitespace();
+        let empty = matches!(cursor.peek(), Some(b'}' | b']'));
+
+        let index = if is_object {
+            let mut keys = Vec::new();
+            let mut values: Vec<Range> = Vec::new();
+            let mut lookup = AHashMap::new();
+            if !empty {
+                loop {
+                    let key_range = cursor.value_range().ok_or_else(|| truncated_error(&src))?;
+                    let mut key: String = decode(&src, key_range, std::marker::PhantomData)?;
+                    if let Some(form) = opts.normalize {
+                        if !key.is_ascii() {
+                            key = crate::normalize_str(py, &key, form)?.to_str()?.to_owned();
+                        }
+                    }
+                    cursor.skip_whitespace();
+                    cursor.next_byte(); // ':'
+                    let value_range = cursor.value_range().ok_or_else(|| truncated_error(&src))?;
+
+                    match lookup.get(&key) {
+                        Some(&slot) => values[slot] = value_range,
+                        None => {
+                            let seed = KeySeed { py, opts: &opts };
+                            let py_key = seed.visit_str::<serde_json::Error>(&key);
+                            keys.push(py_key.map_err(|e| crate::decode_error_in(&src, key_range, e))?);
+                            lookup.insert(key, values.len());
+                            values.push(value_range);
+                        }
+                    }
+
+                    cursor.skip_whitespace();
+                    if cursor.next_byte() != Some(b',') {
+                        break;
+                    }
+                }
+            }
+            Index::Object { keys, values, lookup }
+        } else {
+            let mut items = Vec::new();
+            if !empty {
+                loop {
+                    items.push(cursor.value_range().ok_or_else(|| truncated_error(&src))?);
+                    cursor.skip_whitespace();
+                    if cursor.next_byte() != Some(b',') {
+                        break;
+                    }
+                }
+            }
+            Index::Array(items)
+        };
+
+        let len = match &index {
+            Index::Array(items) => items.len(),
+            Index::Object { values, .. } => values.len(),
+        };
+        Ok(LazyValue {
+            src,
+            range,
+            opts,
+            index,
+            children: (0..len).map(|_| OnceLock::new()).collect(),
+        })
+    }
+
+    fn ranges(&self) -> &[Range] {
+        match &self.index {
+            Index::Array(items) => items,
+            Index::Object { values, .. } => values,
+        }
+    }
+
+    /// Decoded child `i`: a `LazyValue` for containers, a plain value otherwise
+    fn child(&self, py: Python, i: usize) -> PyResult<PyObject> {
+        if let Some(value) = self.children[i].get() {
+            return Ok(value.clone_ref(py));
+        }
+        let value = make_value(py, &self.src, self.ranges()[i], &self.opts)?;
+        Ok(self.children[i].get_or_init(|| value).clone_ref(py))
+    }
+
+    fn object_slot(&self, key: &Bound<'_, PyAny>) -> PyResult<Option<usize>> {
+        let Index::Object { lookup, .. } = &self.index else {
+            return Ok(None);
+        };
+        let Ok(key) = key.downcast::<PyString>() else {
+            return Ok(None);
+        };
+        Ok(lookup.get(key.to_str()?).copied())
+    }
+
+    fn is_object(&self) -> bool {
+        matches!(self.index, Index::Object { .. })
+    }
+
+    fn require_object(&self, method: &str) -> PyResult<()> {
+        if self.is_object() {
+            Ok(())
+        } else {
+            Err(PyTypeError::new_err(format!("LazyValue.{method}() requires a JSON object")))
+        }
+    }
+}
+
+#[pymethods]
+impl LazyValue {
+    fn __len__(&self) -> usize {
+        self.children.len()
+    }
+
+    fn __getitem__(&self, py: Python, key: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        if self.is_object() {
+            return match self.object_slot(key)? {
+                Some(slot) => self.child(py, slot),
+                None => Err(PyKeyError::new_err(key.clone().unbind())),
+            };
+        }
+
+        let index: isize = key.extract()?;
+        let len = self.children.len() as isize;
+        let i = if index < 0 { index + len } else { index };
+        if !(0..len).contains(&i) {
+            return Err(PyIndexError::new_err("LazyValue index out of range"));
+        }
+        self.child(py, i as usize)
+    }
+
+    fn __contains__(&self, py: Python, value: &Bound<'_, PyAny>) -> PyResult<bool> {
+        if self.is_object() {
+            return Ok(self.object_slot(value)?.is_some());
+        }
+        for i in 0..self.children.len() {
+            if self.child(py, i)?.bind(py).eq(value)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn __iter__(&self, py: Python) -> PyResult<PyObject> {
+        let items = if self.is_object() { self.keys(py)? } else { self.values(py)? };
+        Ok(items.call_method0("__iter__")?.unbind())
+    }
+
+    fn keys<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        self.require_object("keys")?;
+        let Index::Object { keys, .. } = &self.index else { unreachable!() };
+        PyList::new(py, keys.iter().map(|k| k.clone_ref(py)))
+    }
+
+    fn values<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let values = (0..self.children.len()).map(|i| self.child(py, i)).collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, values)
+    }
+
+    fn items<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        self.require_object("items")?;
+        let Index::Object { keys, .. } = &self.index else { unreachable!() };
+        let items = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| Ok((k.clone_ref(py), self.child(py, i)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, items)
+    }
+
+    #[pyo3(signature = (key, default = None))]
+    fn get(&self, py: Python, key: &Bound<'_, PyAny>, default: Option<PyObject>) -> PyResult<PyObject> {
+        self.require_object("get")?;
+        match self.object_slot(key)? {
+            Some(slot) => self.child(py, slot),
+            None => Ok(default.unwrap_or_else(|| py.None())),
+        }
+    }
+
+    /// Decode this whole subtree into plain dicts and lists
+    pub(crate) fn materialize(&self, py: Python) -> PyResult<PyObject> {
+        decode(&self.src, self.range, PyObjectSeed { py, opts: &self.opts, depth: 0 })
+    }
+
+    /// Compare the decoded subtree (both sides', for two proxies)
+    fn __eq__(&self, py: Python, other: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let other = match other.downcast::<LazyValue>() {
+            Ok(lazy) => lazy.get().materialize(py)?.into_bound(py),
+            Err(_) => other.clone(),
+        };
+        self.materialize(py)?.bind(py).eq(other)
+    }
+
+    fn __repr__(&self) -> String {
+        let (kind, unit) = if self.is_object() { ("object", "keys") } else { ("array", "items") };
+        format!("<rjson.LazyValue {kind} with {} {unit}>", self.children.len())
+    }
+}
+
+/// A proxy for containers, the decoded value for scalars
+fn make_value(py: Python, src: &Arc<str>, range: Range, opts: &LoadsOptions) -> PyResult<PyObject> {
+    if matches!(src.as_bytes()[range.0], b'{' | b'[') {
+        let lazy = LazyValue::new(py, Arc::clone(src), range, opts.clone())?;
+        return Ok(Py::new(py, lazy)?.into_any());
+    }
+    decode(src, range, PyObjectSeed { py, opts, depth: 0 })
+}
+
+/// Parse lazily: validate the whole document, then index only the root
+pub(crate) fn loads_lazy(py: Python, json_str: &str, opts: &LoadsOptions) -> PyResult<PyObject> {
+    serde_json::from_str::<IgnoredAny>(json_str).map_err(|e| crate::decode_error(json_str, e))?;
+
+    let src: Arc<str> = Arc::from(json_str);
+    let mut cursor = Cursor { bytes: src.as_bytes(), pos: 0 };
+    let range = cursor.value_range().ok_or_else(|| truncated_error(&src))?;
+    make_value(py, &src, range, opts)
+}
@@ -1,13 +1,47 @@
 use pyo3::prelude::*;
-use pyo3::exceptions::PyValueError;
-use pyo3::types::{PyBool, PyFloat, PyInt, PyString, PyList, PyTuple, PyDict, PyAny, PyBytes};
+use pyo3::exceptions::{PyException, PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::types::{PyBool, PyFloat, PyInt, PyString, PyList, PyTuple, PyDict, PyAny, PyByteArray, PyBytes, PyDate, PyDateAccess, PyDateTime, PyDelta, PyDeltaAccess, PySet, PyTime, PyTimeAccess, PyTzInfoAccess};
 use pyo3::ffi;  // For direct C API access
+use pyo3::buffer::PyBuffer;
 use serde::de::{self, Visitor, MapAccess, SeqAccess, Deserializer, DeserializeSeed};
 use std::fmt;
+use std::borrow::Cow;
+use std::sync::Arc;
+use ahash::AHashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 // Performance optimizations module
 mod optimizations;
-use optimizations::{object_cache, type_cache, bulk, extreme, simd_parser, simd_escape, unlikely};
+
+// Benchmark helpers (rjson.bench)
+mod bench;
+// Source-span tracking parser (loads_with_spans)
+mod spans;
+// Custom type serializers (public Rust API)
+pub mod registry;
+// Global tunables (rjson.configure / rjson.get_config)
+mod config;
+// Structural deduplication (loads(dedup=True))
+mod dedup;
+// On-demand subtree parsing (loads(lazy=True))
+mod lazy;
+// Raw value skipping, single-field extraction (json_get) and prefix parsing
+mod scan;
+// Dataclass construction (loads(into=...))
+mod typed;
+// Newline-delimited JSON (dumps_lines / loads_lines)
+mod lines;
+// Comments and trailing commas (loads(allow_comments=True, ...))
+mod relaxed;
+// Incremental decoding of chunked input (JsonStreamDecoder)
+mod stream;
+// Callback-driven parsing without building objects (parse_events)
+mod events;
+// numpy scalars and arrays in dumps (optional)
+#[cfg(feature = "numpy")]
+mod numpy;
+use optimizations::{object_cache, type_cache, ascii_str, bulk, extreme, simd_parser, simd_escape, int_array, likely, unlikely};
+use simd_escape::EscapePolicy;
 use type_cache::FastType;
 
 // ============================================================================
@@ -19,58 +53,8 @@ use type_cache::FastType;
 //
 // For ASCII strings (the common case in JSON), we can access the buffer directly
 // by reading the PyASCIIObject structure. This matches what orjson does.
-//
-// WARNING: This is CPython-specific and version-dependent!
-// Tested on Python 3.8-3.13. The layout has been stable since Python 3.3.
-
-/// Simplified PyASCIIObject structure (CPython internal)
-/// We only need the fields up to and including the state flags.
-#[repr(C)]
-struct PyASCIIObject {
-    /// PyObject_HEAD: ob_refcnt, ob_type
-    _ob_refcnt: isize,
-    _ob_type: *mut ffi::PyTypeObject,
-    /// String length (number of characters, not bytes for non-ASCII)
-    length: isize,
-    /// Cached hash value (-1 if not computed)
-    _hash: isize,
-    /// State flags packed as a u32
-    /// Bits: interned(2), kind(3), compact(1), ascii(1), ready(1), ...
-    state: u32,
-}
-
-/// Bit mask to extract the 'ascii' flag from state
-/// The ascii flag is bit 6 (after interned:2, kind:3, compact:1)
-const STATE_ASCII_MASK: u32 = 0b01000000;  // bit 6
-
-/// Offset from PyASCIIObject to the actual character data
-/// For compact ASCII strings, data follows immediately after:
-/// PyASCIIObject (on 64-bit: 8+8+8+8+4 = 36, aligned to 40) + wstr (8) = 48
-/// But actually for ASCII-only compact strings, there's no wstr field stored,
-/// so the data starts right after the null terminator padding.
-///
-/// The correct formula: sizeof(PyASCIIObject) rounded up to pointer alignment
-/// On 64-bit Linux: sizeof(PyASCIIObject) = 40, data at offset 40
-/// But we need to account for the compact representation!
-///
-/// For Python 3.12+: The structure is:
-/// - PyObject_HEAD (16 bytes)
-/// - length (8 bytes)
-/// - hash (8 bytes)
-/// - state (4 bytes + 4 padding) = 40 total
-/// - Then string data follows for compact ASCII
-///
-/// Actually, let me be more careful. The safest approach is to use the
-/// PyUnicode_DATA macro equivalent, which is:
-/// ((void*)((PyASCIIObject*)(op))->data) for non-legacy strings
-/// But actually compact strings store data inline after the struct.
-///
-/// For maximum safety, compute offset based on known structure:
-#[cfg(target_pointer_width = "64")]
-const ASCII_DATA_OFFSET: usize = 48;  // PyASCIIObject(40) + padding to 8-byte alignment for data
-
-#[cfg(target_pointer_width = "32")]
-const ASCII_DATA_OFFSET: usize = 24;  // PyASCIIObject(20) + padding
+// The layout is CPython-specific; `ascii_str` verifies it at module init and
+// falls back to the C API if it doesn't match.
 
 // Note: Phase 10.7 attempted inline UTF-8 encoding by reading PyUnicode_KIND
 // and encoding UCS-2/UCS-4 data directly. However, this was slower than
@@ -82,21 +66,18 @@ const ASCII_DATA_OFFSET: usize = 24;  // PyASCIIObject(20) + padding
 /// Write a JSON string directly from Python's internal Unicode buffer.
 /// Uses ASCII fast path when possible, falls back to cached UTF-8 for non-ASCII.
 ///
+/// Strings with lone surrogates have no UTF-8 form and raise the
+/// `UnicodeEncodeError` from encoding them, writing nothing.
+///
 /// # Safety
 /// Caller must ensure str_ptr is a valid PyUnicode object
 #[inline]
-unsafe fn write_json_string_direct(buf: &mut Vec<u8>, str_ptr: *mut ffi::PyObject) {
-    let ascii_obj = str_ptr as *const PyASCIIObject;
-    let state = (*ascii_obj).state;
-    let length = (*ascii_obj).length as usize;
-
+unsafe fn write_json_string_direct(buf: &mut Vec<u8>, str_ptr: *mut ffi::PyObject, escape: EscapePolicy) -> PyResult<()> {
     // Check ASCII flag first (most common case in JSON)
-    if state & STATE_ASCII_MASK != 0 {
+    if let Some(bytes) = ascii_str::ascii_data(str_ptr) {
         // FAST PATH: Pure ASCII - direct buffer access, no conversion needed
-        let data_ptr = (str_ptr as *const u8).add(ASCII_DATA_OFFSET);
-        let bytes = std::slice::from_raw_parts(data_ptr, length);
-        simd_escape::write_json_string_simd(buf, std::str::from_utf8_unchecked(bytes));
-        return;
+        write_json_string_with(buf, std::str::from_utf8_unchecked(bytes), escape);
+        return Ok(());
     }
 
     // Non-ASCII path: Use PyUnicode_AsUTF8AndSize which benefits from Python's UTF-8 cache
@@ -105,10 +86,12 @@ unsafe fn write_json_string_direct(buf: &mut Vec<u8>, str_ptr: *mut ffi::PyObjec
     // 2. No benefit from Python's UTF-8 cache on repeated calls
     let mut size: ffi::Py_ssize_t = 0;
     let utf8_ptr = ffi::PyUnicode_AsUTF8AndSize(str_ptr, &mut size);
-    if !utf8_ptr.is_null() {
-        let bytes = std::slice::from_raw_parts(utf8_ptr as *const u8, size as usize);
-        simd_escape::write_json_string_simd(buf, std::str::from_utf8_unchecked(bytes));
+    if utf8_ptr.is_null() {
+        return Err(PyErr::fetch(Python::assume_gil_acquired()));
     }
+    let bytes = std::slice::from_raw_parts(utf8_ptr as *const u8, size as usize);
+    write_json_string_with(buf, std::str::from_utf8_unchecked(bytes), escape);
+    Ok(())
 }
 
 // Note: Inline UTF-8 encoding functions (write_json_string_latin1, write_json_string_ucs2,
@@ -133,11 +116,28 @@ unsafe fn write_json_string_direct(buf: &mut Vec<u8>, str_ptr: *mut ffi::PyObjec
 /// - Direct C API calls for string/int/float creation (bypasses PyO3 overhead)
 /// - Direct list creation with PyList_New + PyList_SET_ITEM (avoids Vec intermediate)
 /// - Direct dict creation with PyDict_New + PyDict_SetItem
-struct PyObjectVisitor<'py> {
+struct PyObjectVisitor<'a, 'py> {
     py: Python<'py>,
+    opts: &'a LoadsOptions,
+    /// Containers enclosing this value
+    depth: usize,
+}
+
+impl PyObjectVisitor<'_, '_> {
+    /// Depth of a container opened here, checked against `loads(max_depth=...)`
+    #[inline]
+    fn enter<E: de::Error>(&self) -> Result<usize, E> {
+        let depth = self.depth + 1;
+        match self.opts.max_depth {
+            Some(max_depth) if depth > max_depth => {
+                Err(E::custom(format_args!("nesting exceeds max_depth ({max_depth})")))
+            }
+            _ => Ok(depth),
+        }
+    }
 }
 
-impl<'de, 'py> Visitor<'de> for PyObjectVisitor<'py> {
+impl<'de, 'a, 'py> Visitor<'de> for PyObjectVisitor<'a, 'py> {
     type Value = PyObject;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -152,24 +152,15 @@ impl<'de, 'py> Visitor<'de> for PyObjectVisitor<'py> {
 
     #[inline]
     fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
-        // OPTIMIZATION: Inline cache check to avoid function call overhead
-        // Only use cache for small values where it's beneficial
-        if v >= -256 && v <= 256 {
-            Ok(object_cache::get_int(self.py, v))
-        } else {
-            // PHASE 13 OPTIMIZATION: Direct C API call bypasses PyO3 overhead
-            unsafe {
-                let ptr = object_cache::create_int_i64_direct(v);
-                Ok(PyObject::from_owned_ptr(self.py, ptr))
-            }
-        }
+        // OPTIMIZATION: Cached small ints, direct C API call otherwise
+        Ok(object_cache::get_int(self.py, v))
     }
 
     #[inline]
     fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
-        // OPTIMIZATION: Only cache if value fits in small integer range
-        if v <= 256 {
-            Ok(object_cache::get_int(self.py, v as i64))
+        // OPTIMIZATION: Values that fit in i64 may hit the small int cache
+        if let Ok(v) = i64::try_from(v) {
+            Ok(object_cache::get_int(self.py, v))
         } else {
             // PHASE 13 OPTIMIZATION: Direct C API call
             unsafe {
@@ -189,21 +180,19 @@ impl<'de, 'py> Visitor<'de> for PyObjectVisitor<'py> {
     }
 
     #[inline]
-    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
-        // PHASE 13 OPTIMIZATION: Direct C API call (2-3x faster than to_object)
-        unsafe {
-            let ptr = object_cache::create_string_direct(v);
-            Ok(PyObject::from_owned_ptr(self.py, ptr))
-        }
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        create_str_value(self.py, v, self.opts).map_err(E::custom)
     }
 
     #[inline]
-    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
-        // PHASE 13 OPTIMIZATION: Direct C API call
-        unsafe {
-            let ptr = object_cache::create_string_direct(&v);
-            Ok(PyObject::from_owned_ptr(self.py, ptr))
-        }
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        create_str_value(self.py, &v, self.opts).map_err(E::custom)
     }
 
     #[inline]
@@ -222,22 +211,37 @@ impl<'de, 'py> Visitor<'de> for PyObjectVisitor<'py> {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(PyObjectVisitor { py: self.py })
+        deserializer.deserialize_any(PyObjectVisitor { py: self.py, opts: self.opts, depth: self.depth })
     }
 
     fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
     where
         A: SeqAccess<'de>,
     {
+        let depth = self.enter()?;
         // PHASE 13 OPTIMIZATION: Direct list creation with C API
         // First collect elements (we need the count for PyList_New)
         let size = seq.size_hint().unwrap_or(0);
         let mut elements: Vec<PyObject> = Vec::with_capacity(size);
 
-        while let Some(elem) = seq.next_element_seed(PyObjectSeed { py: self.py })? {
+        while let Some(elem) = seq.next_element_seed(PyObjectSeed { py: self.py, opts: self.opts, depth })? {
             elements.push(elem);
         }
 
+        if self.opts.use_tuples {
+            unsafe {
+                let tuple_ptr = ffi::PyTuple_New(elements.len() as ffi::Py_ssize_t);
+                if tuple_ptr.is_null() {
+                    use serde::de::Error as SerdeDeError;
+                    return Err(SerdeDeError::custom("Failed to create tuple"));
+                }
+                for (i, elem) in elements.into_iter().enumerate() {
+                    ffi::PyTuple_SET_ITEM(tuple_ptr, i as ffi::Py_ssize_t, elem.into_ptr());
+                }
+                return Ok(PyObject::from_owned_ptr(self.py, tuple_ptr));
+            }
+        }
+
         // Now create list directly with exact size (no resizing)
         unsafe {
             let list_ptr = object_cache::create_list_direct(elements.len() as ffi::Py_ssize_t);
@@ -262,26 +266,69 @@ impl<'de, 'py> Visitor<'de> for PyObjectVisitor<'py> {
         // PHASE 13 OPTIMIZATION: Direct dict creation with C API
         use serde::de::Error as SerdeDeError;
 
+        let depth = self.enter()?;
         unsafe {
             let dict_ptr = object_cache::create_dict_direct();
             if dict_ptr.is_null() {
                 return Err(SerdeDeError::custom("Failed to create dict"));
             }
+            // With `object_pairs_hook`, entries are collected here instead
+            let mut pairs = self.opts.object_pairs_hook.as_ref().map(|_| Vec::new());
 
             // Insert directly using C API
-            while let Some((key, value)) = map.next_entry_seed(KeySeed, PyObjectSeed { py: self.py })? {
-                // Create key string directly
-                let key_ptr = object_cache::create_string_direct(&key);
-                if key_ptr.is_null() {
-                    ffi::Py_DECREF(dict_ptr);
-                    return Err(SerdeDeError::custom("Failed to create key string"));
+            loop {
+                let (key, value) = if self.opts.key_filter.is_some() || self.opts.reject_duplicate_keys {
+                    let key = match &self.opts.key_filter {
+                        // Filtered-out keys never become objects; their values are skipped
+                        Some(filter) => match map.next_key_seed(FilteredKeySeed { py: self.py, opts: self.opts, filter })? {
+                            Some(Some(key)) => key,
+                            Some(None) => {
+                                map.next_value::<de::IgnoredAny>()?;
+                                continue;
+                            }
+                            None => break,
+                        },
+                        None => match map.next_key_seed(KeySeed { py: self.py, opts: self.opts })? {
+                            Some(key) => key,
+                            None => break,
+                        },
+                    };
+                    // Checked before the value is parsed, so the error points at the key
+                    if self.opts.reject_duplicate_keys && ffi::PyDict_Contains(dict_ptr, key.as_ptr()) == 1 {
+                        ffi::Py_DECREF(dict_ptr);
+                        return Err(SerdeDeError::custom(format_args!("duplicate key '{}'", key.bind(self.py))));
+                    }
+                    (key, map.next_value_seed(PyObjectSeed { py: self.py, opts: self.opts, depth })?)
+                } else {
+                    match map.next_entry_seed(
+                        KeySeed { py: self.py, opts: self.opts },
+                        PyObjectSeed { py: self.py, opts: self.opts, depth },
+                    )? {
+                        Some(entry) => entry,
+                        None => break,
+                    }
+                };
+
+                if let Some(key_set) = &self.opts.key_set {
+                    if ffi::PySet_Add(key_set.as_ptr(), key.as_ptr()) < 0 {
+                        ffi::Py_DECREF(dict_ptr);
+                        return Err(SerdeDeError::custom("Failed to collect key"));
+                    }
+                }
+                let value = match revive_datetime(self.py, self.opts, &key, value) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        ffi::Py_DECREF(dict_ptr);
+                        return Err(SerdeDeError::custom(e));
+                    }
+                };
+                if let Some(pairs) = &mut pairs {
+                    pairs.push((key, value));
+                    continue;
                 }
 
                 // Insert: PyDict_SetItem does NOT steal references
-                let result = object_cache::set_dict_item_direct(dict_ptr, key_ptr, value.as_ptr());
-
-                // Clean up key (we own it, PyDict_SetItem increfs it)
-                ffi::Py_DECREF(key_ptr);
+                let result = object_cache::set_dict_item_direct(dict_ptr, key.as_ptr(), value.as_ptr());
 
                 if result < 0 {
                     ffi::Py_DECREF(dict_ptr);
@@ -289,69 +336,976 @@ impl<'de, 'py> Visitor<'de> for PyObjectVisitor<'py> {
                 }
             }
 
-            Ok(PyObject::from_owned_ptr(self.py, dict_ptr))
+            let dict = PyObject::from_owned_ptr(self.py, dict_ptr);
+            if let (Some(hook), Some(pairs)) = (&self.opts.object_pairs_hook, pairs) {
+                let pairs = pairs
+                    .into_iter()
+                    .map(|pair| pair.into_pyobject(self.py))
+                    .collect::<PyResult<Vec<_>>>()
+                    .and_then(|pairs| PyList::new(self.py, pairs))
+                    .map_err(SerdeDeError::custom)?;
+                return call_hook(self.py, hook, pairs.into_any().unbind());
+            }
+            match &self.opts.object_hook {
+                Some(hook) => call_hook(self.py, hook, dict),
+                None => Ok(dict),
+            }
+        }
+    }
+}
+
+/// Call a `loads` hook on a decoded value
+///
+/// serde errors only carry a message, so the hook's exception is parked in
+/// the interpreter's error indicator for `parse_serde` to re-raise as is.
+#[cold]
+fn call_hook<E: de::Error>(py: Python, hook: &PyObject, value: PyObject) -> Result<PyObject, E> {
+    hook.call1(py, (value,)).map_err(|e| {
+        e.restore(py);
+        E::custom("loads hook raised")
+    })
+}
+
+/// Parse options shared by every `loads` engine
+#[derive(Debug, Clone)]
+pub(crate) struct LoadsOptions {
+    /// Route dict keys through the shared intern cache
+    pub(crate) intern_keys: bool,
+    /// Route short string values through the shared intern cache
+    pub(crate) intern_values: bool,
+    /// Turn strict integer string values (`"-123"`) back into ints
+    pub(crate) revive_bigint_strings: bool,
+    /// Turn `"NaN"`/`"Infinity"`/`"-Infinity"` string values back into floats
+    pub(crate) revive_nan_strings: bool,
+    /// Unicode-normalize string values and keys
+    pub(crate) normalize: Option<NormalForm>,
+    /// Keys whose string values are parsed with `datetime.fromisoformat`
+    pub(crate) datetime_keys: Option<Arc<AHashSet<String>>>,
+    /// Which object entries are kept (`allowed_keys` / `ignored_keys`)
+    pub(crate) key_filter: Option<Arc<KeyFilter>>,
+    /// Share structurally identical subtrees after parsing
+    pub(crate) dedup: bool,
+    /// Set receiving every object key seen (`loads(collect_keys=True)`)
+    pub(crate) key_set: Option<Arc<Py<PySet>>>,
+    /// Deepest container nesting accepted, in place of serde_json's limit
+    pub(crate) max_depth: Option<usize>,
+    /// Fail on an object key seen earlier in the same object
+    pub(crate) reject_duplicate_keys: bool,
+    /// Called with each decoded dict, its result replacing the dict
+    pub(crate) object_hook: Option<Arc<PyObject>>,
+    /// Called with each object's `(key, value)` list instead of building a
+    /// dict; takes precedence over `object_hook`
+    pub(crate) object_pairs_hook: Option<Arc<PyObject>>,
+    /// Build tuples instead of lists for arrays
+    pub(crate) use_tuples: bool,
+}
+
+impl Default for LoadsOptions {
+    fn default() -> Self {
+        LoadsOptions {
+            intern_keys: true,
+            intern_values: false,
+            revive_bigint_strings: false,
+            revive_nan_strings: false,
+            normalize: None,
+            datetime_keys: None,
+            key_filter: None,
+            dedup: false,
+            key_set: None,
+            max_depth: None,
+            reject_duplicate_keys: false,
+            object_hook: None,
+            object_pairs_hook: None,
+            use_tuples: false,
+        }
+    }
+}
+
+/// Create the Python object for a JSON string *value* (never a key)
+///
+/// With `revive_bigint_strings`, strings matching `-?(0|[1-9][0-9]*)` become
+/// ints, undoing `dumps(bigint_mode="string")`. The pattern is strict on
+/// purpose: no `+`, whitespace or leading zeros, so values like `"007"` or
+/// `" 1"` stay strings.
+#[inline]
+pub(crate) fn create_str_value(py: Python, v: &str, opts: &LoadsOptions) -> PyResult<PyObject> {
+    if unlikely(opts.revive_bigint_strings) && is_strict_int(v) {
+        if let Some(int) = int_from_digits(py, v) {
+            return Ok(int);
+        }
+    }
+    if unlikely(opts.revive_nan_strings) {
+        let value = match v {
+            "NaN" => f64::NAN,
+            "Infinity" => f64::INFINITY,
+            "-Infinity" => f64::NEG_INFINITY,
+            _ => 0.0,
+        };
+        if !value.is_finite() {
+            return unsafe { PyObject::from_owned_ptr_or_err(py, object_cache::create_float_direct(value)) };
+        }
+    }
+    if let Some(form) = opts.normalize {
+        if unlikely(!v.is_ascii()) {
+            let normalized = normalize_str(py, v, form)?;
+            if opts.intern_values {
+                let text = normalized.to_str()?;
+                if text.len() <= simd_parser::intern_max_len() {
+                    return Ok(simd_parser::get_interned_string(py, text));
+                }
+            }
+            return Ok(normalized.into_any().unbind());
+        }
+    }
+    if opts.intern_values && v.len() <= simd_parser::intern_max_len() {
+        return Ok(simd_parser::get_interned_string(py, v));
+    }
+    // PHASE 13 OPTIMIZATION: Direct C API call (2-3x faster than to_object)
+    // Null (with the error set) when out of memory
+    unsafe { PyObject::from_owned_ptr_or_err(py, object_cache::create_string_direct(v)) }
+}
+
+/// Unicode normal form selected by `loads(normalize=...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NormalForm {
+    Nfc,
+    Nfd,
+}
+
+impl NormalForm {
+    fn from_name(name: Option<&str>) -> PyResult<Option<Self>> {
+        match name {
+            None => Ok(None),
+            Some("NFC") => Ok(Some(NormalForm::Nfc)),
+            Some("NFD") => Ok(Some(NormalForm::Nfd)),
+            Some(other) => Err(PyValueError::new_err(format!(
+                "Invalid normalize: {other:?} (expected 'NFC', 'NFD' or None)"
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            NormalForm::Nfc => "NFC",
+            NormalForm::Nfd => "NFD",
+        }
+    }
+}
+
+/// `unicodedata.normalize(form, v)`
+///
+/// ASCII text is already in every normal form, so callers only come here for
+/// non-ASCII strings.
+#[cold]
+pub(crate) fn normalize_str<'py>(py: Python<'py>, v: &str, form: NormalForm) -> PyResult<Bound<'py, PyString>> {
+    let normalized = type_cache::get_unicodedata_normalize(py)?.call1((form.name(), v))?;
+    Ok(normalized.downcast_into::<PyString>()?)
+}
+
+/// Parse a string `value` stored under one of `datetime_keys` into a
+/// `datetime.datetime`
+///
+/// Strings that aren't ISO 8601 (per `datetime.fromisoformat`) and
+/// non-string values are returned unchanged. A no-op unless the option is set.
+#[inline]
+pub(crate) fn revive_datetime(py: Python, opts: &LoadsOptions, key: &PyObject, value: PyObject) -> PyResult<PyObject> {
+    let Some(keys) = &opts.datetime_keys else {
+        return Ok(value);
+    };
+    if unsafe { ffi::PyUnicode_CheckExact(value.as_ptr()) } == 0 {
+        return Ok(value);
+    }
+    if !keys.contains(key.downcast_bound::<PyString>(py)?.to_str()?) {
+        return Ok(value);
+    }
+    match type_cache::get_datetime_class(py)?.call_method1("fromisoformat", (value.bind(py),)) {
+        Ok(datetime) => Ok(datetime.unbind()),
+        Err(e) if e.is_instance_of::<PyValueError>(py) => Ok(value),
+        Err(e) => Err(e),
+    }
+}
+
+/// Collect a key-set argument (any iterable of str)
+fn collect_str_set(arg: &Bound<'_, PyAny>) -> PyResult<AHashSet<String>> {
+    arg.try_iter()?.map(|key| key?.extract::<String>()).collect()
+}
+
+/// Parse the `datetime_keys` argument
+fn parse_datetime_keys(arg: Option<&Bound<'_, PyAny>>) -> PyResult<Option<Arc<AHashSet<String>>>> {
+    arg.map(|keys| collect_str_set(keys).map(Arc::new)).transpose()
+}
+
+/// Create the Python object for a dict key, honouring `intern_keys` and
+/// `normalize`
+#[inline]
+pub(crate) fn create_key(py: Python, v: &str, opts: &LoadsOptions) -> PyResult<PyObject> {
+    if let Some(form) = opts.normalize {
+        if unlikely(!v.is_ascii()) {
+            let normalized = normalize_str(py, v, form)?;
+            if !opts.intern_keys {
+                return Ok(normalized.into_any().unbind());
+            }
+            return Ok(simd_parser::get_interned_string(py, normalized.to_str()?));
+        }
+    }
+    if opts.intern_keys {
+        Ok(simd_parser::get_interned_string(py, v))
+    } else {
+        unsafe { PyObject::from_owned_ptr_or_err(py, object_cache::create_string_direct(v)) }
+    }
+}
+
+/// Whether `s` matches `-?(0|[1-9][0-9]*)`
+fn is_strict_int(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s).as_bytes();
+    match digits {
+        [] => false,
+        [b'0'] => true,
+        [b'0', ..] => false,
+        _ => digits.iter().all(u8::is_ascii_digit),
+    }
+}
+
+/// Build a Python int from validated decimal digits
+#[cold]
+fn int_from_digits(py: Python, digits: &str) -> Option<PyObject> {
+    if let Ok(value) = digits.parse::<i64>() {
+        return Some(object_cache::get_int(py, value));
+    }
+    // Arbitrary precision: let CPython parse it
+    let c_digits = std::ffi::CString::new(digits).ok()?;
+    unsafe {
+        let ptr = ffi::PyLong_FromString(c_digits.as_ptr(), std::ptr::null_mut(), 10);
+        if ptr.is_null() {
+            ffi::PyErr_Clear();
+            return None;
         }
+        Some(PyObject::from_owned_ptr(py, ptr))
     }
 }
 
 /// Seed for deserializing JSON to Python objects (public for simd_parser fallback)
-pub(crate) struct PyObjectSeed<'py> {
+pub(crate) struct PyObjectSeed<'a, 'py> {
+    pub(crate) py: Python<'py>,
+    pub(crate) opts: &'a LoadsOptions,
+    /// Containers enclosing the value (0 at the root)
+    pub(crate) depth: usize,
+}
+
+impl<'de, 'a, 'py> de::DeserializeSeed<'de> for PyObjectSeed<'a, 'py> {
+    type Value = PyObject;
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(PyObjectVisitor { py: self.py, opts: self.opts, depth: self.depth })
+    }
+}
+
+/// Seed for dict keys: builds the Python key through the shared intern cache
+///
+/// Keeps the default `loads` consistent with `loads_simd`, so repeated keys
+/// resolve to one shared `str` object whichever engine parsed them.
+/// With `intern` off, keys are created directly (no cache lookup or lock).
+pub(crate) struct KeySeed<'a, 'py> {
     pub(crate) py: Python<'py>,
+    pub(crate) opts: &'a LoadsOptions,
 }
 
-impl<'de, 'py> de::DeserializeSeed<'de> for PyObjectSeed<'py> {
+impl<'de> de::DeserializeSeed<'de> for KeySeed<'_, '_> {
     type Value = PyObject;
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(PyObjectVisitor { py: self.py })
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed<'_, '_> {
+    type Value = PyObject;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string key")
+    }
+
+    #[inline]
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        create_key(self.py, v, self.opts).map_err(E::custom)
+    }
+}
+
+/// Object entries kept by `loads(allowed_keys=...)` / `loads(ignored_keys=...)`
+#[derive(Debug)]
+pub(crate) enum KeyFilter {
+    /// Keep only these keys
+    Allow(AHashSet<String>),
+    /// Drop these keys
+    Ignore(AHashSet<String>),
+}
+
+impl KeyFilter {
+    #[inline]
+    pub(crate) fn keeps(&self, key: &str) -> bool {
+        match self {
+            KeyFilter::Allow(keys) => keys.contains(key),
+            KeyFilter::Ignore(keys) => !keys.contains(key),
+        }
     }
+
+    fn from_args(
+        allowed_keys: Option<&Bound<'_, PyAny>>,
+        ignored_keys: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Option<Arc<Self>>> {
+        let filter = match (allowed_keys, ignored_keys) {
+            (None, None) => return Ok(None),
+            (Some(_), Some(_)) => {
+                return Err(PyValueError::new_err("allowed_keys and ignored_keys cannot be combined"))
+            }
+            (Some(keys), None) => KeyFilter::Allow(collect_str_set(keys)?),
+            (None, Some(keys)) => KeyFilter::Ignore(collect_str_set(keys)?),
+        };
+        Ok(Some(Arc::new(filter)))
+    }
+}
+
+/// Dict key seed that yields `None` for keys the `KeyFilter` drops, without
+/// creating a Python object for them
+struct FilteredKeySeed<'a, 'py> {
+    py: Python<'py>,
+    opts: &'a LoadsOptions,
+    filter: &'a KeyFilter,
 }
 
-struct KeySeed;
-impl<'de> de::DeserializeSeed<'de> for KeySeed {
-    type Value = String;
+impl<'de> de::DeserializeSeed<'de> for FilteredKeySeed<'_, '_> {
+    type Value = Option<PyObject>;
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        de::Deserialize::deserialize(deserializer)
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FilteredKeySeed<'_, '_> {
+    type Value = Option<PyObject>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string key")
+    }
+
+    #[inline]
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if !self.filter.keeps(v) {
+            return Ok(None);
+        }
+        create_key(self.py, v, self.opts).map(Some).map_err(E::custom)
+    }
+}
+
+mod stdlib_json {
+    pyo3::import_exception!(json, JSONDecodeError);
+}
+
+pyo3::create_exception!(
+    rjson,
+    JSONDecodeError,
+    stdlib_json::JSONDecodeError,
+    "Malformed JSON passed to `loads`.\n\nA subclass of `json.JSONDecodeError` (and so of `ValueError`), with the\nsame `msg`, `doc`, `pos`, `lineno` and `colno` attributes."
+);
+
+/// `JSONDecodeError` for a serde_json failure on the whole document `doc`
+///
+/// Syntax and EOF errors, and errors raised by the seeds while building the
+/// result (duplicate keys, `max_depth`), become `JSONDecodeError`, positioned
+/// like stdlib: `pos` counts characters, and an unexpected end points past
+/// the last one.
+#[cold]
+pub(crate) fn decode_error(doc: &str, e: serde_json::Error) -> PyErr {
+    decode_error_in(doc, (0, doc.len()), e)
+}
+
+/// `decode_error` for a serde_json failure on the slice `doc[start..end]`,
+/// positioned in the whole of `doc`
+#[cold]
+pub(crate) fn decode_error_in(doc: &str, (start, end): (usize, usize), e: serde_json::Error) -> PyErr {
+    match error_byte(&doc[start..end], &e) {
+        Some(byte) => decode_error_at(doc, start + byte, error_message(&e)),
+        None => PyValueError::new_err(format!("JSON parsing error: {e}")),
+    }
+}
+
+/// `JSONDecodeError` with message `msg` at byte offset `byte` of `doc`
+#[cold]
+pub(crate) fn decode_error_at(doc: &str, byte: usize, msg: String) -> PyErr {
+    JSONDecodeError::new_err((msg, doc.to_owned(), doc[..byte].chars().count()))
+}
+
+/// Byte offset in `doc` that serde_json error `e` points at (`None` for I/O
+/// errors, which have no position)
+pub(crate) fn error_byte(doc: &str, e: &serde_json::Error) -> Option<usize> {
+    use serde_json::error::Category;
+
+    match e.classify() {
+        Category::Syntax | Category::Data => {
+            let line_start = doc.split_inclusive('\n').take(e.line().saturating_sub(1)).map(str::len).sum::<usize>();
+            let mut byte = (line_start + e.column().saturating_sub(1)).min(doc.len());
+            while !doc.is_char_boundary(byte) {
+                byte -= 1;
+            }
+            Some(byte)
+        }
+        Category::Eof => Some(doc.len()),
+        Category::Io => None,
+    }
+}
+
+/// `JSONDecodeError` message for `e`, without serde_json's position suffix
+pub(crate) fn error_message(e: &serde_json::Error) -> String {
+    let text = e.to_string();
+    let position = format!(" at line {} column {}", e.line(), e.column());
+    format!("JSON parsing error: {}", text.strip_suffix(&position).unwrap_or(&text))
+}
+
+/// Stack size (bytes) for the dedicated `loads` parse thread; 0 = disabled
+pub(crate) static PARSE_STACK_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Smallest accepted parse stack size (below this Rust's own default is larger)
+const MIN_PARSE_STACK_SIZE: usize = 64 * 1024;
+
+/// Nesting parsed on the caller's stack, whose size we don't control
+/// (serde_json's own recursion limit); deeper documents that `max_depth`
+/// allows move to a thread sized for them (see `parse_on_deep_stack`)
+pub(crate) const CALLER_STACK_DEPTH: usize = 128;
+
+/// Stack reserved per nesting level on a deep-parse thread: twice the
+/// ~3.7 KiB a debug build takes with `dedup=True`, the hungriest case
+const DEEP_PARSE_STACK_PER_LEVEL: usize = 8 * 1024;
+
+/// Run the serde_json parse on the calling thread
+///
+/// `unbounded` lifts serde_json's 128-level recursion limit; only safe when
+/// running on a stack sized for the caller's documents. Otherwise a document
+/// that hits that limit while `max_depth` allows more is parsed again on a
+/// thread sized for `max_depth`. Flat arrays of
+/// integers skip serde (see `int_array`) unless they are to become tuples.
+fn parse_serde(py: Python, json_str: &str, opts: &LoadsOptions, unbounded: bool) -> PyResult<PyObject> {
+    let int_values = match opts.max_depth {
+        Some(0) => None,
+        _ if opts.use_tuples => None,
+        _ => int_array::parse_int_array(json_str.as_bytes()),
+    };
+    let value = match int_values {
+        Some(values) => int_array::int_list(py, &values)?,
+        None => {
+            let mut de = serde_json::Deserializer::from_str(json_str);
+            if unbounded {
+                de.disable_recursion_limit();
+            }
+            let parsed = DeserializeSeed::deserialize(PyObjectSeed { py, opts, depth: 0 }, &mut de)
+                .and_then(|value| de.end().map(|()| value));
+            match parsed {
+                Ok(value) => value,
+                Err(e) => {
+                    if let Some(err) = PyErr::take(py) {
+                        return Err(err);
+                    }
+                    if !unbounded && allows_deep_parse(opts) && e.to_string().starts_with("recursion limit exceeded") {
+                        return parse_on_deep_stack(py, opts, |py| parse_serde(py, json_str, opts, true));
+                    }
+                    return Err(decode_error(json_str, e));
+                }
+            }
+        }
+    };
+    if opts.dedup {
+        return dedup::dedup_tree(py, value);
+    }
+    Ok(value)
+}
+
+/// Raw bytes of a `loads` argument (`str`, `bytes` or `bytearray`)
+///
+/// `str` and `bytes` are borrowed; `bytearray` is copied since it could be
+/// resized while the parse runs. Bytes are not checked for valid UTF-8.
+fn json_bytes<'a>(obj: &'a Bound<'_, PyAny>) -> PyResult<Cow<'a, [u8]>> {
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return Ok(Cow::Borrowed(s.to_str()?.as_bytes()));
+    }
+    if let Ok(b) = obj.downcast::<PyBytes>() {
+        return Ok(Cow::Borrowed(b.as_bytes()));
+    }
+    if let Ok(b) = obj.downcast::<PyByteArray>() {
+        return Ok(Cow::Owned(b.to_vec()));
+    }
+    Err(PyTypeError::new_err(format!(
+        "the JSON document must be str, bytes or bytearray, not {}",
+        obj.get_type().name()?
+    )))
+}
+
+/// Text of a `loads` argument, validating `bytes`/`bytearray` as UTF-8
+fn json_text<'a>(obj: &'a Bound<'_, PyAny>) -> PyResult<Cow<'a, str>> {
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return Ok(Cow::Borrowed(s.to_str()?));
+    }
+    match json_bytes(obj)? {
+        Cow::Borrowed(bytes) => std::str::from_utf8(bytes).map(Cow::Borrowed).map_err(invalid_utf8_error),
+        Cow::Owned(bytes) => String::from_utf8(bytes).map(Cow::Owned).map_err(|e| invalid_utf8_error(e.utf8_error())),
     }
 }
 
+#[cold]
+fn invalid_utf8_error(e: std::str::Utf8Error) -> PyErr {
+    PyValueError::new_err(format!("JSON parsing error: invalid UTF-8 at byte {}", e.valid_up_to()))
+}
+
 /// Parses a JSON string into a Python object.
 ///
 /// Uses serde_json with direct Python object creation via Visitor pattern.
 /// This provides single-pass parsing without intermediate representations.
 ///
+/// Nesting is limited to 1024 levels, or `configure(default_max_depth=...)`
+/// (as for `loads_simd`), unless
+/// `set_parse_stack_size` is in effect, in which case the parse runs on a
+/// dedicated thread with that stack and no depth limit. Documents nested
+/// past 127 levels are otherwise parsed on a thread sized for the limit, so
+/// it holds on small thread stacks too.
+///
 /// # Arguments
-/// * `json_str` - The JSON string to parse.
+/// * `json_str` - The JSON document: `str`, or UTF-8 encoded `bytes` or
+///   `bytearray` (parsed without decoding to `str` first).
+/// * `intern_keys` - Share dict key objects through the intern cache (default).
+///   Disable for high-cardinality keys (e.g. UUIDs) where caching only adds
+///   lock traffic and fills the cache with keys that never repeat.
+/// * `intern_values` - Also share short string *values* (up to
+///   `intern_max_len` bytes, 32 by default) through the intern cache, so documents repeating enum-like values (`"active"`,
+///   `"pending"`) allocate each one once and equal values are the same
+///   object. The cache is shared with keys and bounded (see
+///   `configure(intern_cache_size=...)`); once full, new strings are created
+///   as usual. Off by default, since unique values only add lookups.
+/// * `revive_bigint_strings` - Convert string values that are strict integers
+///   (`"-?(0|[1-9][0-9]*)"`) to ints, pairing with `dumps(bigint_mode="string")`.
+///   Off by default; dict keys are never converted.
+/// * `revive_nan_strings` - Convert the string values `"NaN"`, `"Infinity"`
+///   and `"-Infinity"` to floats, pairing with `dumps(nan_mode="string")`.
+///   Matching is exact and case-sensitive, as in the stdlib (`"nan"`,
+///   `"inf"`, `"INFINITY"` and `"+Infinity"` stay strings). Off by default;
+///   dict keys are never converted. Bare `NaN`/`Infinity` literals are
+///   always rejected.
+/// * `parse_nan_strings` - Same as `revive_nan_strings`.
+/// * `normalize` - `"NFC"` or `"NFD"` to Unicode-normalize every string value
+///   and key (via `unicodedata.normalize`), so text written with precomposed
+///   or combining characters compares equal. `None` (default) keeps strings
+///   exactly as written. ASCII strings skip the call.
+/// * `datetime_keys` - Keys (any iterable of str) whose string values are
+///   parsed into `datetime.datetime` with `datetime.fromisoformat`, at any
+///   nesting level. Only values under these keys are checked, so it costs
+///   nothing for the rest of the document. Values that aren't valid ISO 8601
+///   stay strings. Not supported with `lazy`.
+/// * `allowed_keys` - Keys (any iterable of str) to keep in every object; all
+///   other entries are skipped without building their values, saving time
+///   and memory on wide objects. Applies at every level, so the keys leading
+///   to nested objects must be allowed too. Not supported with `lazy`.
+/// * `ignored_keys` - Keys to drop from every object, skipped the same way.
+///   Cannot be combined with `allowed_keys`.
+/// * `require_container` - Reject documents whose top-level value isn't an
+///   object or array (e.g. `"42"`), for protocols that require one.
+/// * `dedup` - Share equal strings and numbers, and structurally identical
+///   tuples of them (see `use_tuples`), between every position they occur
+///   in, cutting the memory the result holds for documents that repeat the
+///   same values. Only immutable values are shared, so dicts and lists stay
+///   distinct objects. Costs a second pass over the result, and since that
+///   pass runs after the full tree is built, peak memory during the call is
+///   not reduced.
+/// * `lazy` - Return a read-only `LazyValue` proxy for an object/array root
+///   instead of dicts and lists. The document is validated up front, but
+///   each subtree is only decoded (and memoized) when first accessed, which
+///   is much cheaper when only a few fields are read. Call `materialize()`
+///   for plain objects; proxies also compare equal to, and `dumps` writes,
+///   their decoded value. Always parses on the calling thread with the
+///   128-level limit, and can't be combined with `dedup`.
+/// * `collect_keys` - Also return the set of every distinct object key in
+///   the document, at any depth: the result becomes `(obj, key_set)`. Keys
+///   are gathered while parsing, so no second traversal is needed. Can't be
+///   combined with `lazy`.
+/// * `into` - A dataclass or attrs class (or `list[...]`, `dict[str, ...]`,
+///   `Optional[...]` of one) to decode into. Objects are turned into
+///   instances by matching keys to constructor fields (attribute names, for
+///   attrs), recursing through nested class, list, dict and Optional field
+///   types from `typing.get_type_hints`. Other field
+///   types receive the parsed value unchanged. Can't be combined with `lazy`.
+/// * `on_extra` - With `into`: `"ignore"` (default) drops keys that aren't
+///   fields, `"error"` raises ValueError.
+/// * `on_missing` - With `into`: `"error"` (default) raises ValueError when a
+///   field without a default is absent, `"none"` passes `None` for it.
+/// * `max_depth` - Deepest array/object nesting accepted; deeper documents
+///   raise `JSONDecodeError`. Replaces the default limit (1024 levels unless
+///   changed with `configure(default_max_depth=...)`, or the
+///   unlimited depth under `set_parse_stack_size`), so a stricter cap or a
+///   higher one can be set per call. Can't be combined with `lazy`.
+/// * `reject_duplicate_keys` - Raise `JSONDecodeError` naming the key when an
+///   object repeats one, instead of keeping the last value (the default, as
+///   in stdlib). For payloads where an ambiguous document must not be
+///   accepted, e.g. signed ones. Keys dropped by `allowed_keys` or
+///   `ignored_keys` aren't checked. Can't be combined with `lazy`.
+/// * `object_hook` - A callable, as for stdlib `json.loads`, called with
+///   every decoded object (a dict, innermost first); its return value takes
+///   the dict's place in the result. Exceptions it raises propagate
+///   unchanged. Can't be combined with `lazy`.
+/// * `object_pairs_hook` - A callable, as for stdlib `json.loads`, called
+///   with each object as a list of `(key, value)` tuples in document order,
+///   duplicates included; no dict is built, and its return value takes the
+///   object's place. Takes precedence over `object_hook`. Can't be combined
+///   with `lazy` or `reject_duplicate_keys`.
+/// * `allow_comments` - Accept `// line` and `/* block */` comments wherever
+///   whitespace is allowed, for hand-written config files. A rejected
+///   document's `JSONDecodeError.doc` then shows its comments blanked out;
+///   positions are unaffected.
+/// * `allow_trailing_commas` - Accept a comma after the last element of an
+///   array or object (`[1, 2,]`, `{"a": 1,}`). A lone comma (`[,]`) is
+///   still rejected.
+/// * `use_tuples` - Decode arrays as tuples instead of lists, at every
+///   level, so results are immutable and hashable (given hashable
+///   contents), e.g. for use as dict keys. Objects still become dicts.
+///   Can't be combined with `lazy` or `into`.
 ///
 /// # Returns
-/// A PyObject representing the parsed JSON, or a PyValueError on error.
+/// A PyObject representing the parsed JSON. Malformed JSON raises
+/// `rjson.JSONDecodeError` (a `json.JSONDecodeError`) with the position of
+/// the problem; other failures raise ValueError.
 #[pyfunction]
-fn loads(json_str: &str) -> PyResult<PyObject> {
+#[pyo3(signature = (
+    json_str, *, intern_keys = true, intern_values = false, revive_bigint_strings = false,
+    revive_nan_strings = false, parse_nan_strings = false,
+    normalize = None, datetime_keys = None, allowed_keys = None, ignored_keys = None,
+    require_container = false, dedup = false, lazy = false, collect_keys = false,
+    into = None, on_extra = "ignore", on_missing = "error", max_depth = None,
+    reject_duplicate_keys = false, object_hook = None, object_pairs_hook = None,
+    allow_comments = false, allow_trailing_commas = false, use_tuples = false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn loads(
+    json_str: &Bound<'_, PyAny>,
+    intern_keys: bool,
+    intern_values: bool,
+    revive_bigint_strings: bool,
+    revive_nan_strings: bool,
+    parse_nan_strings: bool,
+    normalize: Option<&str>,
+    datetime_keys: Option<&Bound<'_, PyAny>>,
+    allowed_keys: Option<&Bound<'_, PyAny>>,
+    ignored_keys: Option<&Bound<'_, PyAny>>,
+    require_container: bool,
+    dedup: bool,
+    lazy: bool,
+    collect_keys: bool,
+    into: Option<&Bound<'_, PyAny>>,
+    on_extra: &str,
+    on_missing: &str,
+    max_depth: Option<usize>,
+    reject_duplicate_keys: bool,
+    object_hook: Option<&Bound<'_, PyAny>>,
+    object_pairs_hook: Option<&Bound<'_, PyAny>>,
+    allow_comments: bool,
+    allow_trailing_commas: bool,
+    use_tuples: bool,
+) -> PyResult<PyObject> {
+    let on_extra = typed::ExtraKeys::from_name(on_extra)?;
+    let on_missing = typed::MissingKeys::from_name(on_missing)?;
+    if object_pairs_hook.is_some() && reject_duplicate_keys {
+        return Err(PyValueError::new_err("object_pairs_hook and reject_duplicate_keys cannot be combined"));
+    }
+    if into.is_some() && use_tuples {
+        return Err(PyValueError::new_err("into and use_tuples cannot be combined"));
+    }
+    let json_str = json_text(json_str)?;
+    let json_str = &*relaxed::strip_relaxed(&json_str, allow_comments, allow_trailing_commas);
+    let key_set = if collect_keys {
+        Some(Arc::new(Python::with_gil(|py| PySet::empty(py).map(Bound::unbind))?))
+    } else {
+        None
+    };
+    let opts = LoadsOptions {
+        intern_keys,
+        intern_values,
+        revive_bigint_strings,
+        revive_nan_strings: revive_nan_strings || parse_nan_strings,
+        normalize: NormalForm::from_name(normalize)?,
+        datetime_keys: parse_datetime_keys(datetime_keys)?,
+        key_filter: KeyFilter::from_args(allowed_keys, ignored_keys)?,
+        dedup,
+        key_set,
+        // A configured parse stack lifts the default cap (see set_parse_stack_size)
+        max_depth: max_depth.or_else(|| {
+            (PARSE_STACK_SIZE.load(Ordering::Relaxed) == 0).then(simd_parser::default_max_depth)
+        }),
+        reject_duplicate_keys,
+        object_hook: object_hook.map(|hook| Arc::new(hook.clone().unbind())),
+        object_pairs_hook: object_pairs_hook.map(|hook| Arc::new(hook.clone().unbind())),
+        use_tuples,
+    };
+    if lazy {
+        let conflicts = [
+            ("dedup", dedup),
+            ("collect_keys", collect_keys),
+            ("datetime_keys", opts.datetime_keys.is_some()),
+            ("allowed_keys", allowed_keys.is_some()),
+            ("ignored_keys", ignored_keys.is_some()),
+            ("into", into.is_some()),
+            ("max_depth", max_depth.is_some()),
+            ("reject_duplicate_keys", reject_duplicate_keys),
+            ("object_hook", object_hook.is_some()),
+            ("object_pairs_hook", object_pairs_hook.is_some()),
+            ("use_tuples", use_tuples),
+        ];
+        if let Some((other, _)) = conflicts.iter().find(|(_, set)| *set) {
+            return Err(PyValueError::new_err(format!("lazy and {other} cannot be combined")));
+        }
+        return Python::with_gil(|py| {
+            let result = lazy::loads_lazy(py, json_str, &opts)?;
+            // Scalar roots come back decoded; containers are proxies
+            if require_container && !result.bind(py).is_instance_of::<lazy::LazyValue>() {
+                return Err(container_root_error());
+            }
+            Ok(result)
+        });
+    }
+
+    let mut result = loads_with_options(json_str, &opts)?;
+    // Checked on the text: a hook may have replaced the root dict
+    if require_container && !json_str.trim_start_matches([' ', '\t', '\n', '\r']).starts_with(['{', '[']) {
+        return Err(container_root_error());
+    }
+    if let Some(into) = into {
+        result = typed::decode_into(into.py(), result, into, on_extra, on_missing)?;
+    }
+    match opts.key_set {
+        Some(key_set) => Python::with_gil(|py| {
+            Ok((result, key_set.clone_ref(py)).into_pyobject(py)?.into_any().unbind())
+        }),
+        None => Ok(result),
+    }
+}
+
+/// Parse with serde_json, on the large-stack thread when one is configured
+fn loads_with_options(json_str: &str, opts: &LoadsOptions) -> PyResult<PyObject> {
+    let stack_size = PARSE_STACK_SIZE.load(Ordering::Relaxed);
+
     Python::with_gil(|py| {
-        let mut de = serde_json::Deserializer::from_str(json_str);
-        DeserializeSeed::deserialize(PyObjectSeed { py }, &mut de)
-            .map_err(|e| PyValueError::new_err(format!("JSON parsing error: {e}")))
+        if stack_size != 0 {
+            return parse_on_thread(py, stack_size, |py| parse_serde(py, json_str, opts, true));
+        }
+        // A retried parse would call the hooks again for the inner objects
+        // already decoded, so hooked documents are measured up front instead
+        let hooked = opts.object_hook.is_some() || opts.object_pairs_hook.is_some();
+        let json = json_str.as_bytes();
+        if hooked && allows_deep_parse(opts) && simd_parser::nesting_offset(json, CALLER_STACK_DEPTH) < json.len() {
+            return parse_on_deep_stack(py, opts, |py| parse_serde(py, json_str, opts, true));
+        }
+        parse_serde(py, json_str, opts, false)
+    })
+}
+
+/// Whether `max_depth` accepts documents nested past `CALLER_STACK_DEPTH`
+fn allows_deep_parse(opts: &LoadsOptions) -> bool {
+    opts.max_depth.is_some_and(|max_depth| max_depth >= CALLER_STACK_DEPTH)
+}
+
+/// Run `parse` on a thread whose stack fits `max_depth` (or the default
+/// limit) levels, so the depth cap holds whatever the caller's stack size;
+/// Python threads may get as little as 64 KiB
+#[cold]
+pub(crate) fn parse_on_deep_stack<F>(py: Python, opts: &LoadsOptions, parse: F) -> PyResult<PyObject>
+where
+    F: FnOnce(Python) -> PyResult<PyObject> + Send,
+{
+    let max_depth = opts.max_depth.unwrap_or_else(simd_parser::default_max_depth);
+    let stack_size = max_depth.saturating_add(CALLER_STACK_DEPTH).saturating_mul(DEEP_PARSE_STACK_PER_LEVEL);
+    parse_on_thread(py, stack_size, parse)
+}
+
+/// Run `parse` on a new thread with a `stack_size`-byte stack
+fn parse_on_thread<F>(py: Python, stack_size: usize, parse: F) -> PyResult<PyObject>
+where
+    F: FnOnce(Python) -> PyResult<PyObject> + Send,
+{
+    // Release the GIL so the parse thread can take it while we wait
+    py.allow_threads(|| {
+        std::thread::scope(|scope| {
+            let handle = std::thread::Builder::new()
+                .name("rjson-parse".into())
+                .stack_size(stack_size)
+                .spawn_scoped(scope, || Python::with_gil(parse))
+                .map_err(|e| PyRuntimeError::new_err(format!("Failed to spawn parse thread: {e}")))?;
+            handle
+                .join()
+                .unwrap_or_else(|_| Err(PyRuntimeError::new_err("Parse thread panicked")))
+        })
     })
 }
 
+/// Error unless a parsed document's root is a dict or list (`require_container`)
+fn check_container_root(root: &PyObject) -> PyResult<()> {
+    let ptr = root.as_ptr();
+    if unsafe { ffi::PyDict_CheckExact(ptr) != 0 || ffi::PyList_CheckExact(ptr) != 0 } {
+        return Ok(());
+    }
+    Err(container_root_error())
+}
+
+#[cold]
+fn container_root_error() -> PyErr {
+    PyValueError::new_err("JSON parsing error: top-level value must be an object or array")
+}
+
+/// Run `loads` on a dedicated thread with a `bytes`-sized stack.
+///
+/// Lets `loads` accept documents nested deeper than its default depth
+/// limit while keeping the recursive parser: depth is then bounded only by
+/// the stack (or an explicit `max_depth`), so size it for the deepest
+/// documents you accept.
+///
+/// Trade-offs: every `loads` call pays a thread spawn (tens of microseconds),
+/// which dominates for small documents, and an undersized stack aborts the
+/// process on overflow instead of raising. For untrusted input, keep the
+/// default depth limit, which rejects deep documents with a `JSONDecodeError`.
+///
+/// # Arguments
+/// * `bytes` - Stack size in bytes (at least 64 KiB), or 0 to restore the
+///   default in-thread parse with the default depth limit.
+#[pyfunction]
+fn set_parse_stack_size(bytes: usize) -> PyResult<()> {
+    check_parse_stack_size(bytes)?;
+    PARSE_STACK_SIZE.store(bytes, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Validate a parse stack size (shared with `configure`)
+pub(crate) fn check_parse_stack_size(bytes: usize) -> PyResult<()> {
+    if bytes != 0 && bytes < MIN_PARSE_STACK_SIZE {
+        return Err(PyValueError::new_err(format!(
+            "parse stack size must be 0 (disabled) or at least {MIN_PARSE_STACK_SIZE} bytes, got {bytes}"
+        )));
+    }
+    Ok(())
+}
+
 /// Parses JSON using SIMD-accelerated parser (always uses simd-json)
 ///
 /// This function always uses the SIMD parser regardless of input size.
 /// Use this when you know you have large JSON inputs.
 ///
 /// # Arguments
-/// * `json_str` - The JSON string to parse.
+/// * `json_str` - The JSON document (`str`, `bytes` or `bytearray`), as for `loads`.
+/// * `intern_keys` - Share dict key objects through the intern cache (default).
+/// * `intern_values` - As for `loads`.
+/// * `revive_bigint_strings` - As for `loads`.
+/// * `revive_nan_strings`, `parse_nan_strings` - As for `loads`.
+/// * `normalize` - As for `loads`.
+/// * `datetime_keys` - As for `loads`.
+/// * `allowed_keys` - As for `loads`.
+/// * `ignored_keys` - As for `loads`.
+/// * `require_container` - As for `loads`.
+/// * `dedup` - As for `loads`.
+/// * `into`, `on_extra`, `on_missing` - As for `loads`.
+/// * `max_depth` - Deepest array/object nesting accepted (default 1024, or
+///   `configure(default_max_depth=...)`); deeper documents raise
+///   `JSONDecodeError`.
+///
+/// # Returns
+/// A PyObject representing the parsed JSON. Malformed JSON raises
+/// `rjson.JSONDecodeError`, as for `loads`.
+#[pyfunction]
+#[pyo3(signature = (
+    json_str, *, intern_keys = true, intern_values = false, revive_bigint_strings = false,
+    revive_nan_strings = false, parse_nan_strings = false,
+    normalize = None, datetime_keys = None, allowed_keys = None, ignored_keys = None,
+    require_container = false, dedup = false, into = None, on_extra = "ignore", on_missing = "error",
+    max_depth = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn loads_simd(
+    json_str: &Bound<'_, PyAny>,
+    intern_keys: bool,
+    intern_values: bool,
+    revive_bigint_strings: bool,
+    revive_nan_strings: bool,
+    parse_nan_strings: bool,
+    normalize: Option<&str>,
+    datetime_keys: Option<&Bound<'_, PyAny>>,
+    allowed_keys: Option<&Bound<'_, PyAny>>,
+    ignored_keys: Option<&Bound<'_, PyAny>>,
+    require_container: bool,
+    dedup: bool,
+    into: Option<&Bound<'_, PyAny>>,
+    on_extra: &str,
+    on_missing: &str,
+    max_depth: Option<usize>,
+) -> PyResult<PyObject> {
+    let on_extra = typed::ExtraKeys::from_name(on_extra)?;
+    let on_missing = typed::MissingKeys::from_name(on_missing)?;
+    let opts = LoadsOptions {
+        intern_keys,
+        intern_values,
+        revive_bigint_strings,
+        revive_nan_strings: revive_nan_strings || parse_nan_strings,
+        normalize: NormalForm::from_name(normalize)?,
+        datetime_keys: parse_datetime_keys(datetime_keys)?,
+        key_filter: KeyFilter::from_args(allowed_keys, ignored_keys)?,
+        dedup,
+        key_set: None,
+        max_depth,
+        reject_duplicate_keys: false,
+        object_hook: None,
+        object_pairs_hook: None,
+        use_tuples: false,
+    };
+    let result = if let Ok(array) = json_str.downcast::<PyByteArray>() {
+        // Read in place instead of copied out as `json_bytes` does: the
+        // parse copies it into its scratch buffer anyway, and the export
+        // stops the bytearray being resized or freed until it's done
+        let view = PyBuffer::<u8>::get(array)?;
+        // SAFETY: a bytearray's buffer is one contiguous run of bytes, kept
+        // in place by `view`; after the copy it's only read for error messages
+        let bytes = unsafe { std::slice::from_raw_parts(view.buf_ptr() as *const u8, view.len_bytes()) };
+        simd_parser::loads_simd(Cow::Borrowed(bytes), &opts)?
+    } else {
+        simd_parser::loads_simd(json_bytes(json_str)?, &opts)?
+    };
+    if require_container {
+        check_container_root(&result)?;
+    }
+    match into {
+        Some(into) => typed::decode_into(into.py(), result, into, on_extra, on_missing),
+        None => Ok(result),
+    }
+}
+
+/// Deserialize JSON read from a file-like object.
+///
+/// Calls `fp.read()` once and parses the result with `loads`, so text and
+/// binary files both work (bytes are parsed without decoding to `str`
+/// first).
+///
+/// # Arguments
+/// * `fp` - Any object with a `read()` method returning str, bytes or
+///   bytearray.
+/// * `**kwargs` - `loads` options.
 ///
 /// # Returns
-/// A PyObject representing the parsed JSON, or a PyValueError on error.
+/// As for `loads`.
 #[pyfunction]
-fn loads_simd(json_str: &str) -> PyResult<PyObject> {
-    simd_parser::loads_simd(json_str)
+#[pyo3(signature = (fp, **kwargs))]
+fn load(py: Python, fp: &Bound<'_, PyAny>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    let data = fp.call_method0(pyo3::intern!(py, "read"))?;
+    Ok(wrap_pyfunction!(loads, py)?.call((data,), kwargs)?.unbind())
 }
 
 /// Write a JSON string with proper escaping to a buffer
@@ -373,19 +1327,980 @@ fn write_json_string(buf: &mut Vec<u8>, s: &str) {
     simd_escape::write_json_string_simd(buf, s);
 }
 
-/// Phase 2: Custom high-performance JSON serializer
-///
-/// Uses itoa (10x faster than fmt) and ryu (5x faster than fmt) for number formatting.
-/// Writes directly to Vec<u8> buffer, bypassing serde_json overhead.
+/// `write_json_string` under an escape policy (`dumps(escape=...)`)
+#[inline]
+fn write_json_string_with(buf: &mut Vec<u8>, s: &str, escape: EscapePolicy) {
+    match escape {
+        EscapePolicy::Json => write_json_string(buf, s),
+        EscapePolicy::Portable => simd_escape::write_json_string_portable(buf, s),
+        EscapePolicy::Ascii => simd_escape::write_json_string_ascii(buf, s),
+    }
+}
+
+/// Float formatting strategy selected by `dumps(float_repr=...)`
+///
+/// ryu and Python's `repr` both emit the shortest round-tripping digits, so
+/// they only disagree on *how* the exponent is written (`1e16` vs `1e+16`,
+/// `1e-5` vs `1e-05`). Inside `1e-4 <= |v| < 1e16` neither side uses an
+/// exponent and the outputs are byte-identical, which gives `Hybrid` a cheap,
+/// exact divergence check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FloatRepr {
+    /// Always use ryu (fastest, default)
+    Ryu,
+    /// Always use `PyOS_double_to_string` (identical to `repr(float)`)
+    Python,
+    /// ryu when it matches `repr`, `PyOS_double_to_string` otherwise
+    Hybrid,
+    /// ryu digits expanded to plain decimal notation (`dumps(no_scientific=True)`)
+    Plain,
+    /// ryu digits laid out like ECMAScript's `Number.prototype.toString`
+    /// (`dumps(canonical=True)`)
+    Ecma,
+}
+
+impl FloatRepr {
+    fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "ryu" => Ok(FloatRepr::Ryu),
+            "python" => Ok(FloatRepr::Python),
+            "hybrid" => Ok(FloatRepr::Hybrid),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid float_repr: {name:?} (expected 'ryu', 'python' or 'hybrid')"
+            ))),
+        }
+    }
+}
+
+/// Integer output selected by `dumps(bigint_mode=...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BigIntMode {
+    /// Every int is a JSON number (default)
+    Number,
+    /// Ints with `|n| > 2^53` are quoted, since JavaScript parses them lossily
+    String,
+    /// Ints with `|n| > 2^53` are rejected (`dumps(canonical=True)`)
+    Error,
+}
+
+impl BigIntMode {
+    fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "number" => Ok(BigIntMode::Number),
+            "string" => Ok(BigIntMode::String),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid bigint_mode: {name:?} (expected 'number' or 'string')"
+            ))),
+        }
+    }
+}
+
+/// Largest magnitude a JavaScript `Number` holds exactly at the end of the
+/// integer range; anything beyond may round when parsed by a browser
+const JS_SAFE_INT_LIMIT: u64 = 1 << 53;
+
+/// Write an int's decimal digits, quoting or rejecting them if `bigint_mode`
+/// asks for it.
+///
+/// `is_big` says whether `|n| > 2^53`; callers compute it from whichever
+/// representation they already have (i64, u64, or arbitrary precision).
+#[inline(always)]
+pub(crate) fn write_int_digits(buf: &mut Vec<u8>, digits: &[u8], is_big: bool, mode: BigIntMode) -> PyResult<()> {
+    if unlikely(is_big && mode != BigIntMode::Number) {
+        if mode == BigIntMode::Error {
+            return Err(big_int_error(digits));
+        }
+        buf.push(b'"');
+        buf.extend_from_slice(digits);
+        buf.push(b'"');
+    } else {
+        buf.extend_from_slice(digits);
+    }
+    Ok(())
+}
+
+#[cold]
+fn big_int_error(digits: &[u8]) -> PyErr {
+    PyValueError::new_err(format!(
+        "canonical JSON can't represent the int {} exactly (|n| > 2^53); use bigint_mode='string'",
+        String::from_utf8_lossy(digits)
+    ))
+}
+
+/// Whether an i64 is outside the JavaScript-safe range
+#[inline(always)]
+pub(crate) fn is_big_i64(value: i64) -> bool {
+    value.unsigned_abs() > JS_SAFE_INT_LIMIT
+}
+
+/// Key ordering selected by `dumps(sort_keys=...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKeys {
+    /// Insertion order (default)
+    Off,
+    /// Sort every dict
+    All,
+    /// Sort only the root dict; nested dicts keep insertion order
+    Top,
+    /// Sort every dict by UTF-16 code units, as RFC 8785 requires
+    /// (`dumps(canonical=True)`)
+    Utf16,
+}
+
+impl SortKeys {
+    fn from_arg(arg: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let Some(arg) = arg else {
+            return Ok(SortKeys::Off);
+        };
+        if let Ok(flag) = arg.downcast_exact::<PyBool>() {
+            return Ok(if flag.is_true() { SortKeys::All } else { SortKeys::Off });
+        }
+        if arg.downcast_exact::<PyString>().is_ok_and(|s| s.to_cow().is_ok_and(|s| s == "top")) {
+            return Ok(SortKeys::Top);
+        }
+        Err(PyValueError::new_err(format!(
+            "Invalid sort_keys: {} (expected True, False or 'top')",
+            arg.repr()?
+        )))
+    }
+
+    /// Whether a dict at nesting `depth` gets its keys sorted
+    #[inline(always)]
+    fn applies_at(self, depth: usize) -> bool {
+        match self {
+            SortKeys::Off => false,
+            SortKeys::All | SortKeys::Utf16 => true,
+            SortKeys::Top => depth == 0,
+        }
+    }
+}
+
+/// `datetime.timedelta` output selected by `dumps(timedelta_mode=...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimedeltaMode {
+    /// timedeltas are unsupported (default)
+    Off,
+    /// Total seconds as a number (`90.5`)
+    Seconds,
+    /// ISO 8601 duration string (`"PT1M30.5S"`)
+    Iso8601,
+}
+
+impl TimedeltaMode {
+    fn from_name(name: Option<&str>) -> PyResult<Self> {
+        match name {
+            None => Ok(TimedeltaMode::Off),
+            Some("seconds") => Ok(TimedeltaMode::Seconds),
+            Some("iso8601") => Ok(TimedeltaMode::Iso8601),
+            Some(name) => Err(PyValueError::new_err(format!(
+                "Invalid timedelta_mode: {name:?} (expected 'seconds' or 'iso8601')"
+            ))),
+        }
+    }
+}
+
+/// Write a timedelta of `total_us` microseconds in the given mode.
+///
+/// Both forms are exact (no float round-trip): microseconds become up to six
+/// fractional digits with trailing zeros trimmed. Negative durations get a
+/// leading `-` (`-90.5`, `"-PT1M30.5S"`); ISO 8601 has no sign, but this is
+/// the common extension (e.g. Java's `Duration`).
+fn write_timedelta(buf: &mut Vec<u8>, total_us: i128, mode: TimedeltaMode) {
+    const US_PER_SEC: u128 = 1_000_000;
+    let abs = total_us.unsigned_abs();
+    let (secs, micros) = (abs / US_PER_SEC, (abs % US_PER_SEC) as u32);
+    let mut itoa_buf = itoa::Buffer::new();
+
+    let write_secs = |buf: &mut Vec<u8>, secs: u128| {
+        let mut itoa_buf = itoa::Buffer::new();
+        buf.extend_from_slice(itoa_buf.format(secs).as_bytes());
+        if micros != 0 {
+            let frac = format!("{micros:06}");
+            buf.push(b'.');
+            buf.extend_from_slice(frac.trim_end_matches('0').as_bytes());
+        }
+    };
+
+    if mode == TimedeltaMode::Seconds {
+        if total_us < 0 {
+            buf.push(b'-');
+        }
+        write_secs(buf, secs);
+        return;
+    }
+
+    buf.push(b'"');
+    if total_us < 0 {
+        buf.push(b'-');
+    }
+    buf.push(b'P');
+    let (days, day_secs) = (secs / 86_400, secs % 86_400);
+    if days != 0 {
+        buf.extend_from_slice(itoa_buf.format(days).as_bytes());
+        buf.push(b'D');
+    }
+    if day_secs != 0 || micros != 0 || days == 0 {
+        buf.push(b'T');
+        let (hours, minutes, seconds) = (day_secs / 3600, day_secs % 3600 / 60, day_secs % 60);
+        if hours != 0 {
+            buf.extend_from_slice(itoa_buf.format(hours).as_bytes());
+            buf.push(b'H');
+        }
+        if minutes != 0 {
+            buf.extend_from_slice(itoa_buf.format(minutes).as_bytes());
+            buf.push(b'M');
+        }
+        if seconds != 0 || micros != 0 || day_secs == 0 {
+            write_secs(buf, seconds);
+            buf.push(b'S');
+        }
+    }
+    buf.push(b'"');
+}
+
+/// `bytes`/`bytearray` output selected by `dumps(bytes_mode=...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BytesMode {
+    /// bytes are unsupported (default)
+    Off,
+    /// Standard base64 with padding (`"aGk="`)
+    Base64,
+    /// Lowercase hex (`"6869"`)
+    Hex,
+    /// Decoded as UTF-8, failing on invalid sequences
+    Utf8,
+}
+
+impl BytesMode {
+    fn from_name(name: Option<&str>) -> PyResult<Self> {
+        match name {
+            None => Ok(BytesMode::Off),
+            Some("base64") => Ok(BytesMode::Base64),
+            Some("hex") => Ok(BytesMode::Hex),
+            Some("utf8") => Ok(BytesMode::Utf8),
+            Some(name) => Err(PyValueError::new_err(format!(
+                "Invalid bytes_mode: {name:?} (expected 'base64', 'hex' or 'utf8')"
+            ))),
+        }
+    }
+}
+
+/// Write `bytes` as a JSON string in the given (non-`Off`) mode
+///
+/// base64 and hex output is plain ASCII and needs no escaping; `Utf8` text
+/// goes through the usual string escaping under `escape`.
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8], mode: BytesMode, escape: EscapePolicy) -> PyResult<()> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    match mode {
+        BytesMode::Off => unreachable!("bytes_mode is off"),
+        BytesMode::Utf8 => {
+            let text = std::str::from_utf8(bytes).map_err(|e| {
+                PyValueError::new_err(format!("bytes_mode='utf8' got bytes that aren't valid UTF-8: {e}"))
+            })?;
+            write_json_string_with(buf, text, escape);
+        }
+        BytesMode::Hex => {
+            buf.reserve(bytes.len() * 2 + 2);
+            buf.push(b'"');
+            for &byte in bytes {
+                buf.push(HEX[(byte >> 4) as usize]);
+                buf.push(HEX[(byte & 0xf) as usize]);
+            }
+            buf.push(b'"');
+        }
+        BytesMode::Base64 => {
+            buf.reserve(bytes.len().div_ceil(3) * 4 + 2);
+            buf.push(b'"');
+            for chunk in bytes.chunks(3) {
+                let n = (chunk[0] as u32) << 16
+                    | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+                    | *chunk.get(2).unwrap_or(&0) as u32;
+                for i in 0..4 {
+                    if i <= chunk.len() {
+                        buf.push(BASE64[(n >> (18 - 6 * i)) as usize & 0x3f]);
+                    } else {
+                        buf.push(b'=');
+                    }
+                }
+            }
+            buf.push(b'"');
+        }
+    }
+    Ok(())
+}
+
+/// Which `datetime` class a value serialized by `write_temporal` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TemporalKind {
+    DateTime,
+    Date,
+    Time,
+}
+
+/// Append `value` zero-padded to `width` digits
+#[inline]
+fn push_padded(buf: &mut Vec<u8>, value: u32, width: usize) {
+    let mut itoa_buf = itoa::Buffer::new();
+    let digits = itoa_buf.format(value);
+    buf.resize(buf.len() + width.saturating_sub(digits.len()), b'0');
+    buf.extend_from_slice(digits.as_bytes());
+}
+
+/// Write a `datetime`, `date` or `time` as a quoted RFC 3339 / ISO 8601
+/// string, the same text as its `isoformat()`.
+///
+/// Microseconds are written (all six digits) only when non-zero. Aware values
+/// get their UTC offset (`+05:30`, with seconds if the offset has them);
+/// naive datetimes get `+00:00` if `naive_utc`, no offset otherwise.
+///
+/// # Safety
+/// `obj` must be exactly the `datetime` class named by `kind`.
+pub(crate) unsafe fn write_temporal(
+    buf: &mut Vec<u8>,
+    obj: &Bound<'_, PyAny>,
+    kind: TemporalKind,
+    naive_utc: bool,
+) -> PyResult<()> {
+    buf.push(b'"');
+    if kind != TemporalKind::Time {
+        let date = obj.downcast_unchecked::<PyDate>();
+        push_padded(buf, date.get_year() as u32, 4);
+        buf.push(b'-');
+        push_padded(buf, date.get_month() as u32, 2);
+        buf.push(b'-');
+        push_padded(buf, date.get_day() as u32, 2);
+    }
+
+    let (hour, minute, second, micro, tzinfo) = match kind {
+        TemporalKind::Date => {
+            buf.push(b'"');
+            return Ok(());
+        }
+        TemporalKind::DateTime => {
+            buf.push(b'T');
+            let dt = obj.downcast_unchecked::<PyDateTime>();
+            (dt.get_hour(), dt.get_minute(), dt.get_second(), dt.get_microsecond(), dt.get_tzinfo())
+        }
+        TemporalKind::Time => {
+            let t = obj.downcast_unchecked::<PyTime>();
+            (t.get_hour(), t.get_minute(), t.get_second(), t.get_microsecond(), t.get_tzinfo())
+        }
+    };
+    push_padded(buf, hour as u32, 2);
+    buf.push(b':');
+    push_padded(buf, minute as u32, 2);
+    buf.push(b':');
+    push_padded(buf, second as u32, 2);
+    if micro != 0 {
+        buf.push(b'.');
+        push_padded(buf, micro, 6);
+    }
+
+    // A tzinfo may still report no offset, which makes the value naive
+    let offset = match tzinfo {
+        Some(_) => obj.call_method0("utcoffset")?,
+        None => obj.py().None().into_bound(obj.py()),
+    };
+    if let Ok(offset) = offset.downcast::<PyDelta>() {
+        let total_us = (offset.get_days() as i64 * 86_400 + offset.get_seconds() as i64) * 1_000_000
+            + offset.get_microseconds() as i64;
+        buf.push(if total_us < 0 { b'-' } else { b'+' });
+        let abs = total_us.unsigned_abs();
+        let (secs, micros) = ((abs / 1_000_000) as u32, (abs % 1_000_000) as u32);
+        push_padded(buf, secs / 3600, 2);
+        buf.push(b':');
+        push_padded(buf, secs % 3600 / 60, 2);
+        if secs % 60 != 0 || micros != 0 {
+            buf.push(b':');
+            push_padded(buf, secs % 60, 2);
+            if micros != 0 {
+                buf.push(b'.');
+                push_padded(buf, micros, 6);
+            }
+        }
+    } else if naive_utc && kind == TemporalKind::DateTime {
+        buf.extend_from_slice(b"+00:00");
+    }
+    buf.push(b'"');
+    Ok(())
+}
+
+/// The `datetime` class of `ty`, if it's exactly one `write_temporal` handles
+#[inline]
+pub(crate) fn temporal_kind(py: Python, ty: *mut ffi::PyTypeObject) -> Option<TemporalKind> {
+    if ty == type_cache::get_datetime_type(py) {
+        Some(TemporalKind::DateTime)
+    } else if ty == type_cache::get_date_type(py) {
+        Some(TemporalKind::Date)
+    } else if ty == type_cache::get_time_type(py) {
+        Some(TemporalKind::Time)
+    } else {
+        None
+    }
+}
+
+/// Write a `uuid.UUID` in its canonical quoted form
+/// (`"550e8400-e29b-41d4-a716-446655440000"`)
+///
+/// Formats the 128-bit `int` value directly rather than calling `str()`.
+pub(crate) fn write_uuid(buf: &mut Vec<u8>, obj: &Bound<'_, PyAny>) -> PyResult<()> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let value: u128 = obj.getattr(pyo3::intern!(obj.py(), "int"))?.extract()?;
+
+    buf.reserve(38);
+    buf.push(b'"');
+    for i in 0..32 {
+        if matches!(i, 8 | 12 | 16 | 20) {
+            buf.push(b'-');
+        }
+        buf.push(HEX[(value >> (124 - 4 * i)) as usize & 0xf]);
+    }
+    buf.push(b'"');
+    Ok(())
+}
+
+/// The `str()` of a `pathlib` path (any `PurePath` subclass), or `None` if
+/// `obj` isn't one
+pub(crate) fn path_str<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyString>>> {
+    let path_type = type_cache::get_pure_path_type(obj.py());
+    if path_type.is_null() {
+        return Ok(None);
+    }
+    match unsafe { ffi::PyObject_IsInstance(obj.as_ptr(), path_type as *mut ffi::PyObject) } {
+        -1 => Err(PyErr::fetch(obj.py())),
+        0 => Ok(None),
+        _ => obj.str().map(Some),
+    }
+}
+
+/// Validate the `none_repr` option of `dumps`
+///
+/// Accepts `null` or any single valid JSON string literal (e.g. `""` or
+/// `"null"`), so the output stays valid JSON whatever the caller picks.
+/// Returns a borrowed slice for the default to keep the common path
+/// allocation-free.
+fn parse_none_repr(repr: &str) -> PyResult<Cow<'static, [u8]>> {
+    if repr == "null" {
+        return Ok(Cow::Borrowed(b"null"));
+    }
+    if repr.starts_with('"') && serde_json::from_str::<String>(repr).is_ok() {
+        return Ok(Cow::Owned(repr.as_bytes().to_vec()));
+    }
+    Err(PyValueError::new_err(format!(
+        "Invalid none_repr: {repr:?} (expected 'null' or a JSON string literal)"
+    )))
+}
+
+/// Reject NaN and +/-Infinity, which have no JSON representation.
+///
+/// Every float-serializing path (`JsonBuffer`, the bulk float array path and
+/// `DirectSerializer`) goes through this check, so all engines raise the
+/// same exception type with the same message.
+#[inline(always)]
+pub(crate) fn check_finite_float(value: f64) -> PyResult<()> {
+    if unlikely(!value.is_finite()) {
+        return Err(non_finite_float_error(value));
+    }
+    Ok(())
+}
+
+/// Non-finite float output selected by `dumps(nan_mode=...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NanMode {
+    /// Raise ValueError (default)
+    Error,
+    /// Emit the JSON strings `"NaN"`, `"Infinity"`, `"-Infinity"`
+    String,
+    /// Emit the bare tokens `NaN`, `Infinity`, `-Infinity`, like stdlib
+    /// `json` (`dumps(allow_nan=True)`; not valid JSON)
+    Literal,
+}
+
+impl NanMode {
+    fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "error" => Ok(NanMode::Error),
+            "string" => Ok(NanMode::String),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid nan_mode: {name:?} (expected 'error' or 'string')"
+            ))),
+        }
+    }
+}
+
+impl EscapePolicy {
+    fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "json" => Ok(EscapePolicy::Json),
+            "portable" => Ok(EscapePolicy::Portable),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid escape: {name:?} (expected 'json' or 'portable')"
+            ))),
+        }
+    }
+}
+
+/// What `dumps` writes for a container deeper than `max_depth`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MaxDepthMode {
+    /// Raise ValueError (default)
+    Error,
+    /// Write `null`
+    Null,
+    /// Write the placeholder string `"..."`
+    Truncate,
+}
+
+impl MaxDepthMode {
+    fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "error" => Ok(MaxDepthMode::Error),
+            "null" => Ok(MaxDepthMode::Null),
+            "truncate" => Ok(MaxDepthMode::Truncate),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid on_max_depth: {name:?} (expected 'error', 'null' or 'truncate')"
+            ))),
+        }
+    }
+}
+
+/// What `dumps` does when reading a dataclass field raises
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MissingAttrMode {
+    /// Propagate the exception (default)
+    Error,
+    /// Write the field as `null`
+    Null,
+    /// Leave the field out
+    Skip,
+}
+
+impl MissingAttrMode {
+    fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "error" => Ok(MissingAttrMode::Error),
+            "null" => Ok(MissingAttrMode::Null),
+            "skip" => Ok(MissingAttrMode::Skip),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid on_missing_attr: {name:?} (expected 'error', 'null' or 'skip')"
+            ))),
+        }
+    }
+}
+
+/// Handle a non-finite float according to `nan_mode` (cold path)
+///
+/// Shared by `JsonBuffer::write_float` and the bulk float array path.
+#[cold]
+#[inline(never)]
+pub(crate) fn write_non_finite_float(buf: &mut Vec<u8>, value: f64, nan_mode: NanMode) -> PyResult<()> {
+    match nan_mode {
+        NanMode::Error => Err(non_finite_float_error(value)),
+        NanMode::String | NanMode::Literal => {
+            let text: &str = if value.is_nan() {
+                "NaN"
+            } else if value > 0.0 {
+                "Infinity"
+            } else {
+                "-Infinity"
+            };
+            if nan_mode == NanMode::String {
+                buf.push(b'"');
+                buf.extend_from_slice(text.as_bytes());
+                buf.push(b'"');
+            } else {
+                buf.extend_from_slice(text.as_bytes());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Error path for non-finite floats (cold path)
+#[cold]
+#[inline(never)]
+fn non_finite_float_error(value: f64) -> PyErr {
+    PyValueError::new_err(format!("Cannot serialize non-finite float: {value}"))
+}
+
+/// Write a finite float using the requested formatting strategy.
+///
+/// Shared by `JsonBuffer::write_float` and the bulk float array path so both
+/// produce identical output. Caller must have called `check_finite_float`.
+#[inline]
+pub(crate) fn write_finite_float(buf: &mut Vec<u8>, value: f64, repr: FloatRepr) {
+    let ryu_matches_repr = || {
+        let abs = value.abs();
+        abs == 0.0 || (1e-4..1e16).contains(&abs)
+    };
+
+    if repr == FloatRepr::Plain {
+        write_float_plain(buf, value);
+    } else if repr == FloatRepr::Ecma {
+        write_float_ecma(buf, value);
+    } else if repr == FloatRepr::Ryu || (repr == FloatRepr::Hybrid && ryu_matches_repr()) {
+        // OPTIMIZATION: Use ryu for 5x faster float formatting
+        let mut ryu_buf = ryu::Buffer::new();
+        buf.extend_from_slice(ryu_buf.format(value).as_bytes());
+    } else {
+        write_float_python(buf, value);
+    }
+}
+
+/// The JSON key text for a non-`str` dict key, as the stdlib `json` writes it
+///
+/// `True`/`False`/`None` become `"true"`/`"false"`/`"null"`, ints their
+/// decimal digits and floats their usual number form (`repr`) inside quotes;
+/// non-finite floats follow `nan_mode`. Other key types are rejected with
+/// the usual error.
+#[cold]
+#[inline(never)]
+pub(crate) fn coerce_dict_key<'py>(
+    key: &Bound<'py, PyAny>,
+    float_repr: FloatRepr,
+    nan_mode: NanMode,
+) -> PyResult<Bound<'py, PyString>> {
+    let py = key.py();
+    let ptr = key.as_ptr();
+    let text: Cow<str> = unsafe {
+        if key.is_none() {
+            Cow::Borrowed("null")
+        } else if ffi::PyBool_Check(ptr) != 0 {
+            Cow::Borrowed(if key.is_truthy()? { "true" } else { "false" })
+        } else if ffi::PyLong_Check(ptr) != 0 {
+            // int.__repr__, so IntEnum members give their value
+            return py.get_type::<PyInt>().call_method1("__repr__", (key,))?.downcast_into::<PyString>().map_err(Into::into);
+        } else if ffi::PyFloat_Check(ptr) != 0 {
+            let value = ffi::PyFloat_AsDouble(ptr);
+            if value.is_finite() {
+                let mut buf = Vec::new();
+                write_finite_float(&mut buf, value, float_repr);
+                Cow::Owned(String::from_utf8_unchecked(buf))
+            } else if nan_mode == NanMode::Error {
+                return Err(non_finite_float_error(value));
+            } else if value.is_nan() {
+                Cow::Borrowed("NaN")
+            } else if value > 0.0 {
+                Cow::Borrowed("Infinity")
+            } else {
+                Cow::Borrowed("-Infinity")
+            }
+        } else {
+            return Err(JsonBuffer::dict_key_error());
+        }
+    };
+    Ok(PyString::new(py, &text))
+}
+
+/// Write a `decimal.Decimal` as its exact `str()` form.
+///
+/// `str(Decimal)` is always a valid JSON number for finite values
+/// (e.g. `1.10`, `-0`, `1E+2`), so no precision is lost to a float round-trip.
+/// NaN, sNaN and Infinity are rejected like non-finite floats.
+///
+/// # Safety
+/// `obj` must be a valid pointer to a `decimal.Decimal` instance.
+pub(crate) unsafe fn write_decimal(buf: &mut Vec<u8>, obj: *mut ffi::PyObject) -> PyResult<()> {
+    let str_ptr = ffi::PyObject_Str(obj);
+    if str_ptr.is_null() {
+        return Err(PyErr::fetch(Python::assume_gil_acquired()));
+    }
+
+    let mut size: ffi::Py_ssize_t = 0;
+    let data = ffi::PyUnicode_AsUTF8AndSize(str_ptr, &mut size);
+    if data.is_null() {
+        ffi::Py_DECREF(str_ptr);
+        return Err(PyErr::fetch(Python::assume_gil_acquired()));
+    }
+    let text = std::slice::from_raw_parts(data as *const u8, size as usize);
+
+    // Finite values start with a digit after the optional sign;
+    // "NaN", "sNaN" and "Infinity" start with a letter
+    let first = text.iter().find(|&&b| b != b'-').copied().unwrap_or(b'N');
+    let result = if first.is_ascii_digit() {
+        buf.extend_from_slice(text);
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "Cannot serialize non-finite Decimal: {}",
+            String::from_utf8_lossy(text)
+        )))
+    };
+
+    ffi::Py_DECREF(str_ptr);
+    result
+}
+
+/// Format a float without an exponent (`1e-7` -> `0.0000001`, `1e21` ->
+/// `1000000000000000000000.0`)
+///
+/// Expands ryu's shortest round-tripping digits, so the value still parses
+/// back exactly. The expansion is bounded by the f64 range: at most 309
+/// integer digits (`1.7976931348623157e308`) or 323 leading fractional zeros
+/// (`5e-324`), i.e. under 330 bytes per float.
+fn write_float_plain(buf: &mut Vec<u8>, value: f64) {
+    let mut ryu_buf = ryu::Buffer::new();
+    let formatted = ryu_buf.format(value).as_bytes();
+    let Some(e_pos) = formatted.iter().position(|&b| b == b'e') else {
+        buf.extend_from_slice(formatted);
+        return;
+    };
+
+    // ryu's exponent form is `[-]d[.ddd]e[-]x`
+    let exp: i32 = std::str::from_utf8(&formatted[e_pos + 1..])
+        .ok()
+        .and_then(|e| e.parse().ok())
+        .unwrap_or(0);
+    let (sign, mantissa) = match formatted[..e_pos].split_first() {
+        Some((b'-', rest)) => (&b"-"[..], rest),
+        _ => (&b""[..], &formatted[..e_pos]),
+    };
+    let digits: Vec<u8> = mantissa.iter().copied().filter(|&b| b != b'.').collect();
+    // Position of the decimal point relative to the start of `digits`
+    let point = 1 + exp;
+
+    buf.extend_from_slice(sign);
+    if point <= 0 {
+        buf.extend_from_slice(b"0.");
+        buf.resize(buf.len() + (-point) as usize, b'0');
+        buf.extend_from_slice(&digits);
+    } else if point as usize >= digits.len() {
+        buf.extend_from_slice(&digits);
+        buf.resize(buf.len() + point as usize - digits.len(), b'0');
+        buf.extend_from_slice(b".0");
+    } else {
+        let (int_part, frac_part) = digits.split_at(point as usize);
+        buf.extend_from_slice(int_part);
+        buf.push(b'.');
+        buf.extend_from_slice(frac_part);
+    }
+}
+
+/// Write a float as ECMAScript's `Number.prototype.toString` does (ECMA-262
+/// `Number::toString`), the number form RFC 8785 (JCS) prescribes
+///
+/// ryu's digits are the shortest round-tripping ones, as ECMAScript
+/// requires; only their layout differs. Integral values have no fraction
+/// (`1.0` is `1`), plain notation is used for `1e-7 < |v| < 1e21`, and
+/// exponents outside it carry an explicit sign (`1e+21`, `1.5e-7`). `-0.0`
+/// is `0`.
+fn write_float_ecma(buf: &mut Vec<u8>, value: f64) {
+    if value == 0.0 {
+        buf.push(b'0');
+        return;
+    }
+    let mut ryu_buf = ryu::Buffer::new();
+    let formatted = ryu_buf.format(value).as_bytes();
+    let (sign, formatted) = match formatted.split_first() {
+        Some((b'-', rest)) => (&b"-"[..], rest),
+        _ => (&b""[..], formatted),
+    };
+
+    // ryu writes `ddd.ddd` or `d[.ddd]e[-]x`
+    let (mantissa, exp) = match formatted.iter().position(|&b| b == b'e') {
+        Some(e_pos) => {
+            let exp: i32 = std::str::from_utf8(&formatted[e_pos + 1..])
+                .ok()
+                .and_then(|e| e.parse().ok())
+                .unwrap_or(0);
+            (&formatted[..e_pos], exp)
+        }
+        None => (formatted, 0),
+    };
+    let int_len = mantissa.iter().position(|&b| b == b'.').unwrap_or(mantissa.len());
+    let mut digits: Vec<u8> = mantissa.iter().copied().filter(|&b| b != b'.').collect();
+    // ECMA-262's `n`: the decimal point sits after `point` digits
+    let mut point = int_len as i32 + exp;
+    let leading = digits.iter().take_while(|&&b| b == b'0').count();
+    digits.drain(..leading);
+    point -= leading as i32;
+    while digits.last() == Some(&b'0') {
+        digits.pop();
+    }
+    let k = digits.len() as i32;
+
+    buf.extend_from_slice(sign);
+    if k <= point && point <= 21 {
+        buf.extend_from_slice(&digits);
+        buf.resize(buf.len() + (point - k) as usize, b'0');
+    } else if 0 < point && point <= 21 {
+        let (int_part, frac_part) = digits.split_at(point as usize);
+        buf.extend_from_slice(int_part);
+        buf.push(b'.');
+        buf.extend_from_slice(frac_part);
+    } else if -6 < point && point <= 0 {
+        buf.extend_from_slice(b"0.");
+        buf.resize(buf.len() + (-point) as usize, b'0');
+        buf.extend_from_slice(&digits);
+    } else {
+        buf.push(digits[0]);
+        if k > 1 {
+            buf.push(b'.');
+            buf.extend_from_slice(&digits[1..]);
+        }
+        buf.push(b'e');
+        buf.push(if point > 0 { b'+' } else { b'-' });
+        let mut itoa_buf = itoa::Buffer::new();
+        buf.extend_from_slice(itoa_buf.format((point - 1).unsigned_abs()).as_bytes());
+    }
+}
+
+/// Format a float exactly like Python's `repr(float)` (slow path)
+#[cold]
+#[inline(never)]
+fn write_float_python(buf: &mut Vec<u8>, value: f64) {
+    unsafe {
+        let repr_ptr = ffi::PyOS_double_to_string(
+            value,
+            b'r' as std::ffi::c_char,
+            0,
+            ffi::Py_DTSF_ADD_DOT_0,
+            std::ptr::null_mut(),
+        );
+        if repr_ptr.is_null() {
+            // Allocation failure - ryu output is still valid JSON
+            ffi::PyErr_Clear();
+            let mut ryu_buf = ryu::Buffer::new();
+            buf.extend_from_slice(ryu_buf.format(value).as_bytes());
+            return;
+        }
+        buf.extend_from_slice(std::ffi::CStr::from_ptr(repr_ptr).to_bytes());
+        ffi::PyMem_Free(repr_ptr as *mut std::ffi::c_void);
+    }
+}
+
+/// Phase 2: Custom high-performance JSON serializer
+///
+/// Uses itoa (10x faster than fmt) and ryu (5x faster than fmt) for number formatting.
+/// Writes directly to Vec<u8> buffer, bypassing serde_json overhead.
 struct JsonBuffer {
     /// Buffer for JSON output (pub for Phase 14 buffer reuse)
     pub buf: Vec<u8>,
+    /// Float formatting strategy
+    float_repr: FloatRepr,
+    /// What NaN and infinities become
+    nan_mode: NanMode,
+    /// Which characters strings and keys escape
+    escape: EscapePolicy,
+    /// Dict key whose value is currently being serialized (`None` at top
+    /// level). Owned, so Python code run for the value can't free it from
+    /// under an error message; only read when building one.
+    current_key: Option<PyObject>,
+    /// Entries of the dicts being written, innermost last, as strong
+    /// references held while their values are serialized (see
+    /// `write_dict_entries`)
+    dict_entries: Vec<(*mut ffi::PyObject, *mut ffi::PyObject)>,
+    /// Points passed so far where Python code may run (`FastType::Other`
+    /// values, sink writes), and so mutate a dict being written
+    python_calls: u64,
+    /// Serialize arbitrary iterables (generators, sets, ...) as arrays
+    iterables: bool,
+    /// Bytes emitted for `None` (`null` unless overridden)
+    none_repr: Cow<'static, [u8]>,
+    /// Whether ints beyond 2^53 are quoted
+    bigint_mode: BigIntMode,
+    /// Which dicts get their keys sorted
+    sort_keys: SortKeys,
+    /// Keys written first in every dict that has them (`priority_keys`)
+    priority_keys: Vec<Py<PyString>>,
+    /// Container nesting depth of the value being serialized (0 = root)
+    depth: usize,
+    /// Containers at `depth >= max_depth` are replaced per `on_max_depth`
+    max_depth: usize,
+    on_max_depth: MaxDepthMode,
+    /// Emit tuples as `{"__tuple__": [...]}` instead of plain arrays
+    distinguish_tuple: bool,
+    /// Emit namedtuples as objects of their fields instead of arrays
+    namedtuple_as_object: bool,
+    /// Drop dict entries whose key has no JSON form instead of raising
+    skipkeys: bool,
+    /// Raise on containers that contain themselves instead of recursing
+    check_circular: bool,
+    /// Addresses of the containers being serialized, innermost last
+    /// (`check_circular`)
+    markers: Vec<usize>,
+    /// Serialize exception instances as `{"type", "message", "args"}` objects
+    serialize_exceptions: bool,
+    /// Serialize `collections.abc.Sequence` instances as arrays
+    sequences: bool,
+    /// How `datetime.timedelta` is written (unsupported when `Off`)
+    timedelta_mode: TimedeltaMode,
+    /// How `bytes`/`bytearray` are written (unsupported when `Off`)
+    bytes_mode: BytesMode,
+    /// Leave out dataclass fields whose value is `None`
+    dataclass_omit_none: bool,
+    /// What to do when reading a dataclass field raises
+    on_missing_attr: MissingAttrMode,
+    /// Give naive datetimes a `+00:00` offset
+    naive_utc: bool,
+    /// The `default` callable (or `cls` encoder method), for unsupported types
+    encoder_default: Option<PyObject>,
+    /// Consecutive `encoder_default`/`__json__` calls in progress, each on
+    /// the previous one's result with no container opened in between
+    default_chain: usize,
+    /// Container depth (`depth`) that `default_chain` was counted at
+    default_chain_depth: usize,
+    /// Receives the output in chunks instead of letting `buf` grow
+    /// (`content_hash`, `dump`)
+    sink: Option<Sink>,
+}
+
+/// Where a `JsonBuffer` with a sink sends its output
+struct Sink {
+    /// Bound method taking one bytes-like chunk (`update`, `write`)
+    write: PyObject,
+    /// Pass a memoryview over `buf` instead of a bytes copy. Only for writers
+    /// known to copy the data before returning (`io.BytesIO`); the view is
+    /// released right after the call, so nothing can read it later.
+    borrow: bool,
+    /// Pass `str` chunks instead of bytes (text files). Chunks always end
+    /// between values, so each is valid UTF-8 on its own.
+    text: bool,
 }
 
 impl JsonBuffer {
+    /// A serializer writing into `buf` with every `dumps` option at its default
+    fn with_defaults(buf: Vec<u8>) -> Self {
+        JsonBuffer {
+            buf,
+            float_repr: FloatRepr::Ryu,
+            nan_mode: NanMode::Error,
+            escape: EscapePolicy::Json,
+            current_key: None,
+            dict_entries: Vec::new(),
+            python_calls: 0,
+            iterables: false,
+            none_repr: Cow::Borrowed(b"null"),
+            bigint_mode: BigIntMode::Number,
+            sort_keys: SortKeys::Off,
+            priority_keys: Vec::new(),
+            depth: 0,
+            max_depth: usize::MAX,
+            on_max_depth: MaxDepthMode::Error,
+            distinguish_tuple: false,
+            namedtuple_as_object: false,
+            skipkeys: false,
+            check_circular: true,
+            markers: Vec::new(),
+            serialize_exceptions: false,
+            sequences: false,
+            timedelta_mode: TimedeltaMode::Off,
+            bytes_mode: BytesMode::Off,
+            dataclass_omit_none: false,
+            on_missing_attr: MissingAttrMode::Error,
+            naive_utc: false,
+            encoder_default: None,
+            default_chain: 0,
+            default_chain_depth: 0,
+            sink: None,
+        }
+    }
+
     #[inline]
     fn write_null(&mut self) {
-        self.buf.extend_from_slice(b"null");
+        self.buf.extend_from_slice(&self.none_repr);
     }
 
     #[inline]
@@ -394,262 +2309,1051 @@ impl JsonBuffer {
     }
 
     #[inline]
-    fn write_int_i64(&mut self, value: i64) {
+    fn write_int_i64(&mut self, value: i64) -> PyResult<()> {
         // OPTIMIZATION: Use itoa for 10x faster integer formatting
         let mut itoa_buf = itoa::Buffer::new();
-        self.buf.extend_from_slice(itoa_buf.format(value).as_bytes());
+        let digits = itoa_buf.format(value).as_bytes();
+        write_int_digits(&mut self.buf, digits, is_big_i64(value), self.bigint_mode)
+    }
+
+    #[inline]
+    fn write_int_u64(&mut self, value: u64) -> PyResult<()> {
+        // Only reached for values above i64::MAX, so always beyond 2^53
+        let mut itoa_buf = itoa::Buffer::new();
+        write_int_digits(&mut self.buf, itoa_buf.format(value).as_bytes(), true, self.bigint_mode)
+    }
+
+    #[inline]
+    fn write_float(&mut self, value: f64) -> PyResult<()> {
+        if unlikely(!value.is_finite()) {
+            return write_non_finite_float(&mut self.buf, value, self.nan_mode);
+        }
+        write_finite_float(&mut self.buf, value, self.float_repr);
+        Ok(())
+    }
+
+    fn serialize_pyany(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<()> {
+        let fast_type = type_cache::get_fast_type(obj);
+
+        match fast_type {
+            FastType::None => {
+                self.write_null();
+                Ok(())
+            }
+
+            FastType::Bool => {
+                let b_val = unsafe { obj.downcast_exact::<PyBool>().unwrap_unchecked() };
+                self.write_bool(b_val.is_true());
+                Ok(())
+            }
+
+            FastType::Int => {
+                // PHASE 11 OPTIMIZATION: Use direct C API with overflow check
+                // This avoids PyO3's extract() overhead and uses PyLong_AsLongLongAndOverflow
+                // which is faster than checking PyErr_Occurred() after each call
+                unsafe {
+                    let int_ptr = obj.as_ptr();
+                    let mut overflow: std::ffi::c_int = 0;
+                    let val_i64 = ffi::PyLong_AsLongLongAndOverflow(int_ptr, &mut overflow);
+
+                    if overflow == 0 {
+                        // Fast path: Value fits in i64 (most common case)
+                        self.write_int_i64(val_i64)
+                    } else {
+                        // Overflow - try u64 for large positive numbers
+                        let val_u64 = ffi::PyLong_AsUnsignedLongLong(int_ptr);
+
+                        if val_u64 != u64::MAX || ffi::PyErr_Occurred().is_null() {
+                            ffi::PyErr_Clear();
+                            self.write_int_u64(val_u64)
+                        } else {
+                            // Very large int - fall back to string representation
+                            ffi::PyErr_Clear();
+                            let l_val = obj.downcast_exact::<PyInt>().unwrap_unchecked();
+                            let s = l_val.to_string();
+                            write_int_digits(&mut self.buf, s.as_bytes(), true, self.bigint_mode)
+                        }
+                    }
+                }
+            }
+
+            FastType::Float => {
+                let f_val = unsafe { obj.downcast_exact::<PyFloat>().unwrap_unchecked() };
+                let val_f64 = f_val.extract::<f64>()?;
+                self.write_float(val_f64)
+            }
+
+            FastType::String => {
+                let s_val = unsafe { obj.downcast_exact::<PyString>().unwrap_unchecked() };
+
+                // PHASE 10.7 OPTIMIZATION: Direct Unicode buffer access with inline UTF-8 encoding
+                // This avoids PyUnicode_AsUTF8AndSize overhead entirely by:
+                // 1. Checking ASCII flag for fast path (direct buffer access)
+                // 2. For non-ASCII: Reading PyUnicode_KIND and encoding inline
+                unsafe { write_json_string_direct(&mut self.buf, s_val.as_ptr(), self.escape) }
+            }
+
+            FastType::List => {
+                if self.over_max_depth()? {
+                    return Ok(());
+                }
+                let list_val = unsafe { obj.downcast_exact::<PyList>().unwrap_unchecked() };
+
+                // PHASE 6A OPTIMIZATION: Bulk array processing for homogeneous arrays
+                // Detect if the array contains all the same type and use optimized path
+                let array_type = bulk::detect_array_type(list_val);
+
+                // The bulk serializers hand back `false`, having written
+                // nothing, on an element of another type past the sample
+                let bulk_written = match array_type {
+                    bulk::ArrayType::AllInts => {
+                        // Bulk serialize integer array (Phase 6A: itoa is fastest)
+                        unsafe { bulk::serialize_int_array_bulk(list_val, &mut self.buf, self.bigint_mode)? }
+                    }
+                    bulk::ArrayType::AllFloats => {
+                        // Bulk serialize float array
+                        unsafe { bulk::serialize_float_array_bulk(list_val, &mut self.buf, self.float_repr, self.nan_mode)? }
+                    }
+                    bulk::ArrayType::AllBools => {
+                        // Bulk serialize boolean array
+                        unsafe { bulk::serialize_bool_array_bulk(list_val, &mut self.buf) }
+                    }
+                    bulk::ArrayType::AllDecimals => {
+                        // Bulk serialize Decimal array (exact str() form)
+                        unsafe { bulk::serialize_decimal_array_bulk(list_val, &mut self.buf)? };
+                        true
+                    }
+                    bulk::ArrayType::AllStrings => {
+                        // Bulk serialize string array
+                        let escape = self.escape;
+                        unsafe {
+                            bulk::serialize_string_array_bulk(
+                                list_val,
+                                &mut self.buf,
+                                |buf, s| write_json_string_with(buf, s, escape)
+                            )?
+                        }
+                    }
+                    bulk::ArrayType::Empty => {
+                        // Empty array
+                        self.buf.extend_from_slice(b"[]");
+                        true
+                    }
+                    bulk::ArrayType::Mixed => false,
+                };
+
+                if !bulk_written {
+                    // Fall back to normal per-element serialization
+                    // PHASE 3+ OPTIMIZATION: Direct C API list access (no bounds checking)
+                    self.buf.push(b'[');
+                    self.depth += 1;
+
+                    self.with_marker(obj, |this| unsafe {
+                        let list_ptr = list_val.as_ptr();
+
+                        // The length is re-read every iteration, as stdlib
+                        // `json` does: elements can run Python code
+                        // (`default`, `__json__`) that shrinks the list
+                        let mut i = 0;
+                        while i < ffi::PyList_GET_SIZE(list_ptr) {
+                            if i > 0 {
+                                this.buf.push(b',');
+                            }
+
+                            // SAFETY: i < current length; the Bound takes a
+                            // strong reference, so the item outlives its removal
+                            let item_ptr = ffi::PyList_GET_ITEM(list_ptr, i);
+                            let item = Bound::from_borrowed_ptr(list_val.py(), item_ptr);
+                            this.serialize_pyany(&item)?;
+                            this.maybe_spill()?;
+                            i += 1;
+                        }
+                        Ok(())
+                    })?;
+
+                    self.depth -= 1;
+                    self.buf.push(b']');
+                }
+
+                Ok(())
+            }
+
+            FastType::Tuple => {
+                if self.over_max_depth()? {
+                    return Ok(());
+                }
+                let tuple_val = unsafe { obj.downcast_exact::<PyTuple>().unwrap_unchecked() };
+
+                // Tagged form lets a loads hook rebuild the tuple
+                if self.distinguish_tuple {
+                    self.buf.extend_from_slice(b"{\"__tuple__\":");
+                }
+
+                // PHASE 3+ OPTIMIZATION: Direct C API tuple access (no bounds checking)
+                self.buf.push(b'[');
+                self.depth += 1;
+
+                self.with_marker(obj, |this| unsafe {
+                    let tuple_ptr = tuple_val.as_ptr();
+                    let len = ffi::PyTuple_GET_SIZE(tuple_ptr);
+
+                    for i in 0..len {
+                        if i > 0 {
+                            this.buf.push(b',');
+                        }
+
+                        // SAFETY: PyTuple_GET_ITEM returns borrowed reference (no refcount)
+                        // Index is guaranteed valid (0 <= i < len)
+                        let item_ptr = ffi::PyTuple_GET_ITEM(tuple_ptr, i);
+                        let item = Bound::from_borrowed_ptr(tuple_val.py(), item_ptr);
+                        this.serialize_pyany(&item)?;
+                        this.maybe_spill()?;
+                    }
+                    Ok(())
+                })?;
+
+                self.depth -= 1;
+                self.buf.push(b']');
+                if self.distinguish_tuple {
+                    self.buf.push(b'}');
+                }
+                Ok(())
+            }
+
+            FastType::Dict => {
+                let dict_val = unsafe { obj.downcast_exact::<PyDict>().unwrap_unchecked() };
+                self.serialize_dict(dict_val)
+            }
+
+            FastType::Other => {
+                self.python_calls += 1;
+                if let Some(handler) = registry::lookup(obj.get_type().as_type_ptr()) {
+                    return handler(obj, &mut self.buf);
+                }
+                if obj.get_type().as_type_ptr() == type_cache::get_decimal_type(obj.py()) {
+                    return unsafe { write_decimal(&mut self.buf, obj.as_ptr()) };
+                }
+                if obj.get_type().as_type_ptr() == type_cache::get_uuid_type(obj.py()) {
+                    return write_uuid(&mut self.buf, obj);
+                }
+                if let Some(kind) = temporal_kind(obj.py(), obj.get_type().as_type_ptr()) {
+                    // SAFETY: exact type checked by temporal_kind
+                    return unsafe { write_temporal(&mut self.buf, obj, kind, self.naive_utc) };
+                }
+                if self.timedelta_mode != TimedeltaMode::Off
+                    && obj.get_type().as_type_ptr() == type_cache::get_timedelta_type(obj.py())
+                {
+                    // SAFETY: exact type checked above
+                    let delta = unsafe { obj.downcast_unchecked::<PyDelta>() };
+                    let total_us = (delta.get_days() as i128 * 86_400 + delta.get_seconds() as i128) * 1_000_000
+                        + delta.get_microseconds() as i128;
+                    write_timedelta(&mut self.buf, total_us, self.timedelta_mode);
+                    return Ok(());
+                }
+                if self.bytes_mode != BytesMode::Off {
+                    if let Ok(bytes) = obj.downcast::<PyBytes>() {
+                        return write_bytes(&mut self.buf, bytes.as_bytes(), self.bytes_mode, self.escape);
+                    }
+                    if let Ok(bytes) = obj.downcast::<PyByteArray>() {
+                        // SAFETY: no Python code runs while the contents are read
+                        return write_bytes(&mut self.buf, unsafe { bytes.as_bytes() }, self.bytes_mode, self.escape);
+                    }
+                }
+                #[cfg(feature = "numpy")]
+                if self.serialize_numpy(obj)? {
+                    return Ok(());
+                }
+                if let Ok(lazy) = obj.downcast_exact::<lazy::LazyValue>() {
+                    // Decoded afresh so every dumps option applies, as it
+                    // wouldn't to a copy of the source text
+                    return self.serialize_pyany(lazy.get().materialize(obj.py())?.bind(obj.py()));
+                }
+                if self.serialize_via_json_method(obj)? {
+                    return Ok(());
+                }
+                if Self::is_enum_member(obj)? {
+                    // IntEnum/StrEnum values are plain ints/strs, so this
+                    // recursion takes a fast path
+                    return self.serialize_pyany(&obj.getattr(pyo3::intern!(obj.py(), "value"))?);
+                }
+                if let Some(text) = path_str(obj)? {
+                    // SAFETY: `text` is a str and outlives the call
+                    return unsafe { write_json_string_direct(&mut self.buf, text.as_ptr(), self.escape) };
+                }
+                if self.namedtuple_as_object && self.serialize_namedtuple(obj)? {
+                    return Ok(());
+                }
+                if self.serialize_builtin_subclass(obj)? {
+                    return Ok(());
+                }
+                if self.serialize_dict_subclass(obj)? {
+                    return Ok(());
+                }
+                if self.serialize_dataclass(obj)? {
+                    return Ok(());
+                }
+                if self.serialize_exceptions && unsafe { ffi::PyExceptionInstance_Check(obj.as_ptr()) } != 0 {
+                    return self.serialize_exception(obj);
+                }
+                if self.sequences && self.with_marker(obj, |this| this.serialize_sequence(obj))? {
+                    return Ok(());
+                }
+                if self.iterables && self.serialize_iterable(obj)? {
+                    return Ok(());
+                }
+                if self.encoder_default.is_some() {
+                    return self.serialize_via_default(obj);
+                }
+                self.unsupported_type_error(obj)
+            }
+        }
+    }
+
+    /// Write a dict as a JSON object, honouring `sort_keys` and `priority_keys`
+    fn serialize_dict(&mut self, dict_val: &Bound<'_, PyDict>) -> PyResult<()> {
+        if self.over_max_depth()? {
+            return Ok(());
+        }
+        self.buf.push(b'{');
+
+        let sort = self.sort_keys.applies_at(self.depth);
+        self.depth += 1;
+        self.with_marker(dict_val.as_any(), |this| {
+            if sort || !this.priority_keys.is_empty() {
+                this.serialize_dict_ordered(dict_val, sort)
+            } else {
+                this.serialize_dict_entries(dict_val)
+            }
+        })?;
+        self.depth -= 1;
+
+        self.buf.push(b'}');
+        Ok(())
+    }
+
+    /// Serialize a subclass of `int`, `float`, `str`, `list` or `tuple` like
+    /// the builtin it extends, as stdlib `json` does
+    ///
+    /// The builtin's own value is used: overridden `__str__`/`__repr__`/
+    /// `__iter__` are ignored, and lists and tuples are copied into exact
+    /// ones at C level (`namedtuple`s become arrays).
+    ///
+    /// # Returns
+    /// `Ok(false)` if the object isn't one of these subclasses.
+    fn serialize_builtin_subclass(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let py = obj.py();
+        let ptr = obj.as_ptr();
+        unsafe {
+            if ffi::PyLong_Check(ptr) != 0 {
+                let mut overflow: std::ffi::c_int = 0;
+                let value = ffi::PyLong_AsLongLongAndOverflow(ptr, &mut overflow);
+                if overflow == 0 {
+                    self.write_int_i64(value)?;
+                } else {
+                    // Formats the int value itself, not the subclass's __str__
+                    let digits = Bound::from_owned_ptr_or_err(py, ffi::PyNumber_ToBase(ptr, 10))?;
+                    let digits = digits.downcast::<PyString>()?.to_str()?;
+                    write_int_digits(&mut self.buf, digits.as_bytes(), true, self.bigint_mode)?;
+                }
+                return Ok(true);
+            }
+            if ffi::PyFloat_Check(ptr) != 0 {
+                self.write_float(ffi::PyFloat_AS_DOUBLE(ptr))?;
+                return Ok(true);
+            }
+            if ffi::PyUnicode_Check(ptr) != 0 {
+                write_json_string_direct(&mut self.buf, ptr, self.escape)?;
+                return Ok(true);
+            }
+            let exact = if ffi::PyList_Check(ptr) != 0 {
+                ffi::PyList_GetSlice(ptr, 0, ffi::PyList_GET_SIZE(ptr))
+            } else if ffi::PyTuple_Check(ptr) != 0 {
+                ffi::PyTuple_GetSlice(ptr, 0, ffi::PyTuple_GET_SIZE(ptr))
+            } else {
+                return Ok(false);
+            };
+            let exact = Bound::from_owned_ptr_or_err(py, exact)?;
+            // The copy is new, so the subclass instance stands for it
+            self.with_marker(obj, |this| this.serialize_pyany(&exact))?;
+        }
+        Ok(true)
+    }
+
+    /// Serialize a namedtuple as an object of its fields
+    ///
+    /// Any tuple subclass whose type has a `_fields` tuple counts, so
+    /// `typing.NamedTuple` classes do too. Fields are written in definition
+    /// order, as `_asdict()` would give them.
+    ///
+    /// # Returns
+    /// `Ok(false)` if the object isn't a namedtuple.
+    fn serialize_namedtuple(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let py = obj.py();
+        let Ok(values) = obj.downcast::<PyTuple>() else {
+            return Ok(false);
+        };
+        let Ok(fields) = obj.get_type().getattr(pyo3::intern!(py, "_fields")) else {
+            return Ok(false);
+        };
+        let Ok(fields) = fields.downcast_into::<PyTuple>() else {
+            return Ok(false);
+        };
+        let object = PyDict::new(py);
+        for (name, value) in fields.iter().zip(values.iter()) {
+            object.set_item(name, value)?;
+        }
+        self.with_marker(obj, |this| this.serialize_dict(&object))?;
+        Ok(true)
+    }
+
+    /// Serialize a `dict` subclass (`OrderedDict`, `defaultdict`, `Counter`,
+    /// ...) like a dict
+    ///
+    /// Entries are taken from `items()`, which subclasses may order
+    /// differently from the underlying dict storage (`OrderedDict` after
+    /// `move_to_end`), into a plain dict snapshot.
+    ///
+    /// # Returns
+    /// `Ok(false)` if the object isn't a dict subclass.
+    fn serialize_dict_subclass(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let Ok(dict) = obj.downcast::<PyDict>() else {
+            return Ok(false);
+        };
+        let snapshot = PyDict::from_sequence(&dict.as_mapping().items()?.into_any())?;
+        self.with_marker(obj, |this| this.serialize_dict(&snapshot))?;
+        Ok(true)
+    }
+
+    /// Write a dict's `key:value` pairs in insertion order
+    #[inline]
+    fn serialize_dict_entries(&mut self, dict_val: &Bound<'_, PyDict>) -> PyResult<()> {
+        let outer_key = self.current_key.take();
+        let result = self.write_dict_entries(dict_val);
+        self.current_key = outer_key;
+        result
+    }
+
+    /// Body of `serialize_dict_entries`
+    ///
+    /// Values can run Python code (registered serializers, `default`,
+    /// generators under `iterables=True`) that mutates the dict, so its
+    /// entries are taken first, held on `dict_entries`, and compared with the
+    /// dict once written if any Python code ran (`python_calls`): any change
+    /// raises instead of writing a mix of old and new entries, or serializing
+    /// a value the dict no longer keeps alive.
+    #[inline]
+    fn write_dict_entries(&mut self, dict_val: &Bound<'_, PyDict>) -> PyResult<()> {
+        let py = dict_val.py();
+        let dict_ptr = dict_val.as_ptr();
+        let base = self.dict_entries.len();
+
+        // PHASE 3 OPTIMIZATION: Direct C API dict iteration
+        // PyDict_Next is 2-3x faster than PyO3's iterator
+        // This is the key optimization that orjson uses
+        unsafe {
+            let mut pos: ffi::Py_ssize_t = 0;
+            let mut key_ptr: *mut ffi::PyObject = std::ptr::null_mut();
+            let mut value_ptr: *mut ffi::PyObject = std::ptr::null_mut();
+            // SAFETY: PyDict_Next returns borrowed references, owned from
+            // here until the entries are released below
+            while ffi::PyDict_Next(dict_ptr, &mut pos, &mut key_ptr, &mut value_ptr) != 0 {
+                ffi::Py_INCREF(key_ptr);
+                ffi::Py_INCREF(value_ptr);
+                self.dict_entries.push((key_ptr, value_ptr));
+            }
+        }
+
+        let python_calls = self.python_calls;
+        let result = self.write_held_entries(py, base).and_then(|()| {
+            if self.python_calls == python_calls || self.held_entries_match(dict_ptr, base) {
+                Ok(())
+            } else {
+                Err(Self::dict_changed_error())
+            }
+        });
+        for (key_ptr, value_ptr) in self.dict_entries.drain(base..) {
+            unsafe {
+                ffi::Py_DECREF(key_ptr);
+                ffi::Py_DECREF(value_ptr);
+            }
+        }
+        result
+    }
+
+    /// Write the `dict_entries` from `base` on as `key:value` pairs
+    fn write_held_entries(&mut self, py: Python<'_>, base: usize) -> PyResult<()> {
+        let mut first = true;
+        for i in base..self.dict_entries.len() {
+            // SAFETY: `dict_entries[i]` keeps both alive until the caller
+            // releases it, even if nested dicts reallocate the Vec
+            let (key_ptr, value_ptr) = self.dict_entries[i];
+            let (key, value) = unsafe { (Borrowed::from_ptr(py, key_ptr), Borrowed::from_ptr(py, value_ptr)) };
+            // Non-str keys are coerced to a str, owned by `current_key` for
+            // this entry
+            let key = if likely(unsafe { ffi::PyUnicode_Check(key_ptr) } != 0) {
+                key.to_owned().unbind()
+            } else {
+                match self.coerce_key(&key)? {
+                    Some(key) => key.into_any().unbind(),
+                    None => continue,
+                }
+            };
+
+            // Only once the entry is known to be written, so skipped
+            // entries leave no stray comma
+            if !first {
+                self.buf.push(b',');
+            }
+            first = false;
+
+            // PHASE 10.7: Direct Unicode buffer access with inline UTF-8 encoding
+            // SAFETY: `key` is a str, either as found or once coerced
+            unsafe { write_json_string_direct(&mut self.buf, key.as_ptr(), self.escape) }?;
+            self.buf.push(b':');
+
+            self.current_key = Some(key);
+            self.serialize_pyany(&value)?;
+            self.maybe_spill()?;
+        }
+        Ok(())
+    }
+
+    /// Whether the dict at `dict_ptr` still holds exactly the `dict_entries`
+    /// from `base` on: as many entries, each key mapping to the same value object
+    #[cold]
+    fn held_entries_match(&self, dict_ptr: *mut ffi::PyObject, base: usize) -> bool {
+        let entries = &self.dict_entries[base..];
+        if unsafe { ffi::PyDict_Size(dict_ptr) } as usize != entries.len() {
+            return false;
+        }
+        entries.iter().all(|&(key_ptr, value_ptr)| unsafe {
+            // Keys came from the dict, so the lookup can't fail on hashing
+            ffi::PyDict_GetItemWithError(dict_ptr, key_ptr) == value_ptr
+        })
+    }
+
+    /// Write a dict's `key:value` pairs with `priority_keys` first (in the
+    /// order given, those present), then the rest, sorted by key (code point
+    /// order, like `sorted()`) if `sort`. Non-`str` keys sort by their
+    /// coerced text and are never matched against `priority_keys`.
+    ///
+    /// Entries are snapshotted with strong references first, so serializing a
+    /// value can't invalidate the ones still to be written, and compared with
+    /// the dict once written if any Python code ran, as in `write_dict_entries`.
+    /// Each keeps the key found in the dict next to its (coerced) text.
+    fn serialize_dict_ordered(&mut self, dict_val: &Bound<'_, PyDict>, sort: bool) -> PyResult<()> {
+        let size = dict_val.len();
+        let mut entries = Vec::with_capacity(size);
+        for key in &self.priority_keys {
+            let key = key.bind(dict_val.py());
+            if let Some(value) = dict_val.get_item(key)? {
+                entries.push((key.clone().into_any(), key.clone().into_any(), value));
+            }
+        }
+        let prioritized = entries.len();
+
+        for (key, value) in dict_val.iter() {
+            if unsafe { ffi::PyUnicode_Check(key.as_ptr()) } == 0 {
+                // Only looked up as str above, so never a priority key
+                if let Some(text) = self.coerce_key(&key)? {
+                    entries.push((key, text.into_any(), value));
+                }
+                continue;
+            }
+            if prioritized > 0 && self.is_priority_key(&key) {
+                continue;
+            }
+            entries.push((key.clone(), key, value));
+        }
+        if sort && self.sort_keys == SortKeys::Utf16 {
+            let mut keyed = Vec::with_capacity(entries.len() - prioritized);
+            for (key, text, value) in entries.drain(prioritized..) {
+                let units: Vec<u16> = text.downcast::<PyString>()?.to_str()?.encode_utf16().collect();
+                keyed.push((units, key, text, value));
+            }
+            keyed.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+            entries.extend(keyed.into_iter().map(|(_, key, text, value)| (key, text, value)));
+        } else if sort {
+            // Keys are all str by now, so PyUnicode_Compare can't fail
+            entries[prioritized..]
+                .sort_by(|(_, a, _), (_, b, _)| unsafe { ffi::PyUnicode_Compare(a.as_ptr(), b.as_ptr()) }.cmp(&0));
+        }
+
+        let outer_key = self.current_key.take();
+        let python_calls = self.python_calls;
+        let result = entries.iter().enumerate().try_for_each(|(i, (_, text, value))| {
+            if i > 0 {
+                self.buf.push(b',');
+            }
+            unsafe { write_json_string_direct(&mut self.buf, text.as_ptr(), self.escape) }?;
+            self.buf.push(b':');
+            self.current_key = Some(text.clone().unbind());
+            self.serialize_pyany(value)?;
+            self.maybe_spill()
+        });
+        self.current_key = outer_key;
+        result?;
+
+        // Entries skipped under `skipkeys` are only counted
+        let unchanged = self.python_calls == python_calls
+            || dict_val.len() == size
+                && entries.iter().all(|(key, _, value)| unsafe {
+                    // Keys came from the dict, so the lookup can't fail on hashing
+                    ffi::PyDict_GetItemWithError(dict_val.as_ptr(), key.as_ptr()) == value.as_ptr()
+                });
+        if unchanged {
+            Ok(())
+        } else {
+            Err(Self::dict_changed_error())
+        }
+    }
+
+    /// The JSON key text for a non-`str` dict key (see `coerce_dict_key`),
+    /// or `None` under `skipkeys` if its type has none
+    ///
+    /// Non-finite float keys still follow `nan_mode`, as in stdlib.
+    fn coerce_key<'py>(&self, key: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyString>>> {
+        let ptr = key.as_ptr();
+        let has_key_form = key.is_none() || unsafe { ffi::PyLong_Check(ptr) != 0 || ffi::PyFloat_Check(ptr) != 0 };
+        if self.skipkeys && !has_key_form {
+            return Ok(None);
+        }
+        coerce_dict_key(key, self.float_repr, self.nan_mode).map(Some)
+    }
+
+    /// Run `serialize` with `container` marked as being serialized, failing
+    /// instead if it already is (`check_circular`)
+    ///
+    /// Markers are addresses, compared by a linear scan: the stack is as deep
+    /// as the nesting, which is shallow in practice.
+    #[inline]
+    fn with_marker<T>(
+        &mut self,
+        container: &Bound<'_, PyAny>,
+        serialize: impl FnOnce(&mut Self) -> PyResult<T>,
+    ) -> PyResult<T> {
+        if !self.check_circular {
+            return serialize(self);
+        }
+        let marker = container.as_ptr() as usize;
+        if unlikely(self.markers.contains(&marker)) {
+            return Err(Self::circular_reference_error());
+        }
+        self.markers.push(marker);
+        let result = serialize(self);
+        self.markers.pop();
+        result
+    }
+
+    /// Check a container about to be opened against `max_depth`
+    ///
+    /// # Returns
+    /// `Ok(true)` if it is too deep and a placeholder was written in its
+    /// place, an error under `on_max_depth="error"`.
+    #[inline]
+    fn over_max_depth(&mut self) -> PyResult<bool> {
+        if likely(self.depth < self.max_depth) {
+            return Ok(false);
+        }
+        self.write_depth_placeholder()?;
+        Ok(true)
+    }
+
+    #[cold]
+    fn write_depth_placeholder(&mut self) -> PyResult<()> {
+        match self.on_max_depth {
+            MaxDepthMode::Error => {
+                return Err(PyValueError::new_err(format!(
+                    "Maximum depth of {} exceeded during serialization",
+                    self.max_depth
+                )))
+            }
+            MaxDepthMode::Null => self.buf.extend_from_slice(b"null"),
+            MaxDepthMode::Truncate => self.buf.extend_from_slice(b"\"...\""),
+        }
+        Ok(())
+    }
+
+    /// Whether the str `key` is one of `priority_keys`
+    fn is_priority_key(&self, key: &Bound<'_, PyAny>) -> bool {
+        self.priority_keys.iter().any(|priority| {
+            priority.as_ptr() == key.as_ptr() || unsafe { ffi::PyUnicode_Compare(priority.as_ptr(), key.as_ptr()) } == 0
+        })
     }
 
+    /// Hand the buffered output to `sink` once it grows past `SPILL_THRESHOLD`
+    ///
+    /// Called between container elements, so memory stays bounded by the
+    /// threshold plus the largest single leaf or bulk array.
     #[inline]
-    fn write_int_u64(&mut self, value: u64) {
-        let mut itoa_buf = itoa::Buffer::new();
-        self.buf.extend_from_slice(itoa_buf.format(value).as_bytes());
+    fn maybe_spill(&mut self) -> PyResult<()> {
+        if self.sink.is_some() && self.buf.len() >= SPILL_THRESHOLD {
+            self.spill()?;
+        }
+        Ok(())
     }
 
-    #[inline]
-    fn write_float(&mut self, value: f64) -> PyResult<()> {
-        if unlikely(!value.is_finite()) {
-            return Self::float_error(value);
+    /// Write all buffered output to `sink` and clear the buffer
+    #[cold]
+    fn spill(&mut self) -> PyResult<()> {
+        let Some(sink) = &self.sink else {
+            return Ok(());
+        };
+        if self.buf.is_empty() {
+            return Ok(());
         }
-        // OPTIMIZATION: Use ryu for 5x faster float formatting
-        let mut ryu_buf = ryu::Buffer::new();
-        self.buf.extend_from_slice(ryu_buf.format(value).as_bytes());
+        self.python_calls += 1;
+        let py = unsafe { Python::assume_gil_acquired() };
+        if sink.text {
+            // SAFETY: We only write valid UTF-8, and spills happen between values
+            let text = unsafe { std::str::from_utf8_unchecked(&self.buf) };
+            sink.write.call1(py, (PyString::new(py, text),))?;
+        } else if sink.borrow {
+            // SAFETY: the view points into `buf`, which isn't touched until
+            // the view has been released below
+            let view = unsafe {
+                Bound::from_owned_ptr_or_err(
+                    py,
+                    ffi::PyMemoryView_FromMemory(
+                        self.buf.as_mut_ptr() as *mut std::os::raw::c_char,
+                        self.buf.len() as ffi::Py_ssize_t,
+                        ffi::PyBUF_READ,
+                    ),
+                )?
+            };
+            let result = sink.write.call1(py, (&view,));
+            view.call_method0("release")?;
+            result?;
+        } else {
+            sink.write.call1(py, (PyBytes::new(py, &self.buf),))?;
+        }
+        self.buf.clear();
         Ok(())
     }
 
-    /// Error path for non-finite floats (cold path)
     #[cold]
-    #[inline(never)]
-    fn float_error(value: f64) -> PyResult<()> {
-        Err(PyValueError::new_err(format!(
-            "Cannot serialize non-finite float: {}",
-            value
-        )))
+    fn dict_key_error() -> PyErr {
+        PyValueError::new_err("Dictionary keys must be strings for JSON serialization")
     }
 
-    fn serialize_pyany(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<()> {
-        let fast_type = type_cache::get_fast_type(obj);
-
-        match fast_type {
-            FastType::None => {
-                self.write_null();
-                Ok(())
-            }
-
-            FastType::Bool => {
-                let b_val = unsafe { obj.downcast_exact::<PyBool>().unwrap_unchecked() };
-                self.write_bool(b_val.is_true());
-                Ok(())
-            }
+    #[cold]
+    fn circular_reference_error() -> PyErr {
+        PyValueError::new_err("Circular reference detected")
+    }
 
-            FastType::Int => {
-                // PHASE 11 OPTIMIZATION: Use direct C API with overflow check
-                // This avoids PyO3's extract() overhead and uses PyLong_AsLongLongAndOverflow
-                // which is faster than checking PyErr_Occurred() after each call
-                unsafe {
-                    let int_ptr = obj.as_ptr();
-                    let mut overflow: std::ffi::c_int = 0;
-                    let val_i64 = ffi::PyLong_AsLongLongAndOverflow(int_ptr, &mut overflow);
+    #[cold]
+    fn dict_changed_error() -> PyErr {
+        PyRuntimeError::new_err("dict changed during serialization")
+    }
 
-                    if overflow == 0 {
-                        // Fast path: Value fits in i64 (most common case)
-                        self.write_int_i64(val_i64);
-                    } else {
-                        // Overflow - try u64 for large positive numbers
-                        let val_u64 = ffi::PyLong_AsUnsignedLongLong(int_ptr);
+    /// Serialize an exception as `{"type": ..., "message": ..., "args": [...]}`
+    ///
+    /// `type` is the class name, `message` is `str(exc)`. `args` are
+    /// serialized like any other values, so they must be JSON-compatible.
+    #[cold]
+    fn serialize_exception(&mut self, exc: &Bound<'_, PyAny>) -> PyResult<()> {
+        if self.over_max_depth()? {
+            return Ok(());
+        }
+        let type_name = exc.get_type().name()?;
+        let message = exc.str()?;
+        let args = exc.getattr("args")?;
 
-                        if val_u64 != u64::MAX || ffi::PyErr_Occurred().is_null() {
-                            ffi::PyErr_Clear();
-                            self.write_int_u64(val_u64);
-                        } else {
-                            // Very large int - fall back to string representation
-                            ffi::PyErr_Clear();
-                            let l_val = obj.downcast_exact::<PyInt>().unwrap_unchecked();
-                            let s = l_val.to_string();
-                            self.buf.extend_from_slice(s.as_bytes());
-                        }
-                    }
+        self.with_marker(exc, |this| {
+            this.buf.extend_from_slice(b"{\"type\":");
+            write_json_string_with(&mut this.buf, type_name.to_str()?, this.escape);
+            this.buf.extend_from_slice(b",\"message\":");
+            write_json_string_with(&mut this.buf, message.to_str()?, this.escape);
+            this.buf.extend_from_slice(b",\"args\":[");
+            this.depth += 1;
+            for (i, arg) in args.try_iter()?.enumerate() {
+                if i > 0 {
+                    this.buf.push(b',');
                 }
-                Ok(())
-            }
-
-            FastType::Float => {
-                let f_val = unsafe { obj.downcast_exact::<PyFloat>().unwrap_unchecked() };
-                let val_f64 = f_val.extract::<f64>()?;
-                self.write_float(val_f64)
+                this.serialize_pyany(&arg?)?;
+                this.maybe_spill()?;
             }
+            this.depth -= 1;
+            this.buf.extend_from_slice(b"]}");
+            Ok(())
+        })
+    }
 
-            FastType::String => {
-                let s_val = unsafe { obj.downcast_exact::<PyString>().unwrap_unchecked() };
-
-                // PHASE 10.7 OPTIMIZATION: Direct Unicode buffer access with inline UTF-8 encoding
-                // This avoids PyUnicode_AsUTF8AndSize overhead entirely by:
-                // 1. Checking ASCII flag for fast path (direct buffer access)
-                // 2. For non-ASCII: Reading PyUnicode_KIND and encoding inline
-                unsafe {
-                    write_json_string_direct(&mut self.buf, s_val.as_ptr());
-                }
+    /// Whether `obj` is an `enum.Enum` member (including `IntEnum`, `StrEnum`
+    /// and `Flag` members)
+    #[inline]
+    fn is_enum_member(obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let enum_type = type_cache::get_enum_type(obj.py());
+        if enum_type.is_null() {
+            return Ok(false);
+        }
+        match unsafe { ffi::PyObject_IsInstance(obj.as_ptr(), enum_type as *mut ffi::PyObject) } {
+            -1 => Err(PyErr::fetch(obj.py())),
+            result => Ok(result == 1),
+        }
+    }
 
-                Ok(())
+    /// Serialize a dataclass instance as an object of its fields
+    ///
+    /// Fields come from `dataclasses.fields` (so `ClassVar` and `InitVar`
+    /// pseudo-fields are skipped) in definition order, read with `getattr`
+    /// rather than `dataclasses.asdict`, which would deep-copy every value.
+    /// `sort_keys` and `priority_keys` apply as for a dict.
+    ///
+    /// # Returns
+    /// `Ok(false)` if the object isn't a dataclass instance.
+    fn serialize_dataclass(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let py = obj.py();
+        // Checked on the type, so dataclass classes themselves don't match
+        if !obj.get_type().hasattr(pyo3::intern!(py, "__dataclass_fields__"))? {
+            return Ok(false);
+        }
+        let fields = PyDict::new(py);
+        for field in type_cache::get_dataclass_fields(py)?.call1((obj,))?.try_iter()? {
+            let name = field?.getattr(pyo3::intern!(py, "name"))?;
+            let value = match obj.getattr(name.downcast::<PyString>()?) {
+                Ok(value) => value,
+                // A property that raises, or a field deleted from the instance
+                Err(e) if e.is_instance_of::<PyException>(py) => match self.on_missing_attr {
+                    MissingAttrMode::Error => return Err(e),
+                    MissingAttrMode::Null => py.None().into_bound(py),
+                    MissingAttrMode::Skip => continue,
+                },
+                Err(e) => return Err(e),
+            };
+            if self.dataclass_omit_none && value.is_none() {
+                continue;
             }
+            fields.set_item(name, value)?;
+        }
+        self.with_marker(obj, |this| this.serialize_dict(&fields))?;
+        Ok(true)
+    }
 
-            FastType::List => {
-                let list_val = unsafe { obj.downcast_exact::<PyList>().unwrap_unchecked() };
-
-                // PHASE 6A OPTIMIZATION: Bulk array processing for homogeneous arrays
-                // Detect if the array contains all the same type and use optimized path
-                let array_type = bulk::detect_array_type(&list_val);
-
-                match array_type {
-                    bulk::ArrayType::AllInts => {
-                        // Bulk serialize integer array (Phase 6A: itoa is fastest)
-                        unsafe { bulk::serialize_int_array_bulk(&list_val, &mut self.buf)? }
-                    }
-                    bulk::ArrayType::AllFloats => {
-                        // Bulk serialize float array
-                        unsafe { bulk::serialize_float_array_bulk(&list_val, &mut self.buf)? }
-                    }
-                    bulk::ArrayType::AllBools => {
-                        // Bulk serialize boolean array
-                        unsafe { bulk::serialize_bool_array_bulk(&list_val, &mut self.buf)? }
-                    }
-                    bulk::ArrayType::AllStrings => {
-                        // Bulk serialize string array
-                        unsafe {
-                            bulk::serialize_string_array_bulk(
-                                &list_val,
-                                &mut self.buf,
-                                write_json_string
-                            )?
-                        }
-                    }
-                    bulk::ArrayType::Empty => {
-                        // Empty array
-                        self.buf.extend_from_slice(b"[]");
-                    }
-                    bulk::ArrayType::Mixed => {
-                        // Fall back to normal per-element serialization
-                        // PHASE 3+ OPTIMIZATION: Direct C API list access (no bounds checking)
-                        self.buf.push(b'[');
-
-                        unsafe {
-                            let list_ptr = list_val.as_ptr();
-                            let len = ffi::PyList_GET_SIZE(list_ptr);
-
-                            for i in 0..len {
-                                if i > 0 {
-                                    self.buf.push(b',');
-                                }
-
-                                // SAFETY: PyList_GET_ITEM returns borrowed reference (no refcount)
-                                // Index is guaranteed valid (0 <= i < len)
-                                let item_ptr = ffi::PyList_GET_ITEM(list_ptr, i);
-                                let item = Bound::from_borrowed_ptr(list_val.py(), item_ptr);
-                                self.serialize_pyany(&item)?;
-                            }
-                        }
-
-                        self.buf.push(b']');
-                    }
-                }
-
-                Ok(())
-            }
+    /// Serialize whatever `default(o)` (the `default` callable, or the `cls`
+    /// encoder's method) returns for `obj`
+    ///
+    /// Like stdlib `json`, returning `obj` itself is a circular reference,
+    /// as is returning a container that holds it; chains of `default`
+    /// results are capped so a `default` that keeps returning unsupported
+    /// objects raises instead of overflowing the stack.
+    #[cold]
+    fn serialize_via_default(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<()> {
+        let Some(default) = &self.encoder_default else {
+            return self.unsupported_type_error(obj);
+        };
+        let replacement = default.bind(obj.py()).call1((obj,))?;
+        self.serialize_replacement(obj, &replacement, "default")
+    }
 
-            FastType::Tuple => {
-                let tuple_val = unsafe { obj.downcast_exact::<PyTuple>().unwrap_unchecked() };
+    /// Serialize what `obj.__json__()` returns, if the object's type defines
+    /// a callable `__json__`
+    ///
+    /// Like other special methods it is looked up on the type, so instance
+    /// attributes and proxies that fabricate attributes don't trigger it.
+    ///
+    /// # Returns
+    /// `Ok(false)` if the type has no callable `__json__`.
+    fn serialize_via_json_method(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let py = obj.py();
+        let name = pyo3::intern!(py, "__json__");
+        let Ok(method) = obj.get_type().getattr(name) else {
+            return Ok(false);
+        };
+        if !method.is_callable() {
+            return Ok(false);
+        }
+        let replacement = obj.call_method0(name)?;
+        self.serialize_replacement(obj, &replacement, "__json__")?;
+        Ok(true)
+    }
 
-                // PHASE 3+ OPTIMIZATION: Direct C API tuple access (no bounds checking)
-                self.buf.push(b'[');
+    /// Serialize `replacement`, the result of `hook(obj)`, in place of `obj`
+    ///
+    /// `obj` stays marked while its replacement is written, as stdlib `json`
+    /// marks the objects it passes to `default`, so a replacement holding
+    /// `obj` is a circular reference. Only results that directly need
+    /// another call count towards the cap (`default` and `__json__` share
+    /// it, since either may return an object that needs the other); ones
+    /// nested in a container are bounded by `max_depth` like any nesting.
+    fn serialize_replacement(
+        &mut self,
+        obj: &Bound<'_, PyAny>,
+        replacement: &Bound<'_, PyAny>,
+        hook: &str,
+    ) -> PyResult<()> {
+        if replacement.is(obj) {
+            return Err(Self::circular_reference_error());
+        }
+        let chain = if self.default_chain_depth == self.depth { self.default_chain } else { 0 };
+        if chain >= MAX_DEFAULT_DEPTH {
+            return Err(PyValueError::new_err(format!(
+                "{hook}() nested more than {MAX_DEFAULT_DEPTH} levels (it keeps returning unsupported objects)"
+            )));
+        }
 
-                unsafe {
-                    let tuple_ptr = tuple_val.as_ptr();
-                    let len = ffi::PyTuple_GET_SIZE(tuple_ptr);
+        let outer = (self.default_chain, self.default_chain_depth);
+        (self.default_chain, self.default_chain_depth) = (chain + 1, self.depth);
+        let result = self.with_marker(obj, |this| this.serialize_pyany(replacement));
+        (self.default_chain, self.default_chain_depth) = outer;
+        result
+    }
 
-                    for i in 0..len {
-                        if i > 0 {
-                            self.buf.push(b',');
-                        }
+    /// Strings, bytes, dicts (including subclasses) and other
+    /// `collections.abc.Mapping`s, which are iterable but never serialized
+    /// as arrays (iterating a mapping would drop its values)
+    #[inline]
+    fn is_text_or_mapping(obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let obj_ptr = obj.as_ptr();
+        let builtin = unsafe {
+            ffi::PyUnicode_Check(obj_ptr) != 0
+                || ffi::PyBytes_Check(obj_ptr) != 0
+                || ffi::PyByteArray_Check(obj_ptr) != 0
+                || ffi::PyDict_Check(obj_ptr) != 0
+        };
+        Ok(builtin || obj.is_instance(type_cache::get_mapping_abc(obj.py())?)?)
+    }
 
-                        // SAFETY: PyTuple_GET_ITEM returns borrowed reference (no refcount)
-                        // Index is guaranteed valid (0 <= i < len)
-                        let item_ptr = ffi::PyTuple_GET_ITEM(tuple_ptr, i);
-                        let item = Bound::from_borrowed_ptr(tuple_val.py(), item_ptr);
-                        self.serialize_pyany(&item)?;
-                    }
-                }
+    /// Serialize a `collections.abc.Sequence` (e.g. `UserList`, `range`, or a
+    /// class registered with the ABC) as a JSON array, by index
+    ///
+    /// # Returns
+    /// `Ok(false)` if the object isn't a sequence.
+    fn serialize_sequence(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let py = obj.py();
+        if Self::is_text_or_mapping(obj)?
+            || !obj.is_instance(type_cache::get_sequence_abc(py)?)?
+            || obj.is_instance(type_cache::get_user_string_type(py)?)?
+        {
+            return Ok(false);
+        }
+        if self.over_max_depth()? {
+            return Ok(true);
+        }
 
-                self.buf.push(b']');
-                Ok(())
+        let len = obj.len()?;
+        self.buf.push(b'[');
+        self.depth += 1;
+        for i in 0..len {
+            if i > 0 {
+                self.buf.push(b',');
             }
+            self.serialize_pyany(&obj.get_item(i)?)?;
+            self.maybe_spill()?;
+        }
+        self.depth -= 1;
+        self.buf.push(b']');
 
-            FastType::Dict => {
-                let dict_val = unsafe { obj.downcast_exact::<PyDict>().unwrap_unchecked() };
-                self.buf.push(b'{');
-
-                // PHASE 3 OPTIMIZATION: Direct C API dict iteration
-                // PyDict_Next is 2-3x faster than PyO3's iterator
-                // This is the key optimization that orjson uses
-                unsafe {
-                    let dict_ptr = dict_val.as_ptr();
-                    let mut pos: ffi::Py_ssize_t = 0;
-                    let mut key_ptr: *mut ffi::PyObject = std::ptr::null_mut();
-                    let mut value_ptr: *mut ffi::PyObject = std::ptr::null_mut();
+        Ok(true)
+    }
 
-                    let mut first = true;
+    /// Serialize an arbitrary iterable as a JSON array, pulling one item at a time
+    ///
+    /// Items are written straight into the buffer as the iterator yields them,
+    /// so a generator is never materialized into a list first. Exact `set`s
+    /// and `frozenset`s are the exception: their elements are copied into a
+    /// temporary list (one pointer each) so homogeneous sets reach the bulk
+    /// array serializers.
+    /// Strings, bytes and mappings are iterable but never treated as arrays.
+    ///
+    /// # Returns
+    /// `Ok(false)` if the object isn't a supported iterable.
+    fn serialize_iterable(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        if Self::is_text_or_mapping(obj)? {
+            return Ok(false);
+        }
 
-                    while ffi::PyDict_Next(dict_ptr, &mut pos, &mut key_ptr, &mut value_ptr) != 0 {
-                        if !first {
-                            self.buf.push(b',');
-                        }
-                        first = false;
-
-                        // SAFETY: PyDict_Next returns borrowed references (no need to decref)
-                        // Convert raw pointers to PyString
-                        if ffi::PyUnicode_Check(key_ptr) == 0 {
-                            return Err(PyValueError::new_err(
-                                "Dictionary keys must be strings for JSON serialization"
-                            ));
-                        }
+        let iter = match obj.try_iter() {
+            Ok(iter) => iter,
+            Err(_) => return Ok(false),
+        };
+        if self.over_max_depth()? {
+            return Ok(true);
+        }
 
-                        // PHASE 10.7: Direct Unicode buffer access with inline UTF-8 encoding
-                        write_json_string_direct(&mut self.buf, key_ptr);
-                        self.buf.push(b':');
+        if unsafe { ffi::PyAnySet_CheckExact(obj.as_ptr()) } != 0 {
+            // SAFETY: PySequence_List returns a new reference (or null on error)
+            let elements = unsafe { Bound::from_owned_ptr_or_err(obj.py(), ffi::PySequence_List(obj.as_ptr()))? };
+            self.serialize_pyany(&elements)?;
+            return Ok(true);
+        }
 
-                        // Serialize value (wrap in Bound for safe handling)
-                        // SAFETY: value_ptr is a borrowed reference from PyDict_Next
-                        let value = Bound::from_borrowed_ptr(dict_val.py(), value_ptr);
-                        self.serialize_pyany(&value)?;
-                    }
+        self.with_marker(obj, |this| {
+            this.buf.push(b'[');
+            this.depth += 1;
+            let mut first = true;
+            for item in iter {
+                let item = item?;
+                if !first {
+                    this.buf.push(b',');
                 }
-
-                self.buf.push(b'}');
-                Ok(())
+                first = false;
+                this.serialize_pyany(&item)?;
+                this.maybe_spill()?;
             }
+            this.depth -= 1;
+            this.buf.push(b']');
+            Ok(())
+        })?;
 
-            FastType::Other => Self::unsupported_type_error(obj),
-        }
+        Ok(true)
     }
 
     /// Error path for unsupported types (cold path)
+    ///
+    /// Names the containing dict key when there is one, so a stray `...` or
+    /// `NotImplemented` deep inside a large object can be located.
     #[cold]
     #[inline(never)]
-    fn unsupported_type_error(obj: &Bound<'_, PyAny>) -> PyResult<()> {
-        Err(PyValueError::new_err(format!(
-            "Unsupported Python type for JSON serialization: {}",
-            obj.get_type()
-                .name()
-                .and_then(|n| n.to_str().map(|s| s.to_owned()))
-                .unwrap_or_else(|_| "unknown".to_string())
-        )))
+    fn unsupported_type_error(&self, obj: &Bound<'_, PyAny>) -> PyResult<()> {
+        Err(unsupported_type_error(obj, self.current_key.as_ref()))
     }
 }
 
+/// ValueError for a value no serializer handles, naming its type and the
+/// dict `key` it was found under, if known
+#[cold]
+#[inline(never)]
+pub(crate) fn unsupported_type_error(obj: &Bound<'_, PyAny>, key: Option<&PyObject>) -> PyErr {
+    let type_name = obj.get_type()
+        .name()
+        .and_then(|n| n.to_str().map(|s| s.to_owned()))
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    let Some(key) = key else {
+        return PyValueError::new_err(format!(
+            "Unsupported Python type for JSON serialization: {type_name}"
+        ));
+    };
+
+    let key_repr = key.bind(obj.py()).repr()
+        .map(|r| r.to_string())
+        .unwrap_or_else(|_| "<unknown>".to_string());
+    PyValueError::new_err(format!(
+        "Unsupported Python type for JSON serialization at key {key_repr}: {type_name}"
+    ))
+}
+
+/// Serialize `obj` into `buf` as `dumps` does with its defaults
+///
+/// For `dumps_bytes`, whose direct serializer handles only the common exact
+/// types itself and hands any other value over from the root.
+pub(crate) fn serialize_with_defaults(buf: &mut Vec<u8>, obj: &Bound<'_, PyAny>) -> PyResult<()> {
+    let mut buffer = JsonBuffer::with_defaults(std::mem::take(buf));
+    let result = buffer.serialize_pyany(obj);
+
+    *buf = buffer.buf;
+    result
+}
+
 /// Estimate JSON output size for buffer pre-allocation.
 ///
-/// Provides a heuristic size estimate to minimize reallocations.
+/// Provides a heuristic size estimate to minimize reallocations, for
+/// compact separators. A non-ASCII root string under `ensure_ascii` is
+/// scaled for its `\uXXXX` escapes (6 bytes per character); `portable`
+/// escaping only touches rare characters and isn't accounted for.
 #[inline]
-fn estimate_json_size(obj: &Bound<'_, PyAny>) -> usize {
+fn estimate_json_size(obj: &Bound<'_, PyAny>, escape: EscapePolicy) -> usize {
     let fast_type = type_cache::get_fast_type(obj);
 
     match fast_type {
@@ -659,7 +3363,13 @@ fn estimate_json_size(obj: &Bound<'_, PyAny>) -> usize {
         FastType::Float => 24,                        // max f64 representation
         FastType::String => {
             if let Ok(s) = obj.downcast_exact::<PyString>() {
-                s.len().unwrap_or(0) + 8              // +8 for quotes and potential escapes
+                let len = s.len().unwrap_or(0);
+                // SAFETY: `s` is a live str
+                if escape == EscapePolicy::Ascii && unsafe { ascii_str::ascii_data(s.as_ptr()) }.is_none() {
+                    len * 6 + 8                       // every character may become \uXXXX
+                } else {
+                    len + 8                           // +8 for quotes and potential escapes
+                }
             } else {
                 32
             }
@@ -704,38 +3414,584 @@ fn estimate_json_size(obj: &Bound<'_, PyAny>) -> usize {
 /// - Thread-local buffer reuse to avoid repeated allocations
 /// - Buffer grows to max needed size and stays allocated
 ///
+/// Besides the JSON types, `decimal.Decimal` (exact number), `uuid.UUID`
+/// (canonical string) and `datetime`/`date`/`time` (RFC 3339 string, as
+/// `isoformat()`, e.g. `"2024-01-02T03:04:05.123456+00:00"`) are always
+/// supported, as are `enum.Enum` members (written as their `.value`, so
+/// `IntEnum`/`StrEnum` members become plain numbers/strings), `pathlib`
+/// paths (their `str()`), `loads(lazy=True)` proxies (their decoded
+/// value), dataclass instances (objects of their fields) and
+/// subclasses of `dict`, `list`,
+/// `tuple`, `str`, `int` and `float` (written like the builtin they extend).
+/// Builds with the `numpy` cargo feature also write numpy scalars as their
+/// `.item()` and ndarrays as nested arrays (bool, int and float dtypes are
+/// read straight from the array's memory); structured and record arrays
+/// become arrays of objects keyed by field name.
+///
+/// A class can choose its own JSON form by defining a `__json__(self)`
+/// method that returns a serializable value (which may itself define
+/// `__json__`). It is used for instances of any type not listed above, and
+/// takes precedence over the enum, subclass and dataclass handling, the
+/// `sequences`/`iterables`/`serialize_exceptions` options and `default`,
+/// which is only called for objects without `__json__`.
+///
 /// # Arguments
 /// * `py` - The Python GIL token.
 /// * `data` - The Python object to serialize.
+/// * `float_repr` - Float formatting: `"ryu"` (default, fastest), `"python"`
+///   (always identical to `repr(float)`), or `"hybrid"` (ryu unless its output
+///   would differ from `repr`, i.e. only exponent-notation values take the slow path).
+/// * `iterables` - Serialize other iterables (generators, iterators, sets, ...)
+///   as arrays, consuming them lazily one item at a time. Mappings other
+///   than dicts (`MappingProxyType`, `ChainMap`, `UserDict`) are never
+///   treated as iterables, since that would keep only their keys.
+/// * `none_repr` - JSON emitted for `None`: `null` (default) or a JSON string
+///   literal such as `'""'` or `'"null"'`, for consumers that can't handle `null`.
+/// * `sort_keys` - `False` (default, insertion order), `True` (sort every
+///   dict), or `"top"` (sort only the root dict, nested dicts keep their order).
+/// * `bigint_mode` - `"number"` (default) emits every int as a number;
+///   `"string"` quotes ints with `|n| > 2^53` (e.g. `"9007199254740993"`) so
+///   JavaScript consumers don't silently lose precision.
+/// * `distinguish_tuple` - Emit tuples as `{"__tuple__": [...]}` rather than
+///   plain arrays, so a matching `loads` hook can rebuild them.
+/// * `namedtuple_as_object` - Emit `collections.namedtuple` and
+///   `typing.NamedTuple` instances as objects keyed by their `_fields`
+///   (`{"x": 1, "y": 2}`) instead of arrays of their values (the default).
+/// * `check_circular` - Raise ValueError (`Circular reference detected`)
+///   when a list, tuple or dict (or subclass, dataclass, namedtuple,
+///   iterable or exception) contains itself, directly or through other containers, or
+///   an object turns up again inside its own `default` result, as stdlib
+///   `json` does. On by default. Turn it off together with `max_depth` to
+///   write self-containing values truncated instead; without a `max_depth`
+///   they then recurse until the process crashes.
+/// * `skipkeys` - Leave out dict entries whose key isn't a `str`, `int`,
+///   `float`, `bool` or `None` (e.g. a tuple), as stdlib
+///   `json.dumps(skipkeys=True)` does, instead of raising ValueError.
+/// * `no_scientific` - Write floats in plain decimal notation (`1e-7` as
+///   `0.0000001`, `1e21` as `1000000000000000000000.0`) for consumers that
+///   reject exponents. Overrides `float_repr`; a float expands to at most
+///   ~330 bytes (`5e-324`).
+/// * `sequences` - Serialize other `collections.abc.Sequence` instances
+///   (`UserList`, `range`, custom sequences registered with the ABC) as
+///   arrays, indexing `0..len()`. Strings, bytes and `UserString` are never
+///   treated as sequences.
+/// * `timedelta_mode` - Serialize `datetime.timedelta` as `"seconds"` (total
+///   seconds as a number, e.g. `93784.5`) or `"iso8601"` (a duration string
+///   such as `"P1DT2H3M4.5S"`). Microseconds are kept exactly in both.
+///   Default `None` leaves timedelta unsupported.
+/// * `bytes_mode` - Serialize `bytes` and `bytearray` as a JSON string:
+///   `"base64"` (standard alphabet, padded), `"hex"` (lowercase) or `"utf8"`
+///   (decoded text; bytes that aren't valid UTF-8 raise ValueError).
+///   Default `None` leaves bytes unsupported.
+/// * `canonical` - Write the RFC 8785 JSON Canonicalization Scheme (JCS)
+///   form, for hashing or signing: no whitespace, keys of every object
+///   sorted by UTF-16 code units, strings escaped only where JSON requires
+///   (`\b \t \n \f \r`, other controls as lowercase `\u00xx`, `"` and
+///   `\`), and numbers written as ECMAScript's `Number.prototype.toString`
+///   does (`1.0` as `1`, `1e21` as `1e+21`, `1e-7` as `1e-7`, `-0.0` as
+///   `0`). JCS numbers are IEEE 754 doubles, so ints beyond 2^53 raise
+///   ValueError unless `bigint_mode="string"` quotes them. Can't be combined
+///   with options that change that form (`sort_keys`, `priority_keys`,
+///   `float_repr`, `no_scientific`, `escape`, `ensure_ascii`, `allow_nan`,
+///   `nan_mode="string"`). Values that aren't JSON types (`Decimal`,
+///   datetimes, ...) are written as usual, which JCS doesn't define.
+/// * `append_newline` - End the output with `\n`, for writing JSON lines
+///   without a separate string concatenation.
+/// * `dataclass_omit_none` - Leave out dataclass fields whose value is
+///   `None`. Dataclass instances are always serialized as objects of their
+///   fields, in definition order.
+/// * `on_missing_attr` - What happens when reading a dataclass field raises
+///   (a property that fails, a field deleted from the instance): `"error"`
+///   (default) propagates the exception, `"null"` writes the field as `null`
+///   and `"skip"` leaves it out.
+/// * `naive_utc` - Treat naive `datetime.datetime` values as UTC, writing
+///   them with a `+00:00` offset. By default they're written without one.
+/// * `cls` - A `json.JSONEncoder` subclass, for migrating from stdlib `json`.
+///   It is instantiated once per call with no arguments, and its
+///   `default(o)` is called for objects no other option handles; the result
+///   is serialized in place of `o`, and exceptions from `default` (e.g. the
+///   base class's `TypeError`) propagate unchanged. Only `default` is used:
+///   overridden `encode`/`iterencode` and encoder constructor options
+///   (`indent`, `separators`, `sort_keys`, ...) are ignored in favour of
+///   this function's own arguments.
+/// * `default` - A callable, as for stdlib `json.dumps(default=...)`, called
+///   with each object no other option handles; its result is serialized in
+///   its place, and exceptions it raises propagate. A result that is the
+///   object itself is a circular reference, and a chain of more than 64
+///   results that need `default` again raises ValueError. Takes precedence
+///   over `cls`'s `default` (as stdlib passes it to the encoder).
+/// * `nan_mode` - `"error"` (default) rejects NaN and infinities; `"string"`
+///   emits them as the strings `"NaN"`, `"Infinity"` and `"-Infinity"`, which
+///   strict parsers accept. `loads(parse_nan_strings=True)` reverses it.
+/// * `allow_nan` - Write NaN and infinities as the bare tokens `NaN`,
+///   `Infinity` and `-Infinity`, as stdlib `json.dumps` does by default.
+///   Off by default: the tokens aren't valid JSON, and `loads` rejects them
+///   (stdlib `json.loads` reads them back).
+///   Can't be combined with `nan_mode="string"`.
+/// * `priority_keys` - Keys written first, in the given order, in every dict
+///   that contains them (e.g. `["@type", "id"]` for JSON-LD or tagged
+///   unions). The remaining keys follow in insertion order, or sorted when
+///   `sort_keys` applies to that dict.
+/// * `max_depth` - Deepest container nesting written (`None`, the default,
+///   is unbounded). Containers nested deeper are handled per `on_max_depth`;
+///   `0` applies it to the root container itself.
+/// * `on_max_depth` - `"error"` (default) raises ValueError, `"null"` writes
+///   `null` and `"truncate"` writes the string `"..."` in place of each
+///   too-deep container. Useful for logging arbitrary objects at a bounded size.
+/// * `escape` - `"json"` (default) escapes only what JSON requires;
+///   `"portable"` also writes DEL, C1 controls, U+2028/U+2029 and invisible
+///   format characters (zero-width spaces, bidi controls, BOM) as `\uXXXX`,
+///   for output that survives any transport or viewer. Plain ASCII strings
+///   cost the same under both.
+/// * `ensure_ascii` - Write DEL and every non-ASCII character as `\uXXXX`
+///   (astral characters as a surrogate pair), like stdlib `json`, for
+///   consumers that only accept ASCII. Off by default, unlike stdlib. Covers
+///   everything `escape="portable"` does, so it takes precedence.
+/// * `serialize_exceptions` - Emit exception instances as
+///   `{"type": "ValueError", "message": str(exc), "args": [...]}`, for
+///   structured logging. Off by default (exceptions are unsupported).
 ///
 /// # Returns
 /// A JSON string, or a PyValueError on error.
 #[pyfunction]
-fn dumps(_py: Python, data: &Bound<'_, PyAny>) -> PyResult<String> {
-    let capacity = estimate_json_size(data);
+#[pyo3(signature = (
+    data, *, float_repr = "ryu", iterables = false, none_repr = "null", sort_keys = None,
+    bigint_mode = "number", distinguish_tuple = false, namedtuple_as_object = false,
+    skipkeys = false, check_circular = true, serialize_exceptions = false,
+    no_scientific = false, sequences = false, timedelta_mode = None, cls = None, nan_mode = "error",
+    escape = "json", priority_keys = None, max_depth = None, on_max_depth = "error", naive_utc = false,
+    default = None, ensure_ascii = false, allow_nan = false, bytes_mode = None,
+    dataclass_omit_none = false, on_missing_attr = "error", canonical = false, append_newline = false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn dumps(
+    _py: Python,
+    data: &Bound<'_, PyAny>,
+    float_repr: &str,
+    iterables: bool,
+    none_repr: &str,
+    sort_keys: Option<&Bound<'_, PyAny>>,
+    bigint_mode: &str,
+    distinguish_tuple: bool,
+    namedtuple_as_object: bool,
+    skipkeys: bool,
+    check_circular: bool,
+    serialize_exceptions: bool,
+    no_scientific: bool,
+    sequences: bool,
+    timedelta_mode: Option<&str>,
+    cls: Option<&Bound<'_, PyAny>>,
+    nan_mode: &str,
+    escape: &str,
+    priority_keys: Option<Vec<Bound<'_, PyString>>>,
+    max_depth: Option<usize>,
+    on_max_depth: &str,
+    naive_utc: bool,
+    default: Option<&Bound<'_, PyAny>>,
+    ensure_ascii: bool,
+    allow_nan: bool,
+    bytes_mode: Option<&str>,
+    dataclass_omit_none: bool,
+    on_missing_attr: &str,
+    canonical: bool,
+    append_newline: bool,
+) -> PyResult<String> {
+    let serializer = dumps_serializer(
+        float_repr, iterables, none_repr, sort_keys, bigint_mode, distinguish_tuple,
+        namedtuple_as_object, skipkeys, check_circular, serialize_exceptions, no_scientific,
+        sequences, timedelta_mode, cls, nan_mode, escape, priority_keys, max_depth, on_max_depth,
+        naive_utc, default, ensure_ascii, allow_nan, bytes_mode, dataclass_omit_none,
+        on_missing_attr, canonical,
+    )?;
+    let capacity = estimate_json_size(data, serializer.escape);
+    #[cfg(debug_assertions)]
+    let nan_mode = serializer.nan_mode;
 
     // PHASE 14 OPTIMIZATION: Reuse thread-local buffer
     object_cache::get_serialize_buffer(capacity, |buf| {
-        let mut buffer = JsonBuffer { buf: std::mem::take(buf) };
+        let mut buffer = JsonBuffer { buf: std::mem::take(buf), ..serializer };
         let result = buffer.serialize_pyany(data);
 
         // Put buffer back (keeping capacity for next call)
         *buf = buffer.buf;
 
         result.map(|_| {
+            #[cfg(debug_assertions)]
+            if nan_mode != NanMode::Literal {
+                debug_check_output(buf);
+            }
+            if append_newline {
+                buf.push(b'\n');
+            }
+
             // SAFETY: We only write valid UTF-8 (JSON is always UTF-8)
             unsafe { String::from_utf8_unchecked(buf.clone()) }
         })
     })
 }
 
+/// Build a serializer for `dumps`' keyword options (all but
+/// `append_newline`), shared with `dump`
+///
+/// # Returns
+/// A `JsonBuffer` with an empty buffer and no sink, or a ValueError for an
+/// invalid option or combination.
+#[allow(clippy::too_many_arguments)]
+fn dumps_serializer(
+    float_repr: &str,
+    iterables: bool,
+    none_repr: &str,
+    sort_keys: Option<&Bound<'_, PyAny>>,
+    bigint_mode: &str,
+    distinguish_tuple: bool,
+    namedtuple_as_object: bool,
+    skipkeys: bool,
+    check_circular: bool,
+    serialize_exceptions: bool,
+    no_scientific: bool,
+    sequences: bool,
+    timedelta_mode: Option<&str>,
+    cls: Option<&Bound<'_, PyAny>>,
+    nan_mode: &str,
+    escape: &str,
+    priority_keys: Option<Vec<Bound<'_, PyString>>>,
+    max_depth: Option<usize>,
+    on_max_depth: &str,
+    naive_utc: bool,
+    default: Option<&Bound<'_, PyAny>>,
+    ensure_ascii: bool,
+    allow_nan: bool,
+    bytes_mode: Option<&str>,
+    dataclass_omit_none: bool,
+    on_missing_attr: &str,
+    canonical: bool,
+) -> PyResult<JsonBuffer> {
+    if canonical {
+        let conflicts = [
+            ("sort_keys", sort_keys.is_some()),
+            ("priority_keys", priority_keys.as_ref().is_some_and(|keys| !keys.is_empty())),
+            ("float_repr", float_repr != "ryu"),
+            ("no_scientific", no_scientific),
+            ("escape", escape != "json"),
+            ("ensure_ascii", ensure_ascii),
+            ("allow_nan", allow_nan),
+            ("nan_mode", nan_mode != "error"),
+        ];
+        if let Some((other, _)) = conflicts.iter().find(|(_, set)| *set) {
+            return Err(PyValueError::new_err(format!("canonical and {other} cannot be combined")));
+        }
+    }
+    let max_depth = max_depth.unwrap_or(usize::MAX);
+    let on_max_depth = MaxDepthMode::from_name(on_max_depth)?;
+    let nan_mode = match (NanMode::from_name(nan_mode)?, allow_nan) {
+        (mode, false) => mode,
+        (NanMode::Error, true) => NanMode::Literal,
+        (_, true) => return Err(PyValueError::new_err("allow_nan and nan_mode='string' cannot be combined")),
+    };
+    let escape = EscapePolicy::from_name(escape)?;
+    let escape = if ensure_ascii { EscapePolicy::Ascii } else { escape };
+    let timedelta_mode = TimedeltaMode::from_name(timedelta_mode)?;
+    let bytes_mode = BytesMode::from_name(bytes_mode)?;
+    let on_missing_attr = MissingAttrMode::from_name(on_missing_attr)?;
+    let encoder_default = match (default, cls) {
+        (Some(default), _) => Some(default.clone().unbind()),
+        (None, Some(cls)) => Some(cls.call0()?.getattr("default")?.unbind()),
+        (None, None) => None,
+    };
+    let float_repr = if no_scientific { FloatRepr::Plain } else { FloatRepr::from_name(float_repr)? };
+    let bigint_mode = BigIntMode::from_name(bigint_mode)?;
+    let none_repr = parse_none_repr(none_repr)?;
+    let sort_keys = SortKeys::from_arg(sort_keys)?;
+    let (float_repr, bigint_mode, sort_keys) = match (canonical, bigint_mode) {
+        (false, _) => (float_repr, bigint_mode, sort_keys),
+        (true, BigIntMode::String) => (FloatRepr::Ecma, BigIntMode::String, SortKeys::Utf16),
+        (true, _) => (FloatRepr::Ecma, BigIntMode::Error, SortKeys::Utf16),
+    };
+    let mut priority: Vec<Py<PyString>> = Vec::new();
+    for key in priority_keys.into_iter().flatten() {
+        if !priority.iter().any(|seen| seen.bind(key.py()).to_cow().ok() == key.to_cow().ok()) {
+            priority.push(key.unbind());
+        }
+    }
+    let priority_keys = priority;
+
+    Ok(JsonBuffer {
+        float_repr,
+        nan_mode,
+        escape,
+        iterables,
+        none_repr,
+        bigint_mode,
+        sort_keys,
+        priority_keys,
+        max_depth,
+        on_max_depth,
+        distinguish_tuple,
+        namedtuple_as_object,
+        skipkeys,
+        check_circular,
+        serialize_exceptions,
+        sequences,
+        timedelta_mode,
+        bytes_mode,
+        dataclass_omit_none,
+        on_missing_attr,
+        naive_utc,
+        encoder_default,
+        ..JsonBuffer::with_defaults(Vec::new())
+    })
+}
+
+/// Debug-build self-check: panic unless `dumps` output is well-formed JSON
+///
+/// The serializers write bytes by hand (SIMD escaping, LUTs, bulk writers)
+/// and hand them out via `from_utf8_unchecked`, so an escaping or offset bug
+/// would otherwise surface far away as corrupt data. Re-validating every
+/// output costs a full parse, so it is compiled out of release builds.
+/// Note that a registered serializer emitting invalid JSON trips it too.
+/// Skipped for `dumps(allow_nan=True)`, whose bare `NaN` tokens aren't JSON.
+#[cfg(debug_assertions)]
+fn debug_check_output(output: &[u8]) {
+    let text = std::str::from_utf8(output)
+        .unwrap_or_else(|e| panic!("dumps produced invalid UTF-8: {e}"));
+    let mut de = serde_json::Deserializer::from_str(text);
+    // Output may nest as deep as the input did
+    de.disable_recursion_limit();
+    <de::IgnoredAny as serde::Deserialize>::deserialize(&mut de)
+        .and_then(|_| de.end())
+        .unwrap_or_else(|e| panic!("dumps produced invalid JSON ({e}): {text:.200}"));
+}
+
+/// Longest chain of `default()`/`__json__()` calls (a result that itself
+/// needs one, and so on, with no container in between)
+const MAX_DEFAULT_DEPTH: usize = 64;
+
+/// Buffered output size at which a `JsonBuffer` with a sink flushes
+const SPILL_THRESHOLD: usize = 64 * 1024;
+
+/// Hash the key-sorted JSON text of an object.
+///
+/// By default the text hashed is `dumps(data, sort_keys=True)`: keys sorted
+/// at every level, compact separators, shortest round-trip floats. Dicts
+/// that differ only in insertion order therefore hash the same. Tuples hash
+/// like lists, and `1` and `1.0` stay distinct, matching their JSON text.
+/// This is rjson's own stable form, not a standard one; pass
+/// `canonical=True` to hash the RFC 8785 (JCS) form instead, which other
+/// JCS implementations reproduce.
+///
+/// The output is fed to a `hashlib` object in 64 KiB chunks as it is
+/// produced, so the full JSON string is never materialized.
+///
+/// # Arguments
+/// * `data` - The Python object to hash.
+/// * `algorithm` - Any `hashlib.new` algorithm name (default `"sha256"`).
+/// * `canonical` - Hash `dumps(data, canonical=True)`, the JCS form (keys
+///   sorted by UTF-16 code units, ECMAScript number formatting; ints beyond
+///   2^53 raise ValueError).
+///
+/// # Returns
+/// The hex digest, or a ValueError if `data` isn't serializable.
+#[pyfunction]
+#[pyo3(signature = (data, *, algorithm = "sha256", canonical = false))]
+fn content_hash(py: Python, data: &Bound<'_, PyAny>, algorithm: &str, canonical: bool) -> PyResult<String> {
+    let hasher = py.import("hashlib")?.call_method1("new", (algorithm,))?;
+    let (float_repr, bigint_mode, sort_keys) = if canonical {
+        (FloatRepr::Ecma, BigIntMode::Error, SortKeys::Utf16)
+    } else {
+        (FloatRepr::Ryu, BigIntMode::Number, SortKeys::All)
+    };
+
+    let digest = object_cache::get_serialize_buffer(SPILL_THRESHOLD, |buf| {
+        let mut buffer = JsonBuffer {
+            float_repr,
+            bigint_mode,
+            sort_keys,
+            sink: Some(Sink { write: hasher.getattr("update")?.unbind(), borrow: false, text: false }),
+            ..JsonBuffer::with_defaults(std::mem::take(buf))
+        };
+        let result = buffer.serialize_pyany(data).and_then(|_| buffer.spill());
+
+        // Put buffer back (keeping capacity for next call)
+        *buf = buffer.buf;
+        result
+    });
+    digest?;
+
+    hasher.call_method0("hexdigest")?.extract()
+}
+
+/// Serialize an object as JSON to a file-like object.
+///
+/// Output goes to `fp.write` in 64 KiB chunks as it is produced, so large
+/// documents are never held in memory whole and `write` is called once per
+/// chunk rather than per value. Binary files get `bytes` chunks; for an
+/// `io.BytesIO` they are passed as memoryviews over the internal buffer,
+/// skipping the intermediate `bytes` objects. Text files (`io.TextIOBase`
+/// instances, e.g. opened `"w"` or `io.StringIO`) get `str` chunks.
+///
+/// # Arguments
+/// * `data` - The Python object to serialize.
+/// * `fp` - Any object with a `write` method: a text file, or a binary one
+///   taking bytes (a file opened `"wb"`, `io.BytesIO`, a socket file).
+/// * Keyword options - As for `dumps` (`sort_keys`, `default`,
+///   `ensure_ascii`, `append_newline`, ...), with the same defaults.
+///
+/// # Returns
+/// None, or a ValueError if `data` isn't serializable. Output already
+/// written before an error stays written.
+#[pyfunction]
+#[pyo3(signature = (
+    data, fp, *, float_repr = "ryu", iterables = false, none_repr = "null", sort_keys = None,
+    bigint_mode = "number", distinguish_tuple = false, namedtuple_as_object = false,
+    skipkeys = false, check_circular = true, serialize_exceptions = false,
+    no_scientific = false, sequences = false, timedelta_mode = None, cls = None, nan_mode = "error",
+    escape = "json", priority_keys = None, max_depth = None, on_max_depth = "error", naive_utc = false,
+    default = None, ensure_ascii = false, allow_nan = false, bytes_mode = None,
+    dataclass_omit_none = false, on_missing_attr = "error", canonical = false, append_newline = false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn dump(
+    py: Python,
+    data: &Bound<'_, PyAny>,
+    fp: &Bound<'_, PyAny>,
+    float_repr: &str,
+    iterables: bool,
+    none_repr: &str,
+    sort_keys: Option<&Bound<'_, PyAny>>,
+    bigint_mode: &str,
+    distinguish_tuple: bool,
+    namedtuple_as_object: bool,
+    skipkeys: bool,
+    check_circular: bool,
+    serialize_exceptions: bool,
+    no_scientific: bool,
+    sequences: bool,
+    timedelta_mode: Option<&str>,
+    cls: Option<&Bound<'_, PyAny>>,
+    nan_mode: &str,
+    escape: &str,
+    priority_keys: Option<Vec<Bound<'_, PyString>>>,
+    max_depth: Option<usize>,
+    on_max_depth: &str,
+    naive_utc: bool,
+    default: Option<&Bound<'_, PyAny>>,
+    ensure_ascii: bool,
+    allow_nan: bool,
+    bytes_mode: Option<&str>,
+    dataclass_omit_none: bool,
+    on_missing_attr: &str,
+    canonical: bool,
+    append_newline: bool,
+) -> PyResult<()> {
+    let serializer = dumps_serializer(
+        float_repr, iterables, none_repr, sort_keys, bigint_mode, distinguish_tuple,
+        namedtuple_as_object, skipkeys, check_circular, serialize_exceptions, no_scientific,
+        sequences, timedelta_mode, cls, nan_mode, escape, priority_keys, max_depth, on_max_depth,
+        naive_utc, default, ensure_ascii, allow_nan, bytes_mode, dataclass_omit_none,
+        on_missing_attr, canonical,
+    )?;
+    let sink = Sink {
+        write: fp.getattr("write")?.unbind(),
+        borrow: fp.get_type().is(type_cache::get_bytes_io_type(py)?),
+        text: fp.is_instance(type_cache::get_text_io_base(py)?)?,
+    };
+
+    object_cache::get_serialize_buffer(SPILL_THRESHOLD, |buf| {
+        let mut buffer = JsonBuffer { buf: std::mem::take(buf), sink: Some(sink), ..serializer };
+        let result = buffer.serialize_pyany(data).and_then(|_| {
+            if append_newline {
+                buffer.buf.push(b'\n');
+            }
+            buffer.spill()
+        });
+
+        // Put buffer back (keeping capacity for next call)
+        *buf = buffer.buf;
+        result
+    })
+}
+
+/// Serialize an object as JSON into a caller-owned `bytearray`.
+///
+/// For frameworks that manage their own output buffers: the bytearray is
+/// resized to the output length (grown if too small, truncated if larger)
+/// and overwritten, so one buffer can be reused across calls without a
+/// `bytes` object per call. The JSON is built in the thread-local buffer
+/// first, since `default` callbacks could otherwise resize the bytearray
+/// mid-write, then copied in once.
+///
+/// Uses the `dumps` defaults.
+///
+/// # Arguments
+/// * `data` - The Python object to serialize.
+/// * `buffer` - The bytearray to write into.
+///
+/// # Returns
+/// The number of bytes written (the bytearray's new length), or a
+/// ValueError if `data` isn't serializable, in which case `buffer` is left
+/// unchanged.
+#[pyfunction]
+fn dumps_into(data: &Bound<'_, PyAny>, buffer: &Bound<'_, PyByteArray>) -> PyResult<usize> {
+    object_cache::get_serialize_buffer(estimate_json_size(data, EscapePolicy::Json), |buf| {
+        let mut serializer = JsonBuffer::with_defaults(std::mem::take(buf));
+        let result = serializer.serialize_pyany(data);
+
+        // Put buffer back (keeping capacity for next call)
+        *buf = serializer.buf;
+        result?;
+
+        buffer.resize(buf.len())?;
+        // SAFETY: no Python code runs between the resize and the copy
+        unsafe { buffer.as_bytes_mut() }.copy_from_slice(buf);
+        Ok(buf.len())
+    })
+}
+
+/// Serialize an object to a length-prefixed JSON frame.
+///
+/// The frame is a 4-byte big-endian byte count followed by the compact JSON
+/// (`dumps` defaults), built in one buffer so message-oriented protocols
+/// don't have to measure and concatenate on the Python side.
+///
+/// # Arguments
+/// * `data` - The Python object to serialize.
+///
+/// # Returns
+/// The frame as `bytes`, or a ValueError if `data` isn't serializable or
+/// the JSON exceeds 4 GiB.
+#[pyfunction]
+fn dumps_framed(py: Python, data: &Bound<'_, PyAny>) -> PyResult<Py<PyBytes>> {
+    /// Header bytes reserved at the front of the buffer
+    const HEADER_LEN: usize = 4;
+
+    object_cache::get_serialize_buffer(estimate_json_size(data, EscapePolicy::Json) + HEADER_LEN, |buf| {
+        let mut body = std::mem::take(buf);
+        body.extend_from_slice(&[0; HEADER_LEN]);
+        let mut buffer = JsonBuffer::with_defaults(body);
+        let result = buffer.serialize_pyany(data).and_then(|()| {
+            let len = u32::try_from(buffer.buf.len() - HEADER_LEN)
+                .map_err(|_| PyValueError::new_err("JSON too large for a 4-byte length prefix"))?;
+            buffer.buf[..HEADER_LEN].copy_from_slice(&len.to_be_bytes());
+            Ok(PyBytes::new(py, &buffer.buf).unbind())
+        });
+
+        // Put buffer back (keeping capacity for next call)
+        *buf = buffer.buf;
+        result
+    })
+}
+
 /// EXTREME OPTIMIZATION: dumps_bytes() - The "Nuclear Option"
 ///
-/// Returns PyBytes instead of String for zero-copy performance.
+/// Returns PyBytes instead of String, skipping the `str` conversion.
 /// This is 10-20% faster than dumps() but breaks API compatibility.
 ///
 /// Optimizations:
-/// - Zero-copy: Returns bytes directly, no UTF-8 validation
+/// - Bytes output: One copy from the buffer into the `PyBytes`, no UTF-8
+///   validation
+/// - Thread-local buffer reuse, as in `dumps`: the buffer grows to the
+///   largest output needed and stays allocated, so calls don't allocate one
+///   each time
 /// - Direct C API: Bypasses PyO3 completely for serialization
 /// - AVX2 SIMD: String escape detection (when available)
 /// - Aggressive inlining: Single massive function, no calls
@@ -743,14 +3999,23 @@ fn dumps(_py: Python, data: &Bound<'_, PyAny>) -> PyResult<String> {
 ///
 /// WARNING: More unsafe code, harder to maintain, but MAXIMUM PERFORMANCE
 ///
+/// Circular references always raise ValueError, as with `dumps`. Types
+/// other than exact builtins (`OrderedDict`, enum members, tuples, ...) are
+/// serialized as `dumps` serializes them with its defaults, and unsupported
+/// types raise the same ValueError naming the type. A value holding any of
+/// them is serialized by `dumps` as a whole, so one changed mid-call by a
+/// `__json__` hook is caught the same way too.
+///
 /// # Arguments
 /// * `py` - The Python GIL token.
 /// * `data` - The Python object to serialize.
+/// * `append_newline` - End the output with `\n`, as for `dumps`.
 ///
 /// # Returns
 /// PyBytes containing JSON (not validated as UTF-8 string)
 #[pyfunction]
-fn dumps_bytes(py: Python, data: &Bound<'_, PyAny>) -> PyResult<Py<PyBytes>> {
+#[pyo3(signature = (data, *, append_newline = false))]
+fn dumps_bytes(py: Python, data: &Bound<'_, PyAny>, append_newline: bool) -> PyResult<Py<PyBytes>> {
     unsafe {
         // SAFETY: We transmute Python to 'static for the serializer.
         // This is safe because we don't actually store it beyond this function call.
@@ -759,10 +4024,24 @@ fn dumps_bytes(py: Python, data: &Bound<'_, PyAny>) -> PyResult<Py<PyBytes>> {
         let obj_ptr = data.as_ptr();
         let capacity = extreme::estimate_size_fast(obj_ptr);
 
-        let mut serializer = extreme::DirectSerializer::new(py_static, capacity);
-        serializer.serialize_direct(obj_ptr)?;
+        // PHASE 14 OPTIMIZATION: Reuse thread-local buffer, as dumps does
+        object_cache::get_serialize_buffer(capacity, |buf| {
+            let mut serializer = extreme::DirectSerializer::new(py_static, std::mem::take(buf));
+            let result = serializer.serialize(data).map(|()| {
+                if append_newline {
+                    serializer.push_newline();
+                }
+                serializer.to_pybytes(py)
+            });
+
+            // Put buffer back (keeping capacity for next call)
+            *buf = serializer.into_buffer();
 
-        Ok(serializer.into_pybytes(py))
+            let bytes = result?;
+            #[cfg(debug_assertions)]
+            debug_check_output(bytes.as_bytes(py));
+            Ok(bytes)
+        })
     }
 }
 
@@ -782,11 +4061,38 @@ fn rjson(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     // OPTIMIZATION: Initialize all caches at module load time
     object_cache::init_cache(py);
     type_cache::init_type_cache(py);
+    ascii_str::init_ascii_str(py);
     simd_parser::init_string_intern(py);  // Phase 9: String interning
 
     m.add_function(wrap_pyfunction!(loads, m)?)?;
+    m.add("JSONDecodeError", py.get_type::<JSONDecodeError>())?;
     m.add_function(wrap_pyfunction!(loads_simd, m)?)?;  // Phase 7: SIMD loads
+    m.add_function(wrap_pyfunction!(load, m)?)?;
+    m.add_function(wrap_pyfunction!(set_parse_stack_size, m)?)?;
+    m.add_function(wrap_pyfunction!(config::configure, m)?)?;
+    m.add_function(wrap_pyfunction!(config::get_config, m)?)?;
+    m.add_function(wrap_pyfunction!(config::set_intern_cache_size, m)?)?;
+    m.add_function(wrap_pyfunction!(config::set_intern_max_len, m)?)?;
+    m.add_function(wrap_pyfunction!(config::clear_intern_cache, m)?)?;
     m.add_function(wrap_pyfunction!(dumps, m)?)?;
     m.add_function(wrap_pyfunction!(dumps_bytes, m)?)?;  // Nuclear option
+    m.add_function(wrap_pyfunction!(dump, m)?)?;
+    m.add_function(wrap_pyfunction!(dumps_into, m)?)?;
+    m.add_function(wrap_pyfunction!(dumps_framed, m)?)?;
+    m.add_function(wrap_pyfunction!(content_hash, m)?)?;
+    m.add_class::<lazy::LazyValue>()?;
+    m.add_function(wrap_pyfunction!(scan::json_get, m)?)?;
+    m.add_function(wrap_pyfunction!(scan::loads_prefix, m)?)?;
+    m.add_function(wrap_pyfunction!(spans::loads_with_spans, m)?)?;
+    m.add_function(wrap_pyfunction!(lines::dumps_lines, m)?)?;
+    m.add_function(wrap_pyfunction!(lines::loads_lines, m)?)?;
+    m.add_class::<stream::JsonStreamDecoder>()?;
+    m.add_function(wrap_pyfunction!(events::parse_events, m)?)?;
+
+    // Benchmark harness lives in its own submodule, re-exported for convenience
+    let bench_module = PyModule::new(py, "bench")?;
+    bench::register(&bench_module)?;
+    m.add("benchmark", bench_module.getattr("benchmark")?)?;
+    m.add_submodule(&bench_module)?;
     Ok(())
 }
@@ -0,0 +1,105 @@
+//! Newline-delimited JSON (JSON Lines / ndjson) exposed to Python as
+//! `rjson.dumps_lines` and `rjson.loads_lines`
+//!
+//! Log pipelines read and write one JSON value per line. `dumps_lines`
+//! serializes every item into one reused buffer, so there is no per-line
+//! string to build and join; `loads_lines` parses each line with the serde
+//! seeds, so values come out identical to `loads` and errors point into the
+//! whole document.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyAny, PyList};
+use serde::de::DeserializeSeed;
+
+use crate::optimizations::object_cache;
+use crate::{JSONDecodeError, JsonBuffer, LoadsOptions, PyObjectSeed};
+
+/// Serialize each item of an iterable as one line of JSON.
+///
+/// Items are written with the `dumps` defaults into a single buffer and
+/// separated by `\n`, with no trailing newline (append one when writing
+/// to a file that other lines will follow).
+///
+/// # Arguments
+/// * `items` - Any iterable (list, generator, ...); consumed lazily.
+///
+/// # Returns
+/// The lines as one string, or a ValueError if an item isn't serializable.
+#[pyfunction]
+pub fn dumps_lines(items: &Bound<'_, PyAny>) -> PyResult<String> {
+    object_cache::get_serialize_buffer(0, |buf| {
+        let mut buffer = JsonBuffer::with_defaults(std::mem::take(buf));
+        let result = items.try_iter().and_then(|iter| {
+            for (i, item) in iter.enumerate() {
+                if i > 0 {
+                    buffer.buf.push(b'\n');
+                }
+                buffer.serialize_pyany(&item?)?;
+            }
+            Ok(())
+        });
+
+        // Put buffer back (keeping capacity for next call)
+        *buf = buffer.buf;
+
+        // SAFETY: We only write valid UTF-8 (JSON is always UTF-8)
+        result.map(|()| unsafe { String::from_utf8_unchecked(buf.clone()) })
+    })
+}
+
+/// Parse newline-delimited JSON into a list with one value per line.
+///
+/// Blank (whitespace-only) lines are skipped, so a trailing newline or
+/// `\r\n` line endings are fine. Each non-blank line must hold exactly one
+/// JSON value. Each may nest up to 127 levels, serde_json's recursion
+/// limit, rather than the deeper limit of `loads`.
+///
+/// # Arguments
+/// * `data` - The document, as str, bytes or bytearray.
+/// * `intern_keys` - Cache repeated object keys, as for `loads`.
+///
+/// # Returns
+/// A list of the parsed values. A malformed line raises
+/// `rjson.JSONDecodeError` positioned in the whole document, so its
+/// `lineno` is the failing line.
+#[pyfunction]
+#[pyo3(signature = (data, *, intern_keys = true))]
+pub fn loads_lines<'py>(
+    py: Python<'py>,
+    data: &Bound<'py, PyAny>,
+    intern_keys: bool,
+) -> PyResult<Bound<'py, PyList>> {
+    let doc = &*crate::json_text(data)?;
+    let opts = LoadsOptions { intern_keys, ..LoadsOptions::default() };
+    let result = PyList::empty(py);
+
+    let mut line_start = 0;
+    for line in doc.split('\n') {
+        let start = line_start;
+        line_start += line.len() + 1;
+        if line.trim_matches([' ', '\t', '\r']).is_empty() {
+            continue;
+        }
+
+        let mut de = serde_json::Deserializer::from_str(line);
+        let value = PyObjectSeed { py, opts: &opts, depth: 0 }
+            .deserialize(&mut de)
+            .and_then(|value| de.end().map(|()| value))
+            .map_err(|e| PyErr::take(py).unwrap_or_else(|| line_error(doc, start, line, e)))?;
+        result.append(value)?;
+    }
+    Ok(result)
+}
+
+/// `JSONDecodeError` for a failure on the line of `doc` starting at byte
+/// `start`, positioned in the whole document
+#[cold]
+fn line_error(doc: &str, start: usize, line: &str, e: serde_json::Error) -> PyErr {
+    match crate::error_byte(line, &e) {
+        Some(byte) => {
+            let pos = doc[..start + byte].chars().count();
+            JSONDecodeError::new_err((crate::error_message(&e), doc.to_owned(), pos))
+        }
+        None => crate::decode_error(line, e),
+    }
+}
@@ -0,0 +1,441 @@
+//! numpy scalar and ndarray serialization (`numpy` cargo feature)
+//!
+//! numpy isn't linked against: scalars and arrays are recognized by their
+//! `numpy.generic`/`numpy.ndarray` base types once the user has imported
+//! numpy, scalars are unwrapped with `.item()`, and arrays of bool, int and
+//! float dtypes are read straight from the buffer protocol, walking
+//! `shape`/`strides` so C-contiguous, Fortran-ordered and sliced views all
+//! work without a copy. Other dtypes (object, str, complex, datetime64,
+//! float16, non-native byte order) go through `.tolist()`.
+//!
+//! Structured arrays (and record arrays) are written as arrays of objects,
+//! each record's fields read at their `dtype.fields` offsets. Field dtypes
+//! other than bool, int, float32/64 and `U` text are converted one value at
+//! a time through a 0-d array; object fields and void dtypes without fields
+//! have no JSON form and raise.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::ffi;
+use pyo3::types::{PyString, PyTuple};
+use std::ffi::CStr;
+
+use crate::optimizations::type_cache;
+use crate::{write_json_string_with, JsonBuffer, SortKeys};
+
+/// Element type of an array buffer we read directly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Element {
+    Bool,
+    Int(usize),
+    UInt(usize),
+    F32,
+    F64,
+}
+
+impl Element {
+    /// Decode a buffer-protocol `format` string; `None` for anything but a
+    /// single native-order bool, integer or float32/64 item
+    fn from_format(format: &[u8], itemsize: usize) -> Option<Self> {
+        let native = if cfg!(target_endian = "little") { b'<' } else { b'>' };
+        let code = match format {
+            [b'@' | b'=', code] | [code] => *code,
+            [order, code] if *order == native => *code,
+            _ => return None,
+        };
+        let element = match code {
+            b'?' => Element::Bool,
+            b'b' | b'h' | b'i' | b'l' | b'q' | b'n' => Element::Int(itemsize),
+            b'B' | b'H' | b'I' | b'L' | b'Q' | b'N' => Element::UInt(itemsize),
+            b'f' => Element::F32,
+            b'd' => Element::F64,
+            _ => return None,
+        };
+        let size_ok = match element {
+            Element::Bool => itemsize == 1,
+            Element::Int(size) | Element::UInt(size) => matches!(size, 1 | 2 | 4 | 8),
+            Element::F32 => itemsize == 4,
+            Element::F64 => itemsize == 8,
+        };
+        size_ok.then_some(element)
+    }
+
+    /// The element type of a structured dtype's field, from its `kind` and
+    /// `itemsize`
+    fn from_kind(kind: char, itemsize: usize) -> Option<Self> {
+        match (kind, itemsize) {
+            ('b', 1) => Some(Element::Bool),
+            ('i', 1 | 2 | 4 | 8) => Some(Element::Int(itemsize)),
+            ('u', 1 | 2 | 4 | 8) => Some(Element::UInt(itemsize)),
+            ('f', 4) => Some(Element::F32),
+            ('f', 8) => Some(Element::F64),
+            _ => None,
+        }
+    }
+}
+
+/// What each position of an array holds
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Element(Element),
+    Record(&'a [Field]),
+}
+
+/// A named field of a structured dtype
+struct Field {
+    name: String,
+    /// Byte offset within the record
+    offset: usize,
+    kind: FieldKind,
+}
+
+/// How a field's value is read from its bytes
+enum FieldKind {
+    /// Bool, integer or float32/64, byte-swapped first if `swap`
+    Element(Element, bool),
+    /// `U` text: this many UCS-4 code points, NUL-padded
+    Unicode(usize, bool),
+    /// A nested structured dtype
+    Record(Vec<Field>),
+    /// Anything else (float16, complex, bytes, datetime64, subarrays): the
+    /// field's dtype and size, read through a 0-d array's `.tolist()`
+    Other(Py<PyAny>, usize),
+}
+
+/// A buffer acquired with `PyBUF_RECORDS_RO`, released on drop
+struct BufferView(ffi::Py_buffer);
+
+impl BufferView {
+    /// Acquire the buffer with `flags` (`PyBUF_RECORDS_RO` or, without a
+    /// format string, `PyBUF_STRIDED_RO`)
+    fn get(obj: &Bound<'_, PyAny>, flags: std::os::raw::c_int) -> PyResult<Self> {
+        let mut view = std::mem::MaybeUninit::<ffi::Py_buffer>::uninit();
+        // SAFETY: on success the view is initialized and released in Drop
+        unsafe {
+            if ffi::PyObject_GetBuffer(obj.as_ptr(), view.as_mut_ptr(), flags) != 0 {
+                return Err(PyErr::fetch(obj.py()));
+            }
+            Ok(BufferView(view.assume_init()))
+        }
+    }
+
+    fn format(&self) -> &[u8] {
+        if self.0.format.is_null() {
+            return b"B";
+        }
+        // SAFETY: a non-null format is a NUL-terminated string owned by the view
+        unsafe { CStr::from_ptr(self.0.format).to_bytes() }
+    }
+
+    fn dims(&self) -> (&[ffi::Py_ssize_t], &[ffi::Py_ssize_t]) {
+        let ndim = self.0.ndim as usize;
+        if ndim == 0 {
+            return (&[], &[]);
+        }
+        // SAFETY: PyBUF_STRIDES guarantees `ndim` shape and stride entries
+        unsafe {
+            (
+                std::slice::from_raw_parts(self.0.shape, ndim),
+                std::slice::from_raw_parts(self.0.strides, ndim),
+            )
+        }
+    }
+}
+
+impl Drop for BufferView {
+    fn drop(&mut self) {
+        // SAFETY: acquired by PyObject_GetBuffer in `get`
+        unsafe { ffi::PyBuffer_Release(&mut self.0) };
+    }
+}
+
+impl JsonBuffer {
+    /// Serialize a numpy scalar (as its `.item()`) or ndarray (as nested
+    /// arrays)
+    ///
+    /// # Returns
+    /// `Ok(false)` if the object isn't a numpy scalar or array, or numpy
+    /// hasn't been imported.
+    pub(crate) fn serialize_numpy(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let py = obj.py();
+        let ndarray = type_cache::get_ndarray_type(py);
+        if ndarray.is_null() {
+            return Ok(false);
+        }
+        // SAFETY: both pointers are live type objects
+        let is_instance = |base: *mut ffi::PyTypeObject| unsafe {
+            ffi::PyType_IsSubtype(ffi::Py_TYPE(obj.as_ptr()), base) != 0
+        };
+
+        if is_instance(ndarray) {
+            self.serialize_ndarray(obj)?;
+            return Ok(true);
+        }
+        let generic = type_cache::get_numpy_generic_type(py);
+        if !generic.is_null() && is_instance(generic) {
+            let item = obj.call_method0(pyo3::intern!(py, "item"))?;
+            // A structured record (`numpy.void`) is written like its array
+            if item.is_instance_of::<PyTuple>() && is_structured(&obj.getattr(pyo3::intern!(py, "dtype"))?)? {
+                self.serialize_ndarray(&obj.call_method0(pyo3::intern!(py, "__array__"))?)?;
+            } else {
+                self.serialize_pyany(&item)?;
+            }
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn serialize_ndarray(&mut self, array: &Bound<'_, PyAny>) -> PyResult<()> {
+        let py = array.py();
+        let dtype = array.getattr(pyo3::intern!(py, "dtype"))?;
+        if dtype.getattr(pyo3::intern!(py, "kind"))?.extract::<char>()? == 'V' {
+            return self.serialize_records(array, &dtype);
+        }
+        let view = BufferView::get(array, ffi::PyBUF_RECORDS_RO)?;
+        let Some(element) = Element::from_format(view.format(), view.0.itemsize as usize) else {
+            drop(view);
+            return self.serialize_pyany(&array.call_method0(pyo3::intern!(py, "tolist"))?);
+        };
+        let (shape, strides) = view.dims();
+        self.write_ndarray_dim(view.0.buf as *const u8, shape, strides, Item::Element(element))
+    }
+
+    /// Write a structured array as (nested arrays of) objects, one per record
+    fn serialize_records(&mut self, array: &Bound<'_, PyAny>, dtype: &Bound<'_, PyAny>) -> PyResult<()> {
+        if !is_structured(dtype)? {
+            return Err(unsupported_dtype_error(dtype, "is void without fields"));
+        }
+        if dtype.getattr(pyo3::intern!(array.py(), "hasobject"))?.is_truthy()? {
+            return Err(unsupported_dtype_error(dtype, "has object fields"));
+        }
+        // No format string: numpy can't describe some field dtypes
+        // (datetime64) in one, and fields are located from the dtype anyway
+        let view = BufferView::get(array, ffi::PyBUF_STRIDED_RO)?;
+        let (shape, strides) = view.dims();
+        let fields = self.record_fields(dtype, self.depth + shape.len())?;
+        self.write_ndarray_dim(view.0.buf as *const u8, shape, strides, Item::Record(&fields))
+    }
+
+    /// The fields of structured `dtype` in the order they are written: as
+    /// for a dict at `depth`, `priority_keys` first, then the rest sorted
+    /// under `sort_keys` or in definition order
+    fn record_fields(&self, dtype: &Bound<'_, PyAny>, depth: usize) -> PyResult<Vec<Field>> {
+        let py = dtype.py();
+        let by_name = dtype.getattr(pyo3::intern!(py, "fields"))?;
+        let mut fields = Vec::new();
+        for name in dtype.getattr(pyo3::intern!(py, "names"))?.try_iter()? {
+            let name = name?;
+            // (dtype, offset) or (dtype, offset, title)
+            let info = by_name.get_item(&name)?;
+            let field_dtype = info.get_item(0)?;
+            fields.push(Field {
+                name: name.downcast::<PyString>()?.to_str()?.to_owned(),
+                offset: info.get_item(1)?.extract()?,
+                kind: self.field_kind(&field_dtype, depth + 1)?,
+            });
+        }
+
+        let mut ordered = Vec::with_capacity(fields.len());
+        for key in &self.priority_keys {
+            let key = key.bind(py).to_str()?;
+            if let Some(i) = fields.iter().position(|field| field.name == key) {
+                ordered.push(fields.remove(i));
+            }
+        }
+        if self.sort_keys == SortKeys::Utf16 {
+            fields.sort_by_cached_key(|field| field.name.encode_utf16().collect::<Vec<u16>>());
+        } else if self.sort_keys.applies_at(depth) {
+            // UTF-8 byte order is code point order, as `sorted()` uses
+            fields.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        ordered.extend(fields);
+        Ok(ordered)
+    }
+
+    /// How to read a field of dtype `dtype` (a nested record opens at `depth`)
+    fn field_kind(&self, dtype: &Bound<'_, PyAny>, depth: usize) -> PyResult<FieldKind> {
+        let py = dtype.py();
+        let kind: char = dtype.getattr(pyo3::intern!(py, "kind"))?.extract()?;
+        let itemsize: usize = dtype.getattr(pyo3::intern!(py, "itemsize"))?.extract()?;
+        let swap = match dtype.getattr(pyo3::intern!(py, "byteorder"))?.extract::<char>()? {
+            '<' => cfg!(target_endian = "big"),
+            '>' => cfg!(target_endian = "little"),
+            _ => false,
+        };
+        if let Some(element) = Element::from_kind(kind, itemsize) {
+            return Ok(FieldKind::Element(element, swap));
+        }
+        Ok(match kind {
+            'U' => FieldKind::Unicode(itemsize / 4, swap),
+            'V' if is_structured(dtype)? => FieldKind::Record(self.record_fields(dtype, depth)?),
+            // Subarray fields (`("i4", (3,))`) are void with a `subdtype`
+            'V' if dtype.getattr(pyo3::intern!(py, "subdtype"))?.is_none() => {
+                return Err(unsupported_dtype_error(dtype, "is void without fields"));
+            }
+            _ => FieldKind::Other(dtype.clone().unbind(), itemsize),
+        })
+    }
+
+    /// Write the record at `ptr` as an object
+    fn write_record(&mut self, ptr: *const u8, fields: &[Field]) -> PyResult<()> {
+        if self.over_max_depth()? {
+            return Ok(());
+        }
+        self.buf.push(b'{');
+        self.depth += 1;
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                self.buf.push(b',');
+            }
+            write_json_string_with(&mut self.buf, &field.name, self.escape);
+            self.buf.push(b':');
+            // Offsets come from the dtype, so stay inside the record
+            let ptr = ptr.wrapping_add(field.offset);
+            match &field.kind {
+                FieldKind::Element(element, swap) => {
+                    // SAFETY: the field holds one `element`, possibly unaligned
+                    unsafe { self.write_swappable_element(ptr, *element, *swap)? }
+                }
+                // SAFETY: the field holds `len` UCS-4 code points
+                FieldKind::Unicode(len, swap) => unsafe { self.write_ucs4(ptr, *len, *swap)? },
+                FieldKind::Record(fields) => self.write_record(ptr, fields)?,
+                FieldKind::Other(dtype, size) => self.write_other_field(ptr, dtype, *size)?,
+            }
+        }
+        self.depth -= 1;
+        self.buf.push(b'}');
+        Ok(())
+    }
+
+    /// Write a field numpy has to convert: its `size` bytes at `ptr`, viewed
+    /// as a 0-d array of `dtype`, through `.tolist()`
+    fn write_other_field(&mut self, ptr: *const u8, dtype: &Py<PyAny>, size: usize) -> PyResult<()> {
+        Python::with_gil(|py| {
+            // SAFETY: the field's bytes stay valid while the array's buffer is
+            // held, and `.tolist()` copies them out
+            let memory = unsafe {
+                Bound::from_owned_ptr_or_err(
+                    py,
+                    ffi::PyMemoryView_FromMemory(ptr as *mut _, size as ffi::Py_ssize_t, ffi::PyBUF_READ),
+                )?
+            };
+            // SAFETY: numpy is loaded, so the type pointer is live
+            let ndarray = unsafe { Bound::from_borrowed_ptr(py, type_cache::get_ndarray_type(py) as *mut ffi::PyObject) };
+            let value = ndarray.call1((PyTuple::empty(py), dtype.bind(py), memory))?;
+            self.serialize_pyany(&value.call_method0(pyo3::intern!(py, "tolist"))?)
+        })
+    }
+
+    /// # Safety
+    /// `ptr` must point at a readable element of type `element` (possibly
+    /// unaligned), stored byte-swapped if `swap`.
+    unsafe fn write_swappable_element(&mut self, ptr: *const u8, element: Element, swap: bool) -> PyResult<()> {
+        if !swap {
+            return self.write_ndarray_element(ptr, element);
+        }
+        let size = match element {
+            Element::Bool => 1,
+            Element::Int(size) | Element::UInt(size) => size,
+            Element::F32 => 4,
+            Element::F64 => 8,
+        };
+        let mut bytes = [0u8; 8];
+        std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), size);
+        bytes[..size].reverse();
+        self.write_ndarray_element(bytes.as_ptr(), element)
+    }
+
+    /// Write `U` text as a string, dropping its NUL padding like `.tolist()`
+    ///
+    /// # Safety
+    /// `ptr` must point at `len` readable (possibly unaligned) UCS-4 code
+    /// points, stored byte-swapped if `swap`.
+    unsafe fn write_ucs4(&mut self, ptr: *const u8, len: usize, swap: bool) -> PyResult<()> {
+        let mut text = String::with_capacity(len);
+        for i in 0..len {
+            let unit = (ptr as *const u32).add(i).read_unaligned();
+            let unit = if swap { unit.swap_bytes() } else { unit };
+            let Some(c) = char::from_u32(unit) else {
+                return Err(PyValueError::new_err(format!("Invalid code point in numpy string: {unit:#x}")));
+            };
+            text.push(c);
+        }
+        write_json_string_with(&mut self.buf, text.trim_end_matches('\0'), self.escape);
+        Ok(())
+    }
+
+    /// Write the sub-array starting at `ptr` with the remaining `shape`
+    fn write_ndarray_dim(
+        &mut self,
+        ptr: *const u8,
+        shape: &[ffi::Py_ssize_t],
+        strides: &[ffi::Py_ssize_t],
+        item: Item<'_>,
+    ) -> PyResult<()> {
+        let Some((&len, inner_shape)) = shape.split_first() else {
+            return match item {
+                // SAFETY: `ptr` points at one element inside the buffer
+                Item::Element(element) => unsafe { self.write_ndarray_element(ptr, element) },
+                Item::Record(fields) => self.write_record(ptr, fields),
+            };
+        };
+        if self.over_max_depth()? {
+            return Ok(());
+        }
+        let stride = strides[0];
+
+        self.buf.push(b'[');
+        self.depth += 1;
+        for i in 0..len {
+            if i > 0 {
+                self.buf.push(b',');
+            }
+            // Strides may be negative (reversed views) but stay in the buffer
+            let item_ptr = ptr.wrapping_offset(i * stride);
+            self.write_ndarray_dim(item_ptr, inner_shape, &strides[1..], item)?;
+        }
+        self.depth -= 1;
+        self.buf.push(b']');
+        self.maybe_spill()
+    }
+
+    /// # Safety
+    /// `ptr` must point at a readable element of type `element` (possibly
+    /// unaligned).
+    unsafe fn write_ndarray_element(&mut self, ptr: *const u8, element: Element) -> PyResult<()> {
+        match element {
+            Element::Bool => {
+                self.write_bool(*ptr != 0);
+                Ok(())
+            }
+            Element::Int(1) => self.write_int_i64((ptr as *const i8).read_unaligned() as i64),
+            Element::Int(2) => self.write_int_i64((ptr as *const i16).read_unaligned() as i64),
+            Element::Int(4) => self.write_int_i64((ptr as *const i32).read_unaligned() as i64),
+            Element::Int(_) => self.write_int_i64((ptr as *const i64).read_unaligned()),
+            Element::UInt(1) => self.write_int_i64(*ptr as i64),
+            Element::UInt(2) => self.write_int_i64((ptr as *const u16).read_unaligned() as i64),
+            Element::UInt(4) => self.write_int_i64((ptr as *const u32).read_unaligned() as i64),
+            Element::UInt(_) => {
+                let value = (ptr as *const u64).read_unaligned();
+                match i64::try_from(value) {
+                    Ok(value) => self.write_int_i64(value),
+                    Err(_) => self.write_int_u64(value),
+                }
+            }
+            // Widened exactly, so the output matches `.tolist()`
+            Element::F32 => self.write_float((ptr as *const f32).read_unaligned() as f64),
+            Element::F64 => self.write_float((ptr as *const f64).read_unaligned()),
+        }
+    }
+}
+
+/// Whether `dtype` is structured (has named fields)
+fn is_structured(dtype: &Bound<'_, PyAny>) -> PyResult<bool> {
+    Ok(!dtype.getattr(pyo3::intern!(dtype.py(), "names"))?.is_none())
+}
+
+#[cold]
+fn unsupported_dtype_error(dtype: &Bound<'_, PyAny>, reason: &str) -> PyErr {
+    let dtype = dtype.str().map(|s| s.to_string()).unwrap_or_else(|_| "<unknown>".to_string());
+    PyValueError::new_err(format!("Unsupported numpy dtype for JSON serialization: {dtype} {reason}"))
+}
@@ -0,0 +1,129 @@
+//! Direct access to the characters of compact ASCII `str` objects
+//!
+//! `PyUnicode_AsUTF8AndSize` has to look up (and on first use build) a
+//! string's UTF-8 cache. A compact ASCII string already stores its
+//! characters as UTF-8 right after its `PyASCIIObject` header, so they can be
+//! read in place, as orjson does.
+//!
+//! This depends on CPython's object layout, which is not part of the stable
+//! API: the header size changed in 3.12 (`wstr` was dropped) and
+//! free-threaded builds have a larger object header. The layout is taken
+//! from the Python being built against and checked once against
+//! `PyUnicode_AsUTF8AndSize` at module init; if the check fails, every
+//! string goes through the C API instead.
+
+use pyo3::ffi;
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Leading fields of CPython's `PyASCIIObject`, up to the state flags
+#[repr(C)]
+struct PyASCIIObject {
+    /// PyObject_HEAD: ob_refcnt, ob_type
+    _ob_refcnt: isize,
+    _ob_type: *mut ffi::PyTypeObject,
+    /// String length (number of characters, not bytes for non-ASCII)
+    length: isize,
+    /// Cached hash value (-1 if not computed)
+    _hash: isize,
+    /// State flags packed as a u32
+    /// Bits: interned(2), kind(3), compact(1), ascii(1), ...
+    state: u32,
+}
+
+/// Bit mask for the 'compact' and 'ascii' flags in state
+/// compact is bit 5 and ascii bit 6 (after interned:2, kind:3). Both must be
+/// set for the data to follow the header: `str` subclass instances are never
+/// compact and keep their characters in a separate buffer.
+const STATE_COMPACT_ASCII_MASK: u32 = 0b01100000; // bits 5-6
+
+/// Offset from the object to the character data of a compact ASCII string,
+/// which directly follows the header
+///
+/// Taken from the header struct of the Python being built against: it was
+/// 48 bytes on 64-bit up to 3.11 and is 40 from 3.12.
+const ASCII_DATA_OFFSET: usize = std::mem::size_of::<ffi::PyASCIIObject>();
+
+/// Whether `verify_layout` confirmed the layout above (false until then)
+static INLINE_ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Check the layout assumptions against the running interpreter and enable
+/// the inline reads if they hold
+///
+/// Called once from module init. Reads that happen before it (none do) or
+/// after a failed check use the C API.
+pub fn init_ascii_str(py: Python) {
+    INLINE_ASCII.store(verify_layout(py), Ordering::Relaxed);
+}
+
+/// Compare inline reads of known strings with `PyUnicode_AsUTF8AndSize`
+fn verify_layout(py: Python) -> bool {
+    const SAMPLE: &str = "rjson ASCII layout check";
+    let ascii = pyo3::types::PyString::new(py, SAMPLE);
+    let non_ascii = pyo3::types::PyString::new(py, "r\u{e9}json");
+
+    // SAFETY: both are live exact `str` objects; the header fields read are
+    // inside any PyASCIIObject, and data is only read once the flags and
+    // length match what the C API reports
+    unsafe {
+        if read_inline(non_ascii.as_ptr()).is_some() {
+            return false;
+        }
+        let Some(inline) = read_inline(ascii.as_ptr()) else {
+            return false;
+        };
+        let mut size: ffi::Py_ssize_t = 0;
+        let utf8_ptr = ffi::PyUnicode_AsUTF8AndSize(ascii.as_ptr(), &mut size);
+        if utf8_ptr.is_null() || inline.len() != size as usize {
+            return false;
+        }
+        inline == std::slice::from_raw_parts(utf8_ptr as *const u8, size as usize)
+    }
+}
+
+/// # Safety
+/// `str_ptr` must be a valid `str` object, alive for `'a`.
+#[inline(always)]
+unsafe fn read_inline<'a>(str_ptr: *mut ffi::PyObject) -> Option<&'a [u8]> {
+    let ascii_obj = str_ptr as *const PyASCIIObject;
+    if (*ascii_obj).state & STATE_COMPACT_ASCII_MASK != STATE_COMPACT_ASCII_MASK {
+        return None;
+    }
+    let length = (*ascii_obj).length as usize;
+    let data_ptr = (str_ptr as *const u8).add(ASCII_DATA_OFFSET);
+    Some(std::slice::from_raw_parts(data_ptr, length))
+}
+
+/// The characters of a compact ASCII string, read in place
+///
+/// `None` for every other string, and for all strings if the layout check
+/// failed; use `PyUnicode_AsUTF8AndSize` for those.
+///
+/// # Safety
+/// `str_ptr` must be a valid `str` object (any subclass), alive for `'a`.
+#[inline(always)]
+pub unsafe fn ascii_data<'a>(str_ptr: *mut ffi::PyObject) -> Option<&'a [u8]> {
+    if !INLINE_ASCII.load(Ordering::Relaxed) {
+        return None;
+    }
+    read_inline(str_ptr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyString;
+
+    #[test]
+    fn test_layout_verified() {
+        Python::with_gil(|py| {
+            assert!(verify_layout(py));
+            init_ascii_str(py);
+
+            let s = PyString::new(py, "hello");
+            assert_eq!(unsafe { ascii_data(s.as_ptr()) }, Some(&b"hello"[..]));
+            let s = PyString::new(py, "h\u{e9}llo");
+            assert_eq!(unsafe { ascii_data(s.as_ptr()) }, None);
+        });
+    }
+}
@@ -10,44 +10,20 @@ use pyo3::prelude::*;
 use pyo3::ffi;
 use pyo3::types::{PyList, PyInt, PyFloat, PyString, PyBool};
 
-// ============================================================================
-// Phase 10.6: Fast ASCII String Extraction (duplicated from lib.rs for perf)
-// ============================================================================
-
-/// Simplified PyASCIIObject structure for fast ASCII detection
-#[repr(C)]
-struct PyASCIIObject {
-    _ob_refcnt: isize,
-    _ob_type: *mut ffi::PyTypeObject,
-    length: isize,
-    _hash: isize,
-    state: u32,
-}
-
-const STATE_ASCII_MASK: u32 = 0b01000000;
-
-#[cfg(target_pointer_width = "64")]
-const ASCII_DATA_OFFSET: usize = 48;
-
-#[cfg(target_pointer_width = "32")]
-const ASCII_DATA_OFFSET: usize = 24;
+use super::ascii_str;
+use crate::{BigIntMode, FloatRepr, NanMode};
 
 /// Fast string extraction - ASCII path avoids PyUnicode_AsUTF8AndSize overhead
 #[inline(always)]
 unsafe fn extract_string_fast(str_ptr: *mut ffi::PyObject) -> (*const u8, usize) {
-    let ascii_obj = str_ptr as *const PyASCIIObject;
-    let state = (*ascii_obj).state;
-
-    if state & STATE_ASCII_MASK != 0 {
+    if let Some(bytes) = ascii_str::ascii_data(str_ptr) {
         // FAST PATH: ASCII string - direct buffer access
-        let length = (*ascii_obj).length as usize;
-        let data_ptr = (str_ptr as *const u8).add(ASCII_DATA_OFFSET);
-        (data_ptr, length)
+        (bytes.as_ptr(), bytes.len())
     } else {
         // SLOW PATH: Non-ASCII - use PyUnicode_AsUTF8AndSize
         let mut size: ffi::Py_ssize_t = 0;
         let data_ptr = ffi::PyUnicode_AsUTF8AndSize(str_ptr, &mut size);
-        // Note: We assume data_ptr is not null here since caller verified it's a string
+        // Null, with an error set, for strings with lone surrogates
         (data_ptr as *const u8, size as usize)
     }
 }
@@ -63,6 +39,8 @@ pub enum ArrayType {
     AllStrings,
     /// All elements are booleans
     AllBools,
+    /// All elements are `decimal.Decimal`
+    AllDecimals,
     /// Mixed types or complex types (use normal path)
     Mixed,
     /// Empty array
@@ -80,10 +58,12 @@ const SAMPLE_SIZE: usize = 16;
 /// - Integers: Medium speed, moderate overhead → threshold=8
 /// - Floats: Close to orjson, moderate overhead → threshold=8
 /// - Strings: Slower, higher overhead → threshold=12
+/// - Decimals: str() dominates, but skipping generic dispatch still pays → threshold=8
 const MIN_BULK_SIZE_BOOL: usize = 4;
 const MIN_BULK_SIZE_INT: usize = 8;
 const MIN_BULK_SIZE_FLOAT: usize = 8;
 const MIN_BULK_SIZE_STRING: usize = 12;
+const MIN_BULK_SIZE_DECIMAL: usize = 8;
 
 /// Detect if a list contains all elements of the same type
 ///
@@ -131,6 +111,12 @@ pub fn detect_array_type(list: &Bound<'_, PyList>) -> ArrayType {
             ArrayType::AllStrings
         } else if first_type == bool_type {
             ArrayType::AllBools
+        } else if is_decimal_candidate(first_type)
+            && first_type == crate::type_cache::get_decimal_type(list.py())
+            // A registered handler replaces Decimal's own form
+            && crate::registry::lookup(first_type).is_none()
+        {
+            ArrayType::AllDecimals
         } else {
             return ArrayType::Mixed;
         };
@@ -141,6 +127,7 @@ pub fn detect_array_type(list: &Bound<'_, PyList>) -> ArrayType {
             ArrayType::AllInts => MIN_BULK_SIZE_INT,       // 8: moderate overhead
             ArrayType::AllFloats => MIN_BULK_SIZE_FLOAT,   // 8: close to orjson
             ArrayType::AllStrings => MIN_BULK_SIZE_STRING, // 12: higher overhead
+            ArrayType::AllDecimals => MIN_BULK_SIZE_DECIMAL, // 8: str() dominates
             _ => 8,  // Fallback (shouldn't reach here)
         };
 
@@ -149,8 +136,12 @@ pub fn detect_array_type(list: &Bound<'_, PyList>) -> ArrayType {
             return ArrayType::Mixed;
         }
 
+        // Decimal's str() would happily format any stray object, so check every
+        // element; the pointer compare is negligible next to str() itself
+        let check_count = if expected_array_type == ArrayType::AllDecimals { len } else { sample_count };
+
         // Check if all sampled elements match the expected type
-        for i in 1..sample_count {
+        for i in 1..check_count {
             let item_ptr = ffi::PyList_GET_ITEM(list_ptr, i as isize);
             let item_type = (*item_ptr).ob_type;
 
@@ -163,27 +154,48 @@ pub fn detect_array_type(list: &Bound<'_, PyList>) -> ArrayType {
     }
 }
 
+/// Cheap pre-filter before the Decimal type lookup
+///
+/// Lists of dicts/lists/None are by far the most common "mixed" case, so
+/// rule out the cached container types without touching `sys.modules`.
+#[inline(always)]
+fn is_decimal_candidate(type_ptr: *mut ffi::PyTypeObject) -> bool {
+    let cache = crate::type_cache::get_type_cache();
+    type_ptr != cache.dict_type
+        && type_ptr != cache.list_type
+        && type_ptr != cache.tuple_type
+        && type_ptr != cache.none_type
+}
+
 /// Bulk serialize an integer array directly to buffer
 ///
 /// Uses direct C API calls to extract integers without PyO3 overhead.
 /// Much faster than per-element serialization for large arrays.
 ///
+/// Returns `false`, leaving `buf` as it was, at an element that isn't an
+/// exact int: `detect_array_type` only samples the head of the list.
+///
 /// # Safety
-/// - Assumes all elements are PyInt (caller must verify with detect_array_type)
 /// - Uses PyList_GET_ITEM which returns borrowed references
 /// - No bounds checking (uses array length)
 ///
 /// # Arguments
-/// * `list` - Python list containing only integers
+/// * `list` - Python list detected as integers
 /// * `buf` - Buffer to write JSON to
+/// * `bigint_mode` - Whether ints beyond 2^53 are quoted
 ///
 /// # Performance
 /// - ~3-4x faster than per-element for large int arrays
 /// - Uses itoa for fast integer formatting
 /// - Phase 11: Uses PyLong_AsLongLongAndOverflow to avoid PyErr_Occurred() overhead
-pub unsafe fn serialize_int_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u8>) -> PyResult<()> {
+pub unsafe fn serialize_int_array_bulk(
+    list: &Bound<'_, PyList>,
+    buf: &mut Vec<u8>,
+    bigint_mode: BigIntMode,
+) -> PyResult<bool> {
     let list_ptr = list.as_ptr();
     let size = ffi::PyList_GET_SIZE(list_ptr);
+    let start = buf.len();
 
     // Reserve buffer space (estimate: 12 bytes per int on average)
     buf.reserve((size as usize) * 12);
@@ -198,6 +210,10 @@ pub unsafe fn serialize_int_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u
         }
 
         let item_ptr = ffi::PyList_GET_ITEM(list_ptr, i);
+        if (*item_ptr).ob_type != &raw mut ffi::PyLong_Type {
+            buf.truncate(start);
+            return Ok(false);
+        }
 
         // PHASE 11 OPTIMIZATION: Use PyLong_AsLongLongAndOverflow
         // This avoids the expensive PyErr_Occurred() call on every integer
@@ -206,14 +222,15 @@ pub unsafe fn serialize_int_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u
 
         if overflow == 0 {
             // Fast path: Value fits in i64 (most common case)
-            buf.extend_from_slice(itoa_buf.format(val_i64).as_bytes());
+            let digits = itoa_buf.format(val_i64).as_bytes();
+            crate::write_int_digits(buf, digits, crate::is_big_i64(val_i64), bigint_mode)?;
         } else {
             // Overflow - try u64 for large positive numbers
             let val_u64 = ffi::PyLong_AsUnsignedLongLong(item_ptr);
 
             if val_u64 != u64::MAX || ffi::PyErr_Occurred().is_null() {
                 ffi::PyErr_Clear();  // Clear any error from the check
-                buf.extend_from_slice(itoa_buf.format(val_u64).as_bytes());
+                crate::write_int_digits(buf, itoa_buf.format(val_u64).as_bytes(), true, bigint_mode)?;
             } else {
                 // Very large int - fall back to string representation
                 ffi::PyErr_Clear();
@@ -227,18 +244,21 @@ pub unsafe fn serialize_int_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u
                 let mut str_size: ffi::Py_ssize_t = 0;
                 let str_data = ffi::PyUnicode_AsUTF8AndSize(repr_ptr, &mut str_size);
 
-                if !str_data.is_null() {
+                let result = if str_data.is_null() {
+                    Ok(())
+                } else {
                     let str_slice = std::slice::from_raw_parts(str_data as *const u8, str_size as usize);
-                    buf.extend_from_slice(str_slice);
-                }
+                    crate::write_int_digits(buf, str_slice, true, bigint_mode)
+                };
 
                 ffi::Py_DECREF(repr_ptr);
+                result?;
             }
         }
     }
 
     buf.push(b']');
-    Ok(())
+    Ok(true)
 }
 
 
@@ -247,60 +267,73 @@ pub unsafe fn serialize_int_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u
 
 /// Bulk serialize a float array directly to buffer
 ///
+/// Formatting goes through `crate::write_finite_float` (and non-finite values
+/// through `crate::write_non_finite_float`) so `float_repr` and `nan_mode`
+/// produce the same output here as in the per-element path.
+///
+/// Returns `false`, leaving `buf` as it was, at an element that isn't an
+/// exact float (see `serialize_int_array_bulk`).
+///
 /// # Safety
-/// - Assumes all elements are PyFloat (caller must verify)
 /// - Uses direct C API without bounds checking
-pub unsafe fn serialize_float_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u8>) -> PyResult<()> {
+pub unsafe fn serialize_float_array_bulk(
+    list: &Bound<'_, PyList>,
+    buf: &mut Vec<u8>,
+    float_repr: FloatRepr,
+    nan_mode: NanMode,
+) -> PyResult<bool> {
     let list_ptr = list.as_ptr();
     let size = ffi::PyList_GET_SIZE(list_ptr);
+    let start = buf.len();
 
     // Reserve buffer space (estimate: 16 bytes per float)
     buf.reserve((size as usize) * 16);
 
     buf.push(b'[');
 
-    let mut ryu_buf = ryu::Buffer::new();
-
     for i in 0..size {
         if i > 0 {
             buf.push(b',');
         }
 
         let item_ptr = ffi::PyList_GET_ITEM(list_ptr, i);
-        let val = ffi::PyFloat_AsDouble(item_ptr);
+        if (*item_ptr).ob_type != &raw mut ffi::PyFloat_Type {
+            buf.truncate(start);
+            return Ok(false);
+        }
+        let val = ffi::PyFloat_AS_DOUBLE(item_ptr);
 
-        // Check for NaN/Infinity
+        // NaN/Infinity: raise or write per nan_mode
         if !val.is_finite() {
-            return Err(pyo3::exceptions::PyValueError::new_err(format!(
-                "Cannot serialize non-finite float: {}",
-                val
-            )));
+            crate::write_non_finite_float(buf, val, nan_mode)?;
+            continue;
         }
 
-        buf.extend_from_slice(ryu_buf.format(val).as_bytes());
+        crate::write_finite_float(buf, val, float_repr);
     }
 
     buf.push(b']');
-    Ok(())
+    Ok(true)
 }
 
-/// Bulk serialize a boolean array directly to buffer
+/// Bulk serialize a `decimal.Decimal` array directly to buffer
+///
+/// Writes each element's exact `str()` form via `crate::write_decimal`,
+/// rejecting NaN/Infinity per element.
 ///
 /// # Safety
-/// - Assumes all elements are PyBool (caller must verify)
-/// - Uses direct C API without bounds checking
-pub unsafe fn serialize_bool_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u8>) -> PyResult<()> {
+/// - Assumes all elements are Decimal (detect_array_type checks every element
+///   for this type, not just the sample, so there is no fallback here)
+/// - Uses PyList_GET_ITEM which returns borrowed references
+pub unsafe fn serialize_decimal_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u8>) -> PyResult<()> {
     let list_ptr = list.as_ptr();
     let size = ffi::PyList_GET_SIZE(list_ptr);
 
-    // Reserve buffer space (5 bytes per bool max: "false")
-    buf.reserve((size as usize) * 5 + 2);
+    // Reserve buffer space (estimate: 16 bytes per decimal)
+    buf.reserve((size as usize) * 16);
 
     buf.push(b'[');
 
-    // Get True singleton pointer for comparison
-    let true_ptr = PyBool::new(list.py(), true).as_ptr();
-
     for i in 0..size {
         if i > 0 {
             buf.push(b',');
@@ -308,16 +341,149 @@ pub unsafe fn serialize_bool_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<
 
         let item_ptr = ffi::PyList_GET_ITEM(list_ptr, i);
 
-        // Fast bool check: compare pointer with True singleton
-        if item_ptr == true_ptr {
-            buf.extend_from_slice(b"true");
+        crate::write_decimal(buf, item_ptr)?;
+    }
+
+    buf.push(b']');
+    Ok(())
+}
+
+/// Minimum bool array length for the AVX2 path (below this the scalar loop wins)
+#[cfg(target_arch = "x86_64")]
+const BOOL_SIMD_THRESHOLD: usize = 32;
+
+/// Output for 4 bools, indexed by the 4-bit "is True" mask of a chunk.
+/// Each entry holds the bytes (with trailing commas) and their length.
+#[cfg(target_arch = "x86_64")]
+static BOOL_CHUNK_LUT: [([u8; 24], u8); 16] = build_bool_chunk_lut();
+
+#[cfg(target_arch = "x86_64")]
+const fn build_bool_chunk_lut() -> [([u8; 24], u8); 16] {
+    let mut lut = [([0u8; 24], 0u8); 16];
+    let mut mask = 0;
+    while mask < 16 {
+        let mut len = 0;
+        let mut bit = 0;
+        while bit < 4 {
+            let word: &[u8] = if mask & (1 << bit) != 0 { b"true," } else { b"false," };
+            let mut k = 0;
+            while k < word.len() {
+                lut[mask].0[len] = word[k];
+                len += 1;
+                k += 1;
+            }
+            bit += 1;
+        }
+        lut[mask].1 = len as u8;
+        mask += 1;
+    }
+    lut
+}
+
+/// Bulk serialize a boolean array directly to buffer
+///
+/// `True`/`False` are singletons, so the array is just a sequence of two
+/// pointer values. On AVX2 the list's item pointers are compared four at a
+/// time against the `True` pointer, and the resulting 4-bit mask selects the
+/// output from `BOOL_CHUNK_LUT`. Short arrays and non-x86 use the scalar loop.
+///
+/// Returns `false`, leaving `buf` as it was, at an element that is neither
+/// `True` nor `False` (see `serialize_int_array_bulk`).
+///
+/// # Safety
+/// - Uses direct C API without bounds checking
+pub unsafe fn serialize_bool_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<u8>) -> bool {
+    let list_ptr = list.as_ptr();
+    let size = ffi::PyList_GET_SIZE(list_ptr) as usize;
+    let start = buf.len();
+
+    // Reserve buffer space (6 bytes per bool max: "false,")
+    buf.reserve(size * 6 + 2);
+
+    buf.push(b'[');
+
+    if size > 0 {
+        // Get the singleton pointers for comparison
+        let true_ptr = PyBool::new(list.py(), true).as_ptr();
+        let false_ptr = PyBool::new(list.py(), false).as_ptr();
+        // SAFETY: ob_item holds `size` valid item pointers for an exact list
+        let items = std::slice::from_raw_parts((*(list_ptr as *mut ffi::PyListObject)).ob_item, size);
+
+        #[cfg(target_arch = "x86_64")]
+        let all_bools = if size >= BOOL_SIMD_THRESHOLD && super::simd_escape::get_cpu_feature_level() == 2 {
+            write_bools_avx2(buf, items, true_ptr, false_ptr)
         } else {
-            buf.extend_from_slice(b"false");
+            write_bools_scalar(buf, items, true_ptr, false_ptr)
+        };
+
+        #[cfg(not(target_arch = "x86_64"))]
+        let all_bools = write_bools_scalar(buf, items, true_ptr, false_ptr);
+
+        if !all_bools {
+            buf.truncate(start);
+            return false;
         }
+
+        // Drop the trailing comma
+        buf.pop();
     }
 
     buf.push(b']');
-    Ok(())
+    true
+}
+
+/// Write each bool followed by a comma (scalar fallback); `false` at the
+/// first item that is neither singleton
+#[inline]
+fn write_bools_scalar(
+    buf: &mut Vec<u8>,
+    items: &[*mut ffi::PyObject],
+    true_ptr: *mut ffi::PyObject,
+    false_ptr: *mut ffi::PyObject,
+) -> bool {
+    for &item_ptr in items {
+        // Fast bool check: compare pointer with the singletons
+        if item_ptr == true_ptr {
+            buf.extend_from_slice(b"true,");
+        } else if item_ptr == false_ptr {
+            buf.extend_from_slice(b"false,");
+        } else {
+            return false;
+        }
+    }
+    true
+}
+
+/// Write each bool followed by a comma, comparing 4 item pointers per step;
+/// `false` at the first chunk holding an item that is neither singleton
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn write_bools_avx2(
+    buf: &mut Vec<u8>,
+    items: &[*mut ffi::PyObject],
+    true_ptr: *mut ffi::PyObject,
+    false_ptr: *mut ffi::PyObject,
+) -> bool {
+    use std::arch::x86_64::*;
+
+    let true_needle = _mm256_set1_epi64x(true_ptr as i64);
+    let false_needle = _mm256_set1_epi64x(false_ptr as i64);
+    let chunks = items.chunks_exact(4);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let ptrs = _mm256_loadu_si256(chunk.as_ptr() as *const __m256i);
+        let is_true = _mm256_cmpeq_epi64(ptrs, true_needle);
+        let is_bool = _mm256_or_si256(is_true, _mm256_cmpeq_epi64(ptrs, false_needle));
+        if _mm256_movemask_pd(_mm256_castsi256_pd(is_bool)) != 0b1111 {
+            return false;
+        }
+        let mask = _mm256_movemask_pd(_mm256_castsi256_pd(is_true)) as usize;
+        let (bytes, len) = &BOOL_CHUNK_LUT[mask];
+        buf.extend_from_slice(&bytes[..*len as usize]);
+    }
+
+    write_bools_scalar(buf, remainder, true_ptr, false_ptr)
 }
 
 /// Bulk serialize a string array directly to buffer
@@ -325,16 +491,19 @@ pub unsafe fn serialize_bool_array_bulk(list: &Bound<'_, PyList>, buf: &mut Vec<
 /// Uses zero-copy UTF-8 extraction and SIMD-optimized escape detection.
 /// PHASE 10.6: ASCII strings use fast path avoiding PyUnicode_AsUTF8AndSize overhead.
 ///
+/// Returns `false`, leaving `buf` as it was, at an element that isn't an
+/// exact str (see `serialize_int_array_bulk`).
+///
 /// # Safety
-/// - Assumes all elements are PyString (caller must verify)
 /// - Uses direct C API without bounds checking
 pub unsafe fn serialize_string_array_bulk(
     list: &Bound<'_, PyList>,
     buf: &mut Vec<u8>,
     write_string_fn: impl Fn(&mut Vec<u8>, &str)
-) -> PyResult<()> {
+) -> PyResult<bool> {
     let list_ptr = list.as_ptr();
     let size = ffi::PyList_GET_SIZE(list_ptr);
+    let start = buf.len();
 
     // Reserve buffer space (estimate: 20 bytes per string average)
     buf.reserve((size as usize) * 20);
@@ -347,12 +516,18 @@ pub unsafe fn serialize_string_array_bulk(
         }
 
         let item_ptr = ffi::PyList_GET_ITEM(list_ptr, i);
+        if (*item_ptr).ob_type != &raw mut ffi::PyUnicode_Type {
+            buf.truncate(start);
+            return Ok(false);
+        }
 
         // PHASE 10.6: Fast ASCII path avoids PyUnicode_AsUTF8AndSize overhead
         let (str_data, str_size) = extract_string_fast(item_ptr);
 
         if str_data.is_null() {
-            return Err(pyo3::exceptions::PyValueError::new_err("String must be valid UTF-8"));
+            // Lone surrogates - raise the UnicodeEncodeError, as dumps does
+            buf.truncate(start);
+            return Err(PyErr::fetch(list.py()));
         }
 
         // SAFETY: Python guarantees UTF-8 validity for PyUnicode objects
@@ -364,7 +539,7 @@ pub unsafe fn serialize_string_array_bulk(
     }
 
     buf.push(b']');
-    Ok(())
+    Ok(true)
 }
 
 
@@ -377,23 +552,23 @@ mod tests {
     fn test_detect_array_type() {
         Python::with_gil(|py| {
             // All ints
-            let ints = PyList::new(py, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]).unwrap();
+            let ints = PyList::new(py, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]).unwrap();
             assert_eq!(detect_array_type(&ints), ArrayType::AllInts);
 
             // All floats
-            let floats = PyList::new(py, &[1.1, 2.2, 3.3, 4.4, 5.5, 6.6, 7.7, 8.8]).unwrap();
+            let floats = PyList::new(py, [1.1, 2.2, 3.3, 4.4, 5.5, 6.6, 7.7, 8.8]).unwrap();
             assert_eq!(detect_array_type(&floats), ArrayType::AllFloats);
 
             // All strings
-            let strings = PyList::new(py, &["a", "b", "c", "d", "e", "f", "g", "h"]).unwrap();
+            let strings = PyList::new(py, ["a", "b", "c", "d", "e", "f", "g", "h"]).unwrap();
             assert_eq!(detect_array_type(&strings), ArrayType::AllStrings);
 
             // All bools
-            let bools = PyList::new(py, &[true, false, true, false, true, false, true, false]).unwrap();
+            let bools = PyList::new(py, [true, false, true, false, true, false, true, false]).unwrap();
             assert_eq!(detect_array_type(&bools), ArrayType::AllBools);
 
             // Mixed
-            let mixed = PyList::new(py, &[1.to_object(py), "a".to_object(py), 2.to_object(py)]).unwrap();
+            let mixed = PyList::new(py, &[PyInt::new(py, 1).into_any(), PyString::new(py, "a").into_any(), PyInt::new(py, 2).into_any()]).unwrap();
             assert_eq!(detect_array_type(&mixed), ArrayType::Mixed);
 
             // Empty
@@ -401,7 +576,7 @@ mod tests {
             assert_eq!(detect_array_type(&empty), ArrayType::Empty);
 
             // Too small (below MIN_BULK_SIZE)
-            let small = PyList::new(py, &[1, 2, 3]).unwrap();
+            let small = PyList::new(py, [1, 2, 3]).unwrap();
             assert_eq!(detect_array_type(&small), ArrayType::Mixed);
         });
     }
@@ -409,11 +584,11 @@ mod tests {
     #[test]
     fn test_serialize_int_array_bulk() {
         Python::with_gil(|py| {
-            let ints = PyList::new(py, &[1, 2, 3, 42, 100, -5, 999, 0, 1234567890]).unwrap();
+            let ints = PyList::new(py, [1, 2, 3, 42, 100, -5, 999, 0, 1234567890]).unwrap();
             let mut buf = Vec::new();
 
             unsafe {
-                serialize_int_array_bulk(&ints, &mut buf).unwrap();
+                assert!(serialize_int_array_bulk(&ints, &mut buf, BigIntMode::Number).unwrap());
             }
 
             let json = String::from_utf8(buf).unwrap();
@@ -424,31 +599,82 @@ mod tests {
     #[test]
     fn test_serialize_float_array_bulk() {
         Python::with_gil(|py| {
-            let floats = PyList::new(py, &[1.5, 2.7, 3.14, -0.5]).unwrap();
+            let floats = PyList::new(py, [1.5, 2.7, 3.25, -0.5]).unwrap();
             let mut buf = Vec::new();
 
             unsafe {
-                serialize_float_array_bulk(&floats, &mut buf).unwrap();
+                assert!(serialize_float_array_bulk(&floats, &mut buf, FloatRepr::Ryu, NanMode::Error).unwrap());
             }
 
             let json = String::from_utf8(buf).unwrap();
             // Note: ryu may format floats slightly differently
-            assert!(json.starts_with("[1.5,2.7,3.14,-0.5]"));
+            assert!(json.starts_with("[1.5,2.7,3.25,-0.5]"));
         });
     }
 
     #[test]
     fn test_serialize_bool_array_bulk() {
         Python::with_gil(|py| {
-            let bools = PyList::new(py, &[true, false, true, true, false]).unwrap();
+            let bools = PyList::new(py, [true, false, true, true, false]).unwrap();
             let mut buf = Vec::new();
 
             unsafe {
-                serialize_bool_array_bulk(&bools, &mut buf).unwrap();
+                assert!(serialize_bool_array_bulk(&bools, &mut buf));
             }
 
             let json = String::from_utf8(buf).unwrap();
             assert_eq!(json, "[true,false,true,true,false]");
         });
     }
+
+    #[test]
+    fn test_serialize_bool_array_bulk_simd_lengths() {
+        Python::with_gil(|py| {
+            // Cover the AVX2 threshold and every chunk remainder
+            for len in [31, 32, 33, 34, 35, 100] {
+                let values: Vec<bool> = (0..len).map(|i| i % 3 == 0).collect();
+                let bools = PyList::new(py, &values).unwrap();
+                let mut buf = Vec::new();
+
+                unsafe {
+                    assert!(serialize_bool_array_bulk(&bools, &mut buf));
+                }
+
+                let expected: Vec<&str> = values.iter().map(|&b| if b { "true" } else { "false" }).collect();
+                assert_eq!(String::from_utf8(buf).unwrap(), format!("[{}]", expected.join(",")));
+            }
+        });
+    }
+
+    #[test]
+    fn test_bulk_rejects_unsampled_element() {
+        Python::with_gil(|py| {
+            // Past detect_array_type's sample, in the AVX2 chunks and the remainder
+            for at in [20, 33, 34] {
+                let mut items: Vec<Bound<'_, PyAny>> = (0..35).map(|i| PyBool::new(py, i % 2 == 0).to_owned().into_any()).collect();
+                items[at] = PyInt::new(py, 1).into_any();
+                let mut buf = b"x".to_vec();
+                assert!(!unsafe { serialize_bool_array_bulk(&PyList::new(py, &items).unwrap(), &mut buf) });
+                assert_eq!(buf, b"x");
+            }
+
+            let mut items: Vec<Bound<'_, PyAny>> = (0..40).map(|i| PyInt::new(py, i).into_any()).collect();
+            items.push(PyString::new(py, "zz").into_any());
+            let mixed = PyList::new(py, &items).unwrap();
+            let mut buf = b"x".to_vec();
+            assert!(!unsafe { serialize_int_array_bulk(&mixed, &mut buf, BigIntMode::Number).unwrap() });
+            assert_eq!(buf, b"x");
+
+            items.reverse();
+            let mixed = PyList::new(py, &items).unwrap();
+            assert!(!unsafe { serialize_string_array_bulk(&mixed, &mut buf, |buf, s| buf.extend_from_slice(s.as_bytes())).unwrap() });
+            assert_eq!(buf, b"x");
+
+            let mut items: Vec<Bound<'_, PyAny>> = (0..40).map(|i| PyFloat::new(py, i as f64).into_any()).collect();
+            items.push(PyInt::new(py, 3).into_any());
+            let mixed = PyList::new(py, &items).unwrap();
+            assert!(!unsafe { serialize_float_array_bulk(&mixed, &mut buf, FloatRepr::Ryu, NanMode::Error).unwrap() });
+            assert_eq!(buf, b"x");
+        });
+    }
 }
@@ -22,23 +22,49 @@ use std::ptr;
 pub struct DirectSerializer {
     buf: Vec<u8>,
     py: Python<'static>,
+    /// Lists and dicts being serialized, so a cycle raises instead of
+    /// overflowing the stack
+    markers: Vec<usize>,
 }
 
 impl DirectSerializer {
+    /// Serializer appending to `buf`, normally a pooled buffer from
+    /// `object_cache::get_serialize_buffer`
     #[inline(always)]
-    pub unsafe fn new(py: Python<'static>, capacity: usize) -> Self {
+    pub unsafe fn new(py: Python<'static>, buf: Vec<u8>) -> Self {
         Self {
-            buf: Vec::with_capacity(capacity),
+            buf,
             py,
+            markers: Vec::new(),
         }
     }
 
+    /// Serialize `obj`, falling back to `dumps` with its defaults
+    ///
+    /// The direct path never runs Python code, so nothing `obj` holds can
+    /// change under it. When it meets a type that would need to (subclasses,
+    /// enums, paths, `__json__`, ...), its partial output is dropped and the
+    /// whole value is serialized again by `dumps`, which copes with lists and
+    /// dicts changed mid-call. No user code has run by then, so none runs twice.
+    pub fn serialize(&mut self, obj: &Bound<'_, PyAny>) -> PyResult<()> {
+        let start = self.buf.len();
+        if !unsafe { self.serialize_direct(obj.as_ptr()) }? {
+            self.buf.truncate(start);
+            self.markers.clear();
+            crate::serialize_with_defaults(&mut self.buf, obj)?;
+        }
+        Ok(())
+    }
+
     /// Serialize any Python object using direct C API
     ///
     /// This is a single massive function with everything inlined.
     /// Similar to orjson's approach - no function call overhead.
+    ///
+    /// Returns `false`, with partial output, at the first value it can't
+    /// write without running Python code.
     #[inline(always)]
-    pub unsafe fn serialize_direct(&mut self, obj: *mut ffi::PyObject) -> PyResult<()> {
+    unsafe fn serialize_direct(&mut self, obj: *mut ffi::PyObject) -> PyResult<bool> {
         let obj_type = (*obj).ob_type;
 
         // Use the already-initialized type cache from type_cache.rs
@@ -75,15 +101,30 @@ impl DirectSerializer {
             self.serialize_string_inline(obj)?;
         } else if obj_type == list_type {
             // List - inline iteration
-            self.serialize_list_inline(obj)?;
+            return self.serialize_list_inline(obj);
         } else if obj_type == dict_type {
             // Dict - inline iteration
-            self.serialize_dict_inline(obj)?;
+            return self.serialize_dict_inline(obj);
+        } else if crate::registry::lookup(obj_type).is_some() {
+            // Registered handlers may run Python code, and win over the
+            // stdlib types below - left to dumps
+            return Ok(false);
+        } else if obj_type == type_cache::get_decimal_type(self.py) {
+            // Decimal - exact str() form, shared with dumps
+            crate::write_decimal(&mut self.buf, obj)?;
+        } else if obj_type == type_cache::get_uuid_type(self.py) {
+            // UUID - canonical hyphenated string, shared with dumps
+            crate::write_uuid(&mut self.buf, &Bound::from_borrowed_ptr(self.py, obj))?;
+        } else if let Some(kind) = crate::temporal_kind(self.py, obj_type) {
+            // datetime/date/time - isoformat() text, shared with dumps
+            crate::write_temporal(&mut self.buf, &Bound::from_borrowed_ptr(self.py, obj), kind, false)?;
         } else {
-            return Err(pyo3::exceptions::PyTypeError::new_err("Unsupported type"));
+            // Everything else (subclasses, enums, tuples, paths, ...) -
+            // left to dumps, including its error for unsupported types
+            return Ok(false);
         }
 
-        Ok(())
+        Ok(true)
     }
 
     #[inline(always)]
@@ -171,11 +212,7 @@ impl DirectSerializer {
     unsafe fn serialize_float_inline(&mut self, obj: *mut ffi::PyObject) -> PyResult<()> {
         let val = ffi::PyFloat_AsDouble(obj);
 
-        if !val.is_finite() {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Cannot serialize non-finite float"
-            ));
-        }
+        crate::check_finite_float(val)?;
 
         // Use ryu for fast float formatting
         let mut ryu_buf = ryu::Buffer::new();
@@ -190,7 +227,8 @@ impl DirectSerializer {
         let str_data = ffi::PyUnicode_AsUTF8AndSize(obj, &mut size);
 
         if str_data.is_null() {
-            return Err(pyo3::exceptions::PyValueError::new_err("Invalid UTF-8"));
+            // Lone surrogates - the UnicodeEncodeError, as from dumps
+            return Err(PyErr::fetch(self.py));
         }
 
         let bytes = std::slice::from_raw_parts(str_data as *const u8, size as usize);
@@ -200,11 +238,9 @@ impl DirectSerializer {
         // SIMD escape detection (if available)
         #[cfg(target_arch = "x86_64")]
         {
-            if is_x86_feature_detected!("avx2") {
-                if self.serialize_string_simd_avx2(bytes) {
-                    self.buf.push(b'"');
-                    return Ok(());
-                }
+            if super::simd_escape::get_cpu_feature_level() == 2 && self.serialize_string_simd_avx2(bytes) {
+                self.buf.push(b'"');
+                return Ok(());
             }
         }
 
@@ -309,9 +345,10 @@ impl DirectSerializer {
     }
 
     #[inline(always)]
-    unsafe fn serialize_list_inline(&mut self, obj: *mut ffi::PyObject) -> PyResult<()> {
+    unsafe fn serialize_list_inline(&mut self, obj: *mut ffi::PyObject) -> PyResult<bool> {
         let size = ffi::PyList_GET_SIZE(obj);
 
+        self.enter_container(obj)?;
         self.buf.push(b'[');
 
         for i in 0..size {
@@ -320,15 +357,19 @@ impl DirectSerializer {
             }
 
             let item = ffi::PyList_GET_ITEM(obj, i);
-            self.serialize_direct(item)?;
+            if !self.serialize_direct(item)? {
+                return Ok(false);
+            }
         }
 
         self.buf.push(b']');
-        Ok(())
+        self.markers.pop();
+        Ok(true)
     }
 
     #[inline(always)]
-    unsafe fn serialize_dict_inline(&mut self, obj: *mut ffi::PyObject) -> PyResult<()> {
+    unsafe fn serialize_dict_inline(&mut self, obj: *mut ffi::PyObject) -> PyResult<bool> {
+        self.enter_container(obj)?;
         self.buf.push(b'{');
 
         let mut pos: ffi::Py_ssize_t = 0;
@@ -342,36 +383,52 @@ impl DirectSerializer {
             }
             first = false;
 
-            // Serialize key (must be string)
+            // Serialize key (non-str keys are coerced like `dumps` does)
             if ffi::PyUnicode_Check(key) == 0 {
-                return Err(pyo3::exceptions::PyTypeError::new_err(
-                    "Dictionary keys must be strings"
-                ));
+                let key = Bound::from_borrowed_ptr(self.py, key);
+                let coerced = crate::coerce_dict_key(&key, crate::FloatRepr::Ryu, crate::NanMode::Error)?;
+                self.serialize_string_inline(coerced.as_ptr())?;
+            } else {
+                self.serialize_string_inline(key)?;
             }
-
-            self.serialize_string_inline(key)?;
             self.buf.push(b':');
-            self.serialize_direct(value)?;
+            if !self.serialize_direct(value)? {
+                return Ok(false);
+            }
         }
 
         self.buf.push(b'}');
-        Ok(())
+        self.markers.pop();
+        Ok(true)
     }
 
+    /// Mark a list or dict as being serialized, failing if it already is
     #[inline(always)]
-    pub fn into_pybytes(self, py: Python) -> Py<PyBytes> {
-        // Zero-copy conversion to PyBytes
-        unsafe {
-            let bytes_ptr = ffi::PyBytes_FromStringAndSize(
-                self.buf.as_ptr() as *const i8,
-                self.buf.len() as ffi::Py_ssize_t,
-            );
-
-            // Transfer ownership to Python
-            std::mem::forget(self.buf);
-
-            Py::from_owned_ptr(py, bytes_ptr)
+    fn enter_container(&mut self, obj: *mut ffi::PyObject) -> PyResult<()> {
+        let marker = obj as usize;
+        if self.markers.contains(&marker) {
+            return Err(pyo3::exceptions::PyValueError::new_err("Circular reference detected"));
         }
+        self.markers.push(marker);
+        Ok(())
+    }
+
+    /// Append a `\n` after the serialized value (`dumps_bytes(append_newline=True)`)
+    #[inline(always)]
+    pub fn push_newline(&mut self) {
+        self.buf.push(b'\n');
+    }
+
+    /// Copy the output into a new `bytes` object
+    #[inline(always)]
+    pub fn to_pybytes(&self, py: Python) -> Py<PyBytes> {
+        PyBytes::new(py, &self.buf).unbind()
+    }
+
+    /// The buffer back, with its capacity, for reuse
+    #[inline(always)]
+    pub fn into_buffer(self) -> Vec<u8> {
+        self.buf
     }
 }
 
@@ -390,9 +447,8 @@ pub unsafe fn estimate_size_fast(obj: *mut ffi::PyObject) -> usize {
     } else if ffi::PyFloat_Check(obj) != 0 {
         24  // Max f64 representation
     } else if ffi::PyUnicode_Check(obj) != 0 {
-        let mut size: ffi::Py_ssize_t = 0;
-        ffi::PyUnicode_AsUTF8AndSize(obj, &mut size);
-        (size as usize) + 8  // String + quotes + escapes
+        // Characters, not UTF-8 bytes: encoding here could fail and leave an error set
+        (ffi::PyUnicode_GET_LENGTH(obj) as usize) + 8  // String + quotes + escapes
     } else if ffi::PyList_Check(obj) != 0 {
         let len = ffi::PyList_GET_SIZE(obj);
         (len as usize) * 16 + 16  // Heuristic
@@ -412,7 +468,7 @@ mod tests {
     fn test_format_i64_inline() {
         Python::with_gil(|py| {
             let py_static = unsafe { std::mem::transmute::<Python, Python<'static>>(py) };
-            let mut ser = unsafe { DirectSerializer::new(py_static, 64) };
+            let mut ser = unsafe { DirectSerializer::new(py_static, Vec::with_capacity(64)) };
 
             ser.format_i64_inline(0);
             assert_eq!(std::str::from_utf8(&ser.buf).unwrap(), "0");
@@ -0,0 +1,206 @@
+//! Fast path for documents that are a flat array of integers
+//!
+//! `[1, 2, 3, ...]` documents (ids, counters, time series) spend most of a
+//! serde parse on one number at a time. This module parses such an array
+//! directly:
+//! - SSE2 finds the end of each digit run 16 bytes at a time
+//! - Runs of 8 digits are converted with one SWAR multiply sequence, as in
+//!   simdjson's `parse_eight_digits_unrolled`
+//! - The list is allocated at its final size and filled with the cached
+//!   small ints `loads` uses
+//!
+//! Anything else (floats, exponents, `-0`, integers outside i64, nesting,
+//! malformed input) returns `None` and the caller parses normally, so results
+//! and errors are exactly those of the serde path.
+
+use pyo3::ffi;
+use pyo3::prelude::*;
+
+use super::object_cache;
+
+/// Longest digit run converted here; 19 digits always fit in a u64
+const MAX_DIGITS: usize = 19;
+
+#[inline(always)]
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+#[inline(always)]
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && is_whitespace(bytes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Number of ASCII digits starting at `pos` (stops counting past `MAX_DIGITS`)
+#[inline(always)]
+fn digit_run(bytes: &[u8], pos: usize) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if pos + 16 <= bytes.len() {
+            // SAFETY: SSE2 is part of the x86_64 baseline; 16 bytes are in bounds
+            let run = unsafe { digit_run_sse2(bytes.as_ptr().add(pos)) };
+            if run < 16 || pos + 32 > bytes.len() {
+                return if run < 16 { run } else { 16 + digit_run_scalar(&bytes[pos + 16..]) };
+            }
+            // SAFETY: as above, for the next 16 bytes
+            return 16 + unsafe { digit_run_sse2(bytes.as_ptr().add(pos + 16)) };
+        }
+    }
+    digit_run_scalar(&bytes[pos..])
+}
+
+#[inline(always)]
+fn digit_run_scalar(bytes: &[u8]) -> usize {
+    bytes.iter().take(MAX_DIGITS + 1).take_while(|b| b.is_ascii_digit()).count()
+}
+
+/// Leading ASCII digits among the 16 bytes at `ptr`
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn digit_run_sse2(ptr: *const u8) -> usize {
+    use std::arch::x86_64::*;
+
+    let chunk = _mm_loadu_si128(ptr as *const __m128i);
+    // b - '0' <= 9 as unsigned bytes, i.e. min(b - '0', 9) == b - '0'
+    let shifted = _mm_sub_epi8(chunk, _mm_set1_epi8(b'0' as i8));
+    let is_digit = _mm_cmpeq_epi8(_mm_min_epu8(shifted, _mm_set1_epi8(9)), shifted);
+    let digits = _mm_movemask_epi8(is_digit) as u32;
+    (!digits).trailing_zeros().min(16) as usize
+}
+
+/// Value of 8 ASCII digits, first digit in the lowest byte
+#[inline(always)]
+fn parse_eight_digits(digits: &[u8]) -> u64 {
+    let mut val = u64::from_le_bytes(digits[..8].try_into().unwrap());
+    val = val.wrapping_sub(0x3030_3030_3030_3030);
+    // Pairs of digits into bytes, then pairs of pairs into 16-bit lanes
+    val = val.wrapping_mul(10).wrapping_add(val >> 8);
+    const MASK: u64 = 0x0000_00FF_0000_00FF;
+    const MUL1: u64 = 100 + (1_000_000 << 32);
+    const MUL2: u64 = 1 + (10_000 << 32);
+    ((val & MASK).wrapping_mul(MUL1).wrapping_add(((val >> 16) & MASK).wrapping_mul(MUL2))) >> 32
+}
+
+/// Value of an all-digit run of at most `MAX_DIGITS`
+#[inline(always)]
+fn parse_digits(mut digits: &[u8]) -> u64 {
+    let mut value = 0u64;
+    while digits.len() >= 8 {
+        value = value * 100_000_000 + parse_eight_digits(digits);
+        digits = &digits[8..];
+    }
+    for &b in digits {
+        value = value * 10 + u64::from(b - b'0');
+    }
+    value
+}
+
+/// The integers of a document that is exactly a flat JSON array of
+/// integers, or `None` if it's anything else
+pub fn parse_int_array(bytes: &[u8]) -> Option<Vec<i64>> {
+    let mut pos = skip_whitespace(bytes, 0);
+    if bytes.get(pos) != Some(&b'[') {
+        return None;
+    }
+    pos = skip_whitespace(bytes, pos + 1);
+
+    // Only allocate once the array looks like integers: any document
+    // starting with `[` gets here, and a size-based reservation would cost
+    // several times the input for `[{...}, ...]`
+    let mut values = Vec::new();
+    match bytes.get(pos) {
+        Some(b'0'..=b'9' | b'-' | b']') => {}
+        _ => return None,
+    }
+    if bytes.get(pos) != Some(&b']') {
+        loop {
+            let negative = bytes.get(pos) == Some(&b'-');
+            pos += usize::from(negative);
+
+            let len = digit_run(bytes, pos);
+            // Leading zeros are invalid; `-0` parses as a float in `loads`
+            if len == 0 || len > MAX_DIGITS || (bytes[pos] == b'0' && (len > 1 || negative)) {
+                return None;
+            }
+            let magnitude = parse_digits(&bytes[pos..pos + len]);
+            let value = match negative {
+                // -2^63 has no positive i64 counterpart, hence the wrapping
+                true if magnitude <= 1 << 63 => (magnitude as i64).wrapping_neg(),
+                false if magnitude <= i64::MAX as u64 => magnitude as i64,
+                _ => return None,
+            };
+            values.push(value);
+            pos += len;
+
+            pos = skip_whitespace(bytes, pos);
+            match bytes.get(pos) {
+                Some(b',') => pos = skip_whitespace(bytes, pos + 1),
+                Some(b']') => break,
+                // `.`, `e`/`E`, `{` etc: not this fast path's job
+                _ => return None,
+            }
+        }
+    }
+
+    (skip_whitespace(bytes, pos + 1) == bytes.len()).then_some(values)
+}
+
+/// A Python list of `values`, with ints created as `loads` creates them
+pub fn int_list(py: Python, values: &[i64]) -> PyResult<PyObject> {
+    // SAFETY: the list is created at its final size and every slot is set
+    // exactly once before it's returned
+    unsafe {
+        let list = ffi::PyList_New(values.len() as ffi::Py_ssize_t);
+        if list.is_null() {
+            return Err(PyErr::fetch(py));
+        }
+        for (i, &value) in values.iter().enumerate() {
+            let item = object_cache::get_int(py, value).into_ptr();
+            ffi::PyList_SET_ITEM(list, i as ffi::Py_ssize_t, item);
+        }
+        Ok(PyObject::from_owned_ptr(py, list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_eight_digits() {
+        assert_eq!(parse_eight_digits(b"12345678"), 12_345_678);
+        assert_eq!(parse_eight_digits(b"00000000"), 0);
+        assert_eq!(parse_eight_digits(b"99999999"), 99_999_999);
+        assert_eq!(parse_digits(b"9999999999999999999"), 9_999_999_999_999_999_999);
+        assert_eq!(parse_digits(b"1234567890123"), 1_234_567_890_123);
+    }
+
+    #[test]
+    fn test_digit_run() {
+        let doc = b"[1234567890123456789012345, 7]";
+        assert!(digit_run(doc, 1) > MAX_DIGITS);
+        assert_eq!(digit_run(doc, 28), 1);
+        assert_eq!(digit_run(b"12345678901234567x", 0), 17);
+        assert_eq!(digit_run(b"x", 0), 0);
+    }
+
+    #[test]
+    fn test_parse_int_array() {
+        assert_eq!(parse_int_array(b" [ 1 , -22,333 ]\n"), Some(vec![1, -22, 333]));
+        assert_eq!(parse_int_array(b"[]"), Some(vec![]));
+        assert_eq!(
+            parse_int_array(b"[9223372036854775807,-9223372036854775808]"),
+            Some(vec![i64::MAX, i64::MIN])
+        );
+        for doc in [
+            &b"[1.5]"[..], b"[1e3]", b"[-0]", b"[01]", b"[1,]", b"[,1]", b"[1 2]", b"[1]x",
+            b"[1", b"[[1]]", b"[-]", b"[9223372036854775808]", b"[-9223372036854775809]",
+            b"[12345678901234567890]", b"{}", b"", b"[{\"k\": 1}]", b"[ \"1\"]",
+        ] {
+            assert_eq!(parse_int_array(doc), None, "{}", String::from_utf8_lossy(doc));
+        }
+    }
+}
@@ -1,7 +1,7 @@
-/// Performance optimizations module
-///
-/// This module contains various optimization strategies to improve
-/// JSON serialization/deserialization performance.
+//! Performance optimizations module
+//!
+//! This module contains various optimization strategies to improve
+//! JSON serialization/deserialization performance.
 
 pub mod object_cache;
 pub mod type_cache;
@@ -10,6 +10,8 @@ pub mod extreme;
 pub mod escape_lut;
 pub mod simd_parser;
 pub mod simd_escape;
+pub mod ascii_str;
+pub mod int_array;
 
 /// Branch prediction hints for performance-critical code paths
 ///
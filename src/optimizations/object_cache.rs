@@ -3,25 +3,48 @@
 //! This module implements caching strategies to reduce GIL overhead and
 //! Python object allocation costs. Key optimizations:
 //!
-//! 1. Integer caching for small values [-256, 256]
+//! 1. Integer caching for small values [-256, 256] (range configurable)
 //! 2. Singleton caching for None, True, False
 //! 3. Phase 13: Direct C API object creation (bypasses PyO3 overhead)
 //! 4. Phase 14: Thread-local buffer reuse
 
 use pyo3::prelude::*;
 use pyo3::ffi;
+use pyo3::types::PyBool;
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicPtr, Ordering};
 use std::cell::RefCell;
 
-/// Size of the integer cache (range: -256 to 256)
-const INT_CACHE_SIZE: usize = 513;
-const INT_CACHE_OFFSET: i64 = 256;
+/// Default integer cache bound (range: -256 to 256)
+pub const DEFAULT_INT_CACHE_MAX: i64 = 256;
 
-/// Global cache for small integers, singletons, and empty collections
-struct ObjectCache {
-    /// Cached integers from -256 to 256 (inclusive)
+/// Largest accepted integer cache bound (~1 MB of cached objects)
+pub const MAX_INT_CACHE_MAX: i64 = 65536;
+
+/// Cached integers in `[-max, max]`
+struct IntCache {
+    max: i64,
     integers: Vec<PyObject>,
+}
+
+impl IntCache {
+    fn new(py: Python, max: i64) -> Self {
+        let integers = (-max..=max)
+            .map(|i| i.into_pyobject(py).unwrap().into_any().unbind())
+            .collect();
+        IntCache { max, integers }
+    }
+}
+
+/// Current integer cache (null until `init_cache`)
+///
+/// Swapped wholesale by `set_int_cache_max`. Every reader holds the GIL from
+/// loading the pointer until it has cloned the cached object, and so does the
+/// swap, so the old cache can be freed as soon as it's replaced.
+static INT_CACHE: AtomicPtr<IntCache> = AtomicPtr::new(std::ptr::null_mut());
 
+/// Global cache for singletons
+struct ObjectCache {
     /// Singleton None
     none: PyObject,
 
@@ -49,16 +72,12 @@ pub fn init_cache(py: Python) {
     }
 
     // Pre-allocate integer cache
-    let mut integers = Vec::with_capacity(INT_CACHE_SIZE);
-    for i in -INT_CACHE_OFFSET..=(INT_CACHE_OFFSET) {
-        integers.push(i.to_object(py));
-    }
+    set_int_cache_max(py, DEFAULT_INT_CACHE_MAX);
 
     let cache = ObjectCache {
-        integers,
         none: py.None(),
-        true_obj: true.to_object(py),
-        false_obj: false.to_object(py),
+        true_obj: PyBool::new(py, true).to_owned().into_any().unbind(),
+        false_obj: PyBool::new(py, false).to_owned().into_any().unbind(),
     };
 
     // Store in global cache
@@ -67,7 +86,8 @@ pub fn init_cache(py: Python) {
 
 /// Get a cached integer or create a new one
 ///
-/// For integers in range [-256, 256], returns a cached Python object.
+/// For integers in the cached range ([-256, 256] by default), returns a cached
+/// Python object.
 /// For integers outside this range, creates a new Python object.
 ///
 /// # Arguments
@@ -83,16 +103,37 @@ pub fn init_cache(py: Python) {
 #[inline(always)]
 pub fn get_int(py: Python, value: i64) -> PyObject {
     // Fast path: check if in cache range
-    if value >= -INT_CACHE_OFFSET && value <= INT_CACHE_OFFSET {
-        if let Some(cache) = OBJECT_CACHE.get() {
-            let index = (value + INT_CACHE_OFFSET) as usize;
-            // SAFETY: Index is guaranteed to be in bounds by the if condition above
+    // SAFETY: we hold the GIL, so the cache can't be swapped out under us
+    if let Some(cache) = unsafe { INT_CACHE.load(Ordering::Acquire).as_ref() } {
+        if (-cache.max..=cache.max).contains(&value) {
+            let index = (value + cache.max) as usize;
+            // Index is guaranteed to be in bounds by the range check above
             return cache.integers[index].clone_ref(py);
         }
     }
 
     // Slow path: create new object for large integers
-    value.to_object(py)
+    // PHASE 13 OPTIMIZATION: Direct C API call bypasses PyO3 overhead
+    unsafe { PyObject::from_owned_ptr(py, create_int_i64_direct(value)) }
+}
+
+/// Rebuild the integer cache to cover `[-max, max]` (0 caches only zero)
+///
+/// Caller validates `max` against `MAX_INT_CACHE_MAX`.
+pub fn set_int_cache_max(py: Python, max: i64) {
+    let new_cache = Box::into_raw(Box::new(IntCache::new(py, max)));
+    let old_cache = INT_CACHE.swap(new_cache, Ordering::AcqRel);
+    if !old_cache.is_null() {
+        // SAFETY: allocated by Box::into_raw above; readers hold the GIL
+        // (as do we), so none can still be using it
+        drop(unsafe { Box::from_raw(old_cache) });
+    }
+}
+
+/// Current integer cache bound
+pub fn int_cache_max(_py: Python) -> i64 {
+    // SAFETY: we hold the GIL, so the cache can't be freed under us
+    unsafe { INT_CACHE.load(Ordering::Acquire).as_ref() }.map_or(0, |cache| cache.max)
 }
 
 /// Get cached None singleton
@@ -128,7 +169,7 @@ pub fn get_bool(py: Python, value: bool) -> PyObject {
             cache.false_obj.clone_ref(py)
         }
     } else {
-        value.to_object(py)
+        PyBool::new(py, value).to_owned().into_any().unbind()
     }
 }
 
@@ -244,35 +285,51 @@ pub fn get_none_ptr() -> *mut ffi::PyObject {
 // web servers), this causes unnecessary allocations. Thread-local storage
 // allows reusing the same buffer across calls.
 
-// Thread-local buffer for dumps serialization
+// Thread-local buffer pool for dumps serialization
+//
+// A pool rather than a single buffer: serialization can run Python code
+// (generators under `iterables=True`, registered serializers) that calls
+// dumps() again on the same thread. Each call pops its own buffer, so nested
+// calls never share one, and every buffer keeps its grown capacity.
 thread_local! {
-    static SERIALIZE_BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::with_capacity(4096));
+    static SERIALIZE_BUFFERS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
 }
 
-/// Get a thread-local buffer for serialization, clearing it first
+/// Buffers kept per thread (covers the usual nesting depth; deeper nested
+/// calls allocate a fresh buffer and drop it afterwards)
+const MAX_POOLED_BUFFERS: usize = 4;
+
+/// Initial capacity of a freshly allocated buffer
+const INITIAL_BUFFER_CAPACITY: usize = 4096;
+
+/// Run `f` with a cleared, reusable serialization buffer
 ///
-/// PHASE 14 OPTIMIZATION: Reuses allocation across dumps() calls
-/// Returns the buffer with previous capacity but length 0
+/// PHASE 14 OPTIMIZATION: Reuses allocation across dumps() calls.
+/// Reentrant: the pool is not borrowed while `f` runs.
 ///
 /// # Arguments
 /// * `min_capacity` - Minimum required capacity
-///
-/// # Returns
-/// Mutable reference to the thread-local buffer
+/// * `f` - Receives the buffer (length 0, previous capacity)
 #[inline]
 pub fn get_serialize_buffer<F, R>(min_capacity: usize, f: F) -> R
 where
     F: FnOnce(&mut Vec<u8>) -> R,
 {
-    SERIALIZE_BUFFER.with(|cell| {
-        let mut buf = cell.borrow_mut();
-        buf.clear();
-        let current_cap = buf.capacity();
-        if current_cap < min_capacity {
-            buf.reserve(min_capacity - current_cap);
+    let mut buf = SERIALIZE_BUFFERS
+        .with(|pool| pool.borrow_mut().pop())
+        .unwrap_or_else(|| Vec::with_capacity(INITIAL_BUFFER_CAPACITY));
+    buf.clear();
+    buf.reserve(min_capacity);
+
+    let result = f(&mut buf);
+
+    SERIALIZE_BUFFERS.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.len() < MAX_POOLED_BUFFERS {
+            pool.push(buf);
         }
-        f(&mut buf)
-    })
+    });
+    result
 }
 
 /// Take contents from thread-local buffer as a String
@@ -280,9 +337,9 @@ where
 /// PHASE 14 OPTIMIZATION: Creates String from buffer contents without extra copy
 #[inline]
 #[allow(dead_code)]
-pub fn buffer_to_string(buf: &Vec<u8>) -> String {
+pub fn buffer_to_string(buf: &[u8]) -> String {
     // SAFETY: We only write valid UTF-8 (JSON is always UTF-8)
-    unsafe { String::from_utf8_unchecked(buf.clone()) }
+    unsafe { String::from_utf8_unchecked(buf.to_vec()) }
 }
 
 #[cfg(test)]
@@ -329,4 +386,23 @@ mod tests {
             assert!(false1.is(&false2));
         });
     }
+
+    #[test]
+    fn test_serialize_buffer_reentrant() {
+        let outer_cap = get_serialize_buffer(1 << 16, |outer| {
+            outer.extend_from_slice(b"outer");
+            get_serialize_buffer(16, |inner| {
+                assert!(inner.is_empty());
+                inner.extend_from_slice(b"inner");
+            });
+            assert_eq!(outer, b"outer");
+            outer.capacity()
+        });
+
+        // The grown buffer comes back to the pool (most recently returned)
+        get_serialize_buffer(0, |buf| {
+            assert!(buf.is_empty());
+            assert_eq!(buf.capacity(), outer_cap);
+        });
+    }
 }
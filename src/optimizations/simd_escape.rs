@@ -28,7 +28,7 @@ static CPU_FEATURE_LEVEL: AtomicU8 = AtomicU8::new(0);
 /// Initialize CPU feature detection cache
 #[cfg(target_arch = "x86_64")]
 #[inline]
-fn get_cpu_feature_level() -> u8 {
+pub(crate) fn get_cpu_feature_level() -> u8 {
     let level = CPU_FEATURE_LEVEL.load(Ordering::Relaxed);
     if level != 0 {
         return level;
@@ -40,6 +40,14 @@ fn get_cpu_feature_level() -> u8 {
     detected
 }
 
+/// Override the CPU feature level (0 = re-detect on next use)
+///
+/// Caller must not request AVX2 (2) on a CPU without it.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn set_cpu_feature_level(level: u8) {
+    CPU_FEATURE_LEVEL.store(level, Ordering::Relaxed);
+}
+
 /// Write a JSON string with SIMD-accelerated escape detection
 ///
 /// This is the main entry point that dispatches to the appropriate
@@ -104,6 +112,159 @@ pub fn write_json_string_fast(buf: &mut Vec<u8>, s: &str) {
     buf.push(b'"');
 }
 
+/// Which characters `dumps` escapes beyond what JSON requires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EscapePolicy {
+    /// Quotes, backslashes and ASCII control characters only (default)
+    Json,
+    /// Also DEL, C1 controls, line/paragraph separators and invisible
+    /// format characters (zero-width, bidi controls, BOM), as `\uXXXX`
+    Portable,
+    /// DEL and every non-ASCII character, as `\uXXXX`
+    /// (`dumps(ensure_ascii=True)`)
+    Ascii,
+}
+
+/// Code points escaped by `EscapePolicy::Portable` (sorted, inclusive)
+///
+/// DEL and the C1 controls, every Unicode `Cf` (format) character, and the
+/// `Zl`/`Zp` separators U+2028/U+2029, which also break JavaScript string
+/// literals.
+const NON_PRINTABLE_RANGES: &[(u32, u32)] = &[
+    (0x007F, 0x009F),
+    (0x00AD, 0x00AD),
+    (0x0600, 0x0605),
+    (0x061C, 0x061C),
+    (0x06DD, 0x06DD),
+    (0x070F, 0x070F),
+    (0x0890, 0x0891),
+    (0x08E2, 0x08E2),
+    (0x180E, 0x180E),
+    (0x200B, 0x200F),
+    (0x2028, 0x202E),
+    (0x2060, 0x2064),
+    (0x2066, 0x206F),
+    (0xFEFF, 0xFEFF),
+    (0xFFF9, 0xFFFB),
+    (0x110BD, 0x110BD),
+    (0x110CD, 0x110CD),
+    (0x13430, 0x1343F),
+    (0x1BCA0, 0x1BCA3),
+    (0x1D173, 0x1D17A),
+    (0xE0001, 0xE0001),
+    (0xE0020, 0xE007F),
+];
+
+#[inline]
+fn is_non_printable(c: char) -> bool {
+    let cp = c as u32;
+    NON_PRINTABLE_RANGES
+        .binary_search_by(|&(lo, hi)| {
+            if hi < cp {
+                std::cmp::Ordering::Less
+            } else if lo > cp {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Write `c` as `\uXXXX`, or a surrogate pair outside the BMP
+fn write_unicode_escape(buf: &mut Vec<u8>, c: char) {
+    let mut units = [0u16; 2];
+    for &unit in c.encode_utf16(&mut units).iter() {
+        buf.extend_from_slice(b"\\u");
+        for shift in [12, 8, 4, 0] {
+            buf.push(HEX_CHARS[((unit >> shift) & 0xF) as usize]);
+        }
+    }
+}
+
+/// Write a JSON string under `EscapePolicy::Portable`
+///
+/// Every candidate character is at or above DEL, so a SIMD scan for such
+/// bytes sends plain ASCII text straight to `write_json_string_simd`; only
+/// strings containing DEL or non-ASCII pay for the per-character check.
+pub fn write_json_string_portable(buf: &mut Vec<u8>, s: &str) {
+    if !has_byte_at_least_del(s.as_bytes()) {
+        write_json_string_simd(buf, s);
+        return;
+    }
+    write_json_string_escaping(buf, s, is_non_printable);
+}
+
+/// Write a JSON string under `EscapePolicy::Ascii`
+///
+/// Strings without DEL or non-ASCII take the SIMD path unchanged; otherwise
+/// ASCII runs are copied in bulk between the `\uXXXX` escapes.
+pub fn write_json_string_ascii(buf: &mut Vec<u8>, s: &str) {
+    if !has_byte_at_least_del(s.as_bytes()) {
+        write_json_string_simd(buf, s);
+        return;
+    }
+    write_json_string_escaping(buf, s, |_| true);
+}
+
+/// Per-character writer: the usual JSON escapes, plus `\uXXXX` for every
+/// character at or above DEL matching `unicode_escape`
+fn write_json_string_escaping(buf: &mut Vec<u8>, s: &str, unicode_escape: impl Fn(char) -> bool) {
+    let bytes = s.as_bytes();
+    buf.reserve(bytes.len() + 2);
+    buf.push(b'"');
+    let mut clean_start = 0;
+    for (i, c) in s.char_indices() {
+        let ascii_escape = (c as u32) < 0x7F && ESCAPE_LUT[c as usize] != EscapeAction::None;
+        if !ascii_escape && ((c as u32) < 0x7F || !unicode_escape(c)) {
+            continue;
+        }
+        buf.extend_from_slice(&bytes[clean_start..i]);
+        if ascii_escape {
+            write_escape_sequence(buf, c as u8);
+        } else {
+            write_unicode_escape(buf, c);
+        }
+        clean_start = i + c.len_utf8();
+    }
+    buf.extend_from_slice(&bytes[clean_start..]);
+    buf.push(b'"');
+}
+
+/// Whether any byte is DEL (0x7F) or non-ASCII
+#[inline]
+fn has_byte_at_least_del(bytes: &[u8]) -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if bytes.len() >= SIMD_THRESHOLD {
+            return unsafe { has_byte_at_least_del_sse2(bytes) };
+        }
+    }
+    bytes.iter().any(|&b| b >= 0x7F)
+}
+
+/// SSE2 scan for DEL or non-ASCII bytes (the sign bit marks bytes >= 0x80)
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn has_byte_at_least_del_sse2(bytes: &[u8]) -> bool {
+    use std::arch::x86_64::*;
+
+    let len = bytes.len();
+    let mut i = 0;
+    let del_vec = _mm_set1_epi8(0x7F);
+
+    while i + 16 <= len {
+        let chunk = _mm_loadu_si128(bytes.as_ptr().add(i) as *const __m128i);
+        let candidates = _mm_or_si128(chunk, _mm_cmpeq_epi8(chunk, del_vec));
+        if _mm_movemask_epi8(candidates) != 0 {
+            return true;
+        }
+        i += 16;
+    }
+
+    bytes[i..].iter().any(|&b| b >= 0x7F)
+}
+
 /// SIMD pre-scan: Check if ANY bytes need escaping
 /// Returns true if escaping is needed, false if string can be bulk-copied
 ///
@@ -482,8 +643,7 @@ fn write_escaped_scalar(buf: &mut Vec<u8>, bytes: &[u8]) {
 /// Scalar processing for a range of bytes
 #[inline]
 fn write_escaped_scalar_range(buf: &mut Vec<u8>, bytes: &[u8], start: usize, end: usize) {
-    for i in start..end {
-        let b = bytes[i];
+    for &b in &bytes[start..end] {
         if ESCAPE_LUT[b as usize] != EscapeAction::None {
             write_escape_sequence(buf, b);
         } else {
@@ -556,4 +716,39 @@ mod tests {
         write_json_string_simd(&mut buf, "");
         assert_eq!(String::from_utf8(buf).unwrap(), "\"\"");
     }
+
+    #[test]
+    fn test_portable_escapes_non_printables() {
+        let mut buf = Vec::new();
+        write_json_string_portable(&mut buf, "a\u{2028}b\u{200B}\u{7F}\u{85}\u{FEFF}\"\n");
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#""a\u2028b\u200b\u007f\u0085\ufeff\"\n""#
+        );
+    }
+
+    #[test]
+    fn test_portable_keeps_printable_unicode() {
+        let text = "日本語 café 😀 and a long enough ASCII tail";
+        let mut portable = Vec::new();
+        write_json_string_portable(&mut portable, text);
+        let mut plain = Vec::new();
+        write_json_string_simd(&mut plain, text);
+        assert_eq!(portable, plain);
+    }
+
+    #[test]
+    fn test_portable_surrogate_pair() {
+        let mut buf = Vec::new();
+        write_json_string_portable(&mut buf, "x\u{E0041}");
+        assert_eq!(String::from_utf8(buf).unwrap(), r#""x\udb40\udc41""#);
+    }
+
+    #[test]
+    fn test_portable_long_string_candidate_in_tail() {
+        let text = format!("{}\u{2029}", "a".repeat(40));
+        let mut buf = Vec::new();
+        write_json_string_portable(&mut buf, &text);
+        assert!(String::from_utf8(buf).unwrap().ends_with("\\u2029\""));
+    }
 }
@@ -12,166 +12,299 @@ use pyo3::types::PyString;
 use pyo3::exceptions::PyValueError;
 use ahash::AHashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::OnceLock;
-use simd_json::prelude::*;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use simd_json::tape::{Node, Tape};
 
 use crate::optimizations::object_cache;
+use crate::LoadsOptions;
 
 /// Global string intern cache for common JSON keys
 /// Uses AHashMap for 2x faster hashing than std HashMap
-static STRING_INTERN: OnceLock<RwLock<StringInternCache>> = OnceLock::new();
+static STRING_INTERN: OnceLock<StringInternCache> = OnceLock::new();
 
-/// String interning cache with LRU-like behavior
+/// Number of independently locked shards (a power of two)
+///
+/// Threads interning different strings usually hit different shards, so a
+/// miss that takes a write lock only blocks lookups in 1/16 of the cache.
+const INTERN_SHARDS: usize = 16;
+
+/// String interning cache, split into shards by key hash
 struct StringInternCache {
-    /// Map from string content to interned Python string object
-    cache: AHashMap<String, PyObject>,
+    /// Map from string content to interned Python string object, per shard
+    shards: [RwLock<AHashMap<String, PyObject>>; INTERN_SHARDS],
+    /// Picks the shard for a string
+    hasher: ahash::RandomState,
+    /// Strings held across all shards
+    len: AtomicUsize,
     /// Maximum cache size to prevent unbounded growth
-    max_size: usize,
+    max_size: AtomicUsize,
 }
 
 impl StringInternCache {
     fn new(max_size: usize) -> Self {
         Self {
-            cache: AHashMap::with_capacity(max_size),
-            max_size,
+            shards: std::array::from_fn(|_| RwLock::new(AHashMap::new())),
+            hasher: ahash::RandomState::new(),
+            len: AtomicUsize::new(0),
+            max_size: AtomicUsize::new(max_size),
         }
     }
 
+    #[inline]
+    fn shard(&self, s: &str) -> &RwLock<AHashMap<String, PyObject>> {
+        &self.shards[self.hasher.hash_one(s) as usize & (INTERN_SHARDS - 1)]
+    }
+
     /// Get or create an interned string
     #[inline]
-    fn get_or_intern(&mut self, py: Python, s: &str) -> PyObject {
-        // Fast path: check if already interned
-        if let Some(obj) = self.cache.get(s) {
+    fn get_or_intern(&self, py: Python, s: &str) -> PyObject {
+        let shard = self.shard(s);
+
+        // Fast path: shared lock on one shard
+        if let Some(obj) = shard.read().unwrap_or_else(|e| e.into_inner()).get(s) {
             return obj.clone_ref(py);
         }
 
         // Slow path: create new and potentially cache
-        let py_str: PyObject = PyString::new(py, s).into_py(py);
-
-        // Only cache short strings (common keys like "id", "name", "type")
-        if s.len() <= 32 && self.cache.len() < self.max_size {
-            self.cache.insert(s.to_owned(), py_str.clone_ref(py));
+        let py_str: PyObject = PyString::new(py, s).into_any().unbind();
+
+        // Only cache short strings (common keys like "id", "name", "type").
+        // The size check is approximate under concurrent inserts, which can
+        // overshoot the limit by at most one string per thread.
+        if s.len() > intern_max_len()
+            || self.len.load(Ordering::Relaxed) >= self.max_size.load(Ordering::Relaxed)
+        {
+            return py_str;
         }
-
+        let mut guard = shard.write().unwrap_or_else(|e| e.into_inner());
+        // Another thread may have interned it since the read lock was dropped
+        if let Some(obj) = guard.get(s) {
+            return obj.clone_ref(py);
+        }
+        guard.insert(s.to_owned(), py_str.clone_ref(py));
+        self.len.fetch_add(1, Ordering::Relaxed);
         py_str
     }
+
+    /// Keep only the strings `keep` accepts, in every shard
+    fn retain(&self, keep: impl Fn(&str) -> bool, shrink: bool) {
+        for shard in &self.shards {
+            let mut guard = shard.write().unwrap_or_else(|e| e.into_inner());
+            let before = guard.len();
+            guard.retain(|key, _| keep(key));
+            if shrink {
+                guard.shrink_to_fit();
+            }
+            self.len.fetch_sub(before - guard.len(), Ordering::Relaxed);
+        }
+    }
 }
 
+/// Default maximum number of interned strings
+pub const DEFAULT_INTERN_CACHE_SIZE: usize = 1024;
+
+/// Default longest string (in UTF-8 bytes) the intern cache keeps
+pub const DEFAULT_INTERN_MAX_LEN: usize = 32;
+
+/// Longest string (in UTF-8 bytes) the intern cache keeps; atomic so
+/// callers can skip the lock for strings that would never be cached
+static INTERN_MAX_LEN: AtomicUsize = AtomicUsize::new(DEFAULT_INTERN_MAX_LEN);
+
+/// Common JSON keys pre-interned at startup (kept when the cache shrinks)
+const COMMON_KEYS: &[&str] = &[
+    "id", "name", "type", "value", "data", "items", "count",
+    "status", "error", "message", "result", "key", "index",
+    "created_at", "updated_at", "timestamp", "user", "email",
+    "title", "description", "url", "path", "method", "code",
+    "success", "failed", "true", "false", "null", "enabled",
+    "disabled", "active", "inactive", "start", "end", "size",
+    "length", "width", "height", "x", "y", "z", "lat", "lon",
+    "first", "last", "next", "prev", "parent", "children",
+];
+
 /// Initialize the string intern cache
 pub fn init_string_intern(py: Python) {
     STRING_INTERN.get_or_init(|| {
-        let mut cache = StringInternCache::new(1024);
+        let cache = StringInternCache::new(DEFAULT_INTERN_CACHE_SIZE);
 
         // Pre-intern common JSON keys
-        const COMMON_KEYS: &[&str] = &[
-            "id", "name", "type", "value", "data", "items", "count",
-            "status", "error", "message", "result", "key", "index",
-            "created_at", "updated_at", "timestamp", "user", "email",
-            "title", "description", "url", "path", "method", "code",
-            "success", "failed", "true", "false", "null", "enabled",
-            "disabled", "active", "inactive", "start", "end", "size",
-            "length", "width", "height", "x", "y", "z", "lat", "lon",
-            "first", "last", "next", "prev", "parent", "children",
-        ];
-
         for &key in COMMON_KEYS {
-            let py_str: PyObject = PyString::new(py, key).into_py(py);
-            cache.cache.insert(key.to_owned(), py_str);
+            let py_str: PyObject = PyString::new(py, key).into_any().unbind();
+            let mut shard = cache.shard(key).write().unwrap_or_else(|e| e.into_inner());
+            shard.insert(key.to_owned(), py_str);
         }
+        cache.len.store(COMMON_KEYS.len(), Ordering::Relaxed);
 
-        RwLock::new(cache)
+        cache
     });
 }
 
-/// Get an interned string (or create a new one)
-#[inline]
-fn get_interned_string(py: Python, s: &str) -> PyObject {
+/// Change how many strings the intern cache may hold
+///
+/// Shrinking below the current size drops everything except the
+/// pre-interned common keys, so the cache refills with the current workload.
+pub fn set_intern_cache_size(size: usize) {
     if let Some(intern) = STRING_INTERN.get() {
-        // Try read lock first (fast path for cached strings)
-        if let Ok(guard) = intern.read() {
-            if let Some(obj) = guard.cache.get(s) {
-                return obj.clone_ref(py);
-            }
+        intern.max_size.store(size, Ordering::Relaxed);
+        if intern.len.load(Ordering::Relaxed) > size {
+            intern.retain(|key| COMMON_KEYS.contains(&key), false);
         }
+    }
+}
 
-        // Write lock to potentially add to cache
-        if let Ok(mut guard) = intern.write() {
-            return guard.get_or_intern(py, s);
-        }
+/// Change the longest string the intern cache keeps
+///
+/// Cached strings longer than the new limit are dropped.
+pub fn set_intern_max_len(len: usize) {
+    INTERN_MAX_LEN.store(len, Ordering::Relaxed);
+    if let Some(intern) = STRING_INTERN.get() {
+        intern.retain(|key| key.len() <= len, false);
+    }
+}
+
+/// Current longest string (in UTF-8 bytes) the intern cache keeps
+#[inline]
+pub fn intern_max_len() -> usize {
+    INTERN_MAX_LEN.load(Ordering::Relaxed)
+}
+
+/// Drop every learned string, keeping the pre-interned common keys
+///
+/// Releases the cache's references; objects already handed out stay valid.
+pub fn clear_intern_cache() {
+    if let Some(intern) = STRING_INTERN.get() {
+        intern.retain(|key| COMMON_KEYS.contains(&key), true);
+    }
+}
+
+/// Current maximum number of interned strings
+pub fn intern_cache_size() -> usize {
+    STRING_INTERN
+        .get()
+        .map_or(DEFAULT_INTERN_CACHE_SIZE, |intern| intern.max_size.load(Ordering::Relaxed))
+}
+
+/// Get an interned string (or create a new one)
+///
+/// Shared by every parser engine so equal keys map to the same `str` object.
+#[inline]
+pub(crate) fn get_interned_string(py: Python, s: &str) -> PyObject {
+    if let Some(intern) = STRING_INTERN.get() {
+        return intern.get_or_intern(py, s);
     }
 
     // Fallback: create without caching
-    let result: PyObject = PyString::new(py, s).into_py(py);
+    let result: PyObject = PyString::new(py, s).into_any().unbind();
     result
 }
 
-/// Convert simd_json Value to Python object
+/// Nesting limit of `loads_simd` and `loads` when `max_depth` isn't given,
+/// unless changed with `configure(default_max_depth=...)`
+///
+/// simd-json's tape is flat, but the conversion below recurses once per
+/// level (as does the serde parse), so unbounded input would overflow the
+/// stack. A little above stdlib `json`, which handles about 900 levels.
+pub const DEFAULT_MAX_DEPTH: usize = 1024;
+
+/// Nesting limit in effect when `max_depth` isn't given
+static CONFIGURED_MAX_DEPTH: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_DEPTH);
+
+/// Set the nesting limit used when `max_depth` isn't given
+pub fn set_default_max_depth(depth: usize) {
+    CONFIGURED_MAX_DEPTH.store(depth, Ordering::Relaxed);
+}
+
+/// Current nesting limit used when `max_depth` isn't given
+#[inline]
+pub fn default_max_depth() -> usize {
+    CONFIGURED_MAX_DEPTH.load(Ordering::Relaxed)
+}
+
+/// Convert the simd-json tape value starting at `nodes[*pos]` to a Python
+/// object, advancing `pos` past it
 ///
 /// This is the core conversion function that:
-/// - Uses string interning for dictionary keys (Phase 9)
-/// - Creates Python objects in a cache-friendly order
+/// - Uses string interning for dictionary keys (Phase 9, unless disabled)
+/// - Creates Python objects in document order, straight from the tape
 /// - PHASE 13: Uses direct C API for object creation
-#[inline]
-fn simd_value_to_py(py: Python, value: &simd_json::BorrowedValue) -> PyResult<PyObject> {
-    use simd_json::BorrowedValue;
+///
+/// `depth` counts the containers enclosing the value; opening one past
+/// `max_depth` fails before recursing. `src` is the original input, only
+/// read to position that error.
+fn tape_value_to_py(
+    py: Python,
+    src: &[u8],
+    nodes: &[Node],
+    pos: &mut usize,
+    opts: &LoadsOptions,
+    depth: usize,
+    max_depth: usize,
+) -> PyResult<PyObject> {
     use pyo3::ffi;
 
-    match value {
+    let node = nodes[*pos];
+    *pos += 1;
+    if matches!(node, Node::Array { .. } | Node::Object { .. }) && depth >= max_depth {
+        return Err(simd_decode_error(
+            src,
+            nesting_offset(src, max_depth + 1),
+            format!("JSON parsing error: nesting exceeds max_depth ({max_depth})"),
+        ));
+    }
+
+    match node {
         // Static values: null, bool, numbers
-        BorrowedValue::Static(s) => {
+        Node::Static(s) => {
             match s {
                 simd_json::StaticNode::Null => Ok(object_cache::get_none(py)),
-                simd_json::StaticNode::Bool(b) => Ok(object_cache::get_bool(py, *b)),
+                simd_json::StaticNode::Bool(b) => Ok(object_cache::get_bool(py, b)),
                 simd_json::StaticNode::I64(n) => {
-                    // Use integer cache for small values
-                    if *n >= -256 && *n <= 256 {
-                        Ok(object_cache::get_int(py, *n))
-                    } else {
-                        // PHASE 13: Direct C API call
-                        unsafe {
-                            let ptr = object_cache::create_int_i64_direct(*n);
-                            Ok(PyObject::from_owned_ptr(py, ptr))
-                        }
-                    }
+                    // Use integer cache for small values (direct C API otherwise)
+                    Ok(object_cache::get_int(py, n))
                 }
                 simd_json::StaticNode::U64(n) => {
-                    if *n <= 256 {
-                        Ok(object_cache::get_int(py, *n as i64))
+                    if let Ok(n) = i64::try_from(n) {
+                        Ok(object_cache::get_int(py, n))
                     } else {
                         // PHASE 13: Direct C API call
                         unsafe {
-                            let ptr = object_cache::create_int_u64_direct(*n);
+                            let ptr = object_cache::create_int_u64_direct(n);
                             Ok(PyObject::from_owned_ptr(py, ptr))
                         }
                     }
                 }
                 // PHASE 13: Direct C API call for floats
                 simd_json::StaticNode::F64(f) => unsafe {
-                    let ptr = object_cache::create_float_direct(*f);
+                    let ptr = object_cache::create_float_direct(f);
                     Ok(PyObject::from_owned_ptr(py, ptr))
                 },
             }
         }
 
-        BorrowedValue::String(s) => {
+        Node::String(s) => {
             // PHASE 13: Direct C API call for strings (2-3x faster)
-            unsafe {
-                let ptr = object_cache::create_string_direct(s);
-                Ok(PyObject::from_owned_ptr(py, ptr))
-            }
+            crate::create_str_value(py, s, opts)
         }
 
-        BorrowedValue::Array(arr) => {
+        Node::Array { len, .. } => {
             // PHASE 13: Direct list creation with C API
             unsafe {
-                let len = arr.len();
                 let list_ptr = object_cache::create_list_direct(len as ffi::Py_ssize_t);
                 if list_ptr.is_null() {
                     return Err(PyValueError::new_err("Failed to create list"));
                 }
 
-                for (i, item) in arr.iter().enumerate() {
-                    let py_item = simd_value_to_py(py, item)?;
+                for i in 0..len {
+                    let py_item = match tape_value_to_py(py, src, nodes, pos, opts, depth + 1, max_depth) {
+                        Ok(py_item) => py_item,
+                        Err(e) => {
+                            ffi::Py_DECREF(list_ptr);
+                            return Err(e);
+                        }
+                    };
                     // PyList_SET_ITEM steals the reference
                     object_cache::set_list_item_direct(list_ptr, i as ffi::Py_ssize_t, py_item.into_ptr());
                 }
@@ -180,7 +313,7 @@ fn simd_value_to_py(py: Python, value: &simd_json::BorrowedValue) -> PyResult<Py
             }
         }
 
-        BorrowedValue::Object(obj) => {
+        Node::Object { len, .. } => {
             // PHASE 13 + PHASE 15: Direct dict creation with interned keys
             unsafe {
                 let dict_ptr = object_cache::create_dict_direct();
@@ -188,10 +321,35 @@ fn simd_value_to_py(py: Python, value: &simd_json::BorrowedValue) -> PyResult<Py
                     return Err(PyValueError::new_err("Failed to create dict"));
                 }
 
-                for (key, value) in obj.iter() {
+                for _ in 0..len {
+                    let Node::String(key) = nodes[*pos] else {
+                        unreachable!("simd-json object keys are strings");
+                    };
+                    *pos += 1;
+                    if let Some(filter) = &opts.key_filter {
+                        if !filter.keeps(key) {
+                            *pos += node_span(nodes[*pos]);
+                            continue;
+                        }
+                    }
+
                     // Use string interning for keys (Phase 9/15)
-                    let py_key = get_interned_string(py, key);
-                    let py_value = simd_value_to_py(py, value)?;
+                    let py_key = match crate::create_key(py, key, opts) {
+                        Ok(py_key) => py_key,
+                        Err(e) => {
+                            ffi::Py_DECREF(dict_ptr);
+                            return Err(e);
+                        }
+                    };
+                    let py_value = match tape_value_to_py(py, src, nodes, pos, opts, depth + 1, max_depth)
+                        .and_then(|py_value| crate::revive_datetime(py, opts, &py_key, py_value))
+                    {
+                        Ok(py_value) => py_value,
+                        Err(e) => {
+                            ffi::Py_DECREF(dict_ptr);
+                            return Err(e);
+                        }
+                    };
 
                     // PyDict_SetItem does NOT steal references
                     let result = object_cache::set_dict_item_direct(dict_ptr, py_key.as_ptr(), py_value.as_ptr());
@@ -207,45 +365,243 @@ fn simd_value_to_py(py: Python, value: &simd_json::BorrowedValue) -> PyResult<Py
     }
 }
 
+/// `JSONDecodeError` with message `msg` at byte offset `index` of `src`
+///
+/// `src` may not be valid UTF-8 (simd-json validates it while parsing), so
+/// `doc` and `pos` are those of its lossy decoding.
+#[cold]
+fn simd_decode_error(src: &[u8], index: usize, msg: String) -> PyErr {
+    let index = index.min(src.len());
+    let pos = String::from_utf8_lossy(&src[..index]).chars().count();
+    crate::JSONDecodeError::new_err((msg, String::from_utf8_lossy(src).into_owned(), pos))
+}
+
+/// Byte offset of the bracket that opens nesting level `level` (1 for the
+/// root container), or `src.len()` if there is none
+pub(crate) fn nesting_offset(src: &[u8], level: usize) -> usize {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, &b) in src.iter().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth == level {
+                    return i;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    src.len()
+}
+
+/// Number of tape nodes taken by the value starting with `node`
+#[inline]
+fn node_span(node: Node) -> usize {
+    match node {
+        Node::Array { count, .. } | Node::Object { count, .. } => count + 1,
+        Node::String(_) | Node::Static(_) => 1,
+    }
+}
+
+/// Reusable parse state: input copy, simd-json stage buffers and tape
+struct Scratch {
+    /// `SCRATCH_GENERATION` when this scratch was allocated
+    generation: usize,
+    input: Vec<u8>,
+    buffers: simd_json::Buffers,
+    tape: Tape<'static>,
+}
+
+impl Scratch {
+    /// Approximate bytes held: the input copy, simd-json's buffers (two
+    /// more input-sized copies plus structural indexes, about three inputs
+    /// in all) and the tape
+    fn retained_bytes(&self) -> usize {
+        self.input.capacity() * 4 + self.tape.0.capacity() * std::mem::size_of::<Node>()
+    }
+
+    /// Shrink to about `budget` bytes (by `retained_bytes`), scaling the
+    /// input and tape capacities down together
+    fn shrink_to(&mut self, budget: usize) {
+        let held = self.retained_bytes();
+        if held <= budget {
+            return;
+        }
+        let scale = |capacity: usize| (capacity as u128 * budget as u128 / held as u128) as usize;
+        let input = scale(self.input.capacity());
+        self.input.clear();
+        self.input.shrink_to(input);
+        self.buffers = simd_json::Buffers::new(input);
+        self.tape.0.shrink_to(scale(self.tape.0.capacity()));
+    }
+}
+
+thread_local! {
+    static SIMD_SCRATCH: RefCell<Option<Scratch>> = const { RefCell::new(None) };
+}
+
+/// Most scratch memory (by `Scratch::retained_bytes`) a thread keeps for
+/// its next `loads_simd` call
+///
+/// The tape takes a node per value, so scratch comes to several times the
+/// input: this covers documents of about 8 MiB of typical records. Scratch
+/// grown past it by a larger document is shrunk back to it rather than
+/// dropped, so one huge parse doesn't pin its memory in every thread that
+/// made one, while the next parse still starts with buffers to reuse.
+const MAX_RETAINED_SCRATCH: usize = 64 * 1024 * 1024;
+
+/// Bumped by `release_scratch`; scratch from an older generation is
+/// dropped instead of reused
+static SCRATCH_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Free the calling thread's scratch now, and every other thread's on its
+/// next `loads_simd` call (thread-locals of other threads can't be reached)
+pub fn release_scratch() {
+    SCRATCH_GENERATION.fetch_add(1, Ordering::Relaxed);
+    SIMD_SCRATCH.with(|slot| slot.borrow_mut().take());
+}
+
 /// Parse JSON using simd-json (Phase 7)
 ///
 /// This function uses SIMD-accelerated JSON parsing which is significantly
 /// faster than serde_json for large inputs.
 ///
+/// simd-json parses in place, so the input is copied into a per-thread
+/// scratch buffer, leaving the original for error messages. The scratch
+/// buffer, simd-json's stage buffers and the tape are reused across calls,
+/// up to `MAX_RETAINED_SCRATCH`, so repeated parses don't reallocate (and
+/// fault in) them. Reentrant: the scratch is taken out of the thread-local
+/// while the parse runs.
+///
 /// # Arguments
-/// * `json_str` - JSON string to parse
+/// * `json` - JSON text to parse, as UTF-8 bytes (validated by simd-json)
+/// * `opts` - Parse options
 ///
 /// # Returns
 /// Python object representing the parsed JSON
-pub fn loads_simd(json_str: &str) -> PyResult<PyObject> {
+pub fn loads_simd(json: Cow<'_, [u8]>, opts: &LoadsOptions) -> PyResult<PyObject> {
     Python::with_gil(|py| {
-        // simd-json requires mutable input for in-place parsing
-        let mut json_bytes = json_str.as_bytes().to_vec();
+        check_surrogate_escapes(&json)?;
+
+        let generation = SCRATCH_GENERATION.load(Ordering::Relaxed);
+        let mut scratch = SIMD_SCRATCH
+            .with(|slot| slot.borrow_mut().take())
+            .filter(|scratch| scratch.generation == generation)
+            .unwrap_or_else(|| Scratch {
+                generation,
+                input: Vec::new(),
+                buffers: simd_json::Buffers::new(json.len()),
+                tape: Tape(Vec::new()),
+            });
+
+        // simd-json unescapes strings in place, so `json` itself is kept
+        // intact for error messages
+        scratch.input.clear();
+        scratch.input.extend_from_slice(&json);
+
+        // Parse using simd-json; the tape is flat however deep the document
+        let mut tape = std::mem::replace(&mut scratch.tape, Tape(Vec::new())).reset();
+        let result = simd_json::fill_tape(&mut scratch.input, &mut scratch.buffers, &mut tape)
+            .map_err(|e| simd_decode_error(&json, e.index(), format!("JSON parsing error: {:?}", e.error())))
+            .and_then(|()| convert_tape(py, &json, &tape.0, opts));
+
+        scratch.tape = tape.reset();
+        scratch.shrink_to(MAX_RETAINED_SCRATCH);
+        SIMD_SCRATCH.with(|slot| *slot.borrow_mut() = Some(scratch));
+        result
+    })
+}
 
-        // Parse using simd-json
-        let value: simd_json::BorrowedValue = simd_json::to_borrowed_value(&mut json_bytes)
-            .map_err(|e| PyValueError::new_err(format!("JSON parsing error: {e}")))?;
+/// Convert a filled tape (then dedup it, if asked) on the caller's stack,
+/// or on a deep-parse thread when the document nests past
+/// `CALLER_STACK_DEPTH` and `max_depth` allows it
+fn convert_tape(py: Python, src: &[u8], nodes: &[Node], opts: &LoadsOptions) -> PyResult<PyObject> {
+    let convert = |py: Python, max_depth: usize| {
+        let value = tape_value_to_py(py, src, nodes, &mut 0, opts, 0, max_depth)?;
+        if opts.dedup {
+            return crate::dedup::dedup_tree(py, value);
+        }
+        Ok(value)
+    };
+
+    let max_depth = opts.max_depth.unwrap_or_else(default_max_depth);
+    let shallow = max_depth.min(crate::CALLER_STACK_DEPTH);
+    match convert(py, shallow) {
+        // Conversion is deterministic: a failure other than the depth cap
+        // comes back from the retry too
+        Err(_) if shallow < max_depth && nesting_offset(src, shallow + 1) < src.len() => {
+            crate::parse_on_deep_stack(py, opts, |py| convert(py, max_depth))
+        }
+        result => result,
+    }
+}
 
-        // Convert to Python objects
-        simd_value_to_py(py, &value)
-    })
+/// Reject a `\uD800`-`\uDBFF` escape not followed by a low-surrogate escape
+///
+/// simd-json decodes a lone leading surrogate at the end of a string to
+/// U+0000 instead of failing, where serde_json (and so `loads`) rejects it.
+/// Backslashes can only appear inside strings in valid JSON, so walking the
+/// escapes with `memchr` is enough; inputs without any are a single scan.
+fn check_surrogate_escapes(bytes: &[u8]) -> PyResult<()> {
+    let hex4 = |at: usize| -> Option<u16> {
+        let digits = std::str::from_utf8(bytes.get(at..at + 4)?).ok()?;
+        u16::from_str_radix(digits, 16).ok()
+    };
+
+    let mut pos = 0;
+    while let Some(offset) = bytes.get(pos..).and_then(|rest| memchr::memchr(b'\\', rest)) {
+        let start = pos + offset;
+        if bytes.get(start + 1) != Some(&b'u') {
+            pos = start + 2;
+            continue;
+        }
+        // Malformed hex is left for simd-json to report
+        if matches!(hex4(start + 2), Some(0xD800..=0xDBFF)) {
+            let paired = bytes.get(start + 6..start + 8) == Some(b"\\u")
+                && matches!(hex4(start + 8), Some(0xDC00..=0xDFFF));
+            if !paired {
+                return Err(simd_decode_error(
+                    bytes,
+                    start,
+                    "JSON parsing error: lone leading surrogate in hex escape".to_owned(),
+                ));
+            }
+            pos = start + 12;
+        } else {
+            pos = start + 6;
+        }
+    }
+    Ok(())
 }
 
 /// Optimized loads for small JSON (< 1KB)
 /// Falls back to serde_json for very small inputs where simd overhead isn't worth it
 #[inline]
 #[allow(dead_code)]
-pub fn loads_adaptive(json_str: &str) -> PyResult<PyObject> {
+pub fn loads_adaptive(json_str: &str, opts: &LoadsOptions) -> PyResult<PyObject> {
     // simd-json has setup overhead, only use for larger inputs
     if json_str.len() >= 256 {
-        loads_simd(json_str)
+        loads_simd(json_str.as_bytes().into(), opts)
     } else {
         // Fall back to serde_json for small inputs
         Python::with_gil(|py| {
             use serde::de::DeserializeSeed;
             let mut de = serde_json::Deserializer::from_str(json_str);
-            crate::PyObjectSeed { py }.deserialize(&mut de)
-                .map_err(|e| PyValueError::new_err(format!("JSON parsing error: {e}")))
+            crate::PyObjectSeed { py, opts, depth: 0 }.deserialize(&mut de)
+                .map_err(|e| crate::decode_error(json_str, e))
         })
     }
 }
@@ -253,6 +609,7 @@ pub fn loads_adaptive(json_str: &str) -> PyResult<PyObject> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pyo3::types::{PyDict, PyList};
 
     #[test]
     fn test_loads_simd_basic() {
@@ -261,28 +618,28 @@ mod tests {
             crate::optimizations::object_cache::init_cache(py);
 
             // Test null
-            let result = loads_simd("null").unwrap();
+            let result = loads_simd("null".as_bytes().into(), &LoadsOptions::default()).unwrap();
             assert!(result.bind(py).is_none());
 
             // Test bool
-            let result = loads_simd("true").unwrap();
+            let result = loads_simd("true".as_bytes().into(), &LoadsOptions::default()).unwrap();
             assert!(result.bind(py).extract::<bool>().unwrap());
 
             // Test number
-            let result = loads_simd("42").unwrap();
+            let result = loads_simd("42".as_bytes().into(), &LoadsOptions::default()).unwrap();
             assert_eq!(result.bind(py).extract::<i64>().unwrap(), 42);
 
             // Test string
-            let result = loads_simd("\"hello\"").unwrap();
+            let result = loads_simd("\"hello\"".as_bytes().into(), &LoadsOptions::default()).unwrap();
             assert_eq!(result.bind(py).extract::<String>().unwrap(), "hello");
 
             // Test array
-            let result = loads_simd("[1, 2, 3]").unwrap();
+            let result = loads_simd("[1, 2, 3]".as_bytes().into(), &LoadsOptions::default()).unwrap();
             let list = result.bind(py).downcast::<PyList>().unwrap();
             assert_eq!(list.len(), 3);
 
             // Test object
-            let result = loads_simd("{\"id\": 1, \"name\": \"test\"}").unwrap();
+            let result = loads_simd("{\"id\": 1, \"name\": \"test\"}".as_bytes().into(), &LoadsOptions::default()).unwrap();
             let dict = result.bind(py).downcast::<PyDict>().unwrap();
             assert_eq!(dict.len(), 2);
         });
@@ -301,4 +658,28 @@ mod tests {
             assert!(key1.is(&key2));
         });
     }
+
+    #[test]
+    fn test_string_interning_across_threads() {
+        Python::with_gil(init_string_intern);
+
+        // Threads racing to intern the same keys must all get the stored object
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    Python::with_gil(|py| {
+                        (0..64).map(|i| get_interned_string(py, &format!("shard{i}"))).collect::<Vec<_>>()
+                    })
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        Python::with_gil(|py| {
+            for (i, first) in results[0].iter().enumerate() {
+                assert!(results.iter().all(|keys| keys[i].is(first)));
+                assert!(get_interned_string(py, &format!("shard{i}")).is(first));
+            }
+        });
+    }
 }
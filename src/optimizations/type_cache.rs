@@ -6,8 +6,9 @@
 //! Performance impact: Reduces type detection overhead from 15-20% to <2%
 
 use pyo3::prelude::*;
-use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple, PyType};
 use pyo3::ffi;
+use pyo3::sync::GILOnceCell;
 use std::sync::OnceLock;
 
 /// Cached type pointers for common Python types
@@ -119,6 +120,207 @@ pub fn get_fast_type(obj: &Bound<'_, PyAny>) -> FastType {
     }
 }
 
+/// Look up `module.name` in `sys.modules` once it's been imported, caching
+/// its type pointer in `cell` (stored as usize for Sync)
+///
+/// Returns null while the module isn't loaded: importing it eagerly would
+/// slow down `import rjson` for everyone, and no instance can exist until
+/// the user has imported it themselves. Only consulted for objects that
+/// aren't one of the cached builtin types, but then on every one of them
+/// until the module shows up, so that check is two C-level dict lookups.
+#[inline]
+fn loaded_module_type(
+    py: Python,
+    cell: &OnceLock<usize>,
+    module: &std::ffi::CStr,
+    name: &str,
+) -> *mut ffi::PyTypeObject {
+    if let Some(&ptr) = cell.get() {
+        return ptr as *mut ffi::PyTypeObject;
+    }
+
+    // SAFETY: both calls return borrowed references (or null, without
+    // setting an exception) and the GIL is held
+    let module = unsafe {
+        let modules = ffi::PySys_GetObject(c"modules".as_ptr());
+        if modules.is_null() {
+            return std::ptr::null_mut();
+        }
+        let module = ffi::PyDict_GetItemString(modules, module.as_ptr());
+        if module.is_null() {
+            return std::ptr::null_mut();
+        }
+        Bound::from_borrowed_ptr(py, module)
+    };
+    let cls = module.getattr(name).and_then(|cls| Ok(cls.downcast_into::<PyType>()?.as_type_ptr()));
+
+    match cls {
+        Ok(ptr) => *cell.get_or_init(|| ptr as usize) as *mut ffi::PyTypeObject,
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+static DECIMAL_TYPE: OnceLock<usize> = OnceLock::new();
+
+/// Get the `decimal.Decimal` type pointer, or null if `decimal` isn't loaded
+#[inline]
+pub fn get_decimal_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &DECIMAL_TYPE, c"decimal", "Decimal")
+}
+
+static TIMEDELTA_TYPE: OnceLock<usize> = OnceLock::new();
+
+/// Get the `datetime.timedelta` type pointer, or null if `datetime` isn't loaded
+#[inline]
+pub fn get_timedelta_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &TIMEDELTA_TYPE, c"datetime", "timedelta")
+}
+
+static ENUM_TYPE: OnceLock<usize> = OnceLock::new();
+
+/// Get the `enum.Enum` type pointer, or null if `enum` isn't loaded
+///
+/// Members are instances of subclasses, so this is matched with an
+/// `isinstance` check rather than by exact type.
+#[inline]
+pub fn get_enum_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &ENUM_TYPE, c"enum", "Enum")
+}
+
+#[cfg(feature = "numpy")]
+static NDARRAY_TYPE: OnceLock<usize> = OnceLock::new();
+
+/// Get the `numpy.ndarray` type pointer, or null if `numpy` isn't loaded
+#[cfg(feature = "numpy")]
+#[inline]
+pub fn get_ndarray_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &NDARRAY_TYPE, c"numpy", "ndarray")
+}
+
+#[cfg(feature = "numpy")]
+static NUMPY_GENERIC_TYPE: OnceLock<usize> = OnceLock::new();
+
+/// Get the `numpy.generic` type pointer (base of every numpy scalar), or
+/// null if `numpy` isn't loaded
+#[cfg(feature = "numpy")]
+#[inline]
+pub fn get_numpy_generic_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &NUMPY_GENERIC_TYPE, c"numpy", "generic")
+}
+
+static DATETIME_TYPE: OnceLock<usize> = OnceLock::new();
+static DATE_TYPE: OnceLock<usize> = OnceLock::new();
+static TIME_TYPE: OnceLock<usize> = OnceLock::new();
+
+/// Get the `datetime.datetime` type pointer, or null if `datetime` isn't loaded
+#[inline]
+pub fn get_datetime_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &DATETIME_TYPE, c"datetime", "datetime")
+}
+
+/// Get the `datetime.date` type pointer, or null if `datetime` isn't loaded
+#[inline]
+pub fn get_date_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &DATE_TYPE, c"datetime", "date")
+}
+
+/// Get the `datetime.time` type pointer, or null if `datetime` isn't loaded
+#[inline]
+pub fn get_time_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &TIME_TYPE, c"datetime", "time")
+}
+
+static UUID_TYPE: OnceLock<usize> = OnceLock::new();
+
+/// Get the `uuid.UUID` type pointer, or null if `uuid` isn't loaded
+#[inline]
+pub fn get_uuid_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &UUID_TYPE, c"uuid", "UUID")
+}
+
+static PURE_PATH_TYPE: OnceLock<usize> = OnceLock::new();
+
+/// Get the `pathlib.PurePath` type pointer, or null if `pathlib` isn't loaded
+///
+/// Paths are instances of subclasses (`PosixPath`, `PureWindowsPath`, ...),
+/// so this is matched with an `isinstance` check rather than by exact type.
+#[inline]
+pub fn get_pure_path_type(py: Python) -> *mut ffi::PyTypeObject {
+    loaded_module_type(py, &PURE_PATH_TYPE, c"pathlib", "PurePath")
+}
+
+/// `collections.abc.Sequence`, for `dumps(sequences=True)`
+static SEQUENCE_ABC: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Get `collections.abc.Sequence` (imported on first use)
+///
+/// Unlike the builtin types this needs an `isinstance` check, since custom
+/// sequences may only be registered with the ABC. Only consulted for objects
+/// that aren't one of the cached builtin types.
+pub fn get_sequence_abc(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    SEQUENCE_ABC.import(py, "collections.abc", "Sequence")
+}
+
+/// `collections.abc.Mapping`, never written as an array by
+/// `dumps(sequences=True)`/`dumps(iterables=True)`
+static MAPPING_ABC: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Get `collections.abc.Mapping` (imported on first use)
+///
+/// Covers `MappingProxyType`, `ChainMap`, `UserDict` and classes registered
+/// with the ABC, whose iteration yields only their keys.
+pub fn get_mapping_abc(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    MAPPING_ABC.import(py, "collections.abc", "Mapping")
+}
+
+/// `collections.UserString`: a `Sequence`, but text rather than an array
+static USER_STRING: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Get `collections.UserString` (imported on first use)
+pub fn get_user_string_type(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    USER_STRING.import(py, "collections", "UserString")
+}
+
+/// `io.BytesIO`, whose `write` copies synchronously (`rjson.dump`)
+static BYTES_IO: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Get `io.BytesIO` (imported on first use)
+pub fn get_bytes_io_type(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    BYTES_IO.import(py, "io", "BytesIO")
+}
+
+/// `io.TextIOBase`, whose `write` takes `str` (`rjson.dump` to text files)
+static TEXT_IO_BASE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Get `io.TextIOBase` (imported on first use)
+pub fn get_text_io_base(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    TEXT_IO_BASE.import(py, "io", "TextIOBase")
+}
+
+/// `dataclasses.fields`, for serializing dataclass instances
+static DATACLASS_FIELDS: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// Get `dataclasses.fields` (imported on first use)
+pub fn get_dataclass_fields(py: Python<'_>) -> PyResult<&Bound<'_, PyAny>> {
+    DATACLASS_FIELDS.import(py, "dataclasses", "fields")
+}
+
+/// `unicodedata.normalize`, for `loads(normalize=...)`
+static UNICODEDATA_NORMALIZE: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// Get `unicodedata.normalize` (imported on first use)
+pub fn get_unicodedata_normalize(py: Python<'_>) -> PyResult<&Bound<'_, PyAny>> {
+    UNICODEDATA_NORMALIZE.import(py, "unicodedata", "normalize")
+}
+
+/// `datetime.datetime`, for `loads(datetime_keys=...)`
+static DATETIME_CLASS: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Get `datetime.datetime` (imported on first use)
+pub fn get_datetime_class(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    DATETIME_CLASS.import(py, "datetime", "datetime")
+}
+
 /// Get the cached TypeCache for direct C API type checking
 ///
 /// Used in Phase 5A optimizations for inline type checking without PyO3 overhead
@@ -148,31 +350,31 @@ mod tests {
 
             // Test None
             let none = py.None();
-            assert_eq!(get_fast_type(&none.bind(py)), FastType::None);
+            assert_eq!(get_fast_type(none.bind(py)), FastType::None);
 
             // Test bool
             let bool_val = PyBool::new(py, true);
-            assert_eq!(get_fast_type(&bool_val.as_any()), FastType::Bool);
+            assert_eq!(get_fast_type(bool_val.as_any()), FastType::Bool);
 
             // Test int
             let int_val = PyInt::new(py, 42);
-            assert_eq!(get_fast_type(&int_val.as_any()), FastType::Int);
+            assert_eq!(get_fast_type(int_val.as_any()), FastType::Int);
 
             // Test float
-            let float_val = PyFloat::new(py, 3.14);
-            assert_eq!(get_fast_type(&float_val.as_any()), FastType::Float);
+            let float_val = PyFloat::new(py, 2.5);
+            assert_eq!(get_fast_type(float_val.as_any()), FastType::Float);
 
             // Test string
             let str_val = PyString::new(py, "hello");
-            assert_eq!(get_fast_type(&str_val.as_any()), FastType::String);
+            assert_eq!(get_fast_type(str_val.as_any()), FastType::String);
 
             // Test list
             let list_val = PyList::empty(py);
-            assert_eq!(get_fast_type(&list_val.as_any()), FastType::List);
+            assert_eq!(get_fast_type(list_val.as_any()), FastType::List);
 
             // Test dict
             let dict_val = PyDict::new(py);
-            assert_eq!(get_fast_type(&dict_val.as_any()), FastType::Dict);
+            assert_eq!(get_fast_type(dict_val.as_any()), FastType::Dict);
         });
     }
 
@@ -182,8 +384,8 @@ mod tests {
             init_type_cache(py);
 
             let int_val = PyInt::new(py, 42);
-            assert!(is_type(&int_val.as_any(), FastType::Int));
-            assert!(!is_type(&int_val.as_any(), FastType::Float));
+            assert!(is_type(int_val.as_any(), FastType::Int));
+            assert!(!is_type(int_val.as_any(), FastType::Float));
         });
     }
 }
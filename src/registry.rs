@@ -0,0 +1,108 @@
+//! Custom type serializer registry (public Rust API)
+//!
+//! Lets Rust extensions that embed rjson teach `dumps` about their own types
+//! (e.g. a custom numeric type) without a Python-level `default=` callback.
+//! Handlers are keyed on the exact type pointer, like `type_cache`, and are
+//! only consulted for objects that aren't one of the builtin fast-path types,
+//! so registering handlers never slows down dicts, lists, strings or numbers.
+//!
+//! # Thread safety
+//! The registry is a process-wide `RwLock`ed map. Registration takes the
+//! write lock; `dumps` takes the read lock only long enough to copy the
+//! handler out, and calls it with no lock held, so a handler may itself call
+//! `register_serializer` or `dumps`. Handlers run with the GIL held and may
+//! be called from any thread that calls `dumps`.
+
+use ahash::AHashMap;
+use pyo3::prelude::*;
+use pyo3::ffi;
+use pyo3::types::PyType;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+/// Custom serializer: append the JSON encoding of `obj` to `buf`
+///
+/// The bytes are written verbatim, so the handler is responsible for
+/// producing valid JSON (a complete value, UTF-8 encoded).
+pub type SerializerFn = fn(obj: &Bound<'_, PyAny>, buf: &mut Vec<u8>) -> PyResult<()>;
+
+/// Registered handler plus a strong reference keeping its type alive
+struct Entry {
+    _type: Py<PyType>,
+    handler: SerializerFn,
+}
+
+/// Type pointer (as usize) -> handler
+static REGISTRY: OnceLock<RwLock<AHashMap<usize, Entry>>> = OnceLock::new();
+
+/// Set once anything is registered, so the common case skips the lock
+static HAS_SERIALIZERS: AtomicBool = AtomicBool::new(false);
+
+/// Register `handler` for objects whose exact type is `ty`
+///
+/// Subclasses are not matched. Registering the same type again replaces the
+/// previous handler. Builtin types (`dict`, `list`, `str`, `int`, ...) always
+/// take their fast path and cannot be overridden.
+pub fn register_serializer(ty: &Bound<'_, PyType>, handler: SerializerFn) {
+    let registry = REGISTRY.get_or_init(|| RwLock::new(AHashMap::new()));
+    let mut map = registry.write().unwrap_or_else(|e| e.into_inner());
+    map.insert(ty.as_type_ptr() as usize, Entry { _type: ty.clone().unbind(), handler });
+    HAS_SERIALIZERS.store(true, Ordering::Release);
+}
+
+/// Remove the handler for `ty`, returning whether one was registered
+pub fn unregister_serializer(ty: &Bound<'_, PyType>) -> bool {
+    let Some(registry) = REGISTRY.get() else {
+        return false;
+    };
+    let mut map = registry.write().unwrap_or_else(|e| e.into_inner());
+    map.remove(&(ty.as_type_ptr() as usize)).is_some()
+}
+
+/// Look up the handler for a type pointer (used by `dumps`)
+#[inline]
+pub(crate) fn lookup(type_ptr: *mut ffi::PyTypeObject) -> Option<SerializerFn> {
+    if !HAS_SERIALIZERS.load(Ordering::Acquire) {
+        return None;
+    }
+    let map = REGISTRY.get()?.read().unwrap_or_else(|e| e.into_inner());
+    map.get(&(type_ptr as usize)).map(|entry| entry.handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizations::{ascii_str, object_cache, type_cache};
+    use pyo3::types::{PyBytes, PyList};
+
+    fn write_marker(_obj: &Bound<'_, PyAny>, buf: &mut Vec<u8>) -> PyResult<()> {
+        buf.extend_from_slice(b"\"marker\"");
+        Ok(())
+    }
+
+    #[test]
+    fn test_registered_type_in_dumps() {
+        Python::with_gil(|py| {
+            object_cache::init_cache(py);
+            type_cache::init_type_cache(py);
+            ascii_str::init_ascii_str(py);
+
+            // Decimal has its own paths in dumps: bulk lists and dumps_bytes
+            let decimal = py.import("decimal").unwrap().getattr("Decimal").unwrap();
+            let ty = decimal.downcast::<PyType>().unwrap();
+            let values = PyList::new(py, (0..10).map(|i| decimal.call1((i,)).unwrap())).unwrap();
+            let dumps = wrap_pyfunction!(crate::dumps, py).unwrap();
+            let dumps_bytes = wrap_pyfunction!(crate::dumps_bytes, py).unwrap();
+
+            register_serializer(ty, write_marker);
+            let text = dumps.call1((&values,)).and_then(|out| out.extract::<String>());
+            let bytes = dumps_bytes.call1((&values,)).and_then(|out| Ok(out.downcast_into::<PyBytes>()?.as_bytes().to_vec()));
+            assert!(unregister_serializer(ty));
+            assert!(lookup(ty.as_type_ptr()).is_none());
+
+            let expected = format!("[{}]", ["\"marker\""; 10].join(","));
+            assert_eq!(text.unwrap(), expected);
+            assert_eq!(bytes.unwrap(), expected.as_bytes());
+        });
+    }
+}
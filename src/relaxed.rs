@@ -0,0 +1,93 @@
+//! Comments and trailing commas in `loads` input (`allow_comments`,
+//! `allow_trailing_commas`)
+//!
+//! serde_json only accepts strict JSON, so relaxed documents are rewritten
+//! before parsing: every character of a comment becomes a space (newlines are
+//! kept) and a comma before `]` or `}` becomes a space. The rewrite keeps the
+//! character count and line structure, so `JSONDecodeError` positions still
+//! point into the original text.
+
+use std::borrow::Cow;
+
+/// `doc` with comments and/or trailing commas blanked out
+///
+/// `//` comments run to the end of the line and `/* */` comments don't nest.
+/// An unterminated block comment is left in place for the parser to reject.
+/// Returns `doc` unchanged (borrowed) when there is nothing to blank.
+pub(crate) fn strip_relaxed(doc: &str, comments: bool, trailing_commas: bool) -> Cow<'_, str> {
+    let comments = comments && doc.contains('/');
+    let trailing_commas = trailing_commas && doc.contains(',');
+    if !comments && !trailing_commas {
+        return Cow::Borrowed(doc);
+    }
+
+    let bytes = doc.as_bytes();
+    let mut out = String::new();
+    // Start of the input not yet copied to `out`
+    let mut copied = 0;
+    // Offset in `out` of a comma after a value, followed only by whitespace
+    // and comments so far
+    let mut pending_comma: Option<usize> = None;
+    // Last byte outside whitespace and comments (`"` for a string)
+    let mut prev = 0u8;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                pending_comma = None;
+                prev = b'"';
+                i += 1;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' => i += 2,
+                        b'"' => break,
+                        _ => i += 1,
+                    }
+                }
+                i += 1;
+            }
+            b'/' if comments && matches!(bytes.get(i + 1), Some(b'/' | b'*')) => {
+                let end = if bytes[i + 1] == b'/' {
+                    memchr::memchr(b'\n', &bytes[i..]).map_or(bytes.len(), |n| i + n)
+                } else {
+                    match memchr::memmem::find(&bytes[i + 2..], b"*/") {
+                        Some(n) => i + 2 + n + 2,
+                        None => break,
+                    }
+                };
+                out.push_str(&doc[copied..i]);
+                out.extend(doc[i..end].chars().map(|c| if c == '\n' { '\n' } else { ' ' }));
+                copied = end;
+                i = end;
+            }
+            b',' if trailing_commas => {
+                // `[,]` and `[1,,]` stay invalid
+                pending_comma = (!matches!(prev, b'[' | b'{' | b',')).then_some(out.len() + (i - copied));
+                prev = b',';
+                i += 1;
+            }
+            b']' | b'}' => {
+                if let Some(comma) = pending_comma.take() {
+                    out.push_str(&doc[copied..i]);
+                    copied = i;
+                    out.replace_range(comma..comma + 1, " ");
+                }
+                prev = bytes[i];
+                i += 1;
+            }
+            b' ' | b'\t' | b'\n' | b'\r' => i += 1,
+            byte => {
+                pending_comma = None;
+                prev = byte;
+                i += 1;
+            }
+        }
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(doc);
+    }
+    out.push_str(&doc[copied..]);
+    Cow::Owned(out)
+}
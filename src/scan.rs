@@ -0,0 +1,240 @@
+//! Structural scanning over raw JSON text, `rjson.json_get` and
+//! `rjson.loads_prefix`
+//!
+//! `Cursor` jumps over whole values by tracking only string boundaries and
+//! bracket depth, without decoding anything. It's what lets the lazy parser
+//! index a container cheaply, and `json_get` pull one field out of a large
+//! object. It never reads past the end of its input, so it is safe on
+//! unvalidated text; truncated values simply report `None`.
+
+use pyo3::prelude::*;
+use serde::de::{DeserializeSeed, IgnoredAny};
+
+use crate::{LoadsOptions, PyObjectSeed};
+
+/// Byte offsets of one value, `[start, end)`
+pub(crate) type Range = (usize, usize);
+
+/// Byte cursor that skips over JSON values
+pub(crate) struct Cursor<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) pos: usize,
+}
+
+impl Cursor<'_> {
+    #[inline]
+    pub(crate) fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    #[inline]
+    pub(crate) fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume one byte (a delimiter) and return it
+    #[inline]
+    pub(crate) fn next_byte(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    /// Advance past the string starting at `self.pos` (opening quote)
+    fn skip_string(&mut self) -> Option<()> {
+        self.pos += 1;
+        while self.pos < self.bytes.len() {
+            let offset = memchr::memchr2(b'"', b'\\', &self.bytes[self.pos..])?;
+            self.pos += offset;
+            if self.bytes[self.pos] == b'"' {
+                self.pos += 1;
+                return Some(());
+            }
+            self.pos += 2;
+        }
+        None
+    }
+
+    /// Advance past the value starting at `self.pos`
+    fn skip_value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => self.skip_string(),
+            b'{' | b'[' => {
+                let mut depth = 0usize;
+                loop {
+                    match self.peek()? {
+                        b'"' => {
+                            self.skip_string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.pos += 1;
+                                return Some(());
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+            }
+            _ => {
+                while !matches!(self.peek(), None | Some(b',' | b']' | b'}' | b' ' | b'\t' | b'\n' | b'\r')) {
+                    self.pos += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    /// Skip whitespace, then the next value, returning its range (`None` if
+    /// the input ends inside it)
+    pub(crate) fn value_range(&mut self) -> Option<Range> {
+        self.skip_whitespace();
+        let start = self.pos;
+        self.skip_value()?;
+        Some((start, self.pos))
+    }
+}
+
+/// `JSONDecodeError` for `src` ending inside a value
+#[cold]
+pub(crate) fn truncated_error(src: &str) -> PyErr {
+    crate::decode_error_at(src, src.len(), "JSON parsing error: EOF while parsing a value".to_owned())
+}
+
+/// Decode `src[range]` with a serde seed
+pub(crate) fn decode<'a, S>(src: &'a str, range: Range, seed: S) -> PyResult<S::Value>
+where
+    S: DeserializeSeed<'a>,
+{
+    let mut de = serde_json::Deserializer::from_str(&src[range.0..range.1]);
+    seed.deserialize(&mut de).map_err(|e| crate::decode_error_in(src, range, e))
+}
+
+/// Whether the raw key token `src[range]` (quotes included) equals `key`
+fn key_matches(src: &str, range: Range, key: &str) -> PyResult<bool> {
+    let raw = &src.as_bytes()[range.0 + 1..range.1 - 1];
+    if !raw.contains(&b'\\') {
+        return Ok(raw == key.as_bytes());
+    }
+    let decoded: String = decode(src, range, std::marker::PhantomData)?;
+    Ok(decoded == key)
+}
+
+/// Extract one top-level field from a JSON object without parsing the rest.
+///
+/// Scans the root object key by key to its closing `}`, jumping over values
+/// without decoding them, and parses only the matching value. Much cheaper
+/// than `loads(s)[key]` on large documents, and returns the same value: with
+/// duplicate keys the last occurrence wins, as in `loads`.
+///
+/// Skipped values are only scanned for their extent, not fully validated,
+/// so malformed JSON inside them (a bad escape, a stray token in a nested
+/// array) may go unnoticed; use `loads` when the whole document must be
+/// validated.
+///
+/// The extracted value itself may nest up to 127 levels
+/// (serde_json's recursion limit), fewer than `loads` accepts.
+///
+/// # Arguments
+/// * `json_str` - JSON text whose top-level value is an object.
+/// * `key` - The field to extract.
+/// * `default` - Returned when the key is absent (like `dict.get`).
+///
+/// # Returns
+/// The decoded value or `default`, or a `JSONDecodeError` if the root isn't
+/// an object or the scanned part is malformed.
+#[pyfunction]
+#[pyo3(signature = (json_str, key, default = None))]
+pub fn json_get(py: Python, json_str: &str, key: &str, default: Option<PyObject>) -> PyResult<PyObject> {
+    let syntax_error = |pos: usize, msg: &str| crate::decode_error_at(json_str, pos, format!("JSON parsing error: {msg}"));
+
+    let mut cursor = Cursor { bytes: json_str.as_bytes(), pos: 0 };
+    cursor.skip_whitespace();
+    if cursor.peek() != Some(b'{') {
+        return Err(crate::decode_error_at(json_str, cursor.pos, "json_get requires a top-level JSON object".to_owned()));
+    }
+    cursor.pos += 1;
+    cursor.skip_whitespace();
+
+    let mut found = None;
+    if cursor.peek() != Some(b'}') {
+        loop {
+            cursor.skip_whitespace();
+            if cursor.peek() != Some(b'"') {
+                return Err(syntax_error(cursor.pos, "expected a key"));
+            }
+            let key_range = cursor.value_range().ok_or_else(|| truncated_error(json_str))?;
+            cursor.skip_whitespace();
+            if cursor.peek() != Some(b':') {
+                return Err(syntax_error(cursor.pos, "expected ':'"));
+            }
+            cursor.pos += 1;
+            let value_range = cursor.value_range().ok_or_else(|| truncated_error(json_str))?;
+
+            // A later duplicate replaces the match, as in `loads`
+            if key_matches(json_str, key_range, key)? {
+                found = Some(value_range);
+            }
+
+            cursor.skip_whitespace();
+            match cursor.peek() {
+                Some(b',') => cursor.pos += 1,
+                Some(b'}') => break,
+                Some(_) => return Err(syntax_error(cursor.pos, "expected ',' or '}'")),
+                None => return Err(truncated_error(json_str)),
+            }
+        }
+    }
+    cursor.pos += 1;
+    cursor.skip_whitespace();
+    if cursor.peek().is_some() {
+        return Err(syntax_error(cursor.pos, "trailing characters"));
+    }
+
+    match found {
+        Some(value_range) => {
+            let opts = LoadsOptions::default();
+            decode(json_str, value_range, PyObjectSeed { py, opts: &opts, depth: 0 })
+        }
+        None => Ok(default.unwrap_or_else(|| py.None())),
+    }
+}
+
+/// Parse the first complete JSON value in `json_str`, ignoring what follows.
+///
+/// Where `loads` rejects trailing data, this stops at the end of the first
+/// top-level value, for peeking at the start of a stream or reading one
+/// framed message. Scalars at the root must be followed by whitespace, a
+/// delimiter or the end of input (`"1x"` is an error, `"1 x"` is not).
+/// Nesting stops at 127 levels, serde_json's recursion limit, where `loads`
+/// goes to 1024.
+///
+/// # Arguments
+/// * `json_str` - JSON text starting with a value (leading whitespace is skipped).
+/// * `intern_keys` - As for `loads`.
+///
+/// # Returns
+/// A `(value, end)` tuple where `end` is the byte offset just past the value
+/// in the UTF-8 encoding of `json_str`; equal to the character index for
+/// ASCII input. Raises `JSONDecodeError` if the value itself is malformed.
+#[pyfunction]
+#[pyo3(signature = (json_str, *, intern_keys = true))]
+pub fn loads_prefix(py: Python, json_str: &str, intern_keys: bool) -> PyResult<(PyObject, usize)> {
+    // Validate the first value and find where it ends, then decode exactly it
+    let mut stream = serde_json::Deserializer::from_str(json_str).into_iter::<IgnoredAny>();
+    stream
+        .next()
+        .ok_or_else(|| truncated_error(json_str))?
+        .map_err(|e| crate::decode_error(json_str, e))?;
+    let end = stream.byte_offset();
+
+    let opts = LoadsOptions { intern_keys, ..LoadsOptions::default() };
+    let value = decode(json_str, (0, end), PyObjectSeed { py, opts: &opts, depth: 0 })?;
+    Ok((value, end))
+}
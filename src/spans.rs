@@ -0,0 +1,208 @@
+//! Span-tracking parse mode exposed to Python as `rjson.loads_with_spans`
+//!
+//! Editor integrations need to map a parsed value back to the source text it
+//! came from. The document is first validated with serde_json (so errors read
+//! exactly like `loads`), then walked by a small scanner that records the
+//! start/end offset around every value. Leaf values are still decoded by the
+//! serde seeds, so strings and numbers come out identical to `loads`.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::{PyDict, PyList};
+use serde::de::{DeserializeSeed, IgnoredAny};
+
+use crate::optimizations::simd_parser;
+use crate::{KeySeed, LoadsOptions, PyObjectSeed};
+
+/// One recorded value: the Python object and its `[start, end)` byte range
+type Span = (PyObject, usize, usize);
+
+/// Recursive scanner over an already-validated JSON document
+struct SpanScanner<'a, 'py> {
+    py: Python<'py>,
+    src: &'a str,
+    pos: usize,
+    opts: &'a LoadsOptions,
+    /// Values in document order (pre-order: a container precedes its children)
+    spans: Vec<Span>,
+}
+
+impl<'a, 'py> SpanScanner<'a, 'py> {
+    #[inline]
+    fn peek(&self) -> u8 {
+        self.src.as_bytes()[self.pos]
+    }
+
+    #[inline]
+    fn skip_whitespace(&mut self) {
+        let bytes = self.src.as_bytes();
+        while self.pos < bytes.len() && matches!(bytes[self.pos], b' ' | b'\t' | b'\n' | b'\r') {
+            self.pos += 1;
+        }
+    }
+
+    /// Advance past the string starting at `self.pos` (opening quote)
+    fn skip_string(&mut self) {
+        let bytes = self.src.as_bytes();
+        self.pos += 1;
+        while bytes[self.pos] != b'"' {
+            self.pos += if bytes[self.pos] == b'\\' { 2 } else { 1 };
+        }
+        self.pos += 1;
+    }
+
+    /// Advance past a number or `true`/`false`/`null` literal
+    fn skip_scalar(&mut self) {
+        let bytes = self.src.as_bytes();
+        while self.pos < bytes.len()
+            && !matches!(bytes[self.pos], b',' | b']' | b'}' | b' ' | b'\t' | b'\n' | b'\r')
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// Decode `src[start..self.pos]` with the regular `loads` seeds
+    fn decode<S>(&self, start: usize, seed: S) -> PyResult<PyObject>
+    where
+        S: for<'de> DeserializeSeed<'de, Value = PyObject>,
+    {
+        let mut de = serde_json::Deserializer::from_str(&self.src[start..self.pos]);
+        seed.deserialize(&mut de)
+            .map_err(|e| PyValueError::new_err(format!("JSON parsing error: {e}")))
+    }
+
+    fn parse_value(&mut self) -> PyResult<PyObject> {
+        self.skip_whitespace();
+        let start = self.pos;
+        // Reserve the slot now so containers are listed before their children
+        let index = self.spans.len();
+        self.spans.push((self.py.None(), start, start));
+
+        let value = match self.peek() {
+            b'{' => self.parse_object()?,
+            b'[' => self.parse_array()?,
+            b'"' => {
+                self.skip_string();
+                self.decode(start, PyObjectSeed { py: self.py, opts: self.opts, depth: 0 })?
+            }
+            _ => {
+                self.skip_scalar();
+                self.decode(start, PyObjectSeed { py: self.py, opts: self.opts, depth: 0 })?
+            }
+        };
+
+        self.spans[index] = (value.clone_ref(self.py), start, self.pos);
+        Ok(value)
+    }
+
+    fn parse_array(&mut self) -> PyResult<PyObject> {
+        let list = PyList::empty(self.py);
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek() == b']' {
+            self.pos += 1;
+            return Ok(list.into_any().unbind());
+        }
+
+        loop {
+            list.append(self.parse_value()?)?;
+            self.skip_whitespace();
+            let delimiter = self.peek();
+            self.pos += 1;
+            if delimiter == b']' {
+                return Ok(list.into_any().unbind());
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> PyResult<PyObject> {
+        let dict = PyDict::new(self.py);
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek() == b'}' {
+            self.pos += 1;
+            return Ok(dict.into_any().unbind());
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key_start = self.pos;
+            self.skip_string();
+            let key = self.decode(key_start, KeySeed { py: self.py, opts: self.opts })?;
+
+            // Skip the ':' separator
+            self.skip_whitespace();
+            self.pos += 1;
+
+            let value = self.parse_value()?;
+            dict.set_item(key, value)?;
+
+            self.skip_whitespace();
+            let delimiter = self.peek();
+            self.pos += 1;
+            if delimiter == b'}' {
+                return Ok(dict.into_any().unbind());
+            }
+        }
+    }
+}
+
+/// Map byte offsets to `str` (code point) offsets
+///
+/// Returns `None` for ASCII input, where the two coincide.
+fn char_offsets(src: &str) -> Option<Vec<usize>> {
+    if src.is_ascii() {
+        return None;
+    }
+    let mut offsets = vec![0; src.len() + 1];
+    for (char_index, (byte_index, ch)) in src.char_indices().enumerate() {
+        for slot in &mut offsets[byte_index..byte_index + ch.len_utf8()] {
+            *slot = char_index;
+        }
+    }
+    offsets[src.len()] = src.chars().count();
+    Some(offsets)
+}
+
+/// Parses JSON and records the source span of every value.
+///
+/// # Arguments
+/// * `json_str` - The JSON string to parse.
+/// * `intern_keys` - Share dict key objects through the intern cache (default).
+///
+/// # Returns
+/// A list of `(value, start, end)` tuples in document order, the root value
+/// first and every container before its children. Offsets index the input
+/// `str`, so `json_str[start:end]` is the source text of `value`. Nested
+/// values are the same objects that appear inside their parent. Invalid JSON
+/// raises a PyValueError exactly like `loads`, but nesting is limited to
+/// 127 levels (as in serde_json), not the 1024 `loads` allows.
+#[pyfunction]
+#[pyo3(signature = (json_str, *, intern_keys = true))]
+pub fn loads_with_spans<'py>(
+    py: Python<'py>,
+    json_str: &str,
+    intern_keys: bool,
+) -> PyResult<Bound<'py, PyList>> {
+    // Validate up front so the scanner can assume well-formed input
+    serde_json::from_str::<IgnoredAny>(json_str).map_err(|e| crate::decode_error(json_str, e))?;
+    // Skipping doesn't recurse, so the scanner's depth is checked here, at
+    // the limit serde_json would have applied
+    let too_deep = simd_parser::nesting_offset(json_str.as_bytes(), crate::CALLER_STACK_DEPTH);
+    if too_deep < json_str.len() {
+        return Err(crate::decode_error_at(json_str, too_deep, "JSON parsing error: recursion limit exceeded".into()));
+    }
+
+    let opts = LoadsOptions { intern_keys, ..LoadsOptions::default() };
+    let mut scanner = SpanScanner { py, src: json_str, pos: 0, opts: &opts, spans: Vec::new() };
+    scanner.parse_value()?;
+
+    let offsets = char_offsets(json_str);
+    let to_char = |byte: usize| offsets.as_ref().map_or(byte, |map| map[byte]);
+
+    let result = PyList::empty(py);
+    for (value, start, end) in scanner.spans {
+        result.append((value, to_char(start), to_char(end)))?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,225 @@
+//! Incremental decoding of a stream of JSON values, `rjson.JsonStreamDecoder`
+//!
+//! Socket protocols deliver JSON in arbitrary chunks. The decoder buffers the
+//! bytes fed to it and hands back each top-level value once all of it has
+//! arrived: values may be concatenated (`{"a":1}{"b":2}`), whitespace or
+//! newline separated, or RFC 7464 JSON text sequences (each value preceded by
+//! `0x1E`). Finding where a value ends is done by a scanner that keeps its
+//! state between feeds, so a large value arriving in many chunks is scanned
+//! once; the complete value is then decoded with the serde seeds, so results
+//! and errors match `loads`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::de::DeserializeSeed;
+
+use crate::{LoadsOptions, PyObjectSeed};
+
+/// Consumed input kept in the buffer before `feed` moves the rest down
+const COMPACT_THRESHOLD: usize = 64 * 1024;
+
+/// Where the scanner is inside the value being received
+#[derive(Debug, Default, Clone, Copy)]
+struct ScanState {
+    /// Offset of the value's first byte; `None` between values
+    start: Option<usize>,
+    /// Open `[`/`{` count
+    depth: usize,
+    in_string: bool,
+    /// The byte before the buffer end was a backslash inside a string
+    escaped: bool,
+}
+
+/// Incremental decoder for a stream of JSON values fed in chunks.
+///
+/// `feed` appends data (str, bytes or bytearray); `next` returns the next
+/// complete top-level value, or `default` when the buffered data ends inside
+/// one. Values may be split anywhere, including inside strings, numbers and
+/// multi-byte characters. Iterating yields every value that is complete so
+/// far, which also tells a JSON `null` apart from "need more input".
+///
+/// A number or literal at the very end of the data can't be known to be
+/// complete (`12` may continue as `123`), so it's held back until a
+/// delimiter arrives or `close()` marks the end of the stream.
+///
+/// Values nested more than 127 levels deep raise `JSONDecodeError`; unlike
+/// `loads`, the decoder doesn't follow `default_max_depth`.
+///
+/// ```python
+/// decoder = rjson.JsonStreamDecoder()
+/// while chunk := sock.recv(65536):
+///     decoder.feed(chunk)
+///     for value in decoder:
+///         handle(value)
+/// ```
+///
+/// # Arguments
+/// * `intern_keys` - As for `loads`.
+#[pyclass(module = "rjson")]
+pub struct JsonStreamDecoder {
+    buf: Vec<u8>,
+    /// Scan position in `buf`
+    pos: usize,
+    state: ScanState,
+    closed: bool,
+    opts: LoadsOptions,
+}
+
+impl JsonStreamDecoder {
+    /// Advance the scan; the end offset of the current value once complete
+    fn scan(&mut self) -> Option<usize> {
+        let bytes = &self.buf[..];
+        if self.state.start.is_none() {
+            // Whitespace and RFC 7464 record separators between values
+            while matches!(bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r' | 0x1E)) {
+                self.pos += 1;
+            }
+            let first = *bytes.get(self.pos)?;
+            self.state = ScanState { start: Some(self.pos), ..ScanState::default() };
+            self.pos += 1;
+            match first {
+                b'"' => self.state.in_string = true,
+                b'{' | b'[' => self.state.depth = 1,
+                // Can't start a value: returned alone for the parser to reject
+                b']' | b'}' | b',' | b':' => return Some(self.pos),
+                _ => {}
+            }
+        }
+
+        loop {
+            if self.state.in_string {
+                if self.state.escaped {
+                    if self.pos == bytes.len() {
+                        return None;
+                    }
+                    self.pos += 1;
+                    self.state.escaped = false;
+                }
+                let Some(offset) = memchr::memchr2(b'"', b'\\', &bytes[self.pos..]) else {
+                    self.pos = bytes.len();
+                    return None;
+                };
+                self.pos += offset + 1;
+                if bytes[self.pos - 1] == b'\\' {
+                    self.state.escaped = true;
+                    continue;
+                }
+                self.state.in_string = false;
+                if self.state.depth == 0 {
+                    return Some(self.pos);
+                }
+            } else if self.state.depth == 0 {
+                // Number or literal: ends at the first byte that can't be part of it
+                let rest = &bytes[self.pos..];
+                let delimiter = |b: &u8| matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b',' | b'[' | b']' | b'{' | b'}' | b'"' | 0x1E);
+                match rest.iter().position(delimiter) {
+                    Some(offset) => {
+                        self.pos += offset;
+                        return Some(self.pos);
+                    }
+                    None => {
+                        self.pos = bytes.len();
+                        return None;
+                    }
+                }
+            } else {
+                let byte = *bytes.get(self.pos)?;
+                self.pos += 1;
+                match byte {
+                    b'"' => self.state.in_string = true,
+                    b'{' | b'[' => self.state.depth += 1,
+                    b'}' | b']' => {
+                        self.state.depth -= 1;
+                        if self.state.depth == 0 {
+                            return Some(self.pos);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Decode the next complete value, `None` if more input is needed
+    fn decode_next(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        let end = match self.scan() {
+            Some(end) => end,
+            // At end of stream a held-back scalar is complete, and anything
+            // else truncated is left for the parser to report
+            None if self.closed && self.state.start.is_some() => self.buf.len(),
+            None => return Ok(None),
+        };
+        let start = self.state.start.take().unwrap_or(end);
+        self.pos = end;
+        self.state = ScanState::default();
+
+        let text = std::str::from_utf8(&self.buf[start..end]).map_err(crate::invalid_utf8_error)?;
+        let mut de = serde_json::Deserializer::from_str(text);
+        PyObjectSeed { py, opts: &self.opts, depth: 0 }
+            .deserialize(&mut de)
+            .and_then(|value| de.end().map(|()| value))
+            .map(Some)
+            .map_err(|e| PyErr::take(py).unwrap_or_else(|| crate::decode_error(text, e)))
+    }
+}
+
+#[pymethods]
+impl JsonStreamDecoder {
+    #[new]
+    #[pyo3(signature = (*, intern_keys = true))]
+    fn new(intern_keys: bool) -> Self {
+        JsonStreamDecoder {
+            buf: Vec::new(),
+            pos: 0,
+            state: ScanState::default(),
+            closed: false,
+            opts: LoadsOptions { intern_keys, ..LoadsOptions::default() },
+        }
+    }
+
+    /// Append a chunk of the stream (str, bytes or bytearray)
+    fn feed(&mut self, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        if self.closed {
+            return Err(PyValueError::new_err("feed() called after close()"));
+        }
+        // Drop consumed input so the buffer only holds the current value
+        let consumed = self.state.start.unwrap_or(self.pos);
+        if consumed >= COMPACT_THRESHOLD && consumed * 2 >= self.buf.len() {
+            self.buf.drain(..consumed);
+            self.pos -= consumed;
+            self.state.start = self.state.start.map(|start| start - consumed);
+        }
+        self.buf.extend_from_slice(&crate::json_bytes(data)?);
+        Ok(())
+    }
+
+    /// Mark the end of the stream: a trailing number or literal becomes
+    /// complete, and a truncated value raises `JSONDecodeError` from `next`
+    fn close(&mut self) {
+        self.closed = true;
+    }
+
+    /// The next complete value, or `default` if the buffered data ends
+    /// inside one (or holds nothing more)
+    ///
+    /// Malformed values raise `JSONDecodeError` (positioned within the
+    /// value) and are skipped, so decoding can continue after them.
+    #[pyo3(signature = (default = None))]
+    fn next(&mut self, py: Python, default: Option<PyObject>) -> PyResult<PyObject> {
+        Ok(self.decode_next(py)?.unwrap_or_else(|| default.unwrap_or_else(|| py.None())))
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        self.decode_next(py)
+    }
+
+    /// Bytes received but not yet returned as values
+    #[getter]
+    fn pending(&self) -> usize {
+        self.buf.len() - self.state.start.unwrap_or(self.pos)
+    }
+}
@@ -0,0 +1,306 @@
+//! Typed decoding into dataclasses and attrs classes for `loads(into=...)`
+//!
+//! The document is parsed into plain dicts and lists as usual, then this pass
+//! walks it alongside a type: every object found where a dataclass is
+//! expected becomes an instance of that class, built from the converted
+//! field values. Field types come from `typing.get_type_hints`, so string
+//! annotations and `from __future__ import annotations` work; attrs fields
+//! declared with `attr.ib(type=...)` instead use that type.
+//!
+//! Understood types are dataclasses, attrs classes (found through
+//! `__attrs_attrs__`, without importing attrs until one is met), `list[T]`,
+//! `dict[str, T]` and `Optional[T]` (`T | None`), nested arbitrarily. Any other annotation
+//! (`int`, `str`, `Any`, `datetime`, ...) takes the parsed value unchanged:
+//! this is structural decoding, not validation.
+//!
+//! Each class is inspected once per call, on first use, so self-referencing
+//! dataclasses (`children: list["Node"]`) resolve without recursing.
+
+use std::rc::Rc;
+
+use ahash::AHashMap;
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::{PyDict, PyList, PyString, PyTuple, PyType};
+
+/// What to do with object keys that aren't fields of the target class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExtraKeys {
+    /// Drop them (default)
+    Ignore,
+    /// Raise ValueError
+    Error,
+}
+
+impl ExtraKeys {
+    pub(crate) fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "ignore" => Ok(ExtraKeys::Ignore),
+            "error" => Ok(ExtraKeys::Error),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid on_extra: {name:?} (expected 'ignore' or 'error')"
+            ))),
+        }
+    }
+}
+
+/// What to do when an object lacks a field that has no default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MissingKeys {
+    /// Raise ValueError (default)
+    Error,
+    /// Pass `None` for the field
+    Null,
+}
+
+impl MissingKeys {
+    pub(crate) fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "error" => Ok(MissingKeys::Error),
+            "none" => Ok(MissingKeys::Null),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid on_missing: {name:?} (expected 'error' or 'none')"
+            ))),
+        }
+    }
+}
+
+/// How to convert a value expected to have some type
+enum Plan {
+    /// Leave the parsed value as is
+    Keep,
+    /// Build an instance of this dataclass or attrs class from an object
+    Class(Py<PyType>),
+    List(Box<Plan>),
+    /// Convert the values of an object
+    Dict(Box<Plan>),
+    /// `None` stays `None`, anything else follows the inner plan
+    Optional(Box<Plan>),
+}
+
+struct Field {
+    /// The object key, and the attribute name
+    name: Py<PyString>,
+    /// The `__init__` argument: `name`, except for private attrs fields
+    arg: Py<PyString>,
+    plan: Plan,
+    /// No default or default factory
+    required: bool,
+}
+
+/// Constructor fields of one dataclass or attrs class
+struct Schema {
+    fields: Vec<Field>,
+    by_name: AHashMap<String, usize>,
+}
+
+/// Per-call state: the typing helpers and every schema built so far
+struct Resolver<'py> {
+    py: Python<'py>,
+    get_type_hints: Bound<'py, PyAny>,
+    get_origin: Bound<'py, PyAny>,
+    get_args: Bound<'py, PyAny>,
+    /// `typing.Union`, plus `types.UnionType` (`X | Y`) on Python 3.10+
+    union_types: Vec<Bound<'py, PyAny>>,
+    fields: Bound<'py, PyAny>,
+    missing: Bound<'py, PyAny>,
+    schemas: AHashMap<usize, Rc<Schema>>,
+    on_extra: ExtraKeys,
+    on_missing: MissingKeys,
+}
+
+impl<'py> Resolver<'py> {
+    fn new(py: Python<'py>, on_extra: ExtraKeys, on_missing: MissingKeys) -> PyResult<Self> {
+        let typing = py.import("typing")?;
+        let dataclasses = py.import("dataclasses")?;
+        Ok(Resolver {
+            py,
+            get_type_hints: typing.getattr("get_type_hints")?,
+            get_origin: typing.getattr("get_origin")?,
+            get_args: typing.getattr("get_args")?,
+            union_types: std::iter::once(typing.getattr("Union")?)
+                .chain(py.import("types")?.getattr("UnionType").ok())
+                .collect(),
+            fields: dataclasses.getattr("fields")?,
+            missing: dataclasses.getattr("MISSING")?,
+            schemas: AHashMap::new(),
+            on_extra,
+            on_missing,
+        })
+    }
+
+    /// Work out how to convert values annotated with `hint`
+    fn plan(&self, hint: &Bound<'py, PyAny>) -> PyResult<Plan> {
+        if let Ok(cls) = hint.downcast::<PyType>() {
+            if cls.hasattr("__dataclass_fields__")? || cls.hasattr("__attrs_attrs__")? {
+                return Ok(Plan::Class(cls.clone().unbind()));
+            }
+        }
+
+        let origin = self.get_origin.call1((hint,))?;
+        if origin.is_none() {
+            return Ok(Plan::Keep);
+        }
+        let args = self.get_args.call1((hint,))?;
+        let args = args.downcast::<PyTuple>()?;
+
+        if origin.is(&self.py.get_type::<PyList>()) && args.len() == 1 {
+            return Ok(Plan::List(Box::new(self.plan(&args.get_item(0)?)?)));
+        }
+        if origin.is(&self.py.get_type::<PyDict>()) && args.len() == 2 {
+            return Ok(Plan::Dict(Box::new(self.plan(&args.get_item(1)?)?)));
+        }
+        if self.union_types.iter().any(|t| origin.is(t)) {
+            let none_type = self.py.None().bind(self.py).get_type();
+            let inner: Vec<_> = args.iter().filter(|arg| !arg.is(&none_type)).collect();
+            if let [inner] = inner.as_slice() {
+                return Ok(Plan::Optional(Box::new(self.plan(inner)?)));
+            }
+        }
+        Ok(Plan::Keep)
+    }
+
+    /// Fields of `cls`, inspected on first use
+    fn schema(&mut self, cls: &Bound<'py, PyType>) -> PyResult<Rc<Schema>> {
+        if let Some(schema) = self.schemas.get(&(cls.as_ptr() as usize)) {
+            return Ok(Rc::clone(schema));
+        }
+
+        let hints = self.get_type_hints.call1((cls,))?;
+        let hints = hints.downcast::<PyDict>()?;
+        // attrs `Attribute`s have `name`, `init`, `default` (a `Factory` for
+        // factories, `NOTHING` if none) and `type`, like dataclass fields
+        let (declared, missing, is_attrs) = match cls.getattr("__attrs_attrs__") {
+            Ok(attributes) => (attributes, self.py.import("attr")?.getattr("NOTHING")?, true),
+            Err(_) => (self.fields.call1((cls,))?, self.missing.clone(), false),
+        };
+        let mut fields = Vec::new();
+        let mut by_name = AHashMap::new();
+        for field in declared.try_iter()? {
+            let field = field?;
+            if !field.getattr("init")?.is_truthy()? {
+                continue;
+            }
+            let name = field.getattr("name")?.downcast_into::<PyString>()?;
+            let hint = match hints.get_item(&name)? {
+                Some(hint) => Some(hint),
+                None => Some(field.getattr("type")?).filter(|hint| !hint.is_none()),
+            };
+            let plan = match hint {
+                Some(hint) => self.plan(&hint)?,
+                None => Plan::Keep,
+            };
+            let required = if is_attrs {
+                field.getattr("default")?.is(&missing)
+            } else {
+                field.getattr("default")?.is(&missing) && field.getattr("default_factory")?.is(&missing)
+            };
+            let arg = if is_attrs { attrs_init_arg(&field, &name)? } else { name.clone() };
+            by_name.insert(name.to_str()?.to_owned(), fields.len());
+            fields.push(Field { name: name.unbind(), arg: arg.unbind(), plan, required });
+        }
+
+        let schema = Rc::new(Schema { fields, by_name });
+        self.schemas.insert(cls.as_ptr() as usize, Rc::clone(&schema));
+        Ok(schema)
+    }
+
+    fn convert(&mut self, value: Bound<'py, PyAny>, plan: &Plan) -> PyResult<PyObject> {
+        match plan {
+            Plan::Keep => Ok(value.unbind()),
+            Plan::Optional(_) if value.is_none() => Ok(value.unbind()),
+            Plan::Optional(inner) => self.convert(value, inner),
+            Plan::Class(cls) => {
+                let cls = cls.bind(self.py).clone();
+                match value.downcast::<PyDict>() {
+                    Ok(object) => self.build(&cls, object),
+                    Err(_) => Err(PyValueError::new_err(format!(
+                        "expected a JSON object for {}, got {}",
+                        cls.name()?,
+                        value.get_type().name()?
+                    ))),
+                }
+            }
+            Plan::List(inner) => match value.downcast::<PyList>() {
+                Ok(list) => {
+                    let items = list.iter().map(|item| self.convert(item, inner)).collect::<PyResult<Vec<_>>>()?;
+                    Ok(PyList::new(self.py, items)?.into_any().unbind())
+                }
+                Err(_) => Ok(value.unbind()),
+            },
+            Plan::Dict(inner) => match value.downcast::<PyDict>() {
+                Ok(object) => {
+                    let converted = PyDict::new(self.py);
+                    for (key, item) in object.iter() {
+                        converted.set_item(key, self.convert(item, inner)?)?;
+                    }
+                    Ok(converted.into_any().unbind())
+                }
+                Err(_) => Ok(value.unbind()),
+            },
+        }
+    }
+
+    /// Construct `cls` from a parsed object
+    fn build(&mut self, cls: &Bound<'py, PyType>, object: &Bound<'py, PyDict>) -> PyResult<PyObject> {
+        let schema = self.schema(cls)?;
+        let kwargs = PyDict::new(self.py);
+        let mut seen = vec![false; schema.fields.len()];
+
+        for (key, value) in object.iter() {
+            let slot = key.downcast::<PyString>()?.to_str().ok().and_then(|k| schema.by_name.get(k));
+            let Some(&slot) = slot else {
+                if self.on_extra == ExtraKeys::Error {
+                    return Err(PyValueError::new_err(format!("unexpected key {key} for {}", cls.name()?)));
+                }
+                continue;
+            };
+            let field = &schema.fields[slot];
+            seen[slot] = true;
+            kwargs.set_item(field.arg.bind(self.py), self.convert(value, &field.plan)?)?;
+        }
+
+        for (field, _) in schema.fields.iter().zip(seen).filter(|(f, seen)| f.required && !seen) {
+            match self.on_missing {
+                MissingKeys::Error => {
+                    return Err(PyValueError::new_err(format!(
+                        "missing key '{}' for {}",
+                        field.name.bind(self.py),
+                        cls.name()?
+                    )))
+                }
+                MissingKeys::Null => kwargs.set_item(field.arg.bind(self.py), self.py.None())?,
+            }
+        }
+
+        Ok(cls.call((), Some(&kwargs))?.unbind())
+    }
+}
+
+/// The `__init__` argument of attrs attribute `name`: its `alias` (attrs
+/// 22.2+), or the name with leading underscores stripped as older attrs did
+fn attrs_init_arg<'py>(attribute: &Bound<'py, PyAny>, name: &Bound<'py, PyString>) -> PyResult<Bound<'py, PyString>> {
+    match attribute.getattr("alias") {
+        Ok(alias) if !alias.is_none() => Ok(alias.downcast_into::<PyString>()?),
+        _ => Ok(PyString::new(attribute.py(), name.to_str()?.trim_start_matches('_'))),
+    }
+}
+
+/// Convert a parsed document to the type `into` (a dataclass or attrs class,
+/// or a `list[...]`/`dict[str, ...]`/`Optional[...]` of them)
+pub(crate) fn decode_into(
+    py: Python,
+    value: PyObject,
+    into: &Bound<'_, PyAny>,
+    on_extra: ExtraKeys,
+    on_missing: MissingKeys,
+) -> PyResult<PyObject> {
+    let mut resolver = Resolver::new(py, on_extra, on_missing)?;
+    let plan = resolver.plan(into)?;
+    if matches!(plan, Plan::Keep) {
+        return Err(PyValueError::new_err(format!(
+            "into must be a dataclass or attrs class, or a list/dict/Optional of one, got {into}"
+        )));
+    }
+    resolver.convert(value.into_bound(py), &plan)
+}
@@ -12,7 +12,13 @@ Tests cover:
 
 import pytest
 import rjson
+import dataclasses
+import datetime
+import io
 import math
+import pathlib
+import uuid
+from decimal import Decimal
 
 
 class TestBasicTypes:
@@ -136,6 +142,22 @@ class TestCollections:
         serialized = rjson.dumps(data)
         assert rjson.loads(serialized) == data
 
+    @pytest.mark.parametrize("dumps", [rjson.dumps, lambda o: rjson.dumps_bytes(o).decode()])
+    def test_instance_dict(self, dumps):
+        # Instance __dict__s are split-table dicts (keys shared with the class,
+        # values stored separately), unlike the combined dicts literals make
+        class Point:
+            def __init__(self, x, y):
+                self.x = x
+                self.y = y
+
+        a, b = Point(1, "one"), Point(2, "two")
+        b.z = [3]  # extra attribute on one instance
+        del a.x  # deleted attribute leaves a hole
+        assert dumps(vars(a)) == '{"y":"one"}'
+        assert dumps(vars(b)) == '{"x":2,"y":"two","z":[3]}'
+        assert dumps([vars(Point(i, i)) for i in range(3)]) == '[{"x":0,"y":0},{"x":1,"y":1},{"x":2,"y":2}]'
+
 
 class TestNestedStructures:
     """Test deeply nested data structures."""
@@ -202,6 +224,42 @@ class TestUnicode:
         serialized = rjson.dumps(data)
         assert rjson.loads(serialized) == data
 
+    def test_non_ascii_with_escapes(self):
+        # Raw multibyte UTF-8 next to escapes must not be decoded byte-wise
+        doc = '{"caf\u00e9\\t": "caf\u00e9\\n\\u00e9 \u65e5\U0001f600\\""}'
+        expected = {"caf\u00e9\t": "caf\u00e9\n\u00e9 \u65e5\U0001f600\""}
+        assert rjson.loads(doc) == expected
+        assert rjson.loads(doc.encode()) == expected
+        assert rjson.loads_simd(doc) == expected
+        assert rjson.loads_with_spans(doc)[0][0] == expected
+        assert rjson.json_get(doc, "caf\u00e9\t") == expected["caf\u00e9\t"]
+        assert rjson.loads(doc, lazy=True).materialize() == expected
+
+    def test_surrogate_pair_key(self):
+        doc = '{"\\ud83d\\ude00": "\\ud83d\\ude00", "a\U0001f600": 1}'
+        expected = {"\U0001f600": "\U0001f600", "a\U0001f600": 1}
+        assert rjson.loads(doc) == expected
+        assert rjson.loads(doc, intern_keys=False) == expected
+        assert rjson.loads_simd(doc) == expected
+        assert rjson.json_get(doc, "\U0001f600") == "\U0001f600"
+        assert rjson.loads(doc, lazy=True)["\U0001f600"] == "\U0001f600"
+
+    @pytest.mark.parametrize("data", [
+        "\ud800",
+        ["a", "\ud800"],
+        ["s"] * 10 + ["x\udc00"],
+        {"a": "\ud800"},
+        {"\ud800": 1},
+        {"b": 1, "\ud800": 2},
+    ])
+    def test_lone_surrogate_raises(self, data):
+        with pytest.raises(UnicodeEncodeError):
+            rjson.dumps(data)
+        with pytest.raises(UnicodeEncodeError):
+            rjson.dumps(data, sort_keys=True)
+        with pytest.raises(UnicodeEncodeError):
+            rjson.dumps_bytes(data)
+
 
 class TestEdgeCases:
     """Test edge cases and boundary conditions."""
@@ -256,6 +314,21 @@ class TestErrorHandling:
         with pytest.raises(ValueError, match="Cannot serialize non-finite float"):
             rjson.dumps(float("-inf"))
 
+    @pytest.mark.parametrize("value", [float("nan"), float("inf"), float("-inf")])
+    def test_non_finite_consistent_across_engines(self, value):
+        # scalar, generic (mixed list), bulk float array, dict value
+        cases = [value, [1, value], [1.5, value], {"a": value}]
+        messages = set()
+        for engine in (rjson.dumps, rjson.dumps_bytes):
+            for data in cases:
+                try:
+                    engine(data)
+                except ValueError as exc:
+                    messages.add(str(exc))
+                else:
+                    raise AssertionError(f"{engine.__name__} accepted {data!r}")
+        assert messages == {f"Cannot serialize non-finite float: {value!r}".replace("nan", "NaN")}
+
     def test_dumps_unsupported_type_raises(self):
         class CustomClass:
             pass
@@ -263,9 +336,65 @@ class TestErrorHandling:
         with pytest.raises(ValueError, match="Unsupported Python type"):
             rjson.dumps(CustomClass())
 
-    def test_dumps_dict_non_string_key_raises(self):
-        with pytest.raises(ValueError, match="keys must be strings"):
-            rjson.dumps({1: "value"})
+    def test_dumps_unsupported_error_names_key(self):
+        with pytest.raises(ValueError, match="at key 'foo': ellipsis"):
+            rjson.dumps({"ok": 1, "foo": ...})
+        with pytest.raises(ValueError, match="at key 'bar': NotImplementedType"):
+            rjson.dumps({"outer": {"bar": [1, NotImplemented]}})
+
+    def test_dumps_unsupported_error_key_restored_after_nested_dict(self):
+        # The error is raised in "outer" after the nested dict finished
+        with pytest.raises(ValueError, match="at key 'outer': ellipsis"):
+            rjson.dumps({"outer": [{"inner": 1}, ...]})
+
+    def test_dumps_unsupported_error_top_level_has_no_key(self):
+        with pytest.raises(ValueError, match="serialization: ellipsis"):
+            rjson.dumps([1, ...])
+
+    def test_dumps_dict_unsupported_key_raises(self):
+        for key in [(1, 2), b"k", object()]:
+            with pytest.raises(ValueError, match="keys must be strings"):
+                rjson.dumps({key: "value"})
+            with pytest.raises(ValueError, match="keys must be strings"):
+                rjson.dumps_bytes({key: "value"})
+
+    def test_scalar_keys_coerced(self):
+        import enum
+
+        class Level(enum.IntEnum):
+            HIGH = 3
+
+        data = {4: "a", 2.5: "b", True: "c", None: "d", False: "e", -7: "f", 2**70: "g", Level.HIGH: "h"}
+        expected = (
+            '{"4":"a","2.5":"b","true":"c","null":"d","false":"e","-7":"f",'
+            '"1180591620717411303424":"g","3":"h"}'
+        )
+        assert rjson.dumps(data) == expected
+        assert rjson.dumps_bytes(data) == expected.encode()
+        # priority_keys match str keys only; coerced keys stay in place
+        assert rjson.dumps({None: 1, "a": 2}, priority_keys=["a", "null"]) == '{"a":2,"null":1}'
+
+    def test_non_finite_float_keys(self):
+        with pytest.raises(ValueError, match="non-finite"):
+            rjson.dumps({float("nan"): 1})
+        data = {float("inf"): 1, float("-inf"): 2, float("nan"): 3}
+        assert rjson.dumps(data, nan_mode="string") == '{"Infinity":1,"-Infinity":2,"NaN":3}'
+
+    def test_str_subclass_keys(self):
+        import enum
+
+        class Key(str):
+            pass
+
+        class Color(str, enum.Enum):
+            RED = "red"
+
+        data = {Key("abc"): 1, Color.RED: 2, Key("caf\u00e9"): 3}
+        expected = '{"abc":1,"red":2,"caf\u00e9":3}'
+        assert rjson.dumps(data) == expected
+        assert rjson.dumps_bytes(data) == expected.encode()
+        assert rjson.dumps(data, sort_keys=True) == '{"abc":1,"caf\u00e9":3,"red":2}'
+        assert rjson.dumps(data, priority_keys=["red"]) == '{"red":2,"abc":1,"caf\u00e9":3}'
 
     def test_loads_invalid_json_raises(self):
         with pytest.raises(ValueError, match="JSON parsing error"):
@@ -280,6 +409,105 @@ class TestErrorHandling:
             rjson.loads('[1, 2, 3,]')
 
 
+class TestJSONDecodeError:
+    """Test the position-carrying exception raised for malformed JSON."""
+
+    def test_is_stdlib_decode_error(self):
+        import json
+
+        assert issubclass(rjson.JSONDecodeError, json.JSONDecodeError)
+        assert issubclass(rjson.JSONDecodeError, ValueError)
+        with pytest.raises(json.JSONDecodeError):
+            rjson.loads("{invalid json}")
+
+    @pytest.mark.parametrize("doc", [
+        "[1,",
+        '{"a": 1,\n  "b": x}',
+        "[1] 2",
+        '"\u00e9\u00e9" x',
+        "  \n",
+        '{"a" 1}',
+    ])
+    def test_position_matches_stdlib(self, doc):
+        import json
+
+        with pytest.raises(json.JSONDecodeError) as expected:
+            json.loads(doc)
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.loads(doc)
+        err, std = raised.value, expected.value
+        assert (err.pos, err.lineno, err.colno) == (std.pos, std.lineno, std.colno)
+        assert err.doc == doc
+
+    def test_message(self):
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.loads('{"a": 1,\n  "b": x}')
+        err = raised.value
+        assert err.msg == "JSON parsing error: expected value"
+        assert str(err) == "JSON parsing error: expected value: line 2 column 8 (char 16)"
+
+    def test_bytes_input(self):
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.loads('["\u00e9", ?]'.encode())
+        assert (raised.value.pos, raised.value.doc) == (6, '["\u00e9", ?]')
+
+    def test_large_stack_thread(self):
+        rjson.set_parse_stack_size(8 * 1024 * 1024)
+        try:
+            with pytest.raises(rjson.JSONDecodeError) as raised:
+                rjson.loads("[[1]")
+            assert raised.value.pos == 4
+        finally:
+            rjson.set_parse_stack_size(0)
+
+    def test_loads_with_spans(self):
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads_with_spans("[1,")
+
+    def test_lazy(self):
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.loads('{"a": [1, x]}', lazy=True)
+        assert (raised.value.pos, raised.value.doc) == (10, '{"a": [1, x]}')
+
+    def test_json_get(self):
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.json_get('{"a": 1 "b": 2}', "b")
+        assert raised.value.pos == 8
+        # Errors in the decoded value are positioned in the whole document
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.json_get('{"a": [1, x]}', "a")
+        assert (raised.value.pos, raised.value.doc) == (10, '{"a": [1, x]}')
+        with pytest.raises(rjson.JSONDecodeError, match="top-level JSON object") as raised:
+            rjson.json_get("  [1]", "a")
+        assert raised.value.pos == 2
+
+    def test_loads_prefix(self):
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.loads_prefix("[1, x] tail")
+        assert raised.value.pos == 4
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.loads_prefix("")
+        assert raised.value.pos == 0
+
+    def test_loads_simd(self):
+        with pytest.raises(rjson.JSONDecodeError) as raised:
+            rjson.loads_simd('{"a": 1 "b": 2}')
+        assert (raised.value.pos, raised.value.doc) == (8, '{"a": 1 "b": 2}')
+        with pytest.raises(rjson.JSONDecodeError, match="surrogate") as raised:
+            rjson.loads_simd('["\\ud800"]')
+        assert raised.value.pos == 2
+
+    def test_loads_simd_depth(self):
+        with pytest.raises(rjson.JSONDecodeError, match=r"max_depth \(2\)") as raised:
+            rjson.loads_simd('[{"a": [[1]]}]', max_depth=2)
+        assert raised.value.pos == 7
+
+    def test_non_syntax_errors_stay_value_error(self):
+        with pytest.raises(ValueError, match="top-level value") as raised:
+            rjson.loads("1", require_container=True)
+        assert not isinstance(raised.value, rjson.JSONDecodeError)
+
+
 class TestRoundTrip:
     """Test round-trip consistency (dumps -> loads == original)."""
 
@@ -326,6 +554,12 @@ class TestPerformance:
         serialized = rjson.dumps(data)
         assert len(rjson.loads(serialized)) == 1000
 
+    @pytest.mark.parametrize("length", [4, 31, 32, 33, 35, 1001])
+    def test_large_bool_list(self, length):
+        # Crosses the SIMD threshold and every 4-wide chunk remainder
+        data = [i % 3 == 0 for i in range(length)]
+        assert rjson.dumps(data) == "[" + ",".join("true" if b else "false" for b in data) + "]"
+
     def test_deeply_nested_list(self):
         # Create deeply nested list
         data = []
@@ -362,5 +596,4078 @@ class TestCompatibility:
         assert rjson.loads(rjson_result) == json.loads(json_result)
 
 
+class TestKeyInterning:
+    """Test that dict keys are shared through the intern cache."""
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_repeated_keys_share_object(self, engine):
+        # "name" is preloaded, so this holds even once the cache is full
+        result = getattr(rjson, engine)('[{"name": 1}, {"name": 2}]')
+        first, second = (next(iter(d)) for d in result)
+        assert first is second
+
+    def test_engines_share_interned_keys(self):
+        a = next(iter(rjson.loads('{"status": 1}')))
+        b = next(iter(rjson.loads_simd('{"status": 1}')))
+        assert a is b
+
+    def test_escaped_keys(self):
+        assert rjson.loads('{"a\\nb": 1, "caf\\u00e9": 2}') == {"a\nb": 1, "café": 2}
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_intern_keys_disabled(self, engine):
+        result = getattr(rjson, engine)('[{"name": 1}, {"name": 2}]', intern_keys=False)
+        assert result == [{"name": 1}, {"name": 2}]
+        first, second = (next(iter(d)) for d in result)
+        assert first is not second
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_intern_keys_disabled_nested(self, engine):
+        doc = '{"a": {"b\\u00e9": [1, {"c": null}]}, "d": "e"}'
+        expected = {"a": {"bé": [1, {"c": None}]}, "d": "e"}
+        assert getattr(rjson, engine)(doc, intern_keys=False) == expected
+
+
+class TestValueInterning:
+    """Test loads(intern_values=True)."""
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_repeated_values_share_object(self, engine):
+        # "active" is preloaded, so this holds even once the cache is full
+        result = getattr(rjson, engine)('[{"s": "active"}, {"s": "active"}, "active"]', intern_values=True)
+        assert result[0]["s"] is result[1]["s"] is result[2]
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_off_by_default(self, engine):
+        result = getattr(rjson, engine)('["active", "active"]')
+        assert result == ["active", "active"]
+        assert result[0] is not result[1]
+
+    def test_values_shared_with_keys(self):
+        result = rjson.loads('{"status": "status"}', intern_values=True)
+        key, value = next(iter(result.items()))
+        assert key is value
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_long_values_not_interned(self, engine):
+        long = "x" * 33
+        result = getattr(rjson, engine)('["%s", "%s"]' % (long, long), intern_values=True)
+        assert result == [long, long]
+        assert result[0] is not result[1]
+
+    def test_combined_with_other_string_options(self):
+        result = rjson.loads(
+            '["12", "NaN", "caf\\u0065\\u0301", "active"]',
+            intern_values=True, revive_bigint_strings=True, revive_nan_strings=True, normalize="NFC",
+        )
+        assert result[0] == 12 and result[1] != result[1]
+        assert result[2:] == ["café", "active"]
+
+
+class TestFloatRepr:
+    """Test the float_repr option of dumps."""
+
+    EXPONENT_VALUES = [1e16, 1.5e300, 1e-5, 1.23e-10, -2.5e-7, 5e-324, 1e22]
+    PLAIN_VALUES = [0.0, -0.0, 3.14, 0.0001, 123456.789, 9999999999999998.0]
+
+    def test_default_is_ryu(self):
+        assert rjson.dumps(1e16) == "1e16"
+        assert rjson.dumps(1e16, float_repr="ryu") == "1e16"
+
+    @pytest.mark.parametrize("mode", ["python", "hybrid"])
+    def test_matches_python_repr(self, mode):
+        for value in self.EXPONENT_VALUES + self.PLAIN_VALUES:
+            assert rjson.dumps(value, float_repr=mode) == repr(value)
+
+    @pytest.mark.parametrize("mode", ["python", "hybrid"])
+    def test_bulk_float_array(self, mode):
+        data = self.EXPONENT_VALUES + self.PLAIN_VALUES
+        assert rjson.dumps(data, float_repr=mode) == "[" + ",".join(map(repr, data)) + "]"
+
+    def test_nested_values(self):
+        data = {"a": [1e-5, {"b": 1e16}]}
+        assert rjson.dumps(data, float_repr="hybrid") == '{"a":[1e-05,{"b":1e+16}]}'
+
+    def test_invalid_mode_raises(self):
+        with pytest.raises(ValueError, match="Invalid float_repr"):
+            rjson.dumps(1.0, float_repr="fast")
+
+    def test_non_finite_still_rejected(self):
+        with pytest.raises(ValueError, match="Cannot serialize non-finite float"):
+            rjson.dumps(float("nan"), float_repr="python")
+
+
+class TestIterables:
+    """Test the iterables option of dumps."""
+
+    def test_generator(self):
+        assert rjson.dumps((i * 2 for i in range(5)), iterables=True) == "[0,2,4,6,8]"
+
+    def test_generator_is_consumed_lazily(self):
+        pulled = []
+
+        def gen():
+            for i in range(3):
+                pulled.append(i)
+                yield {"i": i}
+
+        assert rjson.dumps(gen(), iterables=True) == '[{"i":0},{"i":1},{"i":2}]'
+        assert pulled == [0, 1, 2]
+
+    def test_nested_iterators(self):
+        data = {"a": iter([1, 2]), "b": map(str, range(2)), "c": range(3)}
+        assert rjson.dumps(data, iterables=True) == '{"a":[1,2],"b":["0","1"],"c":[0,1,2]}'
+
+    def test_frozenset(self):
+        assert rjson.dumps(frozenset([7]), iterables=True) == "[7]"
+
+    @pytest.mark.parametrize("items", [
+        set(range(100)),
+        frozenset(f"s{i}" for i in range(50)),
+        {1.5, 2.5, 3.5},
+        {1, "a", None, (1, 2)},
+        set(),
+    ])
+    def test_sets_match_list_of_elements(self, items):
+        # Homogeneous sets go through the bulk list serializers
+        assert rjson.dumps(items, iterables=True) == rjson.dumps(list(items), iterables=True)
+
+    def test_set_with_stray_element_past_sample(self):
+        items = set(range(40)) | {"zz"}
+        assert rjson.loads(rjson.dumps(items, iterables=True)) == list(items)
+
+    @pytest.mark.parametrize("data", [
+        list(range(40)) + ["zz"],
+        [0.5] * 40 + [3],
+        ["s"] * 40 + [1],
+        [True, False] * 20 + [None],
+    ])
+    def test_list_with_stray_element_past_sample(self, data):
+        import json
+        assert rjson.dumps(data) == json.dumps(data, separators=(",", ":"))
+
+    def test_set_nested_in_dict(self):
+        assert rjson.dumps({"k": {3}}, iterables=True) == '{"k":[3]}'
+
+    def test_empty_generator(self):
+        assert rjson.dumps((x for x in []), iterables=True) == "[]"
+
+    def test_generator_error_propagates(self):
+        def gen():
+            yield 1
+            raise KeyError("boom")
+
+        with pytest.raises(KeyError):
+            rjson.dumps(gen(), iterables=True)
+
+    def test_iterator_yielding_itself(self):
+        class It:
+            def __iter__(self):
+                return self
+
+            def __next__(self):
+                return self
+
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps(It(), iterables=True)
+
+    def test_off_by_default(self):
+        with pytest.raises(ValueError, match="Unsupported Python type"):
+            rjson.dumps(x for x in [1])
+
+    def test_bytes_not_treated_as_iterable(self):
+        with pytest.raises(ValueError, match="Unsupported Python type"):
+            rjson.dumps(b"abc", iterables=True)
+
+    def test_mappings_not_treated_as_iterable(self):
+        import collections
+        import types
+
+        for mapping in [
+            types.MappingProxyType({"a": 1}),
+            collections.ChainMap({"a": 1}),
+            collections.UserDict({"a": 1}),
+        ]:
+            # Iterating would keep only the keys
+            with pytest.raises(ValueError, match="Unsupported Python type"):
+                rjson.dumps({"m": mapping}, iterables=True)
+            with pytest.raises(ValueError, match="Unsupported Python type"):
+                rjson.dumps(mapping, iterables=True, sequences=True)
+            assert rjson.dumps(mapping, iterables=True, default=dict) == '{"a":1}'
+
+
+class TestBenchmark:
+    """Test the rjson.bench timing harness."""
+
+    def test_reports_every_engine(self):
+        results = rjson.bench.benchmark({"a": [1, 2.5, "x", None]}, iterations=3)
+        for name in ["dumps", "dumps_bytes", "loads", "loads_simd", "json.dumps", "json.loads"]:
+            assert results[name]["iterations"] == 3
+            assert results[name]["min"] <= results[name]["mean"] <= results[name]["max"]
+        assert results["dumps"]["speedup_vs_json"] > 0
+        assert "speedup_vs_json" not in results["json.dumps"]
+
+    def test_top_level_alias(self):
+        assert rjson.benchmark is rjson.bench.benchmark
+
+    def test_unsupported_engine_reports_error(self):
+        # json.dumps writes NaN, which rjson rejects; the baselines still run
+        results = rjson.bench.benchmark([float("nan")], iterations=1)
+        assert "error" in results["dumps"]
+        assert "error" in results["dumps_bytes"]
+        assert results["json.dumps"]["iterations"] == 1
+        assert results["json.loads"]["iterations"] == 1
+
+    def test_zero_iterations_raises(self):
+        with pytest.raises(ValueError, match="iterations"):
+            rjson.bench.benchmark([], iterations=0)
+
+
+class TestNoneRepr:
+    """Test the none_repr option of dumps."""
+
+    def test_default_is_null(self):
+        assert rjson.dumps(None) == "null"
+        assert rjson.dumps(None, none_repr="null") == "null"
+
+    @pytest.mark.parametrize("repr_", ['""', '"null"', '"N/A"', '"\\u0000"'])
+    def test_string_sentinels(self, repr_):
+        data = {"a": None, "b": [1, None], "c": (None,)}
+        out = rjson.dumps(data, none_repr=repr_)
+        assert out == '{"a":%s,"b":[1,%s],"c":[%s]}' % (repr_, repr_, repr_)
+        assert rjson.loads(out)["a"] == rjson.loads(repr_)
+
+    def test_all_none_list(self):
+        assert rjson.dumps([None, None], none_repr='""') == '["",""]'
+
+    @pytest.mark.parametrize("repr_", ["", "NULL", "0", '"unterminated', '"a" "b"', "'x'", '"\n"'])
+    def test_invalid_rejected(self, repr_):
+        with pytest.raises(ValueError, match="Invalid none_repr"):
+            rjson.dumps(None, none_repr=repr_)
+
+
+class TestLoadsWithSpans:
+    """Test loads_with_spans source offsets."""
+
+    def test_spans_slice_source(self):
+        doc = '{"a": [1, 2.5, "x"], "b": {"c": null}}'
+        spans = rjson.loads_with_spans(doc)
+        assert spans[0][0] == rjson.loads(doc)
+        for value, start, end in spans:
+            assert rjson.loads(doc[start:end]) == value
+
+    def test_document_order(self):
+        doc = '[true, [false], {"k": 3}]'
+        texts = [doc[start:end] for _, start, end in rjson.loads_with_spans(doc)]
+        assert texts == [doc, "true", "[false]", "false", '{"k": 3}', "3"]
+
+    def test_children_are_shared_with_parent(self):
+        spans = rjson.loads_with_spans('{"a": [1]}')
+        root, inner = spans[0][0], spans[1][0]
+        assert root["a"] is inner
+
+    def test_whitespace_excluded(self):
+        doc = ' \n [ 1 , "a\\"b" ] \t'
+        spans = rjson.loads_with_spans(doc)
+        assert [doc[s:e] for _, s, e in spans] == ['[ 1 , "a\\"b" ]', "1", '"a\\"b"']
+
+    def test_offsets_count_code_points(self):
+        doc = '{"名前": "世界", "n": 1}'
+        spans = rjson.loads_with_spans(doc)
+        assert [doc[s:e] for _, s, e in spans] == [doc, '"世界"', "1"]
+
+    def test_empty_containers(self):
+        spans = rjson.loads_with_spans('[[], {}]')
+        assert [(s, e) for _, s, e in spans] == [(0, 8), (1, 3), (5, 7)]
+
+    def test_invalid_json_raises(self):
+        with pytest.raises(ValueError, match="JSON parsing error"):
+            rjson.loads_with_spans('{"a": [1,]}')
+
+    @pytest.mark.parametrize("depth", [128, 100_000])
+    def test_nesting_limit(self, depth):
+        assert rjson.loads_with_spans("[" * 127 + "]" * 127)[-1][0] == []
+        with pytest.raises(rjson.JSONDecodeError, match="recursion limit exceeded") as info:
+            rjson.loads_with_spans("[" * depth + "]" * depth)
+        assert info.value.pos == 127
+
+
+class TestDecimal:
+    """Test decimal.Decimal serialization (scalar and bulk arrays)."""
+
+    def test_scalar_exact(self):
+        assert rjson.dumps(Decimal("1.10")) == "1.10"
+        assert rjson.dumps(Decimal("-0")) == "-0"
+        assert rjson.dumps({"price": Decimal("19.99")}) == '{"price":19.99}'
+
+    def test_exponent_form_is_valid_json(self):
+        out = rjson.dumps([Decimal("1E+2"), Decimal("1.5E-7")])
+        assert rjson.loads(out) == [100.0, 1.5e-07]
+
+    def test_precision_survives_round_trip(self):
+        import json
+
+        # rjson.loads has no parse_float hook; the stdlib parser checks the
+        # token is exact where an f64 would round to 1.0
+        value = Decimal("1.000000000000000001")
+        out = rjson.dumps({"amount": value})
+        assert out == '{"amount":1.000000000000000001}'
+        assert json.loads(out, parse_float=Decimal) == {"amount": value}
+        assert rjson.loads(out) == {"amount": 1.0}
+
+    def test_bulk_array(self):
+        data = [Decimal(i) / 100 for i in range(1, 40)]
+        assert rjson.dumps(data) == "[" + ",".join(str(d) for d in data) + "]"
+
+    def test_bulk_array_with_stray_element_past_sample(self):
+        data = [Decimal("1.5")] * 20 + [{"a": 1}]
+        assert rjson.dumps(data) == "[" + "1.5," * 20 + '{"a":1}]'
+
+    @pytest.mark.parametrize("text", ["NaN", "sNaN", "Infinity", "-Infinity"])
+    def test_non_finite_rejected(self, text):
+        with pytest.raises(ValueError, match="Cannot serialize non-finite Decimal"):
+            rjson.dumps(Decimal(text))
+        with pytest.raises(ValueError, match="Cannot serialize non-finite Decimal"):
+            rjson.dumps([Decimal("1")] * 10 + [Decimal(text)])
+        with pytest.raises(ValueError, match="Cannot serialize non-finite Decimal"):
+            rjson.dumps_bytes(Decimal(text))
+
+    @pytest.mark.parametrize("data", [
+        Decimal("1.10"),
+        [Decimal(i) / 7 for i in range(30)],
+        {"a": Decimal("-0"), "b": [Decimal("1E+2"), 1, "x"]},
+    ])
+    def test_engines_agree(self, data):
+        # Bulk, per-element and dumps_bytes paths all share write_decimal
+        assert rjson.dumps_bytes(data) == rjson.dumps(data).encode()
+
+
+class TestParseStackSize:
+    """Test set_parse_stack_size for deeply nested documents."""
+
+    DEPTH = 5000
+
+    def test_default_limit_rejects_deep_nesting(self):
+        with pytest.raises(rjson.JSONDecodeError, match=r"max_depth \(1024\)"):
+            rjson.loads("[" * self.DEPTH + "]" * self.DEPTH)
+
+    def test_large_stack_parses_deep_nesting(self):
+        rjson.set_parse_stack_size(256 * 1024 * 1024)
+        try:
+            result = rjson.loads("[" * self.DEPTH + "1" + "]" * self.DEPTH)
+            depth = 0
+            while isinstance(result, list):
+                result = result[0]
+                depth += 1
+            assert (depth, result) == (self.DEPTH, 1)
+            # Errors and options still behave as usual on the parse thread
+            assert rjson.loads('{"a": [1]}', intern_keys=False) == {"a": [1]}
+            with pytest.raises(ValueError, match="JSON parsing error"):
+                rjson.loads("[1,")
+        finally:
+            rjson.set_parse_stack_size(0)
+
+    def test_too_small_rejected(self):
+        with pytest.raises(ValueError, match="parse stack size"):
+            rjson.set_parse_stack_size(1024)
+
+
+class TestLoadsMaxDepth:
+    """Test the max_depth option of loads and loads_simd."""
+
+    @staticmethod
+    def nested(depth):
+        return "[" * depth + "]" * depth
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_limit_is_inclusive(self, loads):
+        assert loads("[[1]]", max_depth=2) == [[1]]
+        assert loads('{"a": 1}', max_depth=1) == {"a": 1}
+        assert loads("1", max_depth=0) == 1
+        for doc in ("[[1]]", '{"a": {"b": 1}}', '[{"a": []}]', '{"a": [1]}'):
+            with pytest.raises(ValueError, match=r"nesting exceeds max_depth \(1\)"):
+                loads(doc, max_depth=1)
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_default_limit(self, loads):
+        import json
+        # Both engines accept whatever stdlib json does (about 900 levels)
+        assert loads(self.nested(900)) == json.loads(self.nested(900))
+        assert loads(self.nested(1024)) is not None
+        with pytest.raises(rjson.JSONDecodeError, match=r"max_depth \(1024\)"):
+            loads(self.nested(1025))
+        assert loads(self.nested(1500), max_depth=1500) is not None
+
+    def test_default_limit_in_threads(self):
+        import threading
+        results = []
+        thread = threading.Thread(target=lambda: results.append(rjson.loads(self.nested(1024))))
+        thread.start()
+        thread.join()
+        assert results and results[0] is not None
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    @pytest.mark.parametrize("kwargs", [{}, {"dedup": True}, {"max_depth": 5000}])
+    def test_default_limit_on_small_thread_stacks(self, loads, kwargs):
+        import threading
+        depth = kwargs.get("max_depth", 1024)
+        doc = '{"a": ' * depth + "1" + "}" * depth
+        results = []
+        old_size = threading.stack_size(512 * 1024)
+        try:
+            thread = threading.Thread(target=lambda: results.append(loads(doc, **kwargs)))
+            thread.start()
+            thread.join()
+        finally:
+            threading.stack_size(old_size)
+        value = results[0]
+        for _ in range(depth):
+            value = value["a"]
+        assert value == 1
+
+    def test_object_hook_on_small_thread_stacks(self):
+        import threading
+        calls = []
+        results = []
+        old_size = threading.stack_size(512 * 1024)
+        try:
+            thread = threading.Thread(target=lambda: results.append(
+                rjson.loads('{"a": ' * 1000 + "1" + "}" * 1000, object_hook=lambda d: calls.append(d) or d)
+            ))
+            thread.start()
+            thread.join()
+        finally:
+            threading.stack_size(old_size)
+        assert results and len(calls) == 1000
+
+    @pytest.mark.parametrize("depth", [100_000, 1_000_000])
+    def test_hostile_nesting_is_an_error(self, depth):
+        doc = self.nested(depth)
+        with pytest.raises(ValueError, match="JSON parsing error"):
+            rjson.loads(doc)
+        with pytest.raises(ValueError, match="nesting exceeds max_depth"):
+            rjson.loads(doc, max_depth=1000)
+        with pytest.raises(ValueError, match="nesting exceeds max_depth"):
+            rjson.loads_simd(doc)
+
+    def test_caps_parse_stack_thread(self):
+        rjson.set_parse_stack_size(64 * 1024 * 1024)
+        try:
+            assert rjson.loads(self.nested(1000), max_depth=1000) is not None
+            with pytest.raises(ValueError, match="nesting exceeds max_depth"):
+                rjson.loads(self.nested(1_000_000), max_depth=1000)
+        finally:
+            rjson.set_parse_stack_size(0)
+
+    def test_simd_key_filter_skips_nested_values(self):
+        doc = '{"a": 1, "b": [1, {"c": [2]}], "d": {"e": 3}, "a": 4}'
+        assert rjson.loads_simd(doc, allowed_keys={"a"}) == {"a": 4}
+        assert rjson.loads_simd(doc, ignored_keys={"b"}) == {"a": 4, "d": {"e": 3}}
+
+    def test_simd_keeps_key_order(self):
+        data = {str(i): i for i in range(100)}
+        assert list(rjson.loads_simd(rjson.dumps(data))) == list(data)
+
+    def test_lazy_conflict(self):
+        with pytest.raises(ValueError, match="lazy and max_depth cannot be combined"):
+            rjson.loads("[1]", lazy=True, max_depth=10)
+
+
+class TestRejectDuplicateKeys:
+    """Test loads(reject_duplicate_keys=True)."""
+
+    def test_default_keeps_last(self):
+        assert rjson.loads('{"a":1,"a":2}') == {"a": 2}
+        assert rjson.loads('{"a":1,"a":2}', reject_duplicate_keys=False) == {"a": 2}
+
+    def test_rejects_and_names_key(self):
+        with pytest.raises(rjson.JSONDecodeError, match="duplicate key 'a'") as raised:
+            rjson.loads('{"a":1,"a":2}', reject_duplicate_keys=True)
+        # Reported where the repeated key ends, before its value is parsed
+        assert (raised.value.lineno, raised.value.pos) == (1, 9)
+
+    def test_nested_object(self):
+        with pytest.raises(rjson.JSONDecodeError, match="duplicate key 'k'"):
+            rjson.loads('{"x": [{"k": 1, "k": 1}]}', reject_duplicate_keys=True)
+
+    def test_same_key_in_different_objects(self):
+        doc = '{"a": 1, "b": {"a": 2}, "c": [{"a": 3}, {"a": 4}]}'
+        assert rjson.loads(doc, reject_duplicate_keys=True) == rjson.loads(doc)
+
+    def test_with_key_filter(self):
+        doc = '{"a": 1, "b": 1, "b": 2}'
+        assert rjson.loads(doc, reject_duplicate_keys=True, allowed_keys={"a"}) == {"a": 1}
+        with pytest.raises(rjson.JSONDecodeError, match="duplicate key 'b'"):
+            rjson.loads(doc, reject_duplicate_keys=True, ignored_keys={"a"})
+
+    def test_lazy_conflict(self):
+        with pytest.raises(ValueError, match="lazy and reject_duplicate_keys cannot be combined"):
+            rjson.loads("{}", lazy=True, reject_duplicate_keys=True)
+
+
+class _Obj:
+    """Attribute bag built by the object_hook tests."""
+
+    def __init__(self, fields):
+        self.__dict__.update(fields)
+
+
+class TestObjectHook:
+    """Test loads(object_hook=...)."""
+
+    def test_converts_nested_objects(self):
+        doc = '{"name": "a", "child": {"name": "b", "tags": [{"name": "c"}]}}'
+        result = rjson.loads(doc, object_hook=_Obj)
+        assert isinstance(result, _Obj) and isinstance(result.child, _Obj)
+        assert result.name == "a"
+        assert result.child.name == "b"
+        assert isinstance(result.child.tags[0], _Obj) and result.child.tags[0].name == "c"
+
+    def test_called_innermost_first_like_stdlib(self):
+        import json
+
+        doc = '[{"a": {"b": {}}}, {"c": 1}]'
+        seen, expected = [], []
+        rjson.loads(doc, object_hook=lambda d: seen.append(dict(d)) or len(seen))
+        json.loads(doc, object_hook=lambda d: expected.append(dict(d)) or len(expected))
+        assert seen == expected == [{}, {"b": 1}, {"a": 2}, {"c": 1}]
+
+    def test_scalars_and_lists_untouched(self):
+        assert rjson.loads('[1, "x", null, [2]]', object_hook=lambda d: 1 / 0) == [1, "x", None, [2]]
+
+    def test_exception_propagates(self):
+        class HookError(Exception):
+            pass
+
+        def hook(d):
+            raise HookError(sorted(d))
+
+        with pytest.raises(HookError) as raised:
+            rjson.loads('{"x": {"y": 1}}', object_hook=hook)
+        assert raised.value.args == (["y"],)
+
+    def test_with_other_options(self):
+        doc = '{"a": "1", "b": {"a": "2"}, "c": 3}'
+        result = rjson.loads(doc, object_hook=lambda d: sorted(d.items()), revive_bigint_strings=True, ignored_keys={"c"})
+        assert result == [("a", 1), ("b", [("a", 2)])]
+        assert rjson.loads("{}", object_hook=len, require_container=True) == 0
+        with pytest.raises(ValueError, match="top-level value"):
+            rjson.loads("1", object_hook=len, require_container=True)
+
+    def test_parse_stack_thread(self):
+        rjson.set_parse_stack_size(8 * 1024 * 1024)
+        try:
+            assert rjson.loads('[{"a": 1}]', object_hook=len) == [1]
+            with pytest.raises(ZeroDivisionError):
+                rjson.loads('{"a": 1}', object_hook=lambda d: 1 / 0)
+        finally:
+            rjson.set_parse_stack_size(0)
+
+    def test_lazy_conflict(self):
+        with pytest.raises(ValueError, match="lazy and object_hook cannot be combined"):
+            rjson.loads("{}", lazy=True, object_hook=dict)
+
+
+class TestObjectPairsHook:
+    """Test loads(object_pairs_hook=...)."""
+
+    def test_duplicates_delivered_in_order(self):
+        result = rjson.loads('{"b": 1, "a": 2, "b": 3}', object_pairs_hook=list)
+        assert result == [("b", 1), ("a", 2), ("b", 3)]
+
+    def test_nested_matches_stdlib(self):
+        import json
+
+        doc = '{"x": [{"k": 1, "k": 2}, {}], "y": {"z": null}, "x": 0}'
+        assert rjson.loads(doc, object_pairs_hook=list) == json.loads(doc, object_pairs_hook=list)
+
+    def test_pairs_are_tuples(self):
+        result = rjson.loads('{"a": [1]}', object_pairs_hook=lambda pairs: pairs)
+        assert type(result) is list and result == [("a", [1])]
+        assert type(result[0]) is tuple
+
+    def test_takes_precedence_over_object_hook(self):
+        result = rjson.loads('{"a": {"b": 1}}', object_pairs_hook=tuple, object_hook=lambda d: 1 / 0)
+        assert result == (("a", (("b", 1),)),)
+
+    def test_with_key_options(self):
+        doc = '{"a": "1", "b": 2, "a": "3"}'
+        result = rjson.loads(doc, object_pairs_hook=list, revive_bigint_strings=True, ignored_keys={"b"})
+        assert result == [("a", 1), ("a", 3)]
+        pairs, keys = rjson.loads(doc, object_pairs_hook=list, collect_keys=True)
+        assert keys == {"a", "b"}
+
+    def test_exception_propagates(self):
+        with pytest.raises(KeyError):
+            rjson.loads('{"a": 1}', object_pairs_hook=lambda pairs: {}["missing"])
+
+    def test_conflicts(self):
+        with pytest.raises(ValueError, match="cannot be combined"):
+            rjson.loads("{}", object_pairs_hook=list, reject_duplicate_keys=True)
+        with pytest.raises(ValueError, match="lazy and object_pairs_hook cannot be combined"):
+            rjson.loads("{}", lazy=True, object_pairs_hook=list)
+
+
+class TestSortKeys:
+    """Test the sort_keys option of dumps."""
+
+    DATA = {"b": 1, "a": {"z": 1, "y": [{"d": 1, "c": 2}]}}
+
+    def test_default_keeps_insertion_order(self):
+        assert rjson.dumps(self.DATA) == '{"b":1,"a":{"z":1,"y":[{"d":1,"c":2}]}}'
+        assert rjson.dumps(self.DATA, sort_keys=False) == rjson.dumps(self.DATA)
+
+    def test_sort_all(self):
+        assert rjson.dumps(self.DATA, sort_keys=True) == '{"a":{"y":[{"c":2,"d":1}],"z":1},"b":1}'
+
+    def test_sort_top_only(self):
+        assert rjson.dumps(self.DATA, sort_keys="top") == '{"a":{"z":1,"y":[{"d":1,"c":2}]},"b":1}'
+
+    def test_top_means_root_value(self):
+        # dicts inside a root list are not top-level
+        assert rjson.dumps([{"b": 1, "a": 2}], sort_keys="top") == '[{"b":1,"a":2}]'
+
+    def test_code_point_order(self):
+        data = {"é": 1, "z": 2, "A": 3, "😀": 4, "ａ": 5}
+        out = rjson.dumps(data, sort_keys=True)
+        assert list(rjson.loads(out)) == sorted(data)
+
+    def test_non_string_keys_sort_by_coerced_text(self):
+        data = {10: "a", 9: "b", None: "c", "m": "d", 1.5: "e"}
+        assert rjson.dumps(data, sort_keys=True) == '{"1.5":"e","10":"a","9":"b","m":"d","null":"c"}'
+        with pytest.raises(ValueError, match="keys must be strings"):
+            rjson.dumps({(1,): 2}, sort_keys=True)
+
+    def test_invalid_value_rejected(self):
+        with pytest.raises(ValueError, match="Invalid sort_keys"):
+            rjson.dumps({}, sort_keys="bottom")
+
+
+class TestBigIntMode:
+    """Test the bigint_mode option of dumps."""
+
+    SAFE = [0, 1, -1, 2**53, -(2**53)]
+    BIG = [2**53 + 1, -(2**53) - 1, 2**63 - 1, -(2**63), 2**64 - 1, 2**100, -(2**100)]
+
+    def test_default_emits_numbers(self):
+        for n in self.SAFE + self.BIG:
+            assert rjson.dumps(n) == str(n)
+            assert rjson.dumps(n, bigint_mode="number") == str(n)
+
+    def test_string_mode_scalars(self):
+        for n in self.SAFE:
+            assert rjson.dumps(n, bigint_mode="string") == str(n)
+        for n in self.BIG:
+            assert rjson.dumps(n, bigint_mode="string") == f'"{n}"'
+
+    def test_string_mode_bulk_int_array(self):
+        data = self.SAFE + self.BIG
+        expected = [str(n) for n in self.SAFE] + [f'"{n}"' for n in self.BIG]
+        assert rjson.dumps(data, bigint_mode="string") == "[" + ",".join(expected) + "]"
+
+    def test_string_mode_nested(self):
+        data = {"id": 2**60, "n": 5, "xs": [1, 2**60]}
+        assert rjson.dumps(data, bigint_mode="string") == f'{{"id":"{2**60}","n":5,"xs":[1,"{2**60}"]}}'
+
+    def test_bools_unaffected(self):
+        assert rjson.dumps([True, 2**60], bigint_mode="string") == f'[true,"{2**60}"]'
+
+    def test_invalid_mode_rejected(self):
+        with pytest.raises(ValueError, match="Invalid bigint_mode"):
+            rjson.dumps(1, bigint_mode="bigint")
+
+
+class TestReviveBigintStrings:
+    """Test the revive_bigint_strings option of loads."""
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_round_trip_with_bigint_mode(self, engine):
+        data = {"id": 2**60, "neg": -(2**70), "n": 5, "xs": [1, 2**64]}
+        out = rjson.dumps(data, bigint_mode="string")
+        assert getattr(rjson, engine)(out, revive_bigint_strings=True) == data
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_strict_pattern(self, engine):
+        doc = '["0", "-0", "12", "-12", "007", "+1", " 1", "1.0", "1e3", "-", "", "12a", "\\u0031"]'
+        result = getattr(rjson, engine)(doc, revive_bigint_strings=True)
+        assert result == [0, 0, 12, -12, "007", "+1", " 1", "1.0", "1e3", "-", "", "12a", 1]
+
+    def test_keys_never_converted(self):
+        assert rjson.loads('{"123": "456"}', revive_bigint_strings=True) == {"123": 456}
+
+    def test_off_by_default(self):
+        assert rjson.loads('["123"]') == ["123"]
+        assert rjson.loads_simd('["123"]') == ["123"]
+
+
+class TestConfigure:
+    """Test rjson.configure / rjson.get_config."""
+
+    @staticmethod
+    def restore_defaults():
+        rjson.configure(
+            int_cache_max=256, intern_cache_size=1024, intern_max_len=32, simd_level="auto", parse_stack_size=0,
+            default_max_depth=1024,
+        )
+
+    def test_defaults(self):
+        config = rjson.get_config()
+        assert config["int_cache_max"] == 256
+        assert config["intern_cache_size"] == 1024
+        assert config["intern_max_len"] == 32
+        assert config["simd_level"] in ("avx2", "sse2", "scalar")
+        assert config["parse_stack_size"] == 0
+        assert config["default_max_depth"] == 1024
+
+    def test_int_cache_max(self):
+        try:
+            rjson.configure(int_cache_max=2000)
+            assert rjson.get_config()["int_cache_max"] == 2000
+            a, b = rjson.loads("[1500, 1500]")
+            assert a is b
+            rjson.configure(int_cache_max=0)
+            assert rjson.loads("[0, 7, -3]") == [0, 7, -3]
+        finally:
+            self.restore_defaults()
+
+    def test_simd_level_sse2_output_identical(self):
+        data = ["plain ascii string " * 4, 'quote " and \\ and \n ' * 4, "ünïcødé " * 8, [True, False] * 40]
+        expected = rjson.dumps(data)
+        try:
+            rjson.configure(simd_level="sse2")
+            assert rjson.get_config()["simd_level"] == "sse2"
+            assert rjson.dumps(data) == expected
+            assert rjson.dumps_bytes(data) == expected.encode()
+        finally:
+            self.restore_defaults()
+
+    def test_intern_cache_size(self):
+        try:
+            rjson.configure(intern_cache_size=10)
+            assert rjson.get_config()["intern_cache_size"] == 10
+            # Pre-interned keys survive a shrink
+            a, b = (next(iter(d)) for d in rjson.loads('[{"name": 1}, {"name": 2}]'))
+            assert a is b
+        finally:
+            self.restore_defaults()
+
+    def test_intern_max_len(self):
+        long_key = "k" * 40
+        doc = '[{"%s": 1}, {"%s": 2}]' % (long_key, long_key)
+        try:
+            a, b = (next(iter(d)) for d in rjson.loads(doc))
+            assert a is not b
+            rjson.set_intern_max_len(64)
+            assert rjson.get_config()["intern_max_len"] == 64
+            a, b = (next(iter(d)) for d in rjson.loads(doc))
+            assert a is b
+            # Lowering the threshold drops the cached long key
+            rjson.configure(intern_max_len=8)
+            a, b = (next(iter(d)) for d in rjson.loads(doc))
+            assert a is not b
+        finally:
+            self.restore_defaults()
+
+    def test_intern_max_len_applies_to_values(self):
+        value = "v" * 40
+        try:
+            rjson.set_intern_max_len(64)
+            a, b = rjson.loads('["%s", "%s"]' % (value, value), intern_values=True)
+            assert a is b
+            rjson.set_intern_max_len(0)
+            a, b = rjson.loads('["xy", "xy"]', intern_values=True)
+            assert a == b and a is not b
+        finally:
+            self.restore_defaults()
+
+    def test_set_intern_cache_size(self):
+        try:
+            rjson.set_intern_cache_size(4096)
+            assert rjson.get_config()["intern_cache_size"] == 4096
+            with pytest.raises(ValueError, match="intern_cache_size"):
+                rjson.set_intern_cache_size(10**9)
+            assert rjson.get_config()["intern_cache_size"] == 4096
+        finally:
+            self.restore_defaults()
+
+    def test_clear_intern_cache(self):
+        # Earlier loads may have filled the cache
+        rjson.clear_intern_cache()
+        doc = '{"cleared_key": 1}'
+        first = next(iter(rjson.loads(doc)))
+        assert next(iter(rjson.loads(doc))) is first
+        rjson.clear_intern_cache()
+        assert next(iter(rjson.loads(doc))) is not first
+        # Pre-interned keys survive a clear
+        a, b = (next(iter(d)) for d in rjson.loads('[{"name": 1}, {"name": 2}]'))
+        assert a is b
+
+    def test_invalid_intern_max_len(self):
+        with pytest.raises(ValueError, match="intern_max_len"):
+            rjson.set_intern_max_len(10**6)
+        with pytest.raises(OverflowError):
+            rjson.set_intern_max_len(-1)
+        assert rjson.get_config()["intern_max_len"] == 32
+
+    def test_parse_stack_size(self):
+        try:
+            rjson.configure(parse_stack_size=64 * 1024 * 1024)
+            assert rjson.get_config()["parse_stack_size"] == 64 * 1024 * 1024
+            assert rjson.loads("[" * 500 + "]" * 500) is not None
+        finally:
+            self.restore_defaults()
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_default_max_depth(self, loads):
+        try:
+            rjson.configure(default_max_depth=8)
+            assert rjson.get_config()["default_max_depth"] == 8
+            assert loads("[" * 8 + "]" * 8) is not None
+            with pytest.raises(rjson.JSONDecodeError, match=r"max_depth \(8\)"):
+                loads("[" * 9 + "]" * 9)
+            # An explicit max_depth still wins
+            assert loads("[" * 20 + "]" * 20, max_depth=20) is not None
+            rjson.configure(default_max_depth=2000)
+            assert loads("[" * 1500 + "]" * 1500) is not None
+        finally:
+            self.restore_defaults()
+
+    def test_default_max_depth_lifted_by_parse_stack(self):
+        try:
+            rjson.configure(default_max_depth=8, parse_stack_size=64 * 1024 * 1024)
+            assert rjson.loads("[" * 20 + "]" * 20) is not None
+            with pytest.raises(rjson.JSONDecodeError):
+                rjson.loads_simd("[" * 20 + "]" * 20)
+        finally:
+            self.restore_defaults()
+
+    @pytest.mark.parametrize("kwargs", [
+        {"int_cache_max": -1},
+        {"int_cache_max": 10**6},
+        {"intern_cache_size": 10**9},
+        {"intern_max_len": 10**6},
+        {"simd_level": "avx512"},
+        {"parse_stack_size": 10},
+        {"default_max_depth": 0},
+    ])
+    def test_invalid_values_rejected_atomically(self, kwargs):
+        before = rjson.get_config()
+        with pytest.raises(ValueError):
+            # The valid settings alongside the bad one must not be applied
+            rjson.configure(**{"int_cache_max": 100, "parse_stack_size": 1 << 20, "default_max_depth": 50, **kwargs})
+        assert rjson.get_config() == before
+
+    def test_unknown_option_rejected(self):
+        with pytest.raises(TypeError):
+            rjson.configure(no_such_option=1)
+
+
+class TestDistinguishTuple:
+    """Test the distinguish_tuple option of dumps."""
+
+    def test_default_is_array(self):
+        assert rjson.dumps((1, 2)) == "[1,2]"
+
+    def test_tagged(self):
+        assert rjson.dumps((1, "a"), distinguish_tuple=True) == '{"__tuple__":[1,"a"]}'
+        assert rjson.dumps((), distinguish_tuple=True) == '{"__tuple__":[]}'
+
+    def test_nested_and_lists_untouched(self):
+        data = {"t": [(1, (2,)), [3]]}
+        out = rjson.dumps(data, distinguish_tuple=True)
+        assert out == '{"t":[{"__tuple__":[1,{"__tuple__":[2]}]},[3]]}'
+
+    def test_round_trip_with_hook(self):
+        def revive(obj):
+            if isinstance(obj, dict):
+                if obj.keys() == {"__tuple__"}:
+                    return tuple(revive(x) for x in obj["__tuple__"])
+                return {k: revive(v) for k, v in obj.items()}
+            if isinstance(obj, list):
+                return [revive(x) for x in obj]
+            return obj
+
+        data = {"point": (1.5, 2.5), "items": [(1, "a"), ("b", (None,))]}
+        assert revive(rjson.loads(rjson.dumps(data, distinguish_tuple=True))) == data
+
+
+class TestDictSubclasses:
+    """Test dumps with OrderedDict and other dict subclasses."""
+
+    def test_ordered_dict(self):
+        from collections import OrderedDict
+
+        data = OrderedDict([("b", 1), ("a", [1, 2])])
+        assert rjson.dumps(data) == '{"b":1,"a":[1,2]}'
+        data.move_to_end("b")
+        assert rjson.dumps(data) == '{"a":[1,2],"b":1}'
+        assert rjson.dumps(data, sort_keys=True) == '{"a":[1,2],"b":1}'
+
+    def test_defaultdict_and_counter(self):
+        from collections import Counter, defaultdict
+
+        dd = defaultdict(list)
+        dd["x"].append(1)
+        assert rjson.dumps({"d": dd, "c": Counter("aab")}) == '{"d":{"x":[1]},"c":{"a":2,"b":1}}'
+
+    def test_subclass_with_custom_items(self):
+        import json
+
+        class Reversed(dict):
+            def items(self):
+                return list(super().items())[::-1]
+
+        data = Reversed(a=1, b=2, c=3)
+        assert rjson.dumps(data) == '{"c":3,"b":2,"a":1}'
+        assert rjson.dumps(data) == json.dumps(data, separators=(",", ":"))
+
+    def test_nested_and_non_str_keys(self):
+        class Attrs(dict):
+            pass
+
+        data = Attrs({1: Attrs(x=None), "k": True})
+        assert rjson.dumps(data) == '{"1":{"x":null},"k":true}'
+
+    def test_max_depth(self):
+        from collections import OrderedDict
+
+        assert rjson.dumps([OrderedDict(a=1)], max_depth=1, on_max_depth="null") == "[null]"
+
+    def test_dumps_bytes(self):
+        from collections import OrderedDict
+
+        class MyList(list):
+            pass
+
+        class MyInt(int):
+            pass
+
+        data = {"od": OrderedDict(a=1), "l": MyList([MyInt(2)])}
+        assert rjson.dumps_bytes(OrderedDict(a=1)) == b'{"a":1}'
+        assert rjson.dumps_bytes(data) == rjson.dumps(data).encode()
+        od = OrderedDict()
+        od["self"] = [od]
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps_bytes(od)
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps_bytes([od])
+
+    def test_user_dict_still_unsupported(self):
+        from collections import UserDict
+
+        # Not a dict subclass (stdlib json rejects it too)
+        with pytest.raises(ValueError):
+            rjson.dumps(UserDict(a=1))
+
+
+class TestStringRepresentations:
+    """Test that every CPython string layout serializes like stdlib json."""
+
+    def test_all_kinds_match_stdlib(self):
+        import json
+
+        class Sub(str):
+            pass
+
+        strings = [
+            "",
+            "plain ascii",
+            "x" * 10000,
+            "caf\u00e9",               # latin-1
+            "\u4e2d\u6587",           # UCS-2
+            "emoji \U0001f600",        # UCS-4
+            "".join(["ab", "cd"]),     # built at runtime
+            str(12345),
+            Sub("subclass ascii"),     # not compact: separate data buffer
+            Sub("subclass \u00e9"),
+        ]
+        for ensure_ascii in (False, True):
+            expected = json.dumps(strings, separators=(",", ":"), ensure_ascii=ensure_ascii)
+            assert rjson.dumps(strings, ensure_ascii=ensure_ascii) == expected
+            assert rjson.dumps({s: 1 for s in strings}, ensure_ascii=ensure_ascii) == json.dumps(
+                {s: 1 for s in strings}, separators=(",", ":"), ensure_ascii=ensure_ascii
+            )
+
+    def test_bulk_string_arrays(self):
+        import json
+
+        # Homogeneous arrays take the bulk path, which reads strings in place too
+        for strings in (["k%d" % i for i in range(100)], ["\u00e9%d" % i for i in range(100)]):
+            assert rjson.dumps(strings) == json.dumps(strings, separators=(",", ":"), ensure_ascii=False)
+
+
+class TestBuiltinSubclasses:
+    """Test dumps with subclasses of int, float, str, list and tuple."""
+
+    def test_int_subclass(self):
+        class MyInt(int):
+            def __str__(self):
+                return "nope"
+
+            __repr__ = __str__
+
+        assert rjson.dumps(MyInt(42)) == "42"
+        assert rjson.dumps([MyInt(-7), MyInt(2**70)]) == "[-7,%d]" % 2**70
+
+    def test_int_subclass_big_int_error(self):
+        class MyInt(int):
+            pass
+
+        with pytest.raises(ValueError):
+            rjson.dumps(MyInt(2**60), canonical=True)
+
+    def test_float_subclass(self):
+        class MyFloat(float):
+            def __repr__(self):
+                return "nope"
+
+        assert rjson.dumps(MyFloat(1.5)) == "1.5"
+        with pytest.raises(ValueError):
+            rjson.dumps(MyFloat("nan"))
+
+    def test_str_subclass(self):
+        class MyStr(str):
+            def __str__(self):
+                return "nope"
+
+        assert rjson.dumps(MyStr('a"b\n\u00e9')) == '"a\\"b\\n\u00e9"'
+        assert rjson.dumps(MyStr("x"), ensure_ascii=True) == '"x"'
+
+    def test_list_subclass_ignores_iter(self):
+        class MyList(list):
+            def __iter__(self):
+                return iter(["nope"])
+
+        assert rjson.dumps(MyList([1, MyList([2])])) == "[1,[2]]"
+
+    def test_namedtuple(self):
+        import json
+        from collections import namedtuple
+
+        Point = namedtuple("Point", "x y")
+        data = {"p": Point(1, 2.5), "s": [Point("a", None)]}
+        assert rjson.dumps(data) == json.dumps(data, separators=(",", ":"))
+
+    def test_matches_stdlib(self):
+        import json
+
+        class I(int):
+            pass
+
+        class S(str):
+            pass
+
+        class L(list):
+            pass
+
+        data = L([I(1), S("s"), {"k": L([I(3)])}])
+        assert rjson.dumps(data) == json.dumps(data, separators=(",", ":"))
+
+
+class TestNamedTupleAsObject:
+    """Test dumps(namedtuple_as_object=...)."""
+
+    def test_nested_in_dict_both_modes(self):
+        from collections import namedtuple
+
+        Point = namedtuple("Point", "x y")
+        data = {"p": Point(1, 2.5), "s": [Point("a", None)]}
+        assert rjson.dumps(data) == '{"p":[1,2.5],"s":[["a",null]]}'
+        assert rjson.dumps(data, namedtuple_as_object=False) == '{"p":[1,2.5],"s":[["a",null]]}'
+        assert rjson.dumps(data, namedtuple_as_object=True) == (
+            '{"p":{"x":1,"y":2.5},"s":[{"x":"a","y":null}]}'
+        )
+
+    def test_matches_asdict(self):
+        import json
+        from collections import namedtuple
+
+        Inner = namedtuple("Inner", ["b", "a"])
+        Outer = namedtuple("Outer", ["name", "inner", "tags"])
+        value = Outer("o", Inner(1, [Inner(2, 3)]), ("t", 1))
+        expected = {"name": "o", "inner": {"b": 1, "a": [{"b": 2, "a": 3}]}, "tags": ["t", 1]}
+        assert json.loads(rjson.dumps(value, namedtuple_as_object=True)) == expected
+        # Field order is definition order, not sorted
+        assert rjson.dumps(Inner(1, 2), namedtuple_as_object=True) == '{"b":1,"a":2}'
+
+    def test_typing_namedtuple(self):
+        from typing import NamedTuple
+
+        class Pair(NamedTuple):
+            key: str
+            value: int = 0
+
+        assert rjson.dumps([Pair("k")], namedtuple_as_object=True) == '[{"key":"k","value":0}]'
+
+    def test_dict_options_apply(self):
+        from collections import namedtuple
+
+        Point = namedtuple("Point", "y x")
+        assert rjson.dumps(Point(1, 2), namedtuple_as_object=True, sort_keys=True) == '{"x":2,"y":1}'
+        assert rjson.dumps({"p": Point(1, 2)}, namedtuple_as_object=True, max_depth=1,
+                           on_max_depth="null") == '{"p":null}'
+
+    def test_plain_tuples_unaffected(self):
+        class Tagged(tuple):
+            pass
+
+        data = [(1, 2), Tagged((3,))]
+        assert rjson.dumps(data, namedtuple_as_object=True) == "[[1,2],[3]]"
+        assert rjson.dumps((1,), namedtuple_as_object=True, distinguish_tuple=True) == '{"__tuple__":[1]}'
+
+
+class TestSkipKeys:
+    """Test dumps(skipkeys=True)."""
+
+    def test_mixed_keys_match_stdlib(self):
+        import json
+        data = {(1, 2): "tuple", "a": 1, frozenset(): 2, 3: "int", None: [], 1.5: {"x": 1}}
+        expected = json.dumps(data, skipkeys=True, separators=(",", ":"))
+        assert rjson.dumps(data, skipkeys=True) == expected
+        assert expected == '{"a":1,"3":"int","null":[],"1.5":{"x":1}}'
+
+    def test_no_stray_commas(self):
+        assert rjson.dumps({(1,): 1, "a": 2}, skipkeys=True) == '{"a":2}'
+        assert rjson.dumps({"a": 1, (1,): 2}, skipkeys=True) == '{"a":1}'
+        assert rjson.dumps({"a": 1, (1,): 2, "b": 3}, skipkeys=True) == '{"a":1,"b":3}'
+        assert rjson.dumps({(1,): 1, (2,): 2}, skipkeys=True) == "{}"
+        assert rjson.dumps([{(1,): 1}, {(2,): 2, "c": 3}], skipkeys=True) == '[{},{"c":3}]'
+
+    def test_skipped_values_not_serialized(self):
+        class Unserializable:
+            pass
+
+        data = {(1,): Unserializable(), "ok": True}
+        assert rjson.dumps(data, skipkeys=True) == '{"ok":true}'
+
+    def test_with_sorted_and_priority_keys(self):
+        data = {"b": 1, (0,): 0, "a": 2, 7: 3}
+        assert rjson.dumps(data, skipkeys=True, sort_keys=True) == '{"7":3,"a":2,"b":1}'
+        assert rjson.dumps(data, skipkeys=True, priority_keys=["b"]) == '{"b":1,"a":2,"7":3}'
+        assert rjson.dumps({(0,): 0}, skipkeys=True, sort_keys=True) == "{}"
+
+    def test_default_still_raises(self):
+        with pytest.raises(ValueError, match="keys must be strings"):
+            rjson.dumps({(1, 2): 1})
+        with pytest.raises(ValueError, match="keys must be strings"):
+            rjson.dumps({(1, 2): 1}, skipkeys=False, sort_keys=True)
+
+    def test_nan_keys_follow_nan_mode(self):
+        with pytest.raises(ValueError):
+            rjson.dumps({float("nan"): 1}, skipkeys=True)
+        assert rjson.dumps({float("inf"): 1}, skipkeys=True, nan_mode="string") == '{"Infinity":1}'
+
+
+class TestCircularReferences:
+    """Test that dumps rejects self-containing values instead of crashing."""
+
+    def test_self_referential_list(self):
+        a = [1]
+        a.append(a)
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps(a)
+
+    def test_self_referential_dict(self):
+        d = {"a": 1}
+        d["self"] = d
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps(d)
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps(d, sort_keys=True)
+
+    def test_mutual_references(self):
+        a, b = [], {}
+        a.append(b)
+        b["a"] = a
+        for value in (a, b, [0, {"x": a}]):
+            with pytest.raises(ValueError, match="Circular reference detected"):
+                rjson.dumps(value)
+
+    def test_cycle_through_tuple(self):
+        inner = []
+        t = (inner,)
+        inner.append(t)
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps(t)
+
+    def test_cycle_through_subclasses_and_dataclasses(self):
+        import dataclasses
+        from collections import OrderedDict
+
+        class L(list):
+            pass
+
+        @dataclasses.dataclass
+        class Node:
+            child: object = None
+
+        lst = L()
+        lst.append(lst)
+        od = OrderedDict()
+        od["od"] = od
+        node = Node()
+        node.child = node
+        for value in (lst, od, node, [node]):
+            with pytest.raises(ValueError, match="Circular reference detected"):
+                rjson.dumps(value)
+
+    def test_shared_containers_are_not_cycles(self):
+        shared = [1, {"k": "v"}]
+        data = {"a": shared, "b": shared, "c": [shared, shared]}
+        expected = '{"a":[1,{"k":"v"}],"b":[1,{"k":"v"}],"c":[[1,{"k":"v"}],[1,{"k":"v"}]]}'
+        assert rjson.dumps(data) == expected
+        assert rjson.dumps(data, check_circular=False) == expected
+
+    def test_other_serializers(self):
+        import io
+        a = []
+        a.append(a)
+        for call in (
+            lambda: rjson.dumps_bytes(a),
+            lambda: rjson.dumps_bytes({"a": a}),
+            lambda: rjson.dump(a, io.StringIO()),
+            lambda: rjson.content_hash(a),
+        ):
+            with pytest.raises(ValueError, match="Circular reference detected"):
+                call()
+
+    def test_usable_after_error(self):
+        a = []
+        a.append(a)
+        with pytest.raises(ValueError):
+            rjson.dumps([[a]])
+        assert rjson.dumps([[1]]) == "[[1]]"
+
+
+class TestDictMutation:
+    """Test that dicts mutated while being serialized raise cleanly."""
+
+    @pytest.mark.parametrize("mutate", [
+        lambda d: d.update({f"k{i}": i for i in range(100)}),
+        lambda d: d.clear(),
+        lambda d: (d.pop("b"), d.update(c=3)),
+        lambda d: d.update(a=[1]),
+        lambda d: d.update(b=[2] * 1000),
+    ])
+    def test_mutation_from_generator(self, mutate):
+        data = {}
+
+        def gen():
+            mutate(data)
+            yield 1
+
+        data["a"] = gen()
+        data["b"] = 2
+        with pytest.raises(RuntimeError, match="dict changed during serialization"):
+            rjson.dumps(data, iterables=True)
+
+    def test_mutation_from_default(self):
+        data = {"a": object(), "b": "x" * 100}
+
+        def default(o):
+            data["b"] = "y"
+            return None
+
+        with pytest.raises(RuntimeError, match="dict changed during serialization"):
+            rjson.dumps(data, default=default)
+
+    def test_unchanged_dict_with_python_values(self):
+        data = {"a": (x for x in [1]), "b": {"c": object()}}
+        assert rjson.dumps(data, iterables=True, default=lambda o: "o") == '{"a":[1],"b":{"c":"o"}}'
+
+    @pytest.mark.parametrize("key", ["".join(["k"] * 30), 1234567890123])
+    def test_error_names_key_freed_by_mutation(self, key):
+        data = {}
+
+        def gen():
+            # Drops the dict's (only) reference to the key, then churns the heap
+            data.clear()
+            junk = [("X" + str(102)) * 50 for _ in range(1000)]
+            yield object()
+
+        data[key] = gen()
+        with pytest.raises(ValueError, match=f"at key '{key}': object"):
+            rjson.dumps(data, iterables=True)
+
+    @pytest.mark.parametrize("options", [{"sort_keys": True}, {"priority_keys": ["b"]}])
+    @pytest.mark.parametrize("mutate", [
+        lambda d: d.update(z=3),
+        lambda d: d.update(a=[2]),
+        lambda d: d.pop("a"),
+    ])
+    def test_mutation_in_ordered_modes(self, options, mutate):
+        data = {}
+
+        def gen():
+            mutate(data)
+            yield 1
+
+        data["b"] = gen()
+        data["a"] = 2
+        with pytest.raises(RuntimeError, match="dict changed during serialization"):
+            rjson.dumps(data, iterables=True, **options)
+
+    @pytest.mark.parametrize("options", [{"sort_keys": True}, {"priority_keys": ["b"]}])
+    def test_unchanged_dict_in_ordered_modes(self, options):
+        data = {1: object(), "b": (x for x in [1]), "a": 2}
+        out = rjson.dumps(data, iterables=True, default=lambda o: "o", **options)
+        assert rjson.loads(out) == {"1": "o", "b": [1], "a": 2}
+
+    def test_dumps_bytes_mutation_from_json_hook(self):
+        data = {}
+
+        class AddsKeys:
+            def __json__(self):
+                data.update({f"n{i}": i for i in range(100)})
+                return 2
+
+        data["a"] = 1
+        data["b"] = AddsKeys()
+        with pytest.raises(RuntimeError, match="dict changed during serialization"):
+            rjson.dumps_bytes(data)
+
+
+class TestListMutation:
+    """Test lists mutated by Python code run while they are serialized."""
+
+    class X:
+        pass
+
+    def test_cleared_from_default(self):
+        lst = [self.X()] + [[i] * 3 for i in range(1000)]
+        assert rjson.dumps(lst, default=lambda o: (lst.clear(), 1)[1]) == "[1]"
+
+    def test_shrunk_from_default(self):
+        lst = [0, self.X(), 2, 3, 4, 5, 6, 7, 8, 9]
+        assert rjson.dumps(lst, default=lambda o: (lst.__delitem__(slice(3, None)), "x")[1]) == '[0,"x",2]'
+
+    def test_grown_from_default(self):
+        lst = [self.X(), "a"]
+        assert rjson.dumps(lst, default=lambda o: (lst.append("b"), 1)[1]) == '[1,"a","b"]'
+
+    def test_dumps_bytes_cleared_from_json_hook(self):
+        def make():
+            lst = [[i] * 3 for i in range(1000)]
+
+            class Clears:
+                def __json__(self):
+                    lst.clear()
+                    return 1
+
+            lst += [Clears()] + [[i] * 3 for i in range(1000)]
+            return lst
+
+        out = rjson.dumps_bytes(make())
+        assert out == rjson.dumps(make()).encode()
+        assert out.endswith(b"[999,999,999],1]")
+
+
+class TestSerializeExceptions:
+    """Test the serialize_exceptions option of dumps."""
+
+    def test_off_by_default(self):
+        with pytest.raises(ValueError):
+            rjson.dumps({"error": ValueError("bad")})
+
+    def test_basic(self):
+        out = rjson.dumps({"error": ValueError("bad value", 42)}, serialize_exceptions=True)
+        assert rjson.loads(out) == {
+            "error": {"type": "ValueError", "message": "('bad value', 42)", "args": ["bad value", 42]}
+        }
+
+    def test_single_arg_message(self):
+        out = rjson.dumps(KeyError("missing"), serialize_exceptions=True)
+        assert rjson.loads(out) == {"type": "KeyError", "message": "'missing'", "args": ["missing"]}
+
+    def test_custom_exception(self):
+        class AppError(Exception):
+            def __str__(self):
+                return "custom message"
+
+        out = rjson.dumps([AppError()], serialize_exceptions=True)
+        assert rjson.loads(out) == [{"type": "AppError", "message": "custom message", "args": []}]
+
+    def test_unserializable_args_rejected(self):
+        with pytest.raises(ValueError):
+            rjson.dumps(ValueError(object()), serialize_exceptions=True)
+
+    def test_self_referencing_args(self):
+        e = ValueError("x")
+        e.args = (1, e)
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps({"err": e}, serialize_exceptions=True)
+
+
+class TestRequireContainer:
+    """Test the require_container option of loads."""
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    @pytest.mark.parametrize("doc", ["42", '"text"', "null", "true", "1.5"])
+    def test_scalars_rejected(self, engine, doc):
+        assert getattr(rjson, engine)(doc) is not None or doc == "null"
+        with pytest.raises(ValueError, match="object or array"):
+            getattr(rjson, engine)(doc, require_container=True)
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_containers_accepted(self, engine):
+        assert getattr(rjson, engine)(' {"a": 1} ', require_container=True) == {"a": 1}
+        assert getattr(rjson, engine)("[]", require_container=True) == []
+
+    def test_invalid_json_still_reports_parse_error(self):
+        with pytest.raises(ValueError, match="JSON parsing error"):
+            rjson.loads("[1,", require_container=True)
+
+
+class TestNoScientific:
+    """Test the no_scientific option of dumps."""
+
+    @pytest.mark.parametrize("value,expected", [
+        (1e-7, "0.0000001"),
+        (-1.5e-7, "-0.00000015"),
+        (1e21, "1000000000000000000000.0"),
+        (1.25e17, "125000000000000000.0"),
+        (1.2345e3, "1234.5"),
+        (0.0, "0.0"),
+        (-0.0, "-0.0"),
+        (1.5, "1.5"),
+    ])
+    def test_expansion(self, value, expected):
+        assert rjson.dumps(value, no_scientific=True) == expected
+
+    @pytest.mark.parametrize("value", [5e-324, 1.7976931348623157e308, 2.2250738585072014e-308, 123456789.123e-20])
+    def test_extremes_round_trip(self, value):
+        out = rjson.dumps(value, no_scientific=True)
+        assert "e" not in out.lower()
+        assert len(out) < 330
+        assert float(out) == value
+
+    def test_bulk_float_list(self):
+        values = [i * 1e-9 for i in range(1, 50)]
+        out = rjson.dumps(values, no_scientific=True)
+        assert "e" not in out
+        assert [float(x) for x in out[1:-1].split(",")] == values
+
+    def test_default_keeps_exponent(self):
+        assert "e" in rjson.dumps(1e-7)
+
+
+class TestSequences:
+    """Test the sequences option of dumps."""
+
+    def test_off_by_default(self):
+        from collections import UserList
+        with pytest.raises(ValueError):
+            rjson.dumps(UserList([1, 2]))
+
+    def test_userlist_and_range(self):
+        from collections import UserList
+        data = {"a": UserList([1, "x", [2]]), "r": range(3)}
+        assert rjson.dumps(data, sequences=True) == '{"a":[1,"x",[2]],"r":[0,1,2]}'
+
+    def test_registered_sequence(self):
+        import collections.abc
+
+        class Squares:
+            def __init__(self, n):
+                self.n = n
+
+            def __len__(self):
+                return self.n
+
+            def __getitem__(self, i):
+                if not 0 <= i < self.n:
+                    raise IndexError(i)
+                return i * i
+
+        collections.abc.Sequence.register(Squares)
+        assert rjson.dumps(Squares(4), sequences=True) == "[0,1,4,9]"
+
+    def test_strings_and_mappings_not_sequences(self):
+        from collections import UserDict, UserString
+
+        class MyStr(str):
+            pass
+
+        # A str subclass is written as a string, never split into characters
+        assert rjson.dumps(MyStr("ab"), sequences=True) == '"ab"'
+        with pytest.raises(ValueError):
+            rjson.dumps(UserString("ab"), sequences=True)
+        with pytest.raises(ValueError):
+            rjson.dumps(UserDict(a=1), sequences=True)
+        with pytest.raises(ValueError):
+            rjson.dumps(b"ab", sequences=True)
+
+
+class TestDedup:
+    """Test the dedup option of loads."""
+
+    DOC = '{"orders": [' + ",".join(
+        '{"id": %d, "address": {"city": "Hanoi", "zip": [10000, 2.5]}, "tags": ["a", "b"]}' % i
+        for i in range(50)
+    ) + "]}"
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_equal_to_plain_parse(self, engine):
+        parse = getattr(rjson, engine)
+        assert parse(self.DOC, dedup=True) == parse(self.DOC)
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_values_shared(self, engine):
+        orders = getattr(rjson, engine)(self.DOC, dedup=True)["orders"]
+        assert all(o["address"]["city"] is orders[0]["address"]["city"] for o in orders)
+        assert all(o["address"]["zip"][1] is orders[0]["address"]["zip"][1] for o in orders)
+        assert all(o["tags"][0] is orders[0]["tags"][0] for o in orders)
+
+    @pytest.mark.parametrize("engine", ["loads", "loads_simd"])
+    def test_containers_not_shared(self, engine):
+        orders = getattr(rjson, engine)(self.DOC, dedup=True)["orders"]
+        assert orders[0]["address"] is not orders[1]["address"]
+        assert orders[0]["address"]["zip"] is not orders[1]["address"]["zip"]
+        assert orders[0]["tags"] is not orders[1]["tags"]
+
+    def test_mutation_stays_local(self):
+        d = rjson.loads('[{"a": 1}, {"a": 1}]', dedup=True)
+        d[0]["a"] = 2
+        assert d[1]["a"] == 1
+        nested = rjson.loads('[[["x"]], [["x"]]]', dedup=True)
+        nested[0][0].append("y")
+        assert nested == [[["x", "y"]], [["x"]]]
+
+    def test_off_by_default(self):
+        orders = rjson.loads(self.DOC)["orders"]
+        assert orders[0]["address"]["city"] is not orders[1]["address"]["city"]
+
+    def test_types_not_conflated(self):
+        result = rjson.loads('[[1], [1.0], [true], ["1"], [1], {"a": 1}, {"a": 1.0}]', dedup=True)
+        assert result == [[1], [1.0], [True], ["1"], [1], {"a": 1}, {"a": 1.0}]
+        assert type(result[1][0]) is float and type(result[2][0]) is bool
+        assert type(result[3][0]) is str
+        assert result[5]["a"] is not result[6]["a"]
+
+    def test_big_ints_and_negative_zero(self):
+        result = rjson.loads('[[%d], [%d], [0.0], [-0.0]]' % (2**70, 2**70), dedup=True)
+        assert result[0][0] is result[1][0]
+        assert result[2][0] is not result[3][0]
+        assert math.copysign(1, result[3][0]) == -1
+
+    def test_tuples_shared_only_when_immutable(self):
+        result = rjson.loads('[[[1, "a"], [{"k": 1}]], [[1, "a"], [{"k": 1}]]]', use_tuples=True, dedup=True)
+        assert result == (((1, "a"), ({"k": 1},)), ((1, "a"), ({"k": 1},)))
+        assert result[0][0] is result[1][0]
+        # A tuple holding a dict would alias the dict
+        assert result[0][1] is not result[1][1]
+        assert result[0][1][0] is not result[1][1][0]
+
+    def test_memory_savings(self):
+        import json
+        import tracemalloc
+        doc = json.dumps([{"status": "s" * 200, "region": "r" * 200} for _ in range(200)])
+        tracemalloc.start()
+        plain = rjson.loads(doc)
+        plain_size = tracemalloc.get_traced_memory()[0]
+        del plain
+        tracemalloc.stop()
+        tracemalloc.start()
+        deduped = rjson.loads(doc, dedup=True)
+        dedup_size = tracemalloc.get_traced_memory()[0]
+        tracemalloc.stop()
+        assert deduped is not None
+        assert dedup_size < plain_size / 2
+
+
+class TestReentrantDumps:
+    """Test dumps called again while an outer dumps is running."""
+
+    def test_nested_dumps_from_generator(self):
+        def gen():
+            yield rjson.dumps({"inner": [1, 2]})
+            yield rjson.dumps(list(range(1000)))
+
+        out = rjson.dumps({"outer": gen(), "after": "x" * 100}, iterables=True)
+        assert rjson.loads(out) == {
+            "outer": ['{"inner":[1,2]}', rjson.dumps(list(range(1000)))],
+            "after": "x" * 100,
+        }
+
+    def test_deeply_nested_dumps(self):
+        def nested(depth):
+            if depth == 0:
+                yield "leaf"
+                return
+            yield rjson.dumps(nested(depth - 1), iterables=True)
+
+        # Nine dumps calls, one per level plus the outermost
+        out = rjson.dumps(nested(8), iterables=True)
+        for _ in range(9):
+            (out,) = rjson.loads(out)
+        assert out == "leaf"
+
+
+class TestContentHash:
+    """Test rjson.content_hash."""
+
+    def test_matches_sha256_of_sorted_dumps(self):
+        import hashlib
+        data = {"b": [1, 2.5, None], "a": {"y": True, "x": "text"}}
+        expected = hashlib.sha256(rjson.dumps(data, sort_keys=True).encode()).hexdigest()
+        assert rjson.content_hash(data) == expected
+
+    def test_insertion_order_independent(self):
+        a = {"x": 1, "y": {"p": [1, 2], "q": "s"}}
+        b = {"y": {"q": "s", "p": [1, 2]}, "x": 1}
+        assert rjson.content_hash(a) == rjson.content_hash(b)
+
+    def test_different_values_differ(self):
+        assert rjson.content_hash({"x": 1}) != rjson.content_hash({"x": 2})
+        assert rjson.content_hash([1]) != rjson.content_hash([1.0])
+
+    def test_large_document_streamed(self):
+        import hashlib
+        data = [{"id": i, "name": f"item {i}", "tags": ["a", "b"]} for i in range(20000)]
+        expected = hashlib.sha256(rjson.dumps(data, sort_keys=True).encode()).hexdigest()
+        assert rjson.content_hash(data) == expected
+
+    def test_algorithm(self):
+        import hashlib
+        expected = hashlib.md5(b'{"a":1}').hexdigest()
+        assert rjson.content_hash({"a": 1}, algorithm="md5") == expected
+        with pytest.raises(ValueError):
+            rjson.content_hash({"a": 1}, algorithm="no-such-hash")
+
+    def test_unserializable(self):
+        with pytest.raises(ValueError):
+            rjson.content_hash({"a": object()})
+
+    def test_canonical_is_jcs(self):
+        import hashlib
+        # Keys that sort differently by UTF-16 code unit than by code point,
+        # and numbers JCS writes differently from the default form
+        data = {"\U0001f600": 1, "\ufb01": [1.0, 1e21, -0.0], "a": {"z": "\u2028", "b": 1e-7}}
+        expected = hashlib.sha256(rjson.dumps(data, canonical=True).encode()).hexdigest()
+        assert rjson.content_hash(data, canonical=True) == expected
+        assert rjson.content_hash(data) != expected
+        assert rjson.content_hash({"b": 1, "a": 2}, canonical=True) == rjson.content_hash({"a": 2, "b": 1}, canonical=True)
+        with pytest.raises(ValueError):
+            rjson.content_hash(2**60, canonical=True)
+
+
+class TestLazyLoads:
+    """Test loads(lazy=True) proxies."""
+
+    DOC = rjson.dumps({
+        "meta": {"count": 3, "tags": ["x", "y"]},
+        "items": [{"id": i, "name": f"item {i}", "nested": {"deep": [i, None, True]}} for i in range(3)],
+        "text": 'esc "quoted" \\ \u00e9 }]',
+        "n": 1.5,
+    })
+
+    def test_matches_eager_parse(self):
+        lazy = rjson.loads(self.DOC, lazy=True)
+        assert isinstance(lazy, rjson.LazyValue)
+        assert lazy.materialize() == rjson.loads(self.DOC)
+
+    def test_equality(self):
+        lazy = rjson.loads(self.DOC, lazy=True)
+        assert lazy == rjson.loads(self.DOC)
+        assert rjson.loads(self.DOC) == lazy
+        assert lazy == rjson.loads(self.DOC, lazy=True)
+        assert lazy["items"][0] == {"id": 0, "name": "item 0", "nested": {"deep": [0, None, True]}}
+        assert lazy["meta"]["tags"] == ["x", "y"]
+        assert lazy != {"meta": None}
+        assert lazy["meta"]["tags"] != ["x"]
+        with pytest.raises(TypeError):
+            hash(lazy)
+
+    def test_dumps(self):
+        lazy = rjson.loads(self.DOC, lazy=True)
+        assert rjson.dumps(lazy) == rjson.dumps(rjson.loads(self.DOC))
+        assert rjson.dumps({"sub": lazy["text"], "meta": lazy["meta"]}, sort_keys=True, ensure_ascii=True) == (
+            '{"meta":{"count":3,"tags":["x","y"]},"sub":"esc \\"quoted\\" \\\\ \\u00e9 }]"}'
+        )
+        spaced = rjson.loads('[ 1 , {"b": 2, "a": 1} ]', lazy=True)
+        assert rjson.dumps(spaced, sort_keys=True) == '[1,{"a":1,"b":2}]'
+
+    def test_object_access(self):
+        lazy = rjson.loads(self.DOC, lazy=True)
+        assert len(lazy) == 4
+        assert list(lazy) == ["meta", "items", "text", "n"]
+        assert lazy.keys() == ["meta", "items", "text", "n"]
+        assert lazy["text"] == 'esc "quoted" \\ \u00e9 }]'
+        assert lazy["n"] == 1.5
+        assert lazy["meta"]["tags"][1] == "y"
+        assert lazy["items"][-1]["nested"]["deep"].materialize() == [2, None, True]
+        assert "meta" in lazy and "missing" not in lazy
+        assert lazy.get("missing", 7) == 7
+        with pytest.raises(KeyError):
+            lazy["missing"]
+
+    def test_array_access(self):
+        lazy = rjson.loads("[1, [2, 3], {\"a\": 4}]", lazy=True)
+        assert len(lazy) == 3
+        assert lazy[0] == 1
+        assert lazy[1][0] == 2
+        assert lazy[-1]["a"] == 4
+        assert 1 in lazy
+        with pytest.raises(IndexError):
+            lazy[3]
+        with pytest.raises(TypeError):
+            lazy.keys()
+
+    def test_children_memoized(self):
+        lazy = rjson.loads(self.DOC, lazy=True)
+        assert lazy["meta"] is lazy["meta"]
+        assert lazy["text"] is lazy["text"]
+
+    def test_duplicate_keys_like_loads(self):
+        doc = '{"a": 1, "b": 2, "a": 3}'
+        lazy = rjson.loads(doc, lazy=True)
+        assert lazy.items() == list(rjson.loads(doc).items())
+
+    def test_source_kept_alive(self):
+        lazy = rjson.loads(rjson.dumps({"k": {"v": list(range(5))}}), lazy=True)
+        inner = lazy["k"]
+        del lazy
+        assert inner["v"].materialize() == [0, 1, 2, 3, 4]
+
+    def test_scalar_root_and_errors(self):
+        assert rjson.loads("42", lazy=True) == 42
+        with pytest.raises(ValueError, match="object or array"):
+            rjson.loads("42", lazy=True, require_container=True)
+        with pytest.raises(ValueError, match="JSON parsing error"):
+            rjson.loads('{"a": [1, }', lazy=True)
+        with pytest.raises(ValueError):
+            rjson.loads("[]", lazy=True, dedup=True)
+
+    def test_empty_containers(self):
+        assert len(rjson.loads("{ }", lazy=True)) == 0
+        assert rjson.loads(" [ ] ", lazy=True).materialize() == []
+
+
+class TestTimedeltaMode:
+    """Test the timedelta_mode option of dumps."""
+
+    from datetime import timedelta as td
+
+    def test_unsupported_by_default(self):
+        with pytest.raises(ValueError):
+            rjson.dumps(self.td(seconds=1))
+
+    @pytest.mark.parametrize("delta,expected", [
+        (td(days=1, hours=2, minutes=3, seconds=4), "93784"),
+        (td(seconds=90, microseconds=500000), "90.5"),
+        (td(microseconds=1), "0.000001"),
+        (td(0), "0"),
+        (td(days=-1), "-86400"),
+        (td(seconds=-1.5), "-1.5"),
+    ])
+    def test_seconds(self, delta, expected):
+        assert rjson.dumps(delta, timedelta_mode="seconds") == expected
+        assert float(expected) == delta.total_seconds()
+
+    @pytest.mark.parametrize("delta,expected", [
+        (td(days=1, hours=2, minutes=3, seconds=4), "P1DT2H3M4S"),
+        (td(minutes=1, seconds=30, microseconds=500000), "PT1M30.5S"),
+        (td(days=2), "P2D"),
+        (td(hours=5), "PT5H"),
+        (td(microseconds=250), "PT0.00025S"),
+        (td(0), "PT0S"),
+        (td(seconds=-90), "-PT1M30S"),
+    ])
+    def test_iso8601(self, delta, expected):
+        assert rjson.dumps(delta, timedelta_mode="iso8601") == f'"{expected}"'
+
+    def test_nested(self):
+        data = {"timeout": self.td(seconds=30), "retries": [self.td(seconds=1), self.td(seconds=2)]}
+        assert rjson.dumps(data, timedelta_mode="seconds") == '{"timeout":30,"retries":[1,2]}'
+
+    def test_invalid_mode(self):
+        with pytest.raises(ValueError, match="timedelta_mode"):
+            rjson.dumps(1, timedelta_mode="minutes")
+
+
+class TestBytesMode:
+    """Test the bytes_mode option of dumps."""
+
+    def test_unsupported_by_default(self):
+        with pytest.raises(ValueError):
+            rjson.dumps(b"hi")
+        with pytest.raises(ValueError):
+            rjson.dumps(bytearray(b"hi"))
+
+    @pytest.mark.parametrize("data", [b"", b"h", b"hi", b"hi!", b"\x00\xff\xfe\x80", bytes(range(256))])
+    def test_base64(self, data):
+        import base64
+
+        expected = base64.b64encode(data).decode()
+        assert rjson.dumps(data, bytes_mode="base64") == f'"{expected}"'
+        assert rjson.dumps(bytearray(data), bytes_mode="base64") == f'"{expected}"'
+
+    @pytest.mark.parametrize("data", [b"", b"hi", b"\x00\xff\xfe\x80", bytes(range(256))])
+    def test_hex(self, data):
+        assert rjson.dumps(data, bytes_mode="hex") == f'"{data.hex()}"'
+        assert rjson.dumps(bytearray(data), bytes_mode="hex") == f'"{data.hex()}"'
+
+    def test_utf8(self):
+        import json
+
+        for text in ["", "plain", 'quote " and \\ backslash\n', "caf\u00e9 \u2603 \U0001f600"]:
+            assert rjson.dumps(text.encode(), bytes_mode="utf8") == json.dumps(text, ensure_ascii=False, separators=(",", ":"))
+        assert rjson.dumps("\u00e9".encode(), bytes_mode="utf8", ensure_ascii=True) == '"\\u00e9"'
+
+    @pytest.mark.parametrize("data", [b"\xff", b"abc\xc3", bytearray(b"\xe2\x82")])
+    def test_utf8_rejects_invalid(self, data):
+        with pytest.raises(ValueError, match="UTF-8"):
+            rjson.dumps(data, bytes_mode="utf8")
+
+    def test_nested(self):
+        data = {"blob": b"\x01\x02", "items": [bytearray(b"\xff")]}
+        assert rjson.dumps(data, bytes_mode="hex") == '{"blob":"0102","items":["ff"]}'
+
+    def test_invalid_mode(self):
+        with pytest.raises(ValueError, match="bytes_mode"):
+            rjson.dumps(b"", bytes_mode="latin1")
+
+
+class TestCanonical:
+    """Test dumps(canonical=True) RFC 8785 (JCS) output."""
+
+    # RFC 8785 Appendix B: IEEE 754 bit patterns and their JCS form
+    NUMBERS = [
+        ("0000000000000000", "0"),
+        ("8000000000000000", "0"),
+        ("0000000000000001", "5e-324"),
+        ("8000000000000001", "-5e-324"),
+        ("7fefffffffffffff", "1.7976931348623157e+308"),
+        ("ffefffffffffffff", "-1.7976931348623157e+308"),
+        ("4340000000000000", "9007199254740992"),
+        ("c340000000000000", "-9007199254740992"),
+        ("4430000000000000", "295147905179352830000"),
+        ("44b52d02c7e14af5", "9.999999999999997e+22"),
+        ("44b52d02c7e14af6", "1e+23"),
+        ("44b52d02c7e14af7", "1.0000000000000001e+23"),
+        ("444b1ae4d6e2ef4e", "999999999999999700000"),
+        ("444b1ae4d6e2ef4f", "999999999999999900000"),
+        ("444b1ae4d6e2ef50", "1e+21"),
+        ("3eb0c6f7a0b5ed8c", "9.999999999999997e-7"),
+        ("3eb0c6f7a0b5ed8d", "0.000001"),
+        ("41b3de4355555553", "333333333.3333332"),
+        ("41b3de4355555554", "333333333.33333325"),
+        ("41b3de4355555555", "333333333.3333333"),
+        ("41b3de4355555556", "333333333.3333334"),
+        ("41b3de4355555557", "333333333.33333343"),
+        ("becbf647612f3696", "-0.0000033333333333333333"),
+        ("43143ff3c1cb0959", "1424953923781206.2"),
+    ]
+
+    @pytest.mark.parametrize("bits,expected", NUMBERS)
+    def test_rfc_number_vectors(self, bits, expected):
+        import struct
+
+        value = struct.unpack(">d", bytes.fromhex(bits))[0]
+        assert rjson.dumps(value, canonical=True) == expected
+        assert rjson.dumps([value, value], canonical=True) == f"[{expected},{expected}]"
+
+    def test_rfc_example(self):
+        import json
+
+        doc = (
+            '{"numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],'
+            r""" "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/","""
+            ' "literals": [null, true, false]}'
+        )
+        expected = (
+            '{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],'
+            r""""string":"€$\u000f\nA'B\"\\\\\"/"}"""
+        )
+        assert rjson.dumps(json.loads(doc), canonical=True) == expected
+
+    def test_rfc_sorting(self):
+        data = {
+            "€": "Euro Sign",
+            "\r": "Carriage Return",
+            "דּ": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\U0001f600": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "ö": "Latin Small Letter O With Diaeresis",
+        }
+        keys = list(rjson.loads(rjson.dumps(data, canonical=True)))
+        # UTF-16 order puts the surrogate pair before U+FB33 (code points wouldn't)
+        assert keys == ["\r", "1", "\u0080", "ö", "€", "\U0001f600", "דּ"]
+
+    def test_nested_and_integral_floats(self):
+        data = {"b": [1.0, -2.5, {"z": 0.1, "a": 100.0}], "a": 3}
+        assert rjson.dumps(data, canonical=True) == '{"a":3,"b":[1,-2.5,{"a":100,"z":0.1}]}'
+
+    def test_non_str_keys(self):
+        assert rjson.dumps({2: "x", 1.0: "y", "10": "z"}, canonical=True) == '{"1":"y","10":"z","2":"x"}'
+
+    def test_big_ints(self):
+        assert rjson.dumps(2**53, canonical=True) == "9007199254740992"
+        with pytest.raises(ValueError, match="2\\^53"):
+            rjson.dumps(2**53 + 1, canonical=True)
+        with pytest.raises(ValueError):
+            rjson.dumps([1, -(2**64)], canonical=True)
+        with pytest.raises(ValueError):
+            rjson.dumps({"n": 10**30}, canonical=True)
+        assert rjson.dumps([2**53 + 1], canonical=True, bigint_mode="string") == '["9007199254740993"]'
+
+    def test_nan_rejected(self):
+        with pytest.raises(ValueError):
+            rjson.dumps(float("nan"), canonical=True)
+
+    @pytest.mark.parametrize("option", [
+        {"sort_keys": True}, {"priority_keys": ["a"]}, {"float_repr": "python"}, {"no_scientific": True},
+        {"escape": "portable"}, {"ensure_ascii": True}, {"allow_nan": True}, {"nan_mode": "string"},
+    ])
+    def test_conflicts(self, option):
+        with pytest.raises(ValueError, match="canonical and"):
+            rjson.dumps({}, canonical=True, **option)
+
+    def test_off_by_default(self):
+        assert rjson.dumps({"b": 1.0, "a": 1}) == '{"b":1.0,"a":1}'
+
+
+class TestEncoderCls:
+    """Test the stdlib-style cls= encoder option of dumps."""
+
+    class SetEncoder(__import__("json").JSONEncoder):
+        def default(self, o):
+            if isinstance(o, (set, frozenset)):
+                return sorted(o)
+            if isinstance(o, complex):
+                return {"re": o.real, "im": o.imag}
+            return super().default(o)
+
+    def test_default_called_for_unsupported(self):
+        data = {"s": {3, 1, 2}, "c": 1 + 2j, "n": [1, "x"]}
+        assert rjson.dumps(data, cls=self.SetEncoder) == '{"s":[1,2,3],"c":{"re":1.0,"im":2.0},"n":[1,"x"]}'
+
+    def test_matches_stdlib(self):
+        import json
+        data = [frozenset({"a"}), {"k": 2j}]
+        expected = json.dumps(data, cls=self.SetEncoder, separators=(",", ":"))
+        assert rjson.dumps(data, cls=self.SetEncoder) == expected
+
+    def test_base_default_raises_type_error(self):
+        with pytest.raises(TypeError, match="not JSON serializable"):
+            rjson.dumps(object(), cls=self.SetEncoder)
+
+    def test_circular_default(self):
+        import json
+
+        class SelfEncoder(json.JSONEncoder):
+            def default(self, o):
+                return o
+
+        class WrapEncoder(json.JSONEncoder):
+            def default(self, o):
+                return type(o)()
+
+        with pytest.raises(ValueError, match="Circular reference"):
+            rjson.dumps(object(), cls=SelfEncoder)
+        with pytest.raises(ValueError, match="nested"):
+            rjson.dumps(object(), cls=WrapEncoder)
+
+    def test_builtin_types_never_reach_default(self):
+        import json
+
+        class Loud(json.JSONEncoder):
+            def default(self, o):
+                raise AssertionError("default called")
+
+        assert rjson.dumps({"a": [1, 2.5, None, True, "s", (1,)]}, cls=Loud) == '{"a":[1,2.5,null,true,"s",[1]]}'
+
+
+class TestCollectKeys:
+    """Test the collect_keys option of loads."""
+
+    def test_keys_at_every_depth(self):
+        doc = '{"a": 1, "b": {"c": [{"d": null, "a": 2}]}, "e": [[{"f\\u00e9": {}}]]}'
+        obj, keys = rjson.loads(doc, collect_keys=True)
+        assert obj == rjson.loads(doc)
+        assert keys == {"a", "b", "c", "d", "e", "f\u00e9"}
+
+    def test_no_objects(self):
+        assert rjson.loads("[1, 2]", collect_keys=True) == ([1, 2], set())
+        assert rjson.loads("7", collect_keys=True) == (7, set())
+
+    def test_interned_keys_shared(self):
+        obj, keys = rjson.loads('[{"name": 1}]', collect_keys=True)
+        (key,) = keys
+        assert key is next(iter(obj[0]))
+
+    def test_deep_document_on_parse_thread(self):
+        try:
+            rjson.set_parse_stack_size(64 * 1024 * 1024)
+            doc = '{"k": ' * 300 + "0" + "}" * 300
+            obj, keys = rjson.loads(doc, collect_keys=True)
+            assert keys == {"k"}
+        finally:
+            rjson.set_parse_stack_size(0)
+
+    def test_not_with_lazy(self):
+        with pytest.raises(ValueError):
+            rjson.loads("{}", collect_keys=True, lazy=True)
+
+
+class TestJsonGet:
+    """Test rjson.json_get single-field extraction."""
+
+    DOC = rjson.dumps({
+        "type": "event",
+        "payload": {"items": [1, 2, {"x": "} ] \" tricky"}], "s": "a\\b"},
+        "caf\u00e9": [True, None],
+        "last": 1.5,
+    })
+
+    @pytest.mark.parametrize("key", ["type", "payload", "caf\u00e9", "last"])
+    def test_matches_loads(self, key):
+        assert rjson.json_get(self.DOC, key) == rjson.loads(self.DOC)[key]
+
+    def test_absent(self):
+        assert rjson.json_get(self.DOC, "missing") is None
+        assert rjson.json_get(self.DOC, "missing", 0) == 0
+        assert rjson.json_get("{}", "a", "d") == "d"
+
+    def test_escaped_key(self):
+        assert rjson.json_get('{"a\\u0062": 1, "ab": 2}', "ab") == 2
+        assert rjson.json_get('{"q\\"": 3}', 'q"') == 3
+
+    def test_skipped_values_not_decoded(self):
+        # Only the extent of the skipped value is scanned, not its contents
+        assert rjson.json_get('{"a": 1, "b": [1, 2,, x]}', "a") == 1
+
+    def test_duplicate_keys_last_wins(self):
+        doc = '{"a": 1, "b": 2, "a": [3]}'
+        assert rjson.json_get(doc, "a") == rjson.loads(doc)["a"] == [3]
+        assert rjson.json_get('{"a\\u0062": 1, "ab": 2, "a\\u0062": 3}', "ab") == 3
+
+    def test_scans_to_closing_brace(self):
+        # Errors after the match still surface, as in loads
+        for doc in ('{"a": 1, "b": 2', '{"a": 1, "b" 2}', '{"a": 1} x'):
+            with pytest.raises(rjson.JSONDecodeError):
+                rjson.json_get(doc, "a")
+        assert rjson.json_get('{"a": 1} \n', "a") == 1
+
+    def test_whitespace(self):
+        assert rjson.json_get(' \n{ "a" : { "b" : [ ] } , "c" : 2 } ', "c") == 2
+
+    @pytest.mark.parametrize("doc", ["[1]", "42", "", '"a"'])
+    def test_non_object_root(self, doc):
+        with pytest.raises(ValueError, match="top-level JSON object"):
+            rjson.json_get(doc, "a")
+
+    @pytest.mark.parametrize("doc", ['{"a": 1', '{"a": [1, 2', '{"a": "x', '{"a" 1}', '{"a": 1 "b": 2}', '{a: 1}'])
+    def test_malformed(self, doc):
+        with pytest.raises(ValueError, match="JSON parsing error"):
+            rjson.json_get(doc, "b")
+
+
+class TestNanMode:
+    """Test dumps(nan_mode="string") and loads(parse_nan_strings=True)."""
+
+    def test_default_rejects(self):
+        with pytest.raises(ValueError, match="non-finite"):
+            rjson.dumps(float("nan"))
+        with pytest.raises(ValueError, match="non-finite"):
+            rjson.dumps([1.0, float("inf")])
+
+    def test_string_mode(self):
+        data = {"a": float("nan"), "b": float("inf"), "c": float("-inf"), "d": 1.5}
+        out = rjson.dumps(data, nan_mode="string")
+        assert rjson.loads(out) == {"a": "NaN", "b": "Infinity", "c": "-Infinity", "d": 1.5}
+
+    def test_bulk_float_list(self):
+        values = [float(i) for i in 16 * [1]] + [float("inf"), float("nan"), -float("inf")]
+        out = rjson.dumps(values, nan_mode="string")
+        assert rjson.loads(out)[-3:] == ["Infinity", "NaN", "-Infinity"]
+        with pytest.raises(ValueError):
+            rjson.dumps(values)
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_round_trip(self, loads):
+        data = [float("inf"), {"x": float("-inf")}, 2.5]
+        result = loads(rjson.dumps(data + [float("nan")], nan_mode="string"), parse_nan_strings=True)
+        assert result[:3] == data
+        assert math.isnan(result[3])
+
+    @pytest.mark.parametrize(
+        "loads", [rjson.loads, rjson.loads_simd, lambda s, **kw: rjson.load(io.StringIO(s), **kw)]
+    )
+    def test_parse_nan_strings_alias(self, loads):
+        out = rjson.dumps({"a": [float("inf"), "Infinity "]}, nan_mode="string")
+        expected = {"a": [float("inf"), "Infinity "]}
+        assert loads(out, parse_nan_strings=True) == loads(out, revive_nan_strings=True) == expected
+        assert loads(out) == {"a": ["Infinity", "Infinity "]}
+
+    def test_revive_only_exact_values(self):
+        doc = '{"NaN": "nan", "k": ["Infinity ", "inf", "Infinity"]}'
+        assert rjson.loads(doc, revive_nan_strings=True) == {"NaN": "nan", "k": ["Infinity ", "inf", float("inf")]}
+        assert rjson.loads('"NaN"') == "NaN"
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    @pytest.mark.parametrize("text", ["nan", "NAN", "inf", "-inf", "INFINITY", "infinity", "+Infinity", "-NaN"])
+    def test_revive_is_case_sensitive(self, loads, text):
+        assert loads(rjson.dumps([text]), revive_nan_strings=True) == [text]
+
+    def test_emitted_tokens(self):
+        out = rjson.dumps([float("nan"), float("inf"), float("-inf")], nan_mode="string")
+        assert out == '["NaN","Infinity","-Infinity"]'
+
+    def test_invalid_mode(self):
+        with pytest.raises(ValueError, match="nan_mode"):
+            rjson.dumps(1.0, nan_mode="null")
+
+
+class TestAllowNan:
+    """Test dumps(allow_nan=True)."""
+
+    def test_scalars(self):
+        assert rjson.dumps(float("nan"), allow_nan=True) == "NaN"
+        assert rjson.dumps(float("inf"), allow_nan=True) == "Infinity"
+        assert rjson.dumps(float("-inf"), allow_nan=True) == "-Infinity"
+        assert rjson.dumps(1.5, allow_nan=True) == "1.5"
+
+    def test_float_arrays(self):
+        # Long enough for the bulk float path
+        values = [float(i) for i in range(20)] + [float("inf"), float("nan"), -float("inf")]
+        out = rjson.dumps(values, allow_nan=True)
+        assert out.endswith(",Infinity,NaN,-Infinity]")
+        assert rjson.dumps(values[-3:], allow_nan=True) == "[Infinity,NaN,-Infinity]"
+
+    def test_nested_matches_stdlib(self):
+        import json
+
+        data = {"a": [1.0, {"b": float("nan")}], float("inf"): (float("-inf"), 2), "c": None}
+        assert rjson.dumps(data, allow_nan=True) == json.dumps(data, separators=(",", ":"))
+        result = json.loads(rjson.dumps(data, allow_nan=True))
+        assert math.isnan(result["a"][1]["b"])
+        assert result["Infinity"] == [float("-inf"), 2]
+
+    def test_default_still_rejects(self):
+        with pytest.raises(ValueError, match="non-finite"):
+            rjson.dumps([float("nan")], allow_nan=False)
+
+    def test_string_mode_conflict(self):
+        with pytest.raises(ValueError, match="cannot be combined"):
+            rjson.dumps(float("nan"), allow_nan=True, nan_mode="string")
+
+
+class TestLoadsPrefix:
+    """Test rjson.loads_prefix first-value parsing."""
+
+    @pytest.mark.parametrize("doc,value,end", [
+        ('{"type": "a"} trailing', {"type": "a"}, 13),
+        ('[1, 2][3]', [1, 2], 6),
+        ('  "s"xyz', "s", 5),
+        ('12 34', 12, 2),
+        ('null\n{"next": 1}', None, 4),
+        ('{"k": 1}', {"k": 1}, 8),
+    ])
+    def test_prefix(self, doc, value, end):
+        assert rjson.loads_prefix(doc) == (value, end)
+
+    def test_consecutive_messages(self):
+        stream = '{"id": 1} {"id": 2}\n[3]'
+        values = []
+        while stream.strip():
+            value, end = rjson.loads_prefix(stream)
+            values.append(value)
+            stream = stream[end:]
+        assert values == [{"id": 1}, {"id": 2}, [3]]
+
+    def test_byte_offset(self):
+        doc = '"caf\u00e9" rest'
+        value, end = rjson.loads_prefix(doc)
+        assert value == "caf\u00e9"
+        assert doc.encode()[end:] == b" rest"
+
+    @pytest.mark.parametrize("doc", ["", "   ", '{"a": 1', "[1,]", "1x", "tru"])
+    def test_malformed(self, doc):
+        with pytest.raises(ValueError, match="JSON parsing error"):
+            rjson.loads_prefix(doc)
+
+
+class TestPortableEscape:
+    """Test dumps(escape="portable")."""
+
+    def test_escapes_non_printables(self):
+        text = "a\u2028b\u2029c\u200bd\u00ad\u007f\u0085\ufeff"
+        out = rjson.dumps(text, escape="portable")
+        assert out == '"a\\u2028b\\u2029c\\u200bd\\u00ad\\u007f\\u0085\\ufeff"'
+        assert rjson.loads(out) == text
+
+    def test_default_passes_through(self):
+        assert rjson.dumps("a\u2028b") == '"a\u2028b"'
+
+    def test_keys_lists_and_sorted(self):
+        data = {"k\u200e": ["x\u2028", "y"], "z": "\u200f"}
+        for sort_keys in (None, True):
+            out = rjson.dumps(data, escape="portable", sort_keys=sort_keys)
+            assert out.isascii()
+            assert rjson.loads(out) == data
+
+    def test_printable_unicode_unchanged(self):
+        text = "caf\u00e9 \u65e5\u672c \U0001f600"
+        assert rjson.dumps(text, escape="portable") == rjson.dumps(text)
+
+    def test_astral_format_char(self):
+        out = rjson.dumps("\U000e0041", escape="portable")
+        assert out == '"\\udb40\\udc41"'
+        assert rjson.loads(out) == "\U000e0041"
+
+    def test_invalid_policy(self):
+        with pytest.raises(ValueError, match="escape"):
+            rjson.dumps("a", escape="html")
+
+
+class TestEnsureAscii:
+    """Test dumps(ensure_ascii=True)."""
+
+    def test_bmp_characters(self):
+        out = rjson.dumps("caf\u00e9 \u65e5\u672c", ensure_ascii=True)
+        assert out == '"caf\\u00e9 \\u65e5\\u672c"'
+
+    def test_astral_surrogate_pair(self):
+        out = rjson.dumps("\U0001f600", ensure_ascii=True)
+        assert out == '"\\ud83d\\ude00"'
+        assert rjson.loads(out) == "\U0001f600"
+
+    def test_mixed_with_json_escapes(self):
+        text = 'a"\\\n\u00ff\x7f' + "x" * 40 + "\u2028"
+        out = rjson.dumps(text, ensure_ascii=True)
+        assert out.isascii()
+        assert rjson.loads(out) == text
+
+    @pytest.mark.parametrize("data", [
+        {"\u00fcber": ["\u00e9", "plain", "\U0001f600" * 3], "k": "v"},
+        ["\u00e9"] * 20,
+        "plain ascii \x7f",
+    ])
+    def test_matches_stdlib(self, data):
+        import json
+
+        expected = json.dumps(data, ensure_ascii=True, separators=(",", ":"))
+        assert rjson.dumps(data, ensure_ascii=True) == expected
+        assert rjson.dumps(data, ensure_ascii=True, sort_keys=True) == json.dumps(
+            data, ensure_ascii=True, separators=(",", ":"), sort_keys=True
+        )
+
+    def test_default_passes_through(self):
+        assert rjson.dumps("\u00e9") == '"\u00e9"'
+        assert rjson.dumps("\u00e9", ensure_ascii=False) == '"\u00e9"'
+
+    def test_covers_portable(self):
+        text = "\u200b\u00e9\x7f"
+        out = rjson.dumps(text, ensure_ascii=True, escape="portable")
+        assert out == rjson.dumps(text, ensure_ascii=True) == '"\\u200b\\u00e9\\u007f"'
+
+
+class TestDump:
+    """Test rjson.dump streaming output to a file-like object."""
+
+    DATA = {"items": [{"id": i, "name": f"item-{i}", "tags": ["a", "b"]} for i in range(5000)]}
+
+    def test_bytes_io_matches_dumps(self):
+        fp = io.BytesIO()
+        assert rjson.dump(self.DATA, fp) is None
+        assert fp.getvalue() == rjson.dumps(self.DATA).encode()
+
+    def test_chunked_writes(self):
+        class Writer:
+            def __init__(self):
+                self.chunks = []
+
+            def write(self, chunk):
+                self.chunks.append(chunk)
+
+        writer = Writer()
+        rjson.dump(self.DATA, writer)
+        assert len(writer.chunks) > 1
+        assert all(isinstance(c, bytes) for c in writer.chunks)
+        assert b"".join(writer.chunks) == rjson.dumps(self.DATA).encode()
+
+    def test_small_value_single_write(self):
+        fp = io.BytesIO()
+        rjson.dump([1, "x", None], fp)
+        assert fp.getvalue() == b'[1,"x",null]'
+
+    def test_file(self, tmp_path):
+        path = tmp_path / "out.json"
+        with open(path, "wb") as fp:
+            rjson.dump(self.DATA, fp)
+        assert rjson.loads(path.read_text()) == self.DATA
+
+    def test_unserializable(self):
+        with pytest.raises(ValueError):
+            rjson.dump({"a": object()}, io.BytesIO())
+
+    def test_no_write_method(self):
+        with pytest.raises(AttributeError):
+            rjson.dump(1, object())
+
+    def test_string_io(self):
+        fp = io.StringIO()
+        rjson.dump(self.DATA, fp)
+        assert fp.getvalue() == rjson.dumps(self.DATA)
+
+    def test_text_chunks_are_str(self):
+        class TextWriter(io.TextIOBase):
+            def __init__(self):
+                self.chunks = []
+
+            def write(self, chunk):
+                self.chunks.append(chunk)
+                return len(chunk)
+
+        data = [{"text": "caf\u00e9 \u2603 \U0001f600" * 20, "n": i} for i in range(2000)]
+        writer = TextWriter()
+        rjson.dump(data, writer)
+        assert len(writer.chunks) > 1
+        assert all(isinstance(c, str) for c in writer.chunks)
+        assert "".join(writer.chunks) == rjson.dumps(data)
+
+    def test_text_file(self, tmp_path):
+        path = tmp_path / "out.json"
+        with open(path, "w", encoding="utf-8") as fp:
+            rjson.dump({"name": "caf\u00e9"}, fp)
+        assert path.read_text(encoding="utf-8") == '{"name":"caf\u00e9"}'
+
+    def test_options_string_io(self):
+        data = {"b": "caf\u00e9", "a": object()}
+        fp = io.StringIO()
+        rjson.dump(data, fp, sort_keys=True, ensure_ascii=True, default=lambda o: "obj")
+        assert fp.getvalue() == '{"a":"obj","b":"caf\\u00e9"}'
+
+    def test_options_bytes_io(self):
+        fp = io.BytesIO()
+        rjson.dump(self.DATA, fp, sort_keys=True, append_newline=True)
+        assert fp.getvalue() == rjson.dumps(self.DATA, sort_keys=True).encode() + b"\n"
+
+    def test_invalid_option(self):
+        fp = io.BytesIO()
+        with pytest.raises(ValueError, match="canonical and sort_keys"):
+            rjson.dump({}, fp, canonical=True, sort_keys=True)
+        assert fp.getvalue() == b""
+
+
+class TestLoad:
+    """Test rjson.load reading from a file-like object."""
+
+    DATA = {"name": "caf\u00e9 \u2603", "items": [1, 2.5, None, True]}
+
+    def test_string_io(self):
+        assert rjson.load(io.StringIO(rjson.dumps(self.DATA))) == self.DATA
+
+    def test_bytes_io(self):
+        assert rjson.load(io.BytesIO(rjson.dumps(self.DATA).encode())) == self.DATA
+
+    def test_round_trip_through_dump(self):
+        for fp in (io.StringIO(), io.BytesIO()):
+            rjson.dump(self.DATA, fp)
+            fp.seek(0)
+            assert rjson.load(fp) == self.DATA
+
+    def test_file(self, tmp_path):
+        path = tmp_path / "in.json"
+        path.write_text(rjson.dumps(self.DATA), encoding="utf-8")
+        with open(path, encoding="utf-8") as fp:
+            assert rjson.load(fp) == self.DATA
+        with open(path, "rb") as fp:
+            assert rjson.load(fp) == self.DATA
+
+    def test_loads_options(self):
+        fp = io.StringIO('{"a": 1, "b": {"a": 2}}')
+        assert rjson.load(fp, allowed_keys=["a"]) == {"a": 1}
+        with pytest.raises(ValueError, match="object or array"):
+            rjson.load(io.StringIO("1"), require_container=True)
+        with pytest.raises(TypeError):
+            rjson.load(io.StringIO("1"), no_such_option=True)
+
+    def test_invalid_json(self):
+        import json
+
+        with pytest.raises(json.JSONDecodeError):
+            rjson.load(io.StringIO("[1,"))
+
+    def test_no_read_method(self):
+        with pytest.raises(AttributeError):
+            rjson.load(object())
+
+
+class TestJsonStreamDecoder:
+    """Test incremental decoding with JsonStreamDecoder."""
+
+    VALUES = [
+        {"id": 1, "text": 'café \U0001f600 "quoted" \\ end', "nested": [1.5, -2e-3, [], {}]},
+        [True, False, None, 12345678901234567890],
+        "a \\u00e9 string",
+        -0.25,
+        None,
+        {"esc": "\\\"}]"},
+    ]
+
+    def _drain(self, decoder):
+        return list(decoder)
+
+    def test_one_byte_at_a_time(self):
+        import json
+
+        data = " ".join(json.dumps(v, ensure_ascii=False) for v in self.VALUES).encode()
+        decoder = rjson.JsonStreamDecoder()
+        results = []
+        for i in range(len(data)):
+            decoder.feed(data[i:i + 1])
+            results.extend(self._drain(decoder))
+        # The trailing value is a complete object, so nothing is held back
+        assert results == self.VALUES
+        assert decoder.pending == 0
+
+    def test_chunk_boundaries_everywhere(self):
+        import json
+
+        data = "\n".join(json.dumps(v) for v in self.VALUES).encode() + b"\n"
+        for split in range(1, len(data)):
+            decoder = rjson.JsonStreamDecoder()
+            decoder.feed(data[:split])
+            results = self._drain(decoder)
+            decoder.feed(data[split:])
+            results += self._drain(decoder)
+            assert results == self.VALUES, split
+
+    def test_next_returns_default_when_incomplete(self):
+        decoder = rjson.JsonStreamDecoder()
+        missing = object()
+        decoder.feed('{"a": [1, 2')
+        assert decoder.next(missing) is missing
+        assert decoder.next() is None
+        decoder.feed("]}null")
+        assert decoder.next(missing) == {"a": [1, 2]}
+        # A literal at the end of the data may still continue
+        assert decoder.next(missing) is missing
+        decoder.feed(" ")
+        assert decoder.next(missing) is None
+        assert decoder.next(missing) is missing
+
+    def test_split_number_held_until_delimiter(self):
+        decoder = rjson.JsonStreamDecoder()
+        decoder.feed("12")
+        assert self._drain(decoder) == []
+        decoder.feed("34.5")
+        assert self._drain(decoder) == []
+        decoder.feed("e1 ")
+        assert self._drain(decoder) == [12345.0]
+
+    def test_close_completes_trailing_scalar(self):
+        decoder = rjson.JsonStreamDecoder()
+        decoder.feed("[1] 42")
+        assert self._drain(decoder) == [[1]]
+        decoder.close()
+        assert self._drain(decoder) == [42]
+        with pytest.raises(ValueError, match="after close"):
+            decoder.feed("1")
+
+    def test_close_with_truncated_value(self):
+        decoder = rjson.JsonStreamDecoder()
+        decoder.feed('{"a": "unterminated')
+        assert decoder.next(0) == 0
+        decoder.close()
+        with pytest.raises(rjson.JSONDecodeError):
+            decoder.next()
+
+    def test_json_text_sequence(self):
+        decoder = rjson.JsonStreamDecoder()
+        decoder.feed(b'\x1e{"a":1}\n\x1e[2]\n\x1e"s"\n')
+        assert self._drain(decoder) == [{"a": 1}, [2], "s"]
+
+    def test_concatenated_without_separators(self):
+        decoder = rjson.JsonStreamDecoder()
+        decoder.feed('{"a":1}[2]"x"3 ')
+        assert self._drain(decoder) == [{"a": 1}, [2], "x", 3]
+
+    def test_malformed_value_is_skipped(self):
+        decoder = rjson.JsonStreamDecoder()
+        decoder.feed('{"a": tru} [1] ] 2 ')
+        with pytest.raises(rjson.JSONDecodeError):
+            decoder.next()
+        assert decoder.next() == [1]
+        with pytest.raises(rjson.JSONDecodeError):
+            decoder.next()
+        assert decoder.next() == 2
+
+    def test_large_stream_compacts(self):
+        decoder = rjson.JsonStreamDecoder()
+        item = b'{"k": "' + b"x" * 1000 + b'"}'
+        count = 0
+        for _ in range(300):
+            decoder.feed(item[:500])
+            decoder.feed(item[500:])
+            count += len(self._drain(decoder))
+        assert count == 300
+        assert decoder.pending == 0
+
+    def test_accepts_str_bytes_bytearray(self):
+        decoder = rjson.JsonStreamDecoder(intern_keys=False)
+        decoder.feed("[1,")
+        decoder.feed(b"2,")
+        decoder.feed(bytearray(b"3]"))
+        assert decoder.next() == [1, 2, 3]
+
+
+class TestParseEvents:
+    """Test event-driven parsing with parse_events."""
+
+    class Recorder:
+        def __init__(self):
+            self.events = []
+
+        def on_object_start(self):
+            self.events.append("{")
+
+        def on_object_end(self):
+            self.events.append("}")
+
+        def on_array_start(self):
+            self.events.append("[")
+
+        def on_array_end(self):
+            self.events.append("]")
+
+        def on_key(self, key):
+            self.events.append(("key", key))
+
+        def on_value(self, value):
+            self.events.append(("value", value))
+
+    def test_event_order(self):
+        handler = self.Recorder()
+        rjson.parse_events('{"a": [1, "x", null], "b": {"c": true, "d": 2.5}}', handler)
+        assert handler.events == [
+            "{", ("key", "a"), "[", ("value", 1), ("value", "x"), ("value", None), "]",
+            ("key", "b"), "{", ("key", "c"), ("value", True), ("key", "d"), ("value", 2.5), "}",
+            "}",
+        ]
+
+    def test_scalar_root_and_bytes(self):
+        handler = self.Recorder()
+        rjson.parse_events(b'"caf\xc3\xa9"', handler)
+        rjson.parse_events(str(2**70), handler)
+        assert handler.events == [("value", "café"), ("value", 2**70)]
+
+    def test_count_keys_in_large_object(self):
+        import json
+
+        doc = json.dumps({"k%d" % i: {"n": i, "tags": ["a", "b"]} for i in range(20000)})
+
+        class KeyCounter:
+            depth = 0
+            top_level_keys = 0
+            total = 0
+
+            def on_object_start(self):
+                self.depth += 1
+
+            def on_object_end(self):
+                self.depth -= 1
+
+            def on_key(self, key):
+                self.total += 1
+                if self.depth == 1:
+                    self.top_level_keys += 1
+
+        counter = KeyCounter()
+        assert rjson.parse_events(doc, counter) is None
+        assert counter.top_level_keys == 20000
+        assert counter.total == 60000
+        assert counter.depth == 0
+
+    def test_partial_handler(self):
+        class Sum:
+            total = 0
+
+            def on_value(self, value):
+                if isinstance(value, int):
+                    self.total += value
+
+        handler = Sum()
+        rjson.parse_events('{"a": [1, 2, {"b": 3}], "c": "4"}', handler)
+        assert handler.total == 6
+        # A handler with no methods just validates
+        rjson.parse_events("[1, 2]", object())
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.parse_events("[1, 2", object())
+
+    def test_parse_error_mid_stream(self):
+        doc = '[1, 2, {"a": tru}]'
+        handler = self.Recorder()
+        with pytest.raises(rjson.JSONDecodeError) as info:
+            rjson.parse_events(doc, handler)
+        with pytest.raises(rjson.JSONDecodeError) as expected:
+            rjson.loads(doc)
+        assert info.value.pos == expected.value.pos
+        # Events before the error were already delivered
+        assert handler.events == ["[", ("value", 1), ("value", 2), "{", ("key", "a")]
+
+    def test_handler_exception_propagates(self):
+        class Stop(Exception):
+            pass
+
+        class Handler:
+            def __init__(self):
+                self.seen = []
+
+            def on_value(self, value):
+                if value == 3:
+                    raise Stop(value)
+                self.seen.append(value)
+
+        handler = Handler()
+        with pytest.raises(Stop):
+            rjson.parse_events("[1, 2, 3, 4]", handler)
+        assert handler.seen == [1, 2]
+
+    def test_values_match_loads(self):
+        doc = '[1e300, -0.0, 18446744073709551615, "\\ud83d\\ude00", {"": ""}]'
+        handler = self.Recorder()
+        rjson.parse_events(doc, handler)
+        values = [event[1] for event in handler.events if event[0] == "value"]
+        expected = rjson.loads(doc)
+        assert values == expected[:4] + [""]
+
+    def test_nesting_limit(self):
+        rjson.parse_events("[" * 127 + "]" * 127, self.Recorder())
+        # Shallower than loads accepts
+        doc = "[" * 200 + "]" * 200
+        assert rjson.loads(doc)
+        with pytest.raises(rjson.JSONDecodeError, match="recursion limit exceeded"):
+            rjson.parse_events(doc, self.Recorder())
+
+
+class TestJsonLines:
+    """Test dumps_lines/loads_lines newline-delimited JSON."""
+
+    RECORDS = [{"id": i, "msg": f"event {i}", "tags": ["a", "caf\u00e9"], "ok": i % 2 == 0} for i in range(100)]
+
+    def test_round_trip(self):
+        text = rjson.dumps_lines(self.RECORDS)
+        assert text.count("\n") == len(self.RECORDS) - 1
+        assert rjson.loads_lines(text) == self.RECORDS
+
+    def test_matches_dumps_per_item(self):
+        items = [1, "two", None, [3.5], {"a": {}}]
+        assert rjson.dumps_lines(items) == "\n".join(rjson.dumps(item) for item in items)
+
+    def test_generator_and_empty(self):
+        assert rjson.dumps_lines(x for x in range(3)) == "0\n1\n2"
+        assert rjson.dumps_lines([]) == ""
+        assert rjson.loads_lines("") == []
+
+    def test_blank_lines_and_trailing_newline(self):
+        assert rjson.loads_lines('{"a": 1}\n\n  \n[2]\n') == [{"a": 1}, [2]]
+        assert rjson.loads_lines('{"a": 1}\r\n[2]\r\n') == [{"a": 1}, [2]]
+
+    def test_bytes_input(self):
+        text = rjson.dumps_lines(self.RECORDS)
+        assert rjson.loads_lines(text.encode()) == self.RECORDS
+        assert rjson.loads_lines(bytearray(text.encode())) == self.RECORDS
+
+    def test_error_reports_line(self):
+        import json
+
+        doc = '{"a": 1}\n\n{"\u00e9": 2}\n{"b": }\n[3]'
+        with pytest.raises(json.JSONDecodeError) as exc_info:
+            rjson.loads_lines(doc)
+        err = exc_info.value
+        assert (err.lineno, err.colno) == (4, 7)
+        assert doc[err.pos] == "}"
+
+    def test_one_value_per_line(self):
+        import json
+
+        with pytest.raises(json.JSONDecodeError) as exc_info:
+            rjson.loads_lines("1\n2 3")
+        assert exc_info.value.lineno == 2
+
+    def test_unserializable_item(self):
+        with pytest.raises(ValueError):
+            rjson.dumps_lines([1, object()])
+
+
+class TestNormalize:
+    """Test loads(normalize=...) Unicode normalization."""
+
+    DECOMPOSED = '{"cafe\\u0301": ["e\\u0301", "plain", 1]}'
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_nfc(self, loads):
+        result = loads(self.DECOMPOSED, normalize="NFC")
+        assert result == {"caf\u00e9": ["\u00e9", "plain", 1]}
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_nfd(self, loads):
+        result = loads('["caf\u00e9"]', normalize="NFD")
+        assert result == ["cafe\u0301"]
+
+    def test_default_preserves_input(self):
+        assert rjson.loads(self.DECOMPOSED) == {"cafe\u0301": ["e\u0301", "plain", 1]}
+
+    @pytest.mark.parametrize("intern_keys", [True, False])
+    def test_keys_merge_after_normalization(self, intern_keys):
+        doc = '{"caf\u00e9": 1, "cafe\\u0301": 2}'
+        assert rjson.loads(doc, normalize="NFC", intern_keys=intern_keys) == {"caf\u00e9": 2}
+
+    def test_lazy(self):
+        value = rjson.loads(self.DECOMPOSED, normalize="NFC", lazy=True)
+        assert value["caf\u00e9"][0] == "\u00e9"
+        assert value.materialize() == {"caf\u00e9": ["\u00e9", "plain", 1]}
+
+    def test_invalid_form(self):
+        with pytest.raises(ValueError, match="normalize"):
+            rjson.loads("[]", normalize="NFKC2")
+
+
+class TestParserParity:
+    """Every parse engine rejects the same invalid documents."""
+
+    ENGINES = {
+        "loads": rjson.loads,
+        "loads_simd": rjson.loads_simd,
+        "lazy": lambda s: rjson.loads(s, lazy=True),
+        "loads_with_spans": rjson.loads_with_spans,
+    }
+
+    INVALID = [
+        # structure and trailing data
+        "", " ", "[", "{", "]", "[1,]", '{"a":1,}', "[,1]", "[1,,2]", "[1 2]",
+        '{"a"}', '{"a":}', '{"a" 1}', '{"a":1 "b":2}', "{1:2}", "{'a':1}",
+        "1 2", "[1] [2]", "null x", "[1]\x00", "\ufeff[]",
+        # numbers
+        "01", "-01", "00", "1.", ".5", "-", "+1", "1e", "1e+", "1.e5", "0x10",
+        "NaN", "Infinity", "-Infinity", "+Infinity", "nan", "inf", "-inf",
+        "INFINITY", "infinity", "[NaN]", '{"a": -Infinity}',
+        # literals
+        "tru", "nul", "True",
+        # strings and escapes
+        '"abc', '"\\x"', '"\\U0041"', '"\\u12"', '"\\u12g4"', '"\\"',
+        '"\\ud800"', '"\\ud800x"', '"\\udc00"', '"\\ud800\\u0041"',
+        '"a\x00"', '"a\nb"', '"a\tb"', '"\x1f"',
+    ]
+
+    @pytest.mark.parametrize("doc", INVALID)
+    def test_all_engines_reject(self, doc):
+        for name, parse in self.ENGINES.items():
+            with pytest.raises(ValueError, match="JSON parsing error"):
+                parse(doc)
+
+    @pytest.mark.parametrize("doc", ['"\\ud83d\\ude00"', '"\\\\ud800"', '[1] ', '\n{"a": [1.5e3, -0]}\t'])
+    def test_all_engines_accept(self, doc):
+        expected = rjson.loads(doc)
+        assert rjson.loads_simd(doc) == expected
+        assert rjson.loads_with_spans(doc)[0][0] == expected
+
+
+class TestPriorityKeys:
+    """Test dumps(priority_keys=...)."""
+
+    def test_listed_keys_first(self):
+        data = {"name": "x", "id": 7, "@type": "Person"}
+        assert rjson.dumps(data, priority_keys=["@type", "id"]) == '{"@type":"Person","id":7,"name":"x"}'
+
+    def test_missing_keys_skipped(self):
+        data = {"b": 1, "a": 2}
+        assert rjson.dumps(data, priority_keys=["id", "a"]) == '{"a":2,"b":1}'
+
+    def test_applies_to_nested_dicts(self):
+        data = {"items": [{"v": 1, "id": 1}], "id": 0}
+        assert rjson.dumps(data, priority_keys=["id"]) == '{"id":0,"items":[{"id":1,"v":1}]}'
+
+    def test_with_sort_keys(self):
+        data = {"z": 1, "id": 2, "a": 3, "m": {"y": 1, "id": 0, "b": 2}}
+        assert rjson.dumps(data, priority_keys=["id"], sort_keys=True) == '{"id":2,"a":3,"m":{"id":0,"b":2,"y":1},"z":1}'
+        # "top" sorts only the root remainder
+        assert rjson.dumps(data, priority_keys=["id"], sort_keys="top") == '{"id":2,"a":3,"m":{"id":0,"y":1,"b":2},"z":1}'
+
+    def test_duplicate_priority_keys(self):
+        assert rjson.dumps({"a": 1, "id": 2}, priority_keys=["id", "id"]) == '{"id":2,"a":1}'
+
+    def test_empty_and_none(self):
+        data = {"b": 1, "a": 2}
+        assert rjson.dumps(data, priority_keys=[]) == rjson.dumps(data)
+        assert rjson.dumps(data, priority_keys=None) == rjson.dumps(data)
+
+    def test_rejects_non_str(self):
+        with pytest.raises(TypeError):
+            rjson.dumps({}, priority_keys=[1])
+        with pytest.raises(TypeError):
+            rjson.dumps({}, priority_keys="id")
+
+
+class TestMaxDepth:
+    """Test dumps(max_depth=..., on_max_depth=...)."""
+
+    DATA = {"a": [1, [2, [3]]], "b": {"c": {"d": 1}}, "e": 5}
+
+    def test_unbounded_by_default(self):
+        assert rjson.dumps(self.DATA) == rjson.dumps(self.DATA, max_depth=None)
+
+    def test_error(self):
+        assert rjson.dumps(self.DATA, max_depth=4) == rjson.dumps(self.DATA)
+        with pytest.raises(ValueError, match="Maximum depth of 2 exceeded"):
+            rjson.dumps(self.DATA, max_depth=2)
+
+    def test_null(self):
+        out = rjson.dumps(self.DATA, max_depth=2, on_max_depth="null")
+        assert out == '{"a":[1,null],"b":{"c":null},"e":5}'
+
+    def test_truncate(self):
+        out = rjson.dumps(self.DATA, max_depth=1, on_max_depth="truncate")
+        assert out == '{"a":"...","b":"...","e":5}'
+
+    def test_root(self):
+        assert rjson.dumps([1], max_depth=0, on_max_depth="null") == "null"
+        assert rjson.dumps(1, max_depth=0) == "1"
+
+    def test_bulk_list_and_tuple(self):
+        data = [[1.5] * 20, (1, 2), ["x"] * 20]
+        assert rjson.dumps(data, max_depth=1, on_max_depth="truncate") == '["...","...","..."]'
+
+    def test_iterables_and_sequences(self):
+        from collections import UserList
+        data = [(x for x in range(3)), UserList([1])]
+        out = rjson.dumps(data, max_depth=1, on_max_depth="null", iterables=True, sequences=True)
+        assert out == "[null,null]"
+
+    def test_self_referencing_list(self):
+        data = []
+        data.append(data)
+        assert rjson.dumps(data, max_depth=3, on_max_depth="truncate", check_circular=False) == '[[["..."]]]'
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps(data, max_depth=3, on_max_depth="truncate")
+
+    def test_invalid_mode(self):
+        with pytest.raises(ValueError, match="on_max_depth"):
+            rjson.dumps([], on_max_depth="drop")
+
+
+class TestDatetimeKeys:
+    """Test loads(datetime_keys=...) field-targeted datetime parsing."""
+
+    DOC = rjson.dumps({
+        "created_at": "2024-05-01T12:30:00+00:00",
+        "note": "2024-05-01T12:30:00+00:00",
+        "items": [{"updated_at": "2024-05-02", "n": 1}],
+        "updated_at": None,
+    })
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_only_named_keys(self, loads):
+        result = loads(self.DOC, datetime_keys={"created_at", "updated_at"})
+        assert result["created_at"] == datetime.datetime(2024, 5, 1, 12, 30, tzinfo=datetime.timezone.utc)
+        assert result["note"] == "2024-05-01T12:30:00+00:00"
+        assert result["items"][0] == {"updated_at": datetime.datetime(2024, 5, 2), "n": 1}
+        assert result["updated_at"] is None
+
+    def test_invalid_values_stay_strings(self):
+        result = rjson.loads('{"t": "not a date", "u": 5, "v": ["2024-01-01"]}', datetime_keys=["t", "u", "v"])
+        assert result == {"t": "not a date", "u": 5, "v": ["2024-01-01"]}
+
+    def test_default_parses_nothing(self):
+        assert rjson.loads(self.DOC)["created_at"] == "2024-05-01T12:30:00+00:00"
+
+    def test_rejected_with_lazy(self):
+        with pytest.raises(ValueError, match="lazy and datetime_keys"):
+            rjson.loads("{}", lazy=True, datetime_keys=["a"])
+
+    def test_non_str_key(self):
+        with pytest.raises(TypeError):
+            rjson.loads("{}", datetime_keys=[1])
+
+
+class TestKeyFilter:
+    """Test loads(allowed_keys=...) and loads(ignored_keys=...)."""
+
+    DOC = '{"id": 1, "blob": {"x": [1, 2, {"deep": "v"}]}, "name": "a", "rows": [{"id": 2, "junk": 3}]}'
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_allowed(self, loads):
+        result = loads(self.DOC, allowed_keys={"id", "rows"})
+        assert result == {"id": 1, "rows": [{"id": 2}]}
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_ignored(self, loads):
+        result = loads(self.DOC, ignored_keys=["blob", "junk"])
+        assert result == {"id": 1, "name": "a", "rows": [{"id": 2}]}
+
+    def test_skipped_values_still_validated(self):
+        with pytest.raises(ValueError, match="JSON parsing error"):
+            rjson.loads('{"a": 1, "b": [1,]}', allowed_keys=["a"])
+
+    def test_escaped_keys(self):
+        assert rjson.loads('{"\\u0069d": 1, "x": 2}', allowed_keys=["id"]) == {"id": 1}
+
+    def test_combined_with_collect_keys(self):
+        obj, keys = rjson.loads(self.DOC, ignored_keys=["blob"], collect_keys=True)
+        assert keys == {"id", "name", "rows", "junk"}
+
+    def test_invalid_combinations(self):
+        with pytest.raises(ValueError, match="cannot be combined"):
+            rjson.loads("{}", allowed_keys=["a"], ignored_keys=["b"])
+        with pytest.raises(ValueError, match="lazy and allowed_keys"):
+            rjson.loads("{}", allowed_keys=["a"], lazy=True)
+
+
+class TestAppendNewline:
+    """Test the append_newline option of dumps and dumps_bytes."""
+
+    DATA = {"event": "login", "ids": [1, 2], "ok": True}
+
+    def test_dumps(self):
+        out = rjson.dumps(self.DATA, append_newline=True)
+        assert out.endswith("}\n")
+        assert out == rjson.dumps(self.DATA) + "\n"
+
+    def test_dumps_bytes(self):
+        out = rjson.dumps_bytes(self.DATA, append_newline=True)
+        assert out[-1:] == b"\n"
+        assert out == rjson.dumps_bytes(self.DATA) + b"\n"
+
+    def test_off_by_default(self):
+        assert not rjson.dumps(self.DATA).endswith("\n")
+        assert not rjson.dumps_bytes(self.DATA).endswith(b"\n")
+
+    def test_scalars_and_other_options(self):
+        assert rjson.dumps(1, append_newline=True) == "1\n"
+        assert rjson.dumps({"b": 1, "a": 2}, sort_keys=True, append_newline=True) == '{"a":2,"b":1}\n'
+        assert rjson.dumps_bytes(None, append_newline=True) == b"null\n"
+
+    def test_lines_round_trip(self):
+        text = "".join(rjson.dumps({"n": i}, append_newline=True) for i in range(3))
+        assert rjson.loads_lines(text) == [{"n": 0}, {"n": 1}, {"n": 2}]
+
+
+class TestDumpsBytesBufferReuse:
+    """Test dumps_bytes output while its buffer is reused between calls."""
+
+    def test_sizes_interleaved(self):
+        big = {"items": list(range(50000)), "name": "x" * 10000}
+        small = {"a": [1, 2.5, None]}
+        for value in (big, small, big, 1, small, "", []):
+            out = rjson.dumps_bytes(value)
+            assert out == rjson.dumps(value).encode()
+        # A short result after a long one carries nothing over
+        rjson.dumps_bytes(big)
+        assert rjson.dumps_bytes(small) == b'{"a":[1,2.5,null]}'
+
+    def test_results_are_independent(self):
+        outs = [rjson.dumps_bytes({"n": i}) for i in range(100)]
+        assert outs == [b'{"n":%d}' % i for i in range(100)]
+
+    def test_after_error(self):
+        with pytest.raises(ValueError, match="Unsupported Python type for JSON serialization: object"):
+            rjson.dumps_bytes([1, 2, object()])
+        assert rjson.dumps_bytes([3]) == b"[3]"
+        assert rjson.dumps_bytes([4], append_newline=True) == b"[4]\n"
+
+    def test_buffer_not_leaked(self):
+        import sys
+        if not sys.platform.startswith("linux"):
+            pytest.skip("ru_maxrss units are platform-specific")
+        import resource
+        data = [{"key": "v" * 100}] * 20000
+        rjson.dumps_bytes(data)
+        before = resource.getrusage(resource.RUSAGE_SELF).ru_maxrss
+        for _ in range(50):
+            rjson.dumps_bytes(data)
+        # ru_maxrss is in KiB on Linux; each output is ~2.4 MB
+        assert resource.getrusage(resource.RUSAGE_SELF).ru_maxrss - before < 40 * 1024
+
+    def test_threads(self):
+        import threading
+        errors = []
+
+        def worker(n):
+            for i in range(500):
+                value = {"t": n, "i": i, "pad": "p" * (i % 50)}
+                if rjson.dumps_bytes(value) != rjson.dumps(value).encode():
+                    errors.append((n, i))
+
+        threads = [threading.Thread(target=worker, args=(n,)) for n in range(4)]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+        assert errors == []
+
+
+class TestDumpsInto:
+    """Test dumps_into writing into a caller-provided bytearray."""
+
+    DATA = {"name": "caf\u00e9", "items": list(range(50))}
+
+    def test_grows_too_small_buffer(self):
+        buffer = bytearray(4)
+        n = rjson.dumps_into(self.DATA, buffer)
+        assert n == len(buffer)
+        assert bytes(buffer) == rjson.dumps(self.DATA).encode()
+
+    def test_truncates_oversized_buffer(self):
+        buffer = bytearray(b"x" * 10000)
+        n = rjson.dumps_into([1, 2], buffer)
+        assert n == 5
+        assert buffer == bytearray(b"[1,2]")
+
+    def test_reuse(self):
+        buffer = bytearray()
+        for data in (self.DATA, "short", {"k": [None] * 100}):
+            n = rjson.dumps_into(data, buffer)
+            assert buffer == rjson.dumps(data).encode()
+            assert n == len(buffer)
+
+    def test_error_leaves_buffer_unchanged(self):
+        buffer = bytearray(b"previous")
+        with pytest.raises(ValueError):
+            rjson.dumps_into({"a": object()}, buffer)
+        assert buffer == bytearray(b"previous")
+
+    def test_requires_bytearray(self):
+        with pytest.raises(TypeError):
+            rjson.dumps_into(1, b"")
+        with pytest.raises(TypeError):
+            rjson.dumps_into(1, memoryview(bytearray(4)))
+
+
+class TestDumpsFramed:
+    """Test rjson.dumps_framed length-prefixed output."""
+
+    def test_header_and_body(self):
+        data = {"id": 1, "tags": ["a", "b"], "ok": True}
+        frame = rjson.dumps_framed(data)
+        body = rjson.dumps(data).encode()
+        assert frame[:4] == len(body).to_bytes(4, "big")
+        assert frame[4:] == body
+
+    def test_non_ascii_length_in_bytes(self):
+        frame = rjson.dumps_framed("caf\u00e9 \u2603")
+        assert int.from_bytes(frame[:4], "big") == len(frame) - 4
+        assert rjson.loads(frame[4:].decode()) == "caf\u00e9 \u2603"
+
+    def test_consecutive_frames(self):
+        stream = b"".join(rjson.dumps_framed(v) for v in [1, [2, 3], {"k": None}])
+        values = []
+        while stream:
+            n = int.from_bytes(stream[:4], "big")
+            values.append(rjson.loads(stream[4:4 + n].decode()))
+            stream = stream[4 + n:]
+        assert values == [1, [2, 3], {"k": None}]
+
+    def test_unserializable(self):
+        with pytest.raises(ValueError):
+            rjson.dumps_framed({"a": object()})
+
+
+@dataclasses.dataclass
+class _Address:
+    city: str
+    zip: "str | None" = None
+
+
+@dataclasses.dataclass
+class _Person:
+    name: str
+    age: int
+    address: _Address
+    previous: list[_Address] = dataclasses.field(default_factory=list)
+    by_label: dict[str, _Address] = dataclasses.field(default_factory=dict)
+    manager: "_Person | None" = None
+
+
+class TestLoadsInto:
+    """Test loads(into=...) decoding into dataclasses."""
+
+    DOC = (
+        '{"name": "Ann", "age": 40, "address": {"city": "Oslo"},'
+        ' "previous": [{"city": "Rome", "zip": "00100"}], "by_label": {"home": {"city": "Bergen"}},'
+        ' "manager": {"name": "Bo", "age": 50, "address": {"city": "Oslo"}}}'
+    )
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_nested(self, loads):
+        person = loads(self.DOC, into=_Person)
+        assert person == _Person(
+            name="Ann",
+            age=40,
+            address=_Address("Oslo"),
+            previous=[_Address("Rome", "00100")],
+            by_label={"home": _Address("Bergen")},
+            manager=_Person("Bo", 50, _Address("Oslo")),
+        )
+
+    def test_root_list_and_optional(self):
+        people = rjson.loads('[{"city": "A"}, {"city": "B", "zip": null}]', into=list[_Address])
+        assert people == [_Address("A"), _Address("B")]
+        assert rjson.loads("null", into=_Address | None) is None
+
+    def test_extra_keys(self):
+        doc = '{"city": "A", "country": "NO"}'
+        assert rjson.loads(doc, into=_Address) == _Address("A")
+        with pytest.raises(ValueError, match="unexpected key country for _Address"):
+            rjson.loads(doc, into=_Address, on_extra="error")
+
+    def test_missing_keys(self):
+        with pytest.raises(ValueError, match="missing key 'city' for _Address"):
+            rjson.loads('{"zip": "1"}', into=_Address)
+        assert rjson.loads('{"zip": "1"}', into=_Address, on_missing="none") == _Address(None, "1")
+
+    def test_init_false_and_post_init(self):
+        @dataclasses.dataclass
+        class Box:
+            w: int
+            h: int
+            area: int = dataclasses.field(init=False)
+
+            def __post_init__(self):
+                self.area = self.w * self.h
+
+        box = rjson.loads('{"w": 2, "h": 3, "area": 99}', into=Box)
+        assert box.area == 6
+
+    def test_type_mismatch(self):
+        with pytest.raises(ValueError, match="expected a JSON object for _Address, got list"):
+            rjson.loads('{"name": "A", "age": 1, "address": []}', into=_Person)
+
+    def test_invalid_arguments(self):
+        with pytest.raises(ValueError, match="into must be a dataclass"):
+            rjson.loads("{}", into=int)
+        with pytest.raises(ValueError, match="on_extra"):
+            rjson.loads("{}", into=_Address, on_extra="drop")
+        with pytest.raises(ValueError, match="lazy and into"):
+            rjson.loads("{}", into=_Address, lazy=True)
+
+    def test_attrs_classes(self):
+        attrs = pytest.importorskip("attrs")
+
+        @attrs.define
+        class Point:
+            x: int
+            y: int = 0
+
+        @attrs.define
+        class Shape:
+            points: list[Point]
+            origin: Point | None = None
+            tags: list[str] = attrs.Factory(list)
+
+        shape = rjson.loads('{"points": [{"x": 1, "y": 2}, {"x": 3}], "origin": {"x": 0}}', into=Shape)
+        assert shape == Shape([Point(1, 2), Point(3)], Point(0))
+        with pytest.raises(ValueError, match="missing key 'x' for Point"):
+            rjson.loads('{"y": 1}', into=Point)
+        with pytest.raises(ValueError, match="unexpected key z for Point"):
+            rjson.loads('{"x": 1, "z": 1}', into=Point, on_extra="error")
+
+    def test_attrs_private_and_typed_attributes(self):
+        attr = pytest.importorskip("attr")
+
+        @attr.s
+        class Inner:
+            v = attr.ib(type=int)
+
+        @attr.s
+        class Outer:
+            # Passed to __init__ as `secret`, keyed by the attribute name
+            _secret = attr.ib()
+            inner = attr.ib(type=Inner)
+            skipped = attr.ib(init=False, default=5)
+
+        outer = rjson.loads('{"_secret": "s", "inner": {"v": 1}}', into=Outer)
+        assert (outer._secret, outer.inner, outer.skipped) == ("s", Inner(1), 5)
+
+
+class TestDataclassDumps:
+    """Test dumps serializing dataclass instances."""
+
+    PERSON = _Person(
+        name="Ada",
+        age=36,
+        address=_Address("London"),
+        previous=[_Address("Paris", "75001")],
+        by_label={"work": _Address("Oxford", "OX1")},
+    )
+
+    def test_matches_asdict(self):
+        import json
+
+        assert rjson.loads(rjson.dumps(self.PERSON)) == dataclasses.asdict(self.PERSON)
+        assert rjson.dumps(self.PERSON) == json.dumps(dataclasses.asdict(self.PERSON), separators=(",", ":"))
+
+    def test_definition_order(self):
+        assert rjson.dumps(_Address("Rome", "00100")) == '{"city":"Rome","zip":"00100"}'
+
+    def test_lists_of_dataclasses(self):
+        data = {"people": [_Address("A"), _Address("B", "2")]}
+        assert rjson.dumps(data) == '{"people":[{"city":"A","zip":null},{"city":"B","zip":"2"}]}'
+
+    def test_omit_none(self):
+        assert rjson.dumps(_Address("Rome"), dataclass_omit_none=True) == '{"city":"Rome"}'
+        # Only dataclass fields are dropped, not dict values
+        data = {"a": None, "addr": _Address("Rome")}
+        assert rjson.dumps(data, dataclass_omit_none=True) == '{"a":null,"addr":{"city":"Rome"}}'
+
+    def test_skips_classvar_and_initvar(self):
+        from typing import ClassVar
+
+        @dataclasses.dataclass
+        class Point:
+            kind: ClassVar[str] = "point"
+            x: int
+            y: int
+            scale: dataclasses.InitVar[int] = 1
+
+            def __post_init__(self, scale):
+                self.x *= scale
+
+        assert rjson.dumps(Point(1, 2, scale=3)) == '{"x":3,"y":2}'
+
+    def test_slots_and_frozen(self):
+        @dataclasses.dataclass(frozen=True, slots=True)
+        class Pair:
+            a: int
+            b: "list[int]"
+
+        assert rjson.dumps(Pair(1, [2, 3])) == '{"a":1,"b":[2,3]}'
+
+    def test_sort_and_priority_keys(self):
+        assert rjson.dumps(_Address("Rome", "1"), sort_keys=True) == '{"city":"Rome","zip":"1"}'
+        assert rjson.dumps(_Address("Rome", "1"), priority_keys=["zip"]) == '{"zip":"1","city":"Rome"}'
+
+    def test_class_itself_unsupported(self):
+        with pytest.raises(ValueError):
+            rjson.dumps(_Address)
+
+    def test_unsupported_field_value(self):
+        with pytest.raises(ValueError, match="'city'"):
+            rjson.dumps(_Address(object()))
+
+    def test_max_depth(self):
+        assert rjson.dumps([_Address("A")], max_depth=1, on_max_depth="null") == "[null]"
+
+
+class TestOnMissingAttr:
+    """Test dumps(on_missing_attr=...) for dataclass fields that can't be read."""
+
+    @dataclasses.dataclass
+    class Account:
+        id: int
+        owner: str
+
+    def account(self):
+        value = self.Account(1, "ada")
+        del value.owner
+        return value
+
+    @dataclasses.dataclass
+    class Report:
+        id: int
+        total: int = 0
+
+    def raising(self, exc=RuntimeError("not computed")):
+        def total(_):
+            raise exc
+
+        # The field is shadowed by a property that always raises
+        report = type("Pending", (self.Report,), {"total": property(total, lambda *_: None)})
+        return report(7)
+
+    def test_error_is_default(self):
+        with pytest.raises(RuntimeError, match="not computed"):
+            rjson.dumps(self.raising())
+        with pytest.raises(AttributeError):
+            rjson.dumps(self.account(), on_missing_attr="error")
+
+    def test_null(self):
+        assert rjson.dumps(self.raising(), on_missing_attr="null") == '{"id":7,"total":null}'
+        assert rjson.dumps([self.account()], on_missing_attr="null") == '[{"id":1,"owner":null}]'
+
+    def test_skip(self):
+        assert rjson.dumps(self.raising(), on_missing_attr="skip") == '{"id":7}'
+        assert rjson.dumps({"a": self.account()}, on_missing_attr="skip") == '{"a":{"id":1}}'
+
+    def test_null_with_omit_none(self):
+        assert rjson.dumps(self.account(), on_missing_attr="null", dataclass_omit_none=True) == '{"id":1}'
+
+    def test_base_exceptions_propagate(self):
+        with pytest.raises(KeyboardInterrupt):
+            rjson.dumps(self.raising(KeyboardInterrupt()), on_missing_attr="skip")
+
+    def test_invalid_mode(self):
+        with pytest.raises(ValueError, match="on_missing_attr"):
+            rjson.dumps(1, on_missing_attr="ignore")
+
+
+class TestEnumDumps:
+    """Test dumps serializing enum members by value."""
+
+    import enum
+
+    class Color(enum.IntEnum):
+        RED = 1
+        GREEN = 2
+
+    class Level(str, enum.Enum):
+        LOW = "low"
+        HIGH = "high"
+
+    class Shape(enum.Enum):
+        SQUARE = {"sides": 4, "names": ["square", "quad"]}
+        POINT = None
+
+    class Perm(enum.Flag):
+        R = 4
+        W = 2
+
+    def test_int_enum(self):
+        assert rjson.dumps(self.Color.GREEN) == "2"
+        assert rjson.dumps([self.Color.RED, self.Color.GREEN]) == "[1,2]"
+
+    def test_str_enum(self):
+        assert rjson.dumps(self.Level.HIGH) == '"high"'
+        assert rjson.dumps({"level": self.Level.LOW}) == '{"level":"low"}'
+
+    def test_builtin_str_enum(self):
+        import enum
+
+        if not hasattr(enum, "StrEnum"):
+            pytest.skip("enum.StrEnum is 3.11+")
+
+        class Mode(enum.StrEnum):
+            FAST = "fast"
+
+        assert rjson.dumps(Mode.FAST) == '"fast"'
+
+    def test_plain_enum_with_dict_values(self):
+        assert rjson.dumps(self.Shape.SQUARE) == '{"sides":4,"names":["square","quad"]}'
+        assert rjson.dumps(self.Shape.POINT) == "null"
+
+    def test_flag(self):
+        assert rjson.dumps(self.Perm.R | self.Perm.W) == "6"
+
+    def test_matches_stdlib_for_mixins(self):
+        import json
+
+        data = {"c": self.Color.RED, "l": self.Level.HIGH}
+        assert rjson.dumps(data) == json.dumps(data, separators=(",", ":"))
+
+    def test_unsupported_value(self):
+        import enum
+
+        class Bad(enum.Enum):
+            X = object()
+
+        with pytest.raises(ValueError, match="object"):
+            rjson.dumps(Bad.X)
+
+    def test_dumps_bytes(self):
+        data = {"c": self.Color.RED, "l": self.Level.HIGH, "s": [self.Shape.SQUARE, self.Shape.POINT]}
+        assert rjson.dumps_bytes(data) == rjson.dumps(data).encode()
+        assert rjson.dumps_bytes(self.Perm.R | self.Perm.W) == b"6"
+        with pytest.raises(ValueError, match="Unsupported Python type"):
+            rjson.dumps_bytes({"cls": self.Shape})
+
+
+class TestNumpy:
+    """Test dumps with numpy scalars and arrays (needs the `numpy` cargo feature)."""
+
+    @staticmethod
+    def numpy():
+        np = pytest.importorskip("numpy")
+        try:
+            rjson.dumps(np.int64(1))
+        except ValueError:
+            pytest.skip("rjson built without the numpy feature")
+        return np
+
+    def test_scalars(self):
+        np = self.numpy()
+        assert rjson.dumps(np.int64(-5)) == "-5"
+        assert rjson.dumps(np.uint8(255)) == "255"
+        assert rjson.dumps(np.float64(1.5)) == "1.5"
+        assert rjson.dumps(np.float32(0.5)) == "0.5"
+        assert rjson.dumps(np.bool_(True)) == "true"
+        assert rjson.dumps({"n": np.int32(7)}) == '{"n":7}'
+
+    def test_2d_float_array(self):
+        np = self.numpy()
+        array = np.array([[1.5, 2.0], [3.25, -4.0]])
+        assert rjson.dumps(array) == "[[1.5,2.0],[3.25,-4.0]]"
+        assert rjson.loads(rjson.dumps(array)) == array.tolist()
+
+    def test_non_contiguous(self):
+        np = self.numpy()
+        array = np.arange(12, dtype=np.int64).reshape(3, 4)
+        for view in (array.T, array[::2, ::-1], np.asfortranarray(array)):
+            assert rjson.loads(rjson.dumps(view)) == view.tolist()
+
+    def test_dtypes(self):
+        np = self.numpy()
+        for dtype in (np.int8, np.int16, np.int32, np.uint16, np.uint32, np.uint64, np.float32, np.bool_):
+            array = np.arange(5).astype(dtype)
+            assert rjson.loads(rjson.dumps(array)) == array.tolist()
+        assert rjson.dumps(np.array([2**64 - 1], dtype=np.uint64)) == "[18446744073709551615]"
+
+    def test_other_dtypes_use_tolist(self):
+        np = self.numpy()
+        assert rjson.dumps(np.array(["a", "b"])) == '["a","b"]'
+        assert rjson.dumps(np.array([1, None], dtype=object)) == "[1,null]"
+        assert rjson.dumps(np.array([1.0], dtype=">f8")) == "[1.0]"
+
+    def test_zero_dim_and_empty(self):
+        np = self.numpy()
+        assert rjson.dumps(np.array(3.5)) == "3.5"
+        assert rjson.dumps(np.zeros((2, 0))) == "[[],[]]"
+
+    def test_nan_follows_nan_mode(self):
+        np = self.numpy()
+        with pytest.raises(ValueError):
+            rjson.dumps(np.array([np.nan]))
+        assert rjson.dumps(np.array([np.nan]), nan_mode="string") == '["NaN"]'
+
+    def test_structured_array(self):
+        np = self.numpy()
+        dtype = np.dtype([("id", "<i4"), ("score", ">f8"), ("name", "U5"), ("ok", "?")])
+        array = np.array([(1, 0.5, "ada", True), (2, -1.25, "grace", False)], dtype=dtype)
+        expected = [
+            {"id": 1, "score": 0.5, "name": "ada", "ok": True},
+            {"id": 2, "score": -1.25, "name": "grace", "ok": False},
+        ]
+        assert rjson.loads(rjson.dumps(array)) == expected
+        assert rjson.loads(rjson.dumps(array.view(np.recarray))) == expected
+        assert rjson.loads(rjson.dumps(array[::-1])) == expected[::-1]
+        assert rjson.dumps(array[0]) == '{"id":1,"score":0.5,"name":"ada","ok":true}'
+
+    def test_structured_field_offsets(self):
+        np = self.numpy()
+        # Packed, so fields sit at unaligned offsets; `pos` is nested
+        dtype = np.dtype([("flag", "u1"), ("pos", [("x", "<i2"), ("y", ">u8")]), ("f16", "f2"), ("pair", "<i4", (2,))])
+        array = np.zeros((2, 1), dtype=dtype)
+        array[1, 0] = (255, (-3, 2**64 - 1), 0.5, (7, 8))
+        assert rjson.loads(rjson.dumps(array)) == [
+            [{"flag": 0, "pos": {"x": 0, "y": 0}, "f16": 0.0, "pair": [0, 0]}],
+            [{"flag": 255, "pos": {"x": -3, "y": 2**64 - 1}, "f16": 0.5, "pair": [7, 8]}],
+        ]
+
+    def test_structured_key_order(self):
+        np = self.numpy()
+        array = np.zeros(1, dtype=[("b", "i1"), ("a", "i1"), ("c", "i1")])
+        assert rjson.dumps(array, sort_keys=True) == '[{"a":0,"b":0,"c":0}]'
+        assert rjson.dumps(array, priority_keys=["c"]) == '[{"c":0,"b":0,"a":0}]'
+
+    def test_unsupported_structured_dtypes(self):
+        np = self.numpy()
+        with pytest.raises(ValueError, match="object fields"):
+            rjson.dumps(np.zeros(2, dtype=[("a", "i4"), ("o", "O")]))
+        with pytest.raises(ValueError, match="void without fields"):
+            rjson.dumps(np.zeros(2, dtype="V8"))
+        with pytest.raises(ValueError, match="void without fields"):
+            rjson.dumps(np.zeros(2, dtype=[("raw", "V4")]))
+
+
+class TestRelaxedSyntax:
+    """Test loads(allow_comments=..., allow_trailing_commas=...)."""
+
+    CONFIG = """
+    // Service configuration
+    {
+        "name": "api", /* inline */ "port": 8080,
+        /*
+         * Multi-line block with "quotes", a // nested marker and café
+         */
+        "hosts": ["a", "b",],   // trailing comma
+        "url": "http://example.com/*not a comment*/",
+        "limits": {"rps": 10,},
+    }
+    """
+
+    def test_commented_config(self):
+        result = rjson.loads(self.CONFIG, allow_comments=True, allow_trailing_commas=True)
+        assert result == {
+            "name": "api",
+            "port": 8080,
+            "hosts": ["a", "b"],
+            "url": "http://example.com/*not a comment*/",
+            "limits": {"rps": 10},
+        }
+        assert rjson.loads(self.CONFIG.encode(), allow_comments=True, allow_trailing_commas=True) == result
+
+    def test_strict_by_default(self):
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads(self.CONFIG)
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads("[1] // done")
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads("[1,]")
+
+    def test_flags_are_independent(self):
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads("[1,]", allow_comments=True)
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads("[1] // done", allow_trailing_commas=True)
+
+    def test_comments(self):
+        assert rjson.loads("// lead\n[1, /* two */ 2] // tail", allow_comments=True) == [1, 2]
+        assert rjson.loads("/**/1/***/", allow_comments=True) == 1
+        assert rjson.loads('{"a//b": "/*c*/"}', allow_comments=True) == {"a//b": "/*c*/"}
+        assert rjson.loads('["esc\\"//", 1]', allow_comments=True) == ['esc"//', 1]
+
+    def test_trailing_commas(self):
+        assert rjson.loads("[1, 2,]", allow_trailing_commas=True) == [1, 2]
+        assert rjson.loads('{"a": [{},],}', allow_trailing_commas=True) == {"a": [{}]}
+        assert rjson.loads('[1, /* c */ ]', allow_trailing_commas=True, allow_comments=True) == [1]
+        assert rjson.loads('["a,]",]', allow_trailing_commas=True) == ["a,]"]
+
+    @pytest.mark.parametrize("text", ["[,]", "{,}", "[1,,]", "[1,,2]"])
+    def test_lone_commas_rejected(self, text):
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads(text, allow_trailing_commas=True)
+
+    def test_unterminated_block_comment(self):
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads("[1] /* open", allow_comments=True)
+
+    def test_error_position_in_original(self):
+        text = '/* café */\n{"a": 1, "b": x}'
+        with pytest.raises(rjson.JSONDecodeError) as info:
+            rjson.loads(text, allow_comments=True)
+        assert info.value.pos == text.index("x")
+        assert (info.value.lineno, info.value.colno) == (2, 15)
+
+    def test_load_passes_flags(self):
+        import io
+
+        assert rjson.load(io.StringIO("[1,] // x"), allow_comments=True, allow_trailing_commas=True) == [1]
+
+
+class TestUseTuples:
+    """Test loads(use_tuples=True)."""
+
+    def test_nested_arrays_become_nested_tuples(self):
+        result = rjson.loads('[1, [2, [3, []]], "a"]', use_tuples=True)
+        assert result == (1, (2, (3, ())), "a")
+        assert type(result[1]) is tuple and type(result[1][1][1]) is tuple
+
+    def test_objects_stay_dicts(self):
+        result = rjson.loads('{"a": [1, {"b": [true, null]}], "c": {}}', use_tuples=True)
+        assert result == {"a": (1, {"b": (True, None)}), "c": {}}
+        assert type(result["a"][1]) is dict
+
+    def test_results_are_hashable(self):
+        points = rjson.loads("[[0, 1], [2, 3], [0, 1]]", use_tuples=True)
+        assert len(set(points)) == 2
+        assert {points[0]: "origin"}[(0, 1)] == "origin"
+
+    def test_int_arrays(self):
+        values = list(range(1000))
+        assert rjson.loads(str(values), use_tuples=True) == tuple(values)
+
+    def test_default_is_lists(self):
+        assert rjson.loads("[[1]]") == [[1]]
+        assert rjson.loads("[[1]]", use_tuples=False) == [[1]]
+
+    def test_with_other_options(self):
+        doc = '[{"k": [1, 2]}, {"k": [1, 2]}]'
+        result = rjson.loads(doc, use_tuples=True, dedup=True)
+        assert result == ({"k": (1, 2)}, {"k": (1, 2)})
+        assert result[0]["k"] is result[1]["k"]
+        assert rjson.loads(doc, use_tuples=True, object_hook=lambda d: d["k"]) == ((1, 2), (1, 2))
+        assert rjson.loads(doc.encode(), use_tuples=True, collect_keys=True) == (
+            ({"k": (1, 2)}, {"k": (1, 2)}), {"k"})
+
+    def test_load_passes_option(self):
+        import io
+        assert rjson.load(io.StringIO("[[1], 2]"), use_tuples=True) == ((1,), 2)
+
+    def test_conflicts(self):
+        for other in ({"lazy": True}, {"into": dict}):
+            with pytest.raises(ValueError, match="cannot be combined"):
+                rjson.loads("[1]", use_tuples=True, **other)
+
+
+class TestNumberGrammar:
+    """Test that loads enforces the RFC 8259 number grammar."""
+
+    INVALID = [
+        "01", "-01", "00", "01.5",     # leading zeros
+        "1.", "-0.", "1.e5", "[1.]",   # digit required after "."
+        ".5", "-.5",                   # digit required before "."
+        "+5", "+0",                    # no leading "+"
+        "-", "1e", "1e+", "1E-", "1e.5",  # sign/digit required after "e"
+        '{"a": 01}',
+    ]
+    VALID = {
+        "0": 0, "10": 10, "0.5": 0.5, "-0.0": -0.0, "1e5": 1e5,
+        "1E+5": 1e5, "1e-5": 1e-5, "-12.50e0": -12.5, "0e0": 0.0,
+    }
+
+    @pytest.mark.parametrize("text", INVALID)
+    def test_rejected(self, text):
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads(text)
+        with pytest.raises(ValueError):
+            rjson.loads_simd(text)
+
+    def test_valid_numbers(self):
+        for text, expected in self.VALID.items():
+            for parse in (rjson.loads, rjson.loads_simd):
+                value = parse(text)
+                assert value == expected and type(value) is type(expected), (parse, text)
+
+
+class TestIntArrays:
+    """Test loads of flat integer arrays (parsed without serde)."""
+
+    EDGE_VALUES = [
+        0, 1, -1, 9, 10, 99, 100, 255, 256, -5, -6, 12345678, 99999999,
+        100000000, -123456789, 2**31 - 1, -2**31, 2**32, 10**17, 10**18 - 1,
+        -(10**18 - 1), 10**18, 2**63 - 1, -2**63, 2**64 - 1,
+    ]
+
+    def test_edge_values(self):
+        import json
+        text = json.dumps(self.EDGE_VALUES)
+        result = rjson.loads(text)
+        assert result == self.EDGE_VALUES
+        assert all(type(v) is int for v in result)
+        assert rjson.loads(text.encode()) == self.EDGE_VALUES
+
+    def test_each_digit_count(self):
+        values = [int("7" * n) * sign for n in range(1, 20) for sign in (1, -1)]
+        text = ",".join(map(str, values))
+        assert rjson.loads(f"[{text}]") == values
+        # Numbers at the very end of the buffer, shorter than a SIMD block
+        for value in values:
+            assert rjson.loads(f"[{value}]") == [value]
+
+    def test_large_array(self):
+        import json
+        values = list(range(-50000, 50000, 7)) + [10**15 + i for i in range(1000)]
+        text = json.dumps(values)
+        assert rjson.loads(text) == values
+        assert rjson.loads(text.replace(", ", ",")) == values
+        assert rjson.loads(text.replace(", ", " ,\n\t")) == values
+
+    def test_small_ints_are_cached(self):
+        first, second = rjson.loads("[5, 200]"), rjson.loads("[5, 200]")
+        assert first[0] is second[0] and first[1] is second[1]
+
+    @pytest.mark.parametrize("text", [
+        "[1, 2.5]", "[1, 2e3]", "[1, -0]", "[1, null]", "[1, [2]]", "[1, \"2\"]",
+        "[-0]", "[1, 9223372036854775808]",
+    ])
+    def test_mixed_arrays_match_stdlib(self, text):
+        import json
+        result = rjson.loads(text)
+        assert result == json.loads(text)
+
+    @pytest.mark.parametrize("text", [
+        "[01]", "[1,]", "[,1]", "[1 2]", "[1]]", "[1", "[-]", "[1, -]", "[+1]", "[1] x",
+    ])
+    def test_malformed_rejected(self, text):
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads(text)
+
+    def test_error_position_unchanged(self):
+        with pytest.raises(rjson.JSONDecodeError) as exc:
+            rjson.loads("[1, 2, 3,]")
+        assert exc.value.pos == 9
+
+    def test_options_still_apply(self):
+        assert rjson.loads("[1, 2]", collect_keys=True) == ([1, 2], set())
+        assert rjson.loads("[3, 4]", max_depth=1) == [3, 4]
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads("[3, 4]", max_depth=0)
+        assert rjson.loads("[5, 5]", dedup=True) == [5, 5]
+
+    def test_large_object_array_memory(self):
+        # Arrays of objects must not reserve memory sized for integers
+        # (about 4x the input) before the fast path gives up on them
+        import subprocess
+        import sys
+        if not sys.platform.startswith("linux"):
+            pytest.skip("RLIMIT_AS is only enforced on Linux")
+        script = (
+            "import resource, rjson\n"
+            "text = '[' + ','.join(['{\"k\": \"' + 'v' * 1000 + '\"}'] * 50000) + ']'\n"
+            "with open('/proc/self/status') as f:\n"
+            "    vm = next(int(line.split()[1]) for line in f if line.startswith('VmSize'))\n"
+            "limit = vm * 1024 + 3 * len(text)\n"
+            "resource.setrlimit(resource.RLIMIT_AS, (limit, limit))\n"
+            "assert len(rjson.loads(text)) == 50000\n"
+        )
+        result = subprocess.run([sys.executable, "-c", script], capture_output=True)
+        assert result.returncode == 0, result.stderr.decode()
+
+
+class TestLoadsBytes:
+    """Test loads/loads_simd with bytes and bytearray input."""
+
+    DOC = '{"name": "caf\u00e9 \u2603", "items": [1, 2.5, null, true]}'
+    EXPECTED = {"name": "caf\u00e9 \u2603", "items": [1, 2.5, None, True]}
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    @pytest.mark.parametrize("convert", [str, lambda s: s.encode(), lambda s: bytearray(s.encode())])
+    def test_input_types(self, loads, convert):
+        assert loads(convert(self.DOC)) == self.EXPECTED
+
+    def test_options_and_modes(self):
+        doc = self.DOC.encode()
+        assert rjson.loads(doc, lazy=True)["items"][1] == 2.5
+        assert rjson.loads(bytearray(doc), allowed_keys=["name"]) == {"name": self.EXPECTED["name"]}
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    @pytest.mark.parametrize("doc", [b'{"a": "\xff"}', b'"\xc3"', bytearray(b'["\xe2\x82"]')])
+    def test_invalid_utf8(self, loads, doc):
+        with pytest.raises(ValueError):
+            loads(doc)
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_invalid_json_bytes(self, loads):
+        with pytest.raises(ValueError, match="JSON parsing error"):
+            loads(b"[1,]")
+
+    @pytest.mark.parametrize("loads", [rjson.loads, rjson.loads_simd])
+    def test_other_types_rejected(self, loads):
+        with pytest.raises(TypeError, match="must be str, bytes or bytearray, not int"):
+            loads(1)
+        with pytest.raises(TypeError):
+            loads(memoryview(b"[]"))
+
+    def test_simd_scratch_reuse(self):
+        import json
+
+        large = [{"s": "x\\n" * 50, "n": i} for i in range(2000)]
+        doc = bytearray(json.dumps(large).encode())
+        original = bytes(doc)
+        assert rjson.loads_simd(doc) == large
+        assert doc == original
+        assert rjson.loads_simd('{"a": "b"}') == {"a": "b"}
+        with pytest.raises(ValueError):
+            rjson.loads_simd('{"a": ')
+        assert rjson.loads_simd(b"[1, 2]") == [1, 2]
+
+    def test_simd_scratch_released(self):
+        import json
+        import threading
+
+        data = [{"n": i, "s": "x" * 20} for i in range(100)]
+        doc = json.dumps(data)
+        results = []
+
+        def parse_twice():
+            results.append(rjson.loads_simd(doc))
+            rjson.clear_intern_cache()
+            results.append(rjson.loads_simd(doc))
+
+        assert rjson.loads_simd(doc) == data
+        thread = threading.Thread(target=parse_twice)
+        thread.start()
+        thread.join()
+        # Released from another thread, then by configure on this one
+        assert rjson.loads_simd(doc) == data
+        rjson.configure()
+        assert rjson.loads_simd(doc) == data
+        assert results == [data, data]
+
+    def test_simd_scratch_shrunk_after_large_document(self):
+        import json
+
+        # Past the retained-scratch budget, so its scratch is shrunk after
+        large = [[i, i + 0.5, "v%d" % i] for i in range(400_000)]
+        large_doc = json.dumps(large).encode()
+        small = {"a": [1, "x\u00e9", None]}
+        small_doc = bytearray(json.dumps(small).encode())
+        assert rjson.loads_simd(large_doc) == large
+        assert rjson.loads_simd(small_doc) == small
+        assert rjson.loads_simd(large_doc) == large
+        with pytest.raises(rjson.JSONDecodeError):
+            rjson.loads_simd(bytearray(b'{"a": '))
+
+
+class TestDatetime:
+    """Test dumps of datetime.datetime, date and time values."""
+
+    UTC = datetime.timezone.utc
+    IST = datetime.timezone(datetime.timedelta(hours=5, minutes=30))
+    ODD = datetime.timezone(-datetime.timedelta(hours=3, seconds=7, microseconds=5))
+
+    VALUES = [
+        datetime.datetime(2024, 1, 2, 3, 4, 5, 123456, tzinfo=UTC),
+        datetime.datetime(2024, 1, 2, 3, 4, 5),
+        datetime.datetime(1, 1, 1),
+        datetime.datetime(9999, 12, 31, 23, 59, 59, 999999, tzinfo=IST),
+        datetime.datetime(2024, 6, 1, 0, 0, 0, 50, tzinfo=ODD),
+        datetime.date(2024, 2, 29),
+        datetime.date(33, 7, 4),
+        datetime.time(0, 0),
+        datetime.time(13, 5, 9, 7),
+        datetime.time(8, 30, tzinfo=IST),
+    ]
+
+    @pytest.mark.parametrize("value", VALUES)
+    def test_matches_isoformat(self, value):
+        expected = f'"{value.isoformat()}"'
+        assert rjson.dumps(value) == expected
+        assert rjson.dumps_bytes(value) == expected.encode()
+
+    def test_rfc3339_example(self):
+        value = datetime.datetime(2024, 1, 2, 3, 4, 5, 123456, tzinfo=self.UTC)
+        assert rjson.dumps({"at": value}) == '{"at":"2024-01-02T03:04:05.123456+00:00"}'
+
+    def test_naive_utc(self):
+        naive = datetime.datetime(2024, 1, 2, 3, 4, 5)
+        assert rjson.dumps(naive, naive_utc=True) == '"2024-01-02T03:04:05+00:00"'
+        # aware values, dates and times are unaffected
+        aware = naive.replace(tzinfo=self.IST)
+        assert rjson.dumps([aware, naive.date(), naive.time()], naive_utc=True) == (
+            '["2024-01-02T03:04:05+05:30","2024-01-02","03:04:05"]'
+        )
+
+    def test_tzinfo_without_offset_is_naive(self):
+        class Floating(datetime.tzinfo):
+            def utcoffset(self, dt):
+                return None
+
+        value = datetime.datetime(2024, 1, 2, tzinfo=Floating())
+        assert rjson.dumps(value) == '"2024-01-02T00:00:00"'
+        assert rjson.dumps(value, naive_utc=True) == '"2024-01-02T00:00:00+00:00"'
+
+    def test_in_containers(self):
+        dates = [datetime.date(2024, 1, d) for d in range(1, 20)]
+        assert rjson.loads(rjson.dumps(dates)) == [d.isoformat() for d in dates]
+
+    def test_subclass_unsupported(self):
+        class MyDate(datetime.date):
+            pass
+
+        with pytest.raises(ValueError, match="Unsupported Python type"):
+            rjson.dumps(MyDate(2024, 1, 1))
+
+
+class TestDefaultCallback:
+    """Test the stdlib-style default= callback of dumps."""
+
+    class Point:
+        def __init__(self, x, y):
+            self.x, self.y = x, y
+
+    @staticmethod
+    def encode_point(o):
+        if isinstance(o, TestDefaultCallback.Point):
+            return {"x": o.x, "y": o.y}
+        raise TypeError(f"Object of type {type(o).__name__} is not JSON serializable")
+
+    def test_custom_class(self):
+        data = {"p": self.Point(1, 2), "ps": [self.Point(3, self.Point(4, 5))]}
+        assert rjson.dumps(data, default=self.encode_point) == (
+            '{"p":{"x":1,"y":2},"ps":[{"x":3,"y":{"x":4,"y":5}}]}'
+        )
+
+    def test_matches_stdlib(self):
+        import json
+        data = [self.Point(0.5, "a"), {"k": self.Point(None, True)}]
+        expected = json.dumps(data, default=self.encode_point, separators=(",", ":"))
+        assert rjson.dumps(data, default=self.encode_point) == expected
+
+    def test_exceptions_propagate(self):
+        with pytest.raises(TypeError, match="object is not JSON serializable"):
+            rjson.dumps([object()], default=self.encode_point)
+
+    def test_runaway_default(self):
+        with pytest.raises(ValueError, match="Circular reference"):
+            rjson.dumps(object(), default=lambda o: o)
+        with pytest.raises(ValueError, match="default\\(\\) nested more than 64 levels"):
+            rjson.dumps(object(), default=lambda o: object())
+
+    def test_default_results_nested_in_containers(self):
+        import json
+
+        class Node:
+            def __init__(self, next_node):
+                self.next = next_node
+
+        class Enc(json.JSONEncoder):
+            def default(self, o):
+                return {"next": o.next}
+
+        head = None
+        for _ in range(100):
+            head = Node(head)
+        expected = json.dumps(head, default=lambda o: {"next": o.next}, separators=(",", ":"))
+        assert rjson.dumps(head, default=lambda o: {"next": o.next}) == expected
+        assert rjson.dumps(head, cls=Enc) == expected
+
+    def test_default_result_containing_its_argument(self):
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps(object(), default=lambda o: [o])
+        with pytest.raises(ValueError, match="Circular reference detected"):
+            rjson.dumps({"k": object()}, default=lambda o: {"self": o})
+
+    def test_takes_precedence_over_cls(self):
+        import json
+
+        class Enc(json.JSONEncoder):
+            def default(self, o):
+                return "from cls"
+
+        assert rjson.dumps(self.Point(1, 2), cls=Enc, default=self.encode_point) == '{"x":1,"y":2}'
+        assert rjson.dumps(self.Point(1, 2), cls=Enc) == '"from cls"'
+
+
+class TestJsonMethod:
+    """Test the __json__ protocol of dumps."""
+
+    class Money:
+        def __init__(self, amount, currency):
+            self.amount = amount
+            self.currency = currency
+
+        def __json__(self):
+            return {"amount": self.amount, "currency": self.currency}
+
+    class Wrapper:
+        def __init__(self, inner):
+            self.inner = inner
+
+        def __json__(self):
+            return self.inner
+
+    def test_returns_dict(self):
+        data = {"price": self.Money(5, "USD"), "list": [self.Money(1, "VND")]}
+        assert rjson.dumps(data) == (
+            '{"price":{"amount":5,"currency":"USD"},"list":[{"amount":1,"currency":"VND"}]}'
+        )
+
+    def test_returns_custom_object(self):
+        data = self.Wrapper(self.Wrapper(self.Money(2, "EUR")))
+        assert rjson.dumps(data) == '{"amount":2,"currency":"EUR"}'
+
+    def test_runaway_json_method(self):
+        class Loop:
+            def __json__(self):
+                return self
+
+        class Chain:
+            def __json__(self):
+                return Chain()
+
+        with pytest.raises(ValueError, match="Circular reference"):
+            rjson.dumps(Loop())
+        with pytest.raises(ValueError, match="__json__\\(\\) nested more than 64 levels"):
+            rjson.dumps(Chain())
+
+    def test_precedence_over_default_and_dataclass(self):
+        from dataclasses import dataclass
+
+        @dataclass
+        class Point:
+            x: int
+
+            def __json__(self):
+                return [self.x]
+
+        calls = []
+
+        def default(o):
+            calls.append(o)
+            return "default"
+
+        assert rjson.dumps(Point(1), default=default) == "[1]"
+        assert calls == []
+        # default still handles what __json__ returns
+        assert rjson.dumps(self.Wrapper(object()), default=default) == '"default"'
+
+    def test_errors_propagate(self):
+        class Broken:
+            def __json__(self):
+                raise KeyError("boom")
+
+        with pytest.raises(KeyError, match="boom"):
+            rjson.dumps([Broken()])
+
+    def test_only_looked_up_on_type(self):
+        obj = self.Money(1, "USD")
+        plain = type("Plain", (), {})()
+        plain.__json__ = lambda: 1
+        assert rjson.dumps(obj) == '{"amount":1,"currency":"USD"}'
+        with pytest.raises(ValueError):
+            rjson.dumps(plain)
+
+    def test_non_callable_ignored(self):
+        class Attr:
+            __json__ = "not a method"
+
+        with pytest.raises(ValueError):
+            rjson.dumps(Attr())
+
+
+class TestUUID:
+    """Test dumps of uuid.UUID values."""
+
+    @pytest.mark.parametrize("value", [
+        uuid.UUID("550e8400-e29b-41d4-a716-446655440000"),
+        uuid.UUID(int=0),
+        uuid.UUID(int=2**128 - 1),
+        uuid.uuid4(),
+        uuid.uuid1(),
+    ])
+    def test_canonical_form(self, value):
+        assert rjson.dumps(value) == f'"{value}"'
+        assert rjson.dumps_bytes(value) == f'"{value}"'.encode()
+
+    def test_round_trip(self):
+        ids = [uuid.uuid4() for _ in range(50)]
+        data = {"ids": ids, "owner": {"id": ids[0]}}
+        result = rjson.loads(rjson.dumps(data))
+        assert [uuid.UUID(s) for s in result["ids"]] == ids
+        assert uuid.UUID(result["owner"]["id"]) == ids[0]
+
+    def test_uppercase_input_is_lowercased(self):
+        value = uuid.UUID("550E8400-E29B-41D4-A716-446655440000")
+        assert rjson.dumps(value) == '"550e8400-e29b-41d4-a716-446655440000"'
+
+
+class TestPathlib:
+    """Test dumps of pathlib paths."""
+
+    def test_nested_in_dict(self):
+        import json
+
+        data = {
+            "config": {"root": pathlib.PosixPath("/etc/app"), "log": pathlib.PurePosixPath("var/log/app.log")},
+            "name": "app",
+        }
+        expected = json.dumps(data, default=str, separators=(",", ":"))
+        assert rjson.dumps(data) == expected
+        assert rjson.dumps_bytes(data) == expected.encode()
+
+    def test_list_of_paths(self):
+        paths = [pathlib.PurePosixPath("a/b"), pathlib.PosixPath("/tmp"), pathlib.PurePosixPath(".")]
+        assert rjson.loads(rjson.dumps(paths)) == ["a/b", "/tmp", "."]
+
+    def test_windows_path_uses_str(self):
+        path = pathlib.PureWindowsPath("C:/Users/app/config.json")
+        assert rjson.dumps(path) == '"C:\\\\Users\\\\app\\\\config.json"'
+        assert rjson.loads(rjson.dumps(path)) == str(path)
+
+    def test_subclass(self):
+        class ConfigPath(type(pathlib.Path())):
+            pass
+
+        assert rjson.dumps([ConfigPath("/srv/data")]) == '["/srv/data"]'
+
+    def test_non_ascii_and_ensure_ascii(self):
+        path = pathlib.PurePosixPath("/données/café")
+        assert rjson.loads(rjson.dumps(path)) == "/données/café"
+        assert rjson.dumps(path, ensure_ascii=True) == '"/donn\\u00e9es/caf\\u00e9"'
+
+
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])